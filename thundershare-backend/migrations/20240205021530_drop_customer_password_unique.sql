@@ -0,0 +1,2 @@
+-- Add migration script here
+ALTER TABLE customer DROP CONSTRAINT IF EXISTS customer_password_key;
@@ -0,0 +1,9 @@
+-- Add migration script here
+ALTER TABLE signouttoken ADD COLUMN expireat timestamptz;
+
+-- NOTE: expiretime only kept a time of day, today is the closest date that can be attached to it
+UPDATE signouttoken SET expireat = CURRENT_DATE + expiretime WHERE expiretime IS NOT NULL;
+
+ALTER TABLE signouttoken DROP COLUMN IF EXISTS expiretime;
+
+CREATE INDEX signouttoken_expireat_idx ON signouttoken (expireat);
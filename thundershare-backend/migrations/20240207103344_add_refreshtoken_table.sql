@@ -0,0 +1,14 @@
+-- Add migration script here
+CREATE TABLE refreshtoken (
+    id UUID PRIMARY KEY DEFAULT uuid_generate_v4(),
+    customer_id UUID NOT NULL,
+    family_id UUID NOT NULL,
+    token_hash TEXT NOT NULL,
+    expireat timestamptz NOT NULL,
+    usedat timestamptz,
+    revoked BOOLEAN NOT NULL DEFAULT FALSE,
+    UNIQUE(token_hash),
+    FOREIGN KEY(customer_id) REFERENCES customer(id)
+);
+
+CREATE INDEX refreshtoken_family_id_idx ON refreshtoken (family_id);
@@ -0,0 +1,8 @@
+-- Add migration script here
+ALTER TABLE filesharingmeta RENAME COLUMN link TO code;
+
+-- NOTE: every link created so far stored the placeholder 'TODO', give them a unique code of their own
+UPDATE filesharingmeta SET code = replace(uuid_generate_v4()::text, '-', '') WHERE code IS NULL OR code = 'TODO';
+
+ALTER TABLE filesharingmeta ALTER COLUMN code SET NOT NULL;
+ALTER TABLE filesharingmeta ADD CONSTRAINT filesharingmeta_code_key UNIQUE (code);
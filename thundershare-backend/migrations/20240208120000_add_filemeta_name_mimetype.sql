@@ -0,0 +1,3 @@
+-- Add migration script here
+ALTER TABLE filemeta ADD COLUMN name TEXT NOT NULL DEFAULT '';
+ALTER TABLE filemeta ADD COLUMN mimetype TEXT NOT NULL DEFAULT 'application/octet-stream';
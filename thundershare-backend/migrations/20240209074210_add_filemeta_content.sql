@@ -0,0 +1,4 @@
+-- Add migration script here
+ALTER TABLE filemeta ADD COLUMN size BIGINT NOT NULL DEFAULT 0;
+ALTER TABLE filemeta ADD COLUMN sha256 TEXT NOT NULL DEFAULT '';
+ALTER TABLE filemeta ADD COLUMN uploadedat timestamptz NOT NULL DEFAULT now();
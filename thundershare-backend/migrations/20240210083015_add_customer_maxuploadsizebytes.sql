@@ -0,0 +1,3 @@
+-- Add migration script here
+-- NOTE: NULL means the upload.max_size_bytes of the server config applies
+ALTER TABLE customer ADD COLUMN maxuploadsizebytes BIGINT CHECK (maxuploadsizebytes > 0);
@@ -0,0 +1,15 @@
+-- Add migration script here
+CREATE TABLE upload (
+    id UUID PRIMARY KEY DEFAULT uuid_generate_v4(),
+    customer_id UUID NOT NULL,
+    name TEXT NOT NULL DEFAULT '',
+    mimetype TEXT,
+    uploadlength BIGINT NOT NULL CHECK (uploadlength >= 0),
+    uploadoffset BIGINT NOT NULL DEFAULT 0 CHECK (uploadoffset >= 0 AND uploadoffset <= uploadlength),
+    -- NOTE: storage keys of the received parts in upload order, emptied once the file is created
+    parts TEXT[] NOT NULL DEFAULT '{}',
+    file_id UUID,
+    createdat timestamptz NOT NULL DEFAULT now(),
+    FOREIGN KEY(customer_id) REFERENCES customer(id),
+    FOREIGN KEY(file_id) REFERENCES filemeta(id) ON DELETE SET NULL
+);
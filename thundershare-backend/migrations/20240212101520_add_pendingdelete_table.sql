@@ -0,0 +1,10 @@
+-- Add migration script here
+-- NOTE: storage keys of deleted files whose object may still exist, a row is removed once the
+--       storage backend confirmed the delete
+CREATE TABLE pendingdelete (
+    storagekey TEXT PRIMARY KEY,
+    createdat timestamptz NOT NULL DEFAULT now(),
+    attempts INTEGER NOT NULL DEFAULT 0,
+    lastattemptat timestamptz,
+    lasterror TEXT
+);
@@ -0,0 +1,5 @@
+-- Add migration script here
+-- NOTE: set while a file is in the trash, NULL for every file the customer can still use
+ALTER TABLE filemeta ADD COLUMN deletedat timestamptz;
+
+CREATE INDEX filemeta_deletedat_idx ON filemeta (deletedat) WHERE deletedat IS NOT NULL;
@@ -0,0 +1,18 @@
+-- Add migration script here
+CREATE TABLE folder (
+    id UUID PRIMARY KEY DEFAULT uuid_generate_v4(),
+    customer_id UUID NOT NULL,
+    -- NOTE: NULL for the folders at the root of the customer
+    parent_id UUID,
+    name TEXT NOT NULL,
+    createdat timestamptz NOT NULL DEFAULT now(),
+    FOREIGN KEY(customer_id) REFERENCES customer(id),
+    FOREIGN KEY(parent_id) REFERENCES folder(id)
+);
+
+-- NOTE: the root has no parent id, it is folded into the nil uuid so root names are unique as well
+CREATE UNIQUE INDEX folder_parent_name_idx ON folder (customer_id, COALESCE(parent_id, '00000000-0000-0000-0000-000000000000'), name);
+
+-- NOTE: a file whose folder is removed while it sits in the trash is restored to the root
+ALTER TABLE filemeta ADD COLUMN folder_id UUID REFERENCES folder(id) ON DELETE SET NULL;
+CREATE INDEX filemeta_folder_id_idx ON filemeta (customer_id, folder_id);
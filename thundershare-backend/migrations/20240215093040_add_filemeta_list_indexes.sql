@@ -0,0 +1,8 @@
+-- Add migration script here
+-- NOTE: one index per sort of the file listing, id breaks ties so that the keyset of a page is unique
+CREATE INDEX filemeta_customer_name_idx ON filemeta (customer_id, name, id) WHERE deletedat IS NULL;
+CREATE INDEX filemeta_customer_size_idx ON filemeta (customer_id, size, id) WHERE deletedat IS NULL;
+CREATE INDEX filemeta_customer_uploadedat_idx ON filemeta (customer_id, uploadedat, id) WHERE deletedat IS NULL;
+
+-- NOTE: text_pattern_ops lets the mime type prefix filter use the index whatever the collation
+CREATE INDEX filemeta_customer_mimetype_idx ON filemeta (customer_id, mimetype text_pattern_ops) WHERE deletedat IS NULL;
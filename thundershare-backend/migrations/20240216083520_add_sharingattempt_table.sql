@@ -0,0 +1,9 @@
+-- Add migration script here
+-- NOTE: failed password attempts on sharing links, keyed by link and by client address. the
+--       counter restarts once windowstartat is older than the throttling window.
+CREATE TABLE sharingattempt (
+    key TEXT PRIMARY KEY,
+    failures INTEGER NOT NULL DEFAULT 0,
+    windowstartat timestamptz NOT NULL,
+    lockeduntil timestamptz
+);
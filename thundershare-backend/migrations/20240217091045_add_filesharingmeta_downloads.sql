@@ -0,0 +1,4 @@
+-- Add migration script here
+-- NOTE: a null maxdownloads leaves the link unlimited, downloads counts every time it was opened
+ALTER TABLE filesharingmeta ADD COLUMN maxdownloads INTEGER;
+ALTER TABLE filesharingmeta ADD COLUMN downloads INTEGER NOT NULL DEFAULT 0;
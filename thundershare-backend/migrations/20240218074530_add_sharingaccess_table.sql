@@ -0,0 +1,14 @@
+-- Add migration script here
+-- NOTE: every attempt to open a sharing link, sharing_id is null when no link matched the request
+CREATE TABLE sharingaccess (
+    id UUID PRIMARY KEY DEFAULT uuid_generate_v4(),
+    sharing_id UUID,
+    accessedat timestamptz NOT NULL,
+    clientaddr TEXT NOT NULL,
+    useragent TEXT,
+    outcome TEXT NOT NULL,
+    bytesserved BIGINT NOT NULL DEFAULT 0,
+    FOREIGN KEY(sharing_id) REFERENCES filesharingmeta(id) ON DELETE CASCADE
+);
+
+CREATE INDEX sharingaccess_sharing_id_accessedat_idx ON sharingaccess (sharing_id, accessedat DESC, id DESC);
@@ -0,0 +1,23 @@
+-- Add migration script here
+-- NOTE: a null maxfiles takes any number of files, uploads counts the files received so far
+CREATE TABLE filerequest (
+    id UUID PRIMARY KEY DEFAULT uuid_generate_v4(),
+    customer_id UUID NOT NULL,
+    -- NOTE: NULL for a request whose files end up at the root of the customer, a request whose
+    --       folder is deleted falls back to the root
+    folder_id UUID,
+    code TEXT NOT NULL UNIQUE,
+    expireat timestamptz NOT NULL,
+    password TEXT,
+    maxfiles INTEGER,
+    maxsizebytes BIGINT NOT NULL,
+    uploads INTEGER NOT NULL DEFAULT 0,
+    createdat timestamptz NOT NULL DEFAULT now(),
+    FOREIGN KEY(customer_id) REFERENCES customer(id),
+    FOREIGN KEY(folder_id) REFERENCES folder(id) ON DELETE SET NULL
+);
+
+CREATE INDEX filerequest_customer_id_idx ON filerequest (customer_id);
+
+-- NOTE: the files keep their content once the request is removed, only the attribution is lost
+ALTER TABLE filemeta ADD COLUMN filerequest_id UUID REFERENCES filerequest(id) ON DELETE SET NULL;
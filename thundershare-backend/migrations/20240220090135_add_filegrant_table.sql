@@ -0,0 +1,13 @@
+-- Add migration script here
+-- NOTE: permission is either 'read' or 'read_write', a customer has at most one grant per file
+CREATE TABLE filegrant (
+    file_id UUID NOT NULL,
+    customer_id UUID NOT NULL,
+    permission TEXT NOT NULL,
+    grantedat timestamptz NOT NULL,
+    PRIMARY KEY(file_id, customer_id),
+    FOREIGN KEY(file_id) REFERENCES filemeta(id) ON DELETE CASCADE,
+    FOREIGN KEY(customer_id) REFERENCES customer(id)
+);
+
+CREATE INDEX filegrant_customer_id_idx ON filegrant (customer_id, grantedat DESC);
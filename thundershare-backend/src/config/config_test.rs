@@ -0,0 +1,267 @@
+use std::collections::HashMap;
+
+use actix_web::cookie::SameSite;
+
+use super::ServerConfig;
+use crate::domain::entity::jwt_key::JwtKeyAlgorithm;
+
+fn env_of(values: &[(&str, &str)]) -> HashMap<String, String> {
+    values
+        .iter()
+        .map(|(key, value)| (key.to_string(), value.to_string()))
+        .collect()
+}
+
+fn layer_of(name: &str, content: &str) -> (String, String) {
+    (name.to_string(), content.to_string())
+}
+
+#[test]
+fn test_config_defaults_with_env_secret() {
+    let env = env_of(&[("JWT_KEY_DEFAULT_SECRET", "secret")]);
+    let config = ServerConfig::load_from(&[], &env).unwrap();
+
+    assert_eq!(config.http.host, "0.0.0.0");
+    assert_eq!(config.http.port, 8080);
+    assert!(config.http.trusted_proxies.is_empty());
+    assert_eq!(config.upload.max_size_bytes, 10 * 1024 * 1024 * 1024);
+    assert_eq!(config.storage.backend, "local");
+    assert_eq!(config.storage.local.root, ".");
+    assert_eq!(config.storage.cleanup_interval_seconds, 300);
+    assert_eq!(config.trash.retention_seconds, 30 * 24 * 60 * 60);
+    assert_eq!(config.sharing.max_failed_attempts, 5);
+    assert_eq!(config.sharing.attempt_window_seconds, 15 * 60);
+    assert_eq!(config.sharing.lockout_seconds, 15 * 60);
+    assert_eq!(config.session.access_token_lifetime_seconds, 600);
+    assert_eq!(config.cookie.get_same_site(), SameSite::None);
+    assert_eq!(config.jwt.keys.len(), 1);
+    assert_eq!(config.jwt.keys[0].kid, "default");
+    assert_eq!(config.jwt.keys[0].algorithm, JwtKeyAlgorithm::HS256);
+    assert_eq!(config.jwt.keys[0].secret, Some(String::from("secret")));
+}
+
+#[test]
+fn test_config_layers_and_env_overrides() {
+    let layers = vec![
+        layer_of(
+            "base.toml",
+            r#"
+                [http]
+                host = "127.0.0.1"
+                port = 3000
+
+                [database]
+                host = "db"
+                name = "base"
+
+                [[jwt.keys]]
+                kid = "default"
+                algorithm = "HS256"
+                secret = "from-file"
+            "#,
+        ),
+        layer_of(
+            "local.toml",
+            r#"
+                [database]
+                name = "local"
+
+                [cookie]
+                secure = false
+                same_site = "Lax"
+
+                [storage]
+                backend = "s3"
+
+                [storage.s3]
+                bucket = "files"
+                access_key_id = "minio"
+            "#,
+        ),
+    ];
+    let env = env_of(&[
+        ("SERVER_PORT", "4000"),
+        ("UPLOAD_MAX_SIZE_BYTES", "1024"),
+        ("JWT_KEY_DEFAULT_SECRET", "from-env"),
+        ("STORAGE_S3_SECRET_ACCESS_KEY", "minio-secret"),
+        ("STORAGE_S3_PATH_STYLE", "false"),
+    ]);
+    let config = ServerConfig::load_from(&layers, &env).unwrap();
+
+    assert_eq!(config.http.host, "127.0.0.1");
+    assert_eq!(config.http.port, 4000);
+    assert_eq!(config.database.host, "db");
+    assert_eq!(config.database.name, "local");
+    assert_eq!(config.upload.max_size_bytes, 1024);
+    assert!(!config.cookie.secure);
+    assert_eq!(config.cookie.get_same_site(), SameSite::Lax);
+    assert_eq!(config.storage.backend, "s3");
+    assert_eq!(config.storage.s3.bucket, "files");
+    assert_eq!(config.storage.s3.access_key_id, "minio");
+    assert_eq!(config.storage.s3.secret_access_key, "minio-secret");
+    assert!(!config.storage.s3.path_style);
+    assert_eq!(config.jwt.keys.len(), 1);
+    assert_eq!(config.jwt.keys[0].secret, Some(String::from("from-env")));
+}
+
+#[test]
+fn test_config_env_only_jwt_keys() {
+    let env = env_of(&[
+        ("JWT_ACTIVE_KEY_ID", "2024-02"),
+        ("JWT_KEY_IDS", "2024-02,2024-01"),
+        ("JWT_KEY_2024_02_SECRET", "new"),
+        ("JWT_KEY_2024_01_ALGORITHM", "RS256"),
+        ("JWT_KEY_2024_01_PEM_FILE", "/keys/old.pem"),
+    ]);
+    let config = ServerConfig::load_from(&[], &env).unwrap();
+
+    assert_eq!(config.jwt.active_key_id, "2024-02");
+    assert_eq!(config.jwt.keys.len(), 2);
+    assert_eq!(config.jwt.keys[0].kid, "2024-02");
+    assert_eq!(config.jwt.keys[0].secret, Some(String::from("new")));
+    assert_eq!(config.jwt.keys[1].kid, "2024-01");
+    assert_eq!(config.jwt.keys[1].algorithm, JwtKeyAlgorithm::RS256);
+    assert_eq!(
+        config.jwt.keys[1].pem_file,
+        Some(String::from("/keys/old.pem"))
+    );
+}
+
+#[test]
+fn test_config_rejects_invalid_values() {
+    let test_context = vec![
+        (
+            vec![],
+            env_of(&[]),
+            "jwt.keys must contain the active key default",
+        ),
+        (
+            vec![],
+            env_of(&[
+                ("JWT_KEY_DEFAULT_SECRET", "secret"),
+                ("SERVER_PORT", "http"),
+            ]),
+            "invalid value \"http\" for SERVER_PORT",
+        ),
+        (
+            vec![layer_of("broken.toml", "[http")],
+            env_of(&[("JWT_KEY_DEFAULT_SECRET", "secret")]),
+            "failed to parse config file broken.toml",
+        ),
+        (
+            vec![layer_of("typo.toml", "[http]\nhots = \"localhost\"")],
+            env_of(&[("JWT_KEY_DEFAULT_SECRET", "secret")]),
+            "unknown field `hots`",
+        ),
+        (
+            vec![],
+            env_of(&[
+                ("JWT_KEY_DEFAULT_SECRET", "secret"),
+                ("UPLOAD_MAX_SIZE_BYTES", "0"),
+                ("COOKIE_SECURE", "false"),
+            ]),
+            "upload.max_size_bytes must be greater than 0",
+        ),
+        (
+            vec![],
+            env_of(&[
+                ("JWT_KEY_DEFAULT_SECRET", "secret"),
+                ("COOKIE_SECURE", "false"),
+            ]),
+            "cookie.secure must be enabled when cookie.same_site is None",
+        ),
+        (
+            vec![],
+            env_of(&[
+                ("JWT_KEY_DEFAULT_SECRET", "secret"),
+                ("ACCESS_TOKEN_LIFETIME_SECONDS", "3600"),
+                ("REFRESH_TOKEN_LIFETIME_SECONDS", "60"),
+            ]),
+            "session.refresh_token_lifetime_seconds must not be shorter",
+        ),
+        (
+            vec![],
+            env_of(&[
+                ("JWT_KEY_DEFAULT_SECRET", "secret"),
+                ("STORAGE_BACKEND", "ftp"),
+            ]),
+            "storage.backend must be one of local or s3, got ftp",
+        ),
+        (
+            vec![],
+            env_of(&[
+                ("JWT_KEY_DEFAULT_SECRET", "secret"),
+                ("STORAGE_BACKEND", "s3"),
+            ]),
+            "storage.s3.access_key_id and storage.s3.secret_access_key must be set",
+        ),
+        (
+            vec![],
+            env_of(&[
+                ("JWT_KEY_DEFAULT_SECRET", "secret"),
+                ("STORAGE_CLEANUP_INTERVAL_SECONDS", "0"),
+            ]),
+            "storage.cleanup_interval_seconds must be greater than 0",
+        ),
+        (
+            vec![],
+            env_of(&[
+                ("JWT_KEY_DEFAULT_SECRET", "secret"),
+                ("TRASH_RETENTION_SECONDS", "-1"),
+            ]),
+            "trash.retention_seconds must not be negative",
+        ),
+        (
+            vec![],
+            env_of(&[
+                ("JWT_KEY_DEFAULT_SECRET", "secret"),
+                ("SHARING_MAX_FAILED_ATTEMPTS", "0"),
+            ]),
+            "sharing.max_failed_attempts, sharing.attempt_window_seconds and sharing.lockout_seconds must be greater than 0",
+        ),
+        (
+            vec![],
+            env_of(&[
+                ("JWT_KEY_DEFAULT_SECRET", "secret"),
+                ("TRUSTED_PROXIES", "10.0.0.1,proxy.local"),
+            ]),
+            "http.trusted_proxies must only contain ip addresses, got \"proxy.local\"",
+        ),
+    ];
+
+    for (layers, env, expected) in test_context {
+        let err = ServerConfig::load_from(&layers, &env).unwrap_err();
+        assert!(
+            err.to_string().contains(expected),
+            "{} does not contain {}",
+            err,
+            expected
+        );
+    }
+}
+
+#[test]
+fn test_config_example_file_is_valid() {
+    let layers = vec![layer_of(
+        "thundershare.example.toml",
+        include_str!("../../thundershare.example.toml"),
+    )];
+    let config = ServerConfig::load_from(&layers, &env_of(&[])).unwrap();
+
+    assert_eq!(config.jwt.keys.len(), 1);
+    assert_eq!(config.jwt.keys[0].secret, Some(String::from("change-me")));
+}
+
+#[test]
+fn test_config_trusted_proxies() {
+    let env = env_of(&[
+        ("JWT_KEY_DEFAULT_SECRET", "secret"),
+        ("TRUSTED_PROXIES", "10.0.0.1, ::1,"),
+    ]);
+    let config = ServerConfig::load_from(&[], &env).unwrap();
+
+    assert_eq!(config.http.trusted_proxies, vec!["10.0.0.1", "::1"]);
+    assert!(config.http.is_trusted_proxy(&"10.0.0.1".parse().unwrap()));
+    assert!(config.http.is_trusted_proxy(&"::1".parse().unwrap()));
+    assert!(!config.http.is_trusted_proxy(&"10.0.0.2".parse().unwrap()));
+}
@@ -0,0 +1,651 @@
+#[cfg(test)]
+pub mod config_test;
+
+use std::collections::HashMap;
+use std::net::IpAddr;
+use std::str::FromStr;
+
+use actix_web::cookie::SameSite;
+use anyhow::{anyhow, bail, Result};
+use chrono::Duration;
+
+use crate::domain::entity::jwt_key::{JwtKeyAlgorithm, JwtKeyConfig};
+use crate::domain::service::customer::CustomerSessionLifetime;
+use crate::domain::service::file::SharingThrottle;
+
+// NOTE: comma separated list of toml files, later files override earlier ones
+const CONFIG_FILES_ENV: &str = "THUNDERSHARE_CONFIG";
+const DEFAULT_CONFIG_FILE: &str = "thundershare.toml";
+
+#[derive(Debug, Clone, serde::Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct HttpConfig {
+    pub host: String,
+    pub port: u16,
+    // NOTE: the address clients reach the server at, sharing links are built on top of it
+    pub public_base_url: String,
+    // NOTE: addresses of the reverse proxies in front of the server, the client address they
+    //       forward is only believed when the request comes from one of them
+    pub trusted_proxies: Vec<String>,
+}
+
+impl Default for HttpConfig {
+    fn default() -> HttpConfig {
+        HttpConfig {
+            host: String::from("0.0.0.0"),
+            port: 8080,
+            public_base_url: String::from("http://localhost:8080"),
+            trusted_proxies: vec![],
+        }
+    }
+}
+
+impl HttpConfig {
+    pub fn is_trusted_proxy(&self, addr: &IpAddr) -> bool {
+        self.trusted_proxies
+            .iter()
+            .any(|proxy| proxy.parse::<IpAddr>().ok().as_ref() == Some(addr))
+    }
+}
+
+#[derive(Debug, Clone, serde::Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct DatabaseConfig {
+    pub host: String,
+    pub port: u16,
+    pub name: String,
+    pub user: String,
+    pub password: String,
+    pub max_connections: u32,
+}
+
+impl Default for DatabaseConfig {
+    fn default() -> DatabaseConfig {
+        DatabaseConfig {
+            host: String::from("localhost"),
+            port: 5432,
+            name: String::from("thundershare"),
+            user: String::from("pgsql"),
+            password: String::new(),
+            max_connections: 10,
+        }
+    }
+}
+
+#[derive(Debug, Clone, serde::Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct UploadConfig {
+    // NOTE: applies to every customer without a limit of their own
+    pub max_size_bytes: u64,
+}
+
+impl Default for UploadConfig {
+    fn default() -> UploadConfig {
+        UploadConfig {
+            max_size_bytes: 10 * 1024 * 1024 * 1024,
+        }
+    }
+}
+
+#[derive(Debug, Clone, serde::Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct TrashConfig {
+    // NOTE: how long a deleted file can be restored before it is purged for good
+    pub retention_seconds: i64,
+}
+
+impl Default for TrashConfig {
+    fn default() -> TrashConfig {
+        TrashConfig {
+            retention_seconds: 30 * 24 * 60 * 60,
+        }
+    }
+}
+
+#[derive(Debug, Clone, serde::Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct SharingConfig {
+    // NOTE: wrong passwords a sharing link or a client may send within the window before it is
+    //       locked out
+    pub max_failed_attempts: i32,
+    pub attempt_window_seconds: i64,
+    pub lockout_seconds: i64,
+}
+
+impl Default for SharingConfig {
+    fn default() -> SharingConfig {
+        let sharing_throttle = SharingThrottle::default();
+        SharingConfig {
+            max_failed_attempts: sharing_throttle.max_failures,
+            attempt_window_seconds: sharing_throttle.window.num_seconds(),
+            lockout_seconds: sharing_throttle.lockout.num_seconds(),
+        }
+    }
+}
+
+impl From<&SharingConfig> for SharingThrottle {
+    fn from(config: &SharingConfig) -> SharingThrottle {
+        SharingThrottle {
+            max_failures: config.max_failed_attempts,
+            window: Duration::seconds(config.attempt_window_seconds),
+            lockout: Duration::seconds(config.lockout_seconds),
+        }
+    }
+}
+
+// NOTE: relative roots are resolved against the working directory, "." keeps the files
+//       uploaded before the storage section existed reachable.
+#[derive(Debug, Clone, serde::Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct LocalStorageConfig {
+    pub root: String,
+}
+
+impl Default for LocalStorageConfig {
+    fn default() -> LocalStorageConfig {
+        LocalStorageConfig {
+            root: String::from("."),
+        }
+    }
+}
+
+#[derive(Debug, Clone, serde::Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct S3StorageConfig {
+    pub endpoint: String,
+    pub region: String,
+    pub bucket: String,
+    pub access_key_id: String,
+    pub secret_access_key: String,
+    // NOTE: MinIO and most self hosted stores only understand http://endpoint/bucket/key
+    pub path_style: bool,
+}
+
+impl Default for S3StorageConfig {
+    fn default() -> S3StorageConfig {
+        S3StorageConfig {
+            endpoint: String::from("http://localhost:9000"),
+            region: String::from("us-east-1"),
+            bucket: String::from("thundershare"),
+            access_key_id: String::new(),
+            secret_access_key: String::new(),
+            path_style: true,
+        }
+    }
+}
+
+#[derive(Debug, Clone, serde::Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct StorageConfig {
+    // NOTE: "local" or "s3"
+    pub backend: String,
+    pub local: LocalStorageConfig,
+    pub s3: S3StorageConfig,
+    // NOTE: how often the trash is purged and objects of purged files that could not be removed
+    //       right away are retried
+    pub cleanup_interval_seconds: u64,
+}
+
+impl Default for StorageConfig {
+    fn default() -> StorageConfig {
+        StorageConfig {
+            backend: String::from("local"),
+            local: LocalStorageConfig::default(),
+            s3: S3StorageConfig::default(),
+            cleanup_interval_seconds: 300,
+        }
+    }
+}
+
+#[derive(Debug, Clone, serde::Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct SessionConfig {
+    pub access_token_lifetime_seconds: i64,
+    pub refresh_token_lifetime_seconds: i64,
+}
+
+impl Default for SessionConfig {
+    fn default() -> SessionConfig {
+        let session_lifetime = CustomerSessionLifetime::default();
+        SessionConfig {
+            access_token_lifetime_seconds: session_lifetime.access_token.num_seconds(),
+            refresh_token_lifetime_seconds: session_lifetime.refresh_token.num_seconds(),
+        }
+    }
+}
+
+impl From<&SessionConfig> for CustomerSessionLifetime {
+    fn from(config: &SessionConfig) -> CustomerSessionLifetime {
+        CustomerSessionLifetime {
+            access_token: Duration::seconds(config.access_token_lifetime_seconds),
+            refresh_token: Duration::seconds(config.refresh_token_lifetime_seconds),
+        }
+    }
+}
+
+#[derive(Debug, Clone, serde::Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct CookieConfig {
+    pub secure: bool,
+    pub same_site: String,
+    pub domain: Option<String>,
+}
+
+impl Default for CookieConfig {
+    fn default() -> CookieConfig {
+        CookieConfig {
+            secure: true,
+            same_site: String::from("None"),
+            domain: None,
+        }
+    }
+}
+
+impl CookieConfig {
+    pub fn get_same_site(&self) -> SameSite {
+        match self.same_site.as_str() {
+            "Strict" => SameSite::Strict,
+            "Lax" => SameSite::Lax,
+            _ => SameSite::None,
+        }
+    }
+}
+
+#[derive(Debug, Clone, serde::Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct JwtConfig {
+    pub active_key_id: String,
+    pub keys: Vec<JwtKeyConfig>,
+}
+
+impl Default for JwtConfig {
+    fn default() -> JwtConfig {
+        JwtConfig {
+            active_key_id: String::from("default"),
+            keys: vec![],
+        }
+    }
+}
+
+#[derive(Debug, Clone, Default, serde::Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct ServerConfig {
+    pub http: HttpConfig,
+    pub database: DatabaseConfig,
+    pub upload: UploadConfig,
+    pub trash: TrashConfig,
+    pub sharing: SharingConfig,
+    pub storage: StorageConfig,
+    pub session: SessionConfig,
+    pub cookie: CookieConfig,
+    pub jwt: JwtConfig,
+}
+
+impl ServerConfig {
+    // NOTE: defaults < toml files < environment variables
+    pub fn load() -> Result<ServerConfig> {
+        let env: HashMap<String, String> = std::env::vars().collect();
+        let files: Vec<String> = match env.get(CONFIG_FILES_ENV) {
+            Some(files) => files
+                .split(',')
+                .map(|file| file.trim().to_string())
+                .filter(|file| !file.is_empty())
+                .collect(),
+            None if std::path::Path::new(DEFAULT_CONFIG_FILE).exists() => {
+                vec![String::from(DEFAULT_CONFIG_FILE)]
+            }
+            None => vec![],
+        };
+
+        let mut layers = vec![];
+        for file in files.iter() {
+            let content = std::fs::read_to_string(file)
+                .map_err(|err| anyhow!("failed to read config file {}: {}", file, err))?;
+            layers.push((file.clone(), content));
+        }
+
+        ServerConfig::load_from(&layers, &env)
+    }
+
+    pub fn load_from(
+        layers: &[(String, String)],
+        env: &HashMap<String, String>,
+    ) -> Result<ServerConfig> {
+        let mut merged = toml::Table::new();
+        for (name, content) in layers {
+            let layer: toml::Table = toml::from_str(content)
+                .map_err(|err| anyhow!("failed to parse config file {}: {}", name, err))?;
+            merge_table(&mut merged, layer);
+        }
+
+        let mut config: ServerConfig = toml::Value::Table(merged)
+            .try_into()
+            .map_err(|err| anyhow!("invalid configuration: {}", err))?;
+
+        config.apply_env_overrides(env)?;
+        config.validate()?;
+
+        Ok(config)
+    }
+
+    // NOTE: the variable names predate the config file and are kept so existing deployments
+    //       (docker-compose, .env) keep working unchanged.
+    fn apply_env_overrides(&mut self, env: &HashMap<String, String>) -> Result<()> {
+        override_value(env, "SERVER_HOST", &mut self.http.host)?;
+        override_value(env, "SERVER_PORT", &mut self.http.port)?;
+        override_value(env, "PUBLIC_BASE_URL", &mut self.http.public_base_url)?;
+        if let Some(proxies) = env.get("TRUSTED_PROXIES") {
+            self.http.trusted_proxies = proxies
+                .split(',')
+                .map(|proxy| proxy.trim().to_string())
+                .filter(|proxy| !proxy.is_empty())
+                .collect();
+        }
+
+        override_value(env, "DB_HOST", &mut self.database.host)?;
+        override_value(env, "DB_PORT", &mut self.database.port)?;
+        override_value(env, "DB_NAME", &mut self.database.name)?;
+        override_value(env, "DB_USER", &mut self.database.user)?;
+        override_value(env, "DB_PASS", &mut self.database.password)?;
+        override_value(
+            env,
+            "DB_MAX_CONNECTIONS",
+            &mut self.database.max_connections,
+        )?;
+
+        override_value(
+            env,
+            "UPLOAD_MAX_SIZE_BYTES",
+            &mut self.upload.max_size_bytes,
+        )?;
+
+        override_value(
+            env,
+            "TRASH_RETENTION_SECONDS",
+            &mut self.trash.retention_seconds,
+        )?;
+
+        override_value(
+            env,
+            "SHARING_MAX_FAILED_ATTEMPTS",
+            &mut self.sharing.max_failed_attempts,
+        )?;
+        override_value(
+            env,
+            "SHARING_ATTEMPT_WINDOW_SECONDS",
+            &mut self.sharing.attempt_window_seconds,
+        )?;
+        override_value(
+            env,
+            "SHARING_LOCKOUT_SECONDS",
+            &mut self.sharing.lockout_seconds,
+        )?;
+
+        override_value(env, "STORAGE_BACKEND", &mut self.storage.backend)?;
+        override_value(env, "STORAGE_LOCAL_ROOT", &mut self.storage.local.root)?;
+        override_value(env, "STORAGE_S3_ENDPOINT", &mut self.storage.s3.endpoint)?;
+        override_value(env, "STORAGE_S3_REGION", &mut self.storage.s3.region)?;
+        override_value(env, "STORAGE_S3_BUCKET", &mut self.storage.s3.bucket)?;
+        override_value(
+            env,
+            "STORAGE_S3_ACCESS_KEY_ID",
+            &mut self.storage.s3.access_key_id,
+        )?;
+        override_value(
+            env,
+            "STORAGE_S3_SECRET_ACCESS_KEY",
+            &mut self.storage.s3.secret_access_key,
+        )?;
+        override_value(
+            env,
+            "STORAGE_S3_PATH_STYLE",
+            &mut self.storage.s3.path_style,
+        )?;
+        override_value(
+            env,
+            "STORAGE_CLEANUP_INTERVAL_SECONDS",
+            &mut self.storage.cleanup_interval_seconds,
+        )?;
+
+        override_value(
+            env,
+            "ACCESS_TOKEN_LIFETIME_SECONDS",
+            &mut self.session.access_token_lifetime_seconds,
+        )?;
+        override_value(
+            env,
+            "REFRESH_TOKEN_LIFETIME_SECONDS",
+            &mut self.session.refresh_token_lifetime_seconds,
+        )?;
+
+        override_value(env, "COOKIE_SECURE", &mut self.cookie.secure)?;
+        override_value(env, "COOKIE_SAME_SITE", &mut self.cookie.same_site)?;
+        if let Some(domain) = env.get("COOKIE_DOMAIN") {
+            self.cookie.domain = Some(domain.clone()).filter(|domain| !domain.is_empty());
+        }
+
+        override_value(env, "JWT_ACTIVE_KEY_ID", &mut self.jwt.active_key_id)?;
+        self.apply_jwt_key_env_overrides(env)
+    }
+
+    // NOTE: a key is described by JWT_KEY_<ID>_ALGORITHM, JWT_KEY_<ID>_SECRET and
+    //       JWT_KEY_<ID>_PEM_FILE, keys only known from the environment are listed in JWT_KEY_IDS.
+    fn apply_jwt_key_env_overrides(&mut self, env: &HashMap<String, String>) -> Result<()> {
+        let mut kid_list: Vec<String> = self.jwt.keys.iter().map(|key| key.kid.clone()).collect();
+        kid_list.push(self.jwt.active_key_id.clone());
+        if let Some(kids) = env.get("JWT_KEY_IDS") {
+            kid_list.extend(kids.split(',').map(|kid| kid.trim().to_string()));
+        }
+
+        for kid in kid_list.into_iter().filter(|kid| !kid.is_empty()) {
+            let prefix = format!(
+                "JWT_KEY_{}_",
+                kid.to_uppercase()
+                    .replace(|c: char| !c.is_ascii_alphanumeric(), "_")
+            );
+            let algorithm = env.get(&(prefix.clone() + "ALGORITHM"));
+            let secret = env.get(&(prefix.clone() + "SECRET"));
+            let pem_file = env.get(&(prefix + "PEM_FILE"));
+            if algorithm.is_none() && secret.is_none() && pem_file.is_none() {
+                continue;
+            }
+
+            let position = match self.jwt.keys.iter().position(|key| key.kid == kid) {
+                Some(position) => position,
+                None => {
+                    self.jwt.keys.push(JwtKeyConfig {
+                        kid: kid.clone(),
+                        algorithm: JwtKeyAlgorithm::HS256,
+                        secret: None,
+                        pem_file: None,
+                    });
+                    self.jwt.keys.len() - 1
+                }
+            };
+
+            let key = &mut self.jwt.keys[position];
+            if let Some(algorithm) = algorithm {
+                key.algorithm = algorithm
+                    .parse()
+                    .map_err(|err| anyhow!("invalid value for JWT_KEY of {}: {}", kid, err))?;
+            }
+            if let Some(secret) = secret {
+                key.secret = Some(secret.clone());
+            }
+            if let Some(pem_file) = pem_file {
+                key.pem_file = Some(pem_file.clone());
+            }
+        }
+
+        Ok(())
+    }
+
+    fn validate(&self) -> Result<()> {
+        let mut errors = vec![];
+
+        if self.http.host.is_empty() {
+            errors.push(String::from("http.host must not be empty"));
+        }
+        if self.http.port == 0 {
+            errors.push(String::from("http.port must not be 0"));
+        }
+        if !self.http.public_base_url.starts_with("http://")
+            && !self.http.public_base_url.starts_with("https://")
+        {
+            errors.push(format!(
+                "http.public_base_url must be an absolute http(s) url, got {:?}",
+                self.http.public_base_url
+            ));
+        }
+        for proxy in self.http.trusted_proxies.iter() {
+            if proxy.parse::<IpAddr>().is_err() {
+                errors.push(format!(
+                    "http.trusted_proxies must only contain ip addresses, got {:?}",
+                    proxy
+                ));
+            }
+        }
+
+        if self.database.host.is_empty() {
+            errors.push(String::from("database.host must not be empty"));
+        }
+        if self.database.name.is_empty() {
+            errors.push(String::from("database.name must not be empty"));
+        }
+        if self.database.user.is_empty() {
+            errors.push(String::from("database.user must not be empty"));
+        }
+        if self.database.max_connections == 0 {
+            errors.push(String::from(
+                "database.max_connections must be greater than 0",
+            ));
+        }
+
+        if self.upload.max_size_bytes == 0 {
+            errors.push(String::from("upload.max_size_bytes must be greater than 0"));
+        }
+
+        if self.trash.retention_seconds < 0 {
+            errors.push(String::from("trash.retention_seconds must not be negative"));
+        }
+
+        if self.sharing.max_failed_attempts <= 0
+            || self.sharing.attempt_window_seconds <= 0
+            || self.sharing.lockout_seconds <= 0
+        {
+            errors.push(String::from(
+                "sharing.max_failed_attempts, sharing.attempt_window_seconds and sharing.lockout_seconds must be greater than 0",
+            ));
+        }
+
+        if self.storage.cleanup_interval_seconds == 0 {
+            errors.push(String::from(
+                "storage.cleanup_interval_seconds must be greater than 0",
+            ));
+        }
+
+        match self.storage.backend.as_str() {
+            "local" => {
+                if self.storage.local.root.is_empty() {
+                    errors.push(String::from("storage.local.root must not be empty"));
+                }
+            }
+            "s3" => {
+                let s3 = &self.storage.s3;
+                if !s3.endpoint.starts_with("http://") && !s3.endpoint.starts_with("https://") {
+                    errors.push(format!(
+                        "storage.s3.endpoint must be an absolute http(s) url, got {:?}",
+                        s3.endpoint
+                    ));
+                }
+                if s3.region.is_empty() {
+                    errors.push(String::from("storage.s3.region must not be empty"));
+                }
+                if s3.bucket.is_empty() {
+                    errors.push(String::from("storage.s3.bucket must not be empty"));
+                }
+                if s3.access_key_id.is_empty() || s3.secret_access_key.is_empty() {
+                    errors.push(String::from(
+                        "storage.s3.access_key_id and storage.s3.secret_access_key must be set",
+                    ));
+                }
+            }
+            backend => {
+                errors.push(format!(
+                    "storage.backend must be one of local or s3, got {}",
+                    backend
+                ));
+            }
+        }
+
+        if self.session.access_token_lifetime_seconds <= 0 {
+            errors.push(String::from(
+                "session.access_token_lifetime_seconds must be greater than 0",
+            ));
+        }
+        if self.session.refresh_token_lifetime_seconds < self.session.access_token_lifetime_seconds
+        {
+            errors.push(String::from(
+                "session.refresh_token_lifetime_seconds must not be shorter than the access token lifetime",
+            ));
+        }
+
+        if !["Strict", "Lax", "None"].contains(&self.cookie.same_site.as_str()) {
+            errors.push(format!(
+                "cookie.same_site must be one of Strict, Lax or None, got {}",
+                self.cookie.same_site
+            ));
+        }
+        if self.cookie.same_site == "None" && !self.cookie.secure {
+            errors.push(String::from(
+                "cookie.secure must be enabled when cookie.same_site is None",
+            ));
+        }
+
+        if !self
+            .jwt
+            .keys
+            .iter()
+            .any(|key| key.kid == self.jwt.active_key_id)
+        {
+            errors.push(format!(
+                "jwt.keys must contain the active key {}, set JWT_KEY_{}_SECRET or add it to the config file",
+                self.jwt.active_key_id,
+                self.jwt.active_key_id.to_uppercase()
+            ));
+        }
+
+        if !errors.is_empty() {
+            bail!("invalid configuration:\n  - {}", errors.join("\n  - "))
+        }
+
+        Ok(())
+    }
+}
+
+fn override_value<T>(env: &HashMap<String, String>, name: &str, value: &mut T) -> Result<()>
+where
+    T: FromStr,
+    T::Err: std::fmt::Display,
+{
+    if let Some(raw) = env.get(name) {
+        *value = raw
+            .parse()
+            .map_err(|err| anyhow!("invalid value {:?} for {}: {}", raw, name, err))?;
+    }
+
+    Ok(())
+}
+
+fn merge_table(base: &mut toml::Table, layer: toml::Table) {
+    for (key, value) in layer {
+        match (base.get_mut(&key), value) {
+            (Some(toml::Value::Table(base_table)), toml::Value::Table(layer_table)) => {
+                merge_table(base_table, layer_table)
+            }
+            (_, value) => {
+                base.insert(key, value);
+            }
+        }
+    }
+}
@@ -4,6 +4,8 @@ use sqlx::types::Uuid;
 pub struct Customer {
     id: Uuid,
     username: String,
+    // NOTE: None falls back to upload.max_size_bytes of the server config
+    max_upload_size_bytes: Option<u64>,
 }
 
 impl Customer {
@@ -11,6 +13,7 @@ impl Customer {
         Customer {
             id: Uuid::default(),
             username: username.to_string(),
+            max_upload_size_bytes: None,
         }
     }
 
@@ -18,6 +21,7 @@ impl Customer {
         Customer {
             id: id.clone(),
             username: username.to_string(),
+            max_upload_size_bytes: None,
         }
     }
 
@@ -28,4 +32,12 @@ impl Customer {
     pub fn get_username(&self) -> String {
         self.username.clone()
     }
+
+    pub fn get_max_upload_size_bytes(&self) -> Option<u64> {
+        self.max_upload_size_bytes
+    }
+
+    pub fn set_max_upload_size_bytes(&mut self, max_upload_size_bytes: Option<u64>) {
+        self.max_upload_size_bytes = max_upload_size_bytes;
+    }
 }
@@ -0,0 +1,117 @@
+use chrono::{DateTime, Utc};
+use uuid::Uuid;
+
+use super::file_meta::FileMeta;
+
+#[derive(PartialEq, Eq, Clone, Copy, Debug, serde::Serialize, serde::Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum FilePermission {
+    Read,
+    ReadWrite,
+}
+
+impl FilePermission {
+    pub fn from_name(name: &str) -> Option<FilePermission> {
+        match name {
+            "read" => Some(FilePermission::Read),
+            "read_write" => Some(FilePermission::ReadWrite),
+            _ => None,
+        }
+    }
+
+    pub fn get_name(&self) -> &'static str {
+        match self {
+            FilePermission::Read => "read",
+            FilePermission::ReadWrite => "read_write",
+        }
+    }
+
+    // NOTE: read_write covers everything read does
+    pub fn allows(&self, permission: FilePermission) -> bool {
+        match self {
+            FilePermission::Read => permission == FilePermission::Read,
+            FilePermission::ReadWrite => true,
+        }
+    }
+}
+
+// NOTE: a file of one customer made accessible to another one, the owner never needs a grant
+#[derive(PartialEq, Clone, Debug)]
+pub struct FileGrant {
+    file_id: Uuid,
+    customer_id: Uuid,
+    username: String,
+    permission: FilePermission,
+    granted_at: DateTime<Utc>,
+}
+
+impl FileGrant {
+    pub fn new_full(
+        file_id: &Uuid,
+        customer_id: &Uuid,
+        username: &str,
+        permission: FilePermission,
+        granted_at: &DateTime<Utc>,
+    ) -> FileGrant {
+        FileGrant {
+            file_id: *file_id,
+            customer_id: *customer_id,
+            username: username.to_string(),
+            permission,
+            granted_at: *granted_at,
+        }
+    }
+
+    // NOTE: the customer the file is granted to
+    pub fn get_customer_id(&self) -> Uuid {
+        self.customer_id
+    }
+
+    pub fn get_username(&self) -> String {
+        self.username.clone()
+    }
+
+    pub fn get_permission(&self) -> FilePermission {
+        self.permission
+    }
+
+    pub fn get_granted_at(&self) -> DateTime<Utc> {
+        self.granted_at
+    }
+}
+
+// NOTE: a file granted to the customer, as the customer sees it
+#[derive(PartialEq, Clone, Debug)]
+pub struct SharedFile {
+    file_meta: FileMeta,
+    owner_username: String,
+    permission: FilePermission,
+    granted_at: DateTime<Utc>,
+}
+
+impl SharedFile {
+    pub fn new(file_meta: FileMeta, owner_username: &str, permission: FilePermission, granted_at: &DateTime<Utc>) -> SharedFile {
+        SharedFile {
+            file_meta,
+            owner_username: owner_username.to_string(),
+            permission,
+            granted_at: *granted_at,
+        }
+    }
+
+    pub fn get_file_meta(&self) -> &FileMeta {
+        &self.file_meta
+    }
+
+    pub fn get_owner_username(&self) -> String {
+        self.owner_username.clone()
+    }
+
+    pub fn get_permission(&self) -> FilePermission {
+        self.permission
+    }
+
+    pub fn get_granted_at(&self) -> DateTime<Utc> {
+        self.granted_at
+    }
+}
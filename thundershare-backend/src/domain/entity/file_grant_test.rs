@@ -0,0 +1,24 @@
+use super::file_grant::FilePermission;
+
+#[test]
+fn test_file_permission_name() {
+    for permission in [FilePermission::Read, FilePermission::ReadWrite] {
+        assert_eq!(FilePermission::from_name(permission.get_name()), Some(permission));
+    }
+    assert_eq!(FilePermission::from_name("write"), None);
+}
+
+#[test]
+fn test_file_permission_allows() {
+    // NOTE: permission granted, permission needed, expected
+    let test_context = vec![
+        (FilePermission::Read, FilePermission::Read, true),
+        (FilePermission::Read, FilePermission::ReadWrite, false),
+        (FilePermission::ReadWrite, FilePermission::Read, true),
+        (FilePermission::ReadWrite, FilePermission::ReadWrite, true),
+    ];
+
+    for (granted, needed, expected) in test_context {
+        assert_eq!(granted.allows(needed), expected);
+    }
+}
@@ -0,0 +1,192 @@
+use base64::{engine::general_purpose::URL_SAFE_NO_PAD, Engine};
+use chrono::{DateTime, Utc};
+use uuid::Uuid;
+
+use super::file_meta::FileMeta;
+
+#[derive(PartialEq, Clone, Copy, Debug, Default, serde::Serialize, serde::Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum FileMetaSortKey {
+    Name,
+    Size,
+    #[default]
+    UploadedAt,
+}
+
+#[derive(PartialEq, Clone, Copy, Debug, Default, serde::Serialize, serde::Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SortOrder {
+    Asc,
+    #[default]
+    Desc,
+}
+
+// NOTE: every bound is inclusive except uploaded_before, so adjacent date ranges never overlap
+#[derive(PartialEq, Clone, Debug, Default)]
+pub struct FileMetaFilter {
+    mime_type_prefix: Option<String>,
+    min_size: Option<i64>,
+    max_size: Option<i64>,
+    uploaded_after: Option<DateTime<Utc>>,
+    uploaded_before: Option<DateTime<Utc>>,
+}
+
+impl FileMetaFilter {
+    pub fn new(
+        mime_type_prefix: Option<String>,
+        min_size: Option<i64>,
+        max_size: Option<i64>,
+        uploaded_after: Option<DateTime<Utc>>,
+        uploaded_before: Option<DateTime<Utc>>,
+    ) -> FileMetaFilter {
+        FileMetaFilter {
+            mime_type_prefix,
+            min_size,
+            max_size,
+            uploaded_after,
+            uploaded_before,
+        }
+    }
+
+    pub fn get_mime_type_prefix(&self) -> Option<String> {
+        self.mime_type_prefix.clone()
+    }
+
+    pub fn get_min_size(&self) -> Option<i64> {
+        self.min_size
+    }
+
+    pub fn get_max_size(&self) -> Option<i64> {
+        self.max_size
+    }
+
+    pub fn get_uploaded_after(&self) -> Option<DateTime<Utc>> {
+        self.uploaded_after
+    }
+
+    pub fn get_uploaded_before(&self) -> Option<DateTime<Utc>> {
+        self.uploaded_before
+    }
+}
+
+#[derive(PartialEq, Clone, Debug, serde::Serialize, serde::Deserialize)]
+#[serde(tag = "key", content = "value", rename_all = "snake_case")]
+pub enum FileMetaCursorValue {
+    Name(String),
+    Size(i64),
+    UploadedAt(DateTime<Utc>),
+}
+
+// NOTE: the sort value and id of the last file of a page, the next page starts right after it.
+//       clients only ever see the encoded form and hand it back as is.
+#[derive(PartialEq, Clone, Debug, serde::Serialize, serde::Deserialize)]
+pub struct FileMetaCursor {
+    value: FileMetaCursorValue,
+    order: SortOrder,
+    id: Uuid,
+}
+
+impl FileMetaCursor {
+    pub fn new(file_meta: &FileMeta, sort_key: FileMetaSortKey, order: SortOrder) -> FileMetaCursor {
+        let content = file_meta.get_content();
+        let value = match sort_key {
+            FileMetaSortKey::Name => FileMetaCursorValue::Name(content.get_name()),
+            FileMetaSortKey::Size => FileMetaCursorValue::Size(content.get_size()),
+            FileMetaSortKey::UploadedAt => FileMetaCursorValue::UploadedAt(file_meta.get_uploaded_at()),
+        };
+
+        FileMetaCursor {
+            value,
+            order,
+            id: file_meta.get_id(),
+        }
+    }
+
+    pub fn get_value(&self) -> &FileMetaCursorValue {
+        &self.value
+    }
+
+    pub fn get_id(&self) -> Uuid {
+        self.id
+    }
+
+    pub fn get_sort_key(&self) -> FileMetaSortKey {
+        match self.value {
+            FileMetaCursorValue::Name(_) => FileMetaSortKey::Name,
+            FileMetaCursorValue::Size(_) => FileMetaSortKey::Size,
+            FileMetaCursorValue::UploadedAt(_) => FileMetaSortKey::UploadedAt,
+        }
+    }
+
+    pub fn get_order(&self) -> SortOrder {
+        self.order
+    }
+
+    pub fn encode(&self) -> String {
+        URL_SAFE_NO_PAD.encode(serde_json::to_vec(self).unwrap_or_default())
+    }
+
+    pub fn decode(cursor: &str) -> Option<FileMetaCursor> {
+        let json = URL_SAFE_NO_PAD.decode(cursor).ok()?;
+        serde_json::from_slice(&json).ok()
+    }
+}
+
+#[derive(PartialEq, Clone, Debug, Default)]
+pub struct FileMetaListQuery {
+    sort_key: FileMetaSortKey,
+    order: SortOrder,
+    filter: FileMetaFilter,
+    cursor: Option<FileMetaCursor>,
+}
+
+impl FileMetaListQuery {
+    pub fn new(sort_key: FileMetaSortKey, order: SortOrder, filter: FileMetaFilter, cursor: Option<FileMetaCursor>) -> FileMetaListQuery {
+        FileMetaListQuery {
+            sort_key,
+            order,
+            filter,
+            cursor,
+        }
+    }
+
+    pub fn get_sort_key(&self) -> FileMetaSortKey {
+        self.sort_key
+    }
+
+    pub fn get_order(&self) -> SortOrder {
+        self.order
+    }
+
+    pub fn get_filter(&self) -> &FileMetaFilter {
+        &self.filter
+    }
+
+    pub fn get_cursor(&self) -> Option<FileMetaCursor> {
+        self.cursor.clone()
+    }
+}
+
+// NOTE: next_cursor is None on the last page
+#[derive(PartialEq, Clone, Debug)]
+pub struct FileMetaPage {
+    file_meta_list: Vec<FileMeta>,
+    next_cursor: Option<FileMetaCursor>,
+}
+
+impl FileMetaPage {
+    pub fn new(file_meta_list: Vec<FileMeta>, next_cursor: Option<FileMetaCursor>) -> FileMetaPage {
+        FileMetaPage {
+            file_meta_list,
+            next_cursor,
+        }
+    }
+
+    pub fn get_file_meta_list(&self) -> Vec<FileMeta> {
+        self.file_meta_list.clone()
+    }
+
+    pub fn get_next_cursor(&self) -> Option<FileMetaCursor> {
+        self.next_cursor.clone()
+    }
+}
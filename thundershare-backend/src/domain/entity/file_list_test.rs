@@ -0,0 +1,36 @@
+use chrono::{TimeZone, Utc};
+use uuid::uuid;
+
+use super::file_list::{FileMetaCursor, FileMetaCursorValue, FileMetaSortKey, SortOrder};
+use super::file_meta::{FileContent, FileMeta};
+
+#[test]
+fn test_file_meta_cursor_round_trip() {
+    let id = uuid!("67e55044-10b1-426f-9247-bb680e5fe0c8");
+    let uploaded_at = Utc.with_ymd_and_hms(1990, 3, 3, 0, 0, 0).unwrap();
+    let content = FileContent::new("報告.pdf", 1024, "application/pdf", "");
+    let file_meta = FileMeta::new_with_content(&id, &id, "stored-key", &content, &uploaded_at);
+
+    let test_context = vec![
+        (FileMetaSortKey::Name, FileMetaCursorValue::Name(String::from("報告.pdf"))),
+        (FileMetaSortKey::Size, FileMetaCursorValue::Size(1024)),
+        (FileMetaSortKey::UploadedAt, FileMetaCursorValue::UploadedAt(uploaded_at)),
+    ];
+
+    for (sort_key, expected_value) in test_context {
+        let cursor = FileMetaCursor::new(&file_meta, sort_key, SortOrder::Asc);
+        assert_eq!(cursor.get_value(), &expected_value);
+        assert_eq!(cursor.get_sort_key(), sort_key);
+
+        let encoded = cursor.encode();
+        assert!(encoded.chars().all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_'));
+        assert_eq!(FileMetaCursor::decode(&encoded), Some(cursor));
+    }
+}
+
+#[test]
+fn test_file_meta_cursor_decode_invalid() {
+    for cursor in ["", "not a cursor", "eyJ2YWx1ZSI6MX0"] {
+        assert_eq!(FileMetaCursor::decode(cursor), None);
+    }
+}
@@ -1,30 +1,51 @@
 use chrono::{DateTime, Utc};
+use rand::rngs::OsRng;
+use rand::Rng;
 use uuid::Uuid;
 
+use super::password::{verify_password, PasswordVerifyResult};
+
+const SHARING_CODE_ALPHABET: &[u8] = b"0123456789ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz";
+
+// NOTE: 22 base62 characters carry about 131 bits of entropy, slightly more than a uuid v4
+pub const SHARING_CODE_LENGTH: usize = 22;
+
+pub fn generate_sharing_code() -> String {
+    (0..SHARING_CODE_LENGTH)
+        .map(|_| SHARING_CODE_ALPHABET[OsRng.gen_range(0..SHARING_CODE_ALPHABET.len())] as char)
+        .collect()
+}
+
 #[derive(PartialEq, Clone, Debug, serde::Serialize, serde::Deserialize)]
 pub struct FileSharingMeta {
     id: Uuid,
     file_id: Uuid,
+    code: String,
     link: String,
     #[serde(with = "chrono::serde::ts_seconds")]
     expireat: DateTime<Utc>,
     password: Option<String>,
+    max_downloads: Option<i32>,
+    downloads: i32,
 }
 
 impl FileSharingMeta {
     pub fn new_full(
         id: &Uuid,
         file_id: &Uuid,
-        link: &str,
+        code: &str,
         expireat: &DateTime<Utc>,
         password: &Option<String>,
     ) -> FileSharingMeta {
         FileSharingMeta {
             id: id.clone(),
             file_id: file_id.clone(),
-            link: link.to_string(),
+            code: code.to_string(),
+            link: format!("/s/{}", code),
             expireat: expireat.clone(),
             password: password.clone(),
+            max_downloads: None,
+            downloads: 0,
         }
     }
 
@@ -36,10 +57,15 @@ impl FileSharingMeta {
         self.file_id
     }
 
+    // NOTE: relative to the server until set_public_base_url is called
     pub fn get_link(&self) -> String {
         self.link.clone()
     }
 
+    pub fn set_public_base_url(&mut self, public_base_url: &str) {
+        self.link = format!("{}/s/{}", public_base_url.trim_end_matches('/'), self.code);
+    }
+
     pub fn get_expireat(&self) -> DateTime<Utc> {
         self.expireat
     }
@@ -48,36 +74,169 @@ impl FileSharingMeta {
         self.expireat < *curr_time
     }
 
-    pub fn is_password_correct(&self, password: &str) -> bool {
-        match self.password.clone() {
-            Some(p) => p == password,
-            None => true
+    // NOTE: a link without max_downloads can be downloaded any number of times
+    pub fn get_max_downloads(&self) -> Option<i32> {
+        self.max_downloads
+    }
+
+    pub fn get_downloads(&self) -> i32 {
+        self.downloads
+    }
+
+    pub fn set_downloads(&mut self, max_downloads: &Option<i32>, downloads: i32) {
+        self.max_downloads = *max_downloads;
+        self.downloads = downloads;
+    }
+
+    pub fn is_exhausted(&self) -> bool {
+        match self.max_downloads {
+            Some(max_downloads) => self.downloads >= max_downloads,
+            None => false,
+        }
+    }
+
+    pub fn has_password(&self) -> bool {
+        self.password.is_some()
+    }
+
+    // NOTE: the stored password is an argon2 hash, a link without password accepts any
+    pub fn check_password(&self, password: &str) -> PasswordVerifyResult {
+        match &self.password {
+            Some(stored) => verify_password(password, stored),
+            None => PasswordVerifyResult::Matched,
         }
     }
 
 }
 
+pub const DEFAULT_MIME_TYPE: &str = "application/octet-stream";
+
+// NOTE: signatures of the formats people commonly share, checked against the first bytes of an
+//       upload. anything else falls back to the file extension and finally to the declared type.
+const MIME_SIGNATURES: &[(&[u8], &str)] = &[
+    (b"\x89PNG\r\n\x1a\n", "image/png"),
+    (b"\xff\xd8\xff", "image/jpeg"),
+    (b"GIF87a", "image/gif"),
+    (b"GIF89a", "image/gif"),
+    (b"%PDF-", "application/pdf"),
+    (b"PK\x03\x04", "application/zip"),
+    (b"\x1f\x8b", "application/gzip"),
+    (b"7z\xbc\xaf\x27\x1c", "application/x-7z-compressed"),
+    (b"Rar!\x1a\x07", "application/vnd.rar"),
+    (b"ID3", "audio/mpeg"),
+    (b"OggS", "audio/ogg"),
+    (b"fLaC", "audio/flac"),
+    (b"\x1aE\xdf\xa3", "video/webm"),
+];
+
+pub fn sniff_mime_type(head: &[u8], name: &str, declared_mime_type: Option<&str>) -> String {
+    for (signature, mime_type) in MIME_SIGNATURES {
+        if head.starts_with(signature) {
+            return mime_type.to_string();
+        }
+    }
+
+    if head.len() >= 12 && &head[0..4] == b"RIFF" {
+        match &head[8..12] {
+            b"WEBP" => return String::from("image/webp"),
+            b"WAVE" => return String::from("audio/wav"),
+            _ => {}
+        }
+    }
+
+    if head.len() >= 12 && &head[4..8] == b"ftyp" {
+        return String::from("video/mp4");
+    }
+
+    if let Some(mime_type) = mime_guess::from_path(name).first() {
+        return mime_type.essence_str().to_string();
+    }
+
+    match declared_mime_type {
+        Some(mime_type) if mime_type.parse::<mime_guess::mime::Mime>().is_ok() => mime_type.to_string(),
+        _ => String::from(DEFAULT_MIME_TYPE),
+    }
+}
+
+// NOTE: what is known about the bytes of an upload, the original name is only informative and
+//       never used to build a path.
+#[derive(PartialEq, Clone, Debug, serde::Serialize, serde::Deserialize)]
+pub struct FileContent {
+    name: String,
+    size: i64,
+    mime_type: String,
+    sha256: String,
+}
+
+impl Default for FileContent {
+    fn default() -> FileContent {
+        FileContent {
+            name: String::new(),
+            size: 0,
+            mime_type: String::from(DEFAULT_MIME_TYPE),
+            sha256: String::new(),
+        }
+    }
+}
+
+impl FileContent {
+    pub fn new(name: &str, size: i64, mime_type: &str, sha256: &str) -> FileContent {
+        FileContent {
+            name: name.to_string(),
+            size,
+            mime_type: mime_type.to_string(),
+            sha256: sha256.to_string(),
+        }
+    }
+
+    pub fn get_name(&self) -> String {
+        self.name.clone()
+    }
+
+    pub fn get_size(&self) -> i64 {
+        self.size
+    }
+
+    pub fn get_mime_type(&self) -> String {
+        self.mime_type.clone()
+    }
+
+    pub fn get_sha256(&self) -> String {
+        self.sha256.clone()
+    }
+}
+
 #[derive(PartialEq, Clone, Debug, serde::Serialize, serde::Deserialize)]
 pub struct FileMeta {
     id: Uuid,
     customer_id: Uuid,
     url: String,
+    content: FileContent,
+    #[serde(with = "chrono::serde::ts_seconds")]
+    uploaded_at: DateTime<Utc>,
+    folder_id: Option<Uuid>,
+    #[serde(with = "chrono::serde::ts_seconds_option")]
+    deleted_at: Option<DateTime<Utc>>,
+    file_request_id: Option<Uuid>,
 }
 
 impl FileMeta {
-    pub fn new(url: &str) -> FileMeta {
-        FileMeta {
-            id: Uuid::default(),
-            customer_id: Uuid::default(),
-            url: url.to_string(),
-        }
-    }
-
-    pub fn new_full(id: &Uuid, customer_id: &Uuid, url: &str) -> FileMeta {
+    pub fn new_with_content(
+        id: &Uuid,
+        customer_id: &Uuid,
+        url: &str,
+        content: &FileContent,
+        uploaded_at: &DateTime<Utc>,
+    ) -> FileMeta {
         FileMeta {
-            id: id.clone(),
-            customer_id: customer_id.clone(),
+            id: *id,
+            customer_id: *customer_id,
             url: url.to_string(),
+            content: content.clone(),
+            uploaded_at: *uploaded_at,
+            folder_id: None,
+            deleted_at: None,
+            file_request_id: None,
         }
     }
 
@@ -92,4 +251,51 @@ impl FileMeta {
     pub fn get_url(&self) -> String {
         self.url.clone()
     }
+
+    pub fn get_content(&self) -> &FileContent {
+        &self.content
+    }
+
+    pub fn get_uploaded_at(&self) -> DateTime<Utc> {
+        self.uploaded_at
+    }
+
+    // NOTE: None for the files at the root of the customer
+    pub fn get_folder_id(&self) -> Option<Uuid> {
+        self.folder_id
+    }
+
+    pub fn set_folder_id(&mut self, folder_id: Option<Uuid>) {
+        self.folder_id = folder_id;
+    }
+
+    // NOTE: Some while the file is in the trash
+    pub fn get_deleted_at(&self) -> Option<DateTime<Utc>> {
+        self.deleted_at
+    }
+
+    pub fn set_deleted_at(&mut self, deleted_at: Option<DateTime<Utc>>) {
+        self.deleted_at = deleted_at;
+    }
+
+    pub fn is_trashed(&self) -> bool {
+        self.deleted_at.is_some()
+    }
+
+    // NOTE: Some for a file an outsider uploaded through a file request of the customer
+    pub fn get_file_request_id(&self) -> Option<Uuid> {
+        self.file_request_id
+    }
+
+    pub fn set_file_request_id(&mut self, file_request_id: Option<Uuid>) {
+        self.file_request_id = file_request_id;
+    }
+
+    // NOTE: files uploaded before names were recorded are offered under their id
+    pub fn get_download_name(&self) -> String {
+        match self.content.get_name() {
+            name if name.is_empty() => self.id.to_string(),
+            name => name,
+        }
+    }
 }
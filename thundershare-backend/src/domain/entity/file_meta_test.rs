@@ -0,0 +1,124 @@
+use chrono::{DateTime, TimeZone, Utc};
+use uuid::Uuid;
+
+use super::password::{hash_password, PasswordVerifyResult};
+use super::file_meta::{
+    generate_sharing_code, sniff_mime_type, FileContent, FileMeta, FileSharingMeta,
+    SHARING_CODE_LENGTH,
+};
+
+#[test]
+fn test_generate_sharing_code() {
+    let code = generate_sharing_code();
+    assert_eq!(code.len(), SHARING_CODE_LENGTH);
+    assert!(code.chars().all(|c| c.is_ascii_alphanumeric()));
+    assert_ne!(code, generate_sharing_code());
+}
+
+#[test]
+fn test_file_sharing_meta_link() {
+    let expireat = Utc.with_ymd_and_hms(1990, 3, 3, 0, 0, 0).unwrap();
+    let mut file_sharing_meta = FileSharingMeta::new_full(
+        &Uuid::default(),
+        &Uuid::default(),
+        "abc123",
+        &expireat,
+        &None,
+    );
+    assert_eq!(file_sharing_meta.get_link(), "/s/abc123");
+
+    file_sharing_meta.set_public_base_url("https://thundershare.test/");
+    assert_eq!(
+        file_sharing_meta.get_link(),
+        "https://thundershare.test/s/abc123"
+    );
+}
+
+#[test]
+fn test_sniff_mime_type() {
+    let test_context = vec![
+        (
+            &b"\x89PNG\r\n\x1a\n...."[..],
+            "photo.txt",
+            None,
+            "image/png",
+        ),
+        (
+            &b"%PDF-1.7"[..],
+            "report",
+            Some("text/plain"),
+            "application/pdf",
+        ),
+        (&b"RIFF\0\0\0\0WEBPVP8 "[..], "", None, "image/webp"),
+        (&b"hello world"[..], "notes.txt", None, "text/plain"),
+        (
+            &b"hello world"[..],
+            "notes",
+            Some("text/markdown"),
+            "text/markdown",
+        ),
+        (
+            &b"hello world"[..],
+            "notes",
+            Some("not a mime"),
+            "application/octet-stream",
+        ),
+        (&b""[..], "", None, "application/octet-stream"),
+    ];
+
+    for (head, name, declared_mime_type, expected) in test_context {
+        assert_eq!(sniff_mime_type(head, name, declared_mime_type), expected);
+    }
+}
+
+#[test]
+fn test_file_meta_download_name() {
+    let id = Uuid::new_v4();
+    let uploaded_at = Utc.with_ymd_and_hms(1990, 3, 3, 0, 0, 0).unwrap();
+
+    let legacy = FileMeta::new_with_content(&id, &Uuid::default(), "", &FileContent::default(), &DateTime::<Utc>::default());
+    assert_eq!(legacy.get_download_name(), id.to_string());
+
+    let content = FileContent::new("report.pdf", 8, "application/pdf", "");
+    let file_meta = FileMeta::new_with_content(&id, &Uuid::default(), "", &content, &uploaded_at);
+    assert_eq!(file_meta.get_download_name(), "report.pdf");
+}
+
+#[test]
+fn test_file_sharing_meta_check_password() {
+    let expireat = Utc.with_ymd_and_hms(1990, 3, 3, 0, 0, 0).unwrap();
+    let hashed = hash_password("hunter2").unwrap();
+
+    let test_context = vec![
+        (None, "", PasswordVerifyResult::Matched),
+        (None, "hunter2", PasswordVerifyResult::Matched),
+        (Some(hashed.clone()), "hunter2", PasswordVerifyResult::Matched),
+        (Some(hashed), "", PasswordVerifyResult::Mismatched),
+        (Some(String::from("hunter2")), "hunter2", PasswordVerifyResult::MatchedLegacy),
+    ];
+
+    for (password, input, expected) in test_context {
+        let file_sharing_meta = FileSharingMeta::new_full(&Uuid::default(), &Uuid::default(), "", &expireat, &password);
+        assert_eq!(file_sharing_meta.check_password(input), expected);
+    }
+}
+
+#[test]
+fn test_file_sharing_meta_is_exhausted() {
+    let expireat = Utc.with_ymd_and_hms(1990, 3, 3, 0, 0, 0).unwrap();
+
+    let test_context = vec![
+        (None, 0, false),
+        (None, 100, false),
+        (Some(1), 0, false),
+        (Some(1), 1, true),
+        (Some(3), 2, false),
+        (Some(3), 4, true),
+    ];
+
+    for (max_downloads, downloads, expected) in test_context {
+        let mut file_sharing_meta = FileSharingMeta::new_full(&Uuid::default(), &Uuid::default(), "", &expireat, &None);
+        file_sharing_meta.set_downloads(&max_downloads, downloads);
+        assert_eq!(file_sharing_meta.is_exhausted(), expected);
+    }
+}
@@ -0,0 +1,112 @@
+use chrono::{DateTime, Utc};
+use uuid::Uuid;
+
+use super::password::{verify_password, PasswordVerifyResult};
+
+// NOTE: the reverse of a sharing link, anyone holding the code may upload files into the account
+//       of the customer, nothing in the account is ever readable through it.
+#[derive(PartialEq, Clone, Debug)]
+pub struct FileRequest {
+    id: Uuid,
+    customer_id: Uuid,
+    folder_id: Option<Uuid>,
+    code: String,
+    link: String,
+    expireat: DateTime<Utc>,
+    password: Option<String>,
+    max_files: Option<i32>,
+    max_size_bytes: i64,
+    uploads: i32,
+}
+
+impl FileRequest {
+    pub fn new_full(
+        id: &Uuid,
+        customer_id: &Uuid,
+        folder_id: &Option<Uuid>,
+        code: &str,
+        expireat: &DateTime<Utc>,
+        password: &Option<String>,
+    ) -> FileRequest {
+        FileRequest {
+            id: *id,
+            customer_id: *customer_id,
+            folder_id: *folder_id,
+            code: code.to_string(),
+            link: format!("/r/{}", code),
+            expireat: *expireat,
+            password: password.clone(),
+            max_files: None,
+            max_size_bytes: 0,
+            uploads: 0,
+        }
+    }
+
+    pub fn get_id(&self) -> Uuid {
+        self.id
+    }
+
+    pub fn get_customer_id(&self) -> Uuid {
+        self.customer_id
+    }
+
+    // NOTE: None for a request whose files end up at the root of the customer
+    pub fn get_folder_id(&self) -> Option<Uuid> {
+        self.folder_id
+    }
+
+    // NOTE: relative to the server until set_public_base_url is called
+    pub fn get_link(&self) -> String {
+        self.link.clone()
+    }
+
+    pub fn set_public_base_url(&mut self, public_base_url: &str) {
+        self.link = format!("{}/r/{}", public_base_url.trim_end_matches('/'), self.code);
+    }
+
+    pub fn get_expireat(&self) -> DateTime<Utc> {
+        self.expireat
+    }
+
+    pub fn is_expired(&self, curr_time: &DateTime<Utc>) -> bool {
+        self.expireat < *curr_time
+    }
+
+    // NOTE: a request without max_files takes any number of files
+    pub fn get_max_files(&self) -> Option<i32> {
+        self.max_files
+    }
+
+    pub fn get_max_size_bytes(&self) -> i64 {
+        self.max_size_bytes
+    }
+
+    pub fn get_uploads(&self) -> i32 {
+        self.uploads
+    }
+
+    pub fn set_limits(&mut self, max_files: &Option<i32>, max_size_bytes: i64, uploads: i32) {
+        self.max_files = *max_files;
+        self.max_size_bytes = max_size_bytes;
+        self.uploads = uploads;
+    }
+
+    pub fn is_full(&self) -> bool {
+        match self.max_files {
+            Some(max_files) => self.uploads >= max_files,
+            None => false,
+        }
+    }
+
+    pub fn has_password(&self) -> bool {
+        self.password.is_some()
+    }
+
+    // NOTE: the stored password is an argon2 hash, a request without password accepts any
+    pub fn check_password(&self, password: &str) -> PasswordVerifyResult {
+        match &self.password {
+            Some(stored) => verify_password(password, stored),
+            None => PasswordVerifyResult::Matched,
+        }
+    }
+}
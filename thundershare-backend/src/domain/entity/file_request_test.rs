@@ -0,0 +1,62 @@
+use chrono::{TimeZone, Utc};
+use uuid::Uuid;
+
+use super::file_request::FileRequest;
+use super::password::{hash_password, PasswordVerifyResult};
+
+#[test]
+fn test_file_request_link() {
+    let expireat = Utc.with_ymd_and_hms(1990, 3, 3, 0, 0, 0).unwrap();
+    let mut file_request = FileRequest::new_full(&Uuid::default(), &Uuid::default(), &None, "abc123", &expireat, &None);
+    assert_eq!(file_request.get_link(), "/r/abc123");
+
+    file_request.set_public_base_url("https://thundershare.test/");
+    assert_eq!(file_request.get_link(), "https://thundershare.test/r/abc123");
+}
+
+#[test]
+fn test_file_request_is_expired() {
+    let expireat = Utc.with_ymd_and_hms(1990, 3, 3, 0, 0, 0).unwrap();
+    let file_request = FileRequest::new_full(&Uuid::default(), &Uuid::default(), &None, "abc123", &expireat, &None);
+
+    assert!(!file_request.is_expired(&Utc.with_ymd_and_hms(1990, 3, 2, 0, 0, 0).unwrap()));
+    assert!(!file_request.is_expired(&expireat));
+    assert!(file_request.is_expired(&Utc.with_ymd_and_hms(1990, 3, 4, 0, 0, 0).unwrap()));
+}
+
+#[test]
+fn test_file_request_is_full() {
+    let expireat = Utc.with_ymd_and_hms(1990, 3, 3, 0, 0, 0).unwrap();
+
+    // NOTE: max files, files uploaded so far, expected
+    let test_context = vec![
+        (None, 100, false),
+        (Some(3), 2, false),
+        (Some(3), 3, true),
+        (Some(1), 2, true),
+    ];
+
+    for (max_files, uploads, expected) in test_context {
+        let mut file_request = FileRequest::new_full(&Uuid::default(), &Uuid::default(), &None, "abc123", &expireat, &None);
+        file_request.set_limits(&max_files, 1024, uploads);
+        assert_eq!(file_request.is_full(), expected);
+    }
+}
+
+#[test]
+fn test_file_request_check_password() {
+    let expireat = Utc.with_ymd_and_hms(1990, 3, 3, 0, 0, 0).unwrap();
+    let password_hash = hash_password("secret").unwrap();
+
+    let test_context = vec![
+        (None, "anything", PasswordVerifyResult::Matched),
+        (Some(password_hash.clone()), "secret", PasswordVerifyResult::Matched),
+        (Some(password_hash), "wrong", PasswordVerifyResult::Mismatched),
+    ];
+
+    for (password, input, expected) in test_context {
+        let file_request = FileRequest::new_full(&Uuid::default(), &Uuid::default(), &None, "abc123", &expireat, &password);
+        assert_eq!(file_request.has_password(), password.is_some());
+        assert_eq!(file_request.check_password(input), expected);
+    }
+}
@@ -0,0 +1,95 @@
+use chrono::{DateTime, Utc};
+use uuid::Uuid;
+
+use super::file_meta::FileMeta;
+
+const FOLDER_NAME_MAX_LENGTH: usize = 255;
+
+#[derive(PartialEq, Clone, Debug)]
+pub struct Folder {
+    id: Uuid,
+    customer_id: Uuid,
+    parent_id: Option<Uuid>,
+    name: String,
+    created_at: DateTime<Utc>,
+}
+
+impl Folder {
+    pub fn new_full(
+        id: &Uuid,
+        customer_id: &Uuid,
+        parent_id: &Option<Uuid>,
+        name: &str,
+        created_at: &DateTime<Utc>,
+    ) -> Folder {
+        Folder {
+            id: *id,
+            customer_id: *customer_id,
+            parent_id: *parent_id,
+            name: name.to_string(),
+            created_at: *created_at,
+        }
+    }
+
+    pub fn get_id(&self) -> Uuid {
+        self.id
+    }
+
+    pub fn get_customer_id(&self) -> Uuid {
+        self.customer_id
+    }
+
+    // NOTE: None for the folders at the root of the customer
+    pub fn get_parent_id(&self) -> Option<Uuid> {
+        self.parent_id
+    }
+
+    pub fn get_name(&self) -> String {
+        self.name.clone()
+    }
+
+    pub fn get_created_at(&self) -> DateTime<Utc> {
+        self.created_at
+    }
+
+    // NOTE: names are shown as path components, so they may neither be empty nor contain a separator
+    pub fn is_name_valid(name: &str) -> bool {
+        !name.trim().is_empty()
+            && name.trim() == name
+            && name.chars().count() <= FOLDER_NAME_MAX_LENGTH
+            && name != "."
+            && name != ".."
+            && !name.contains(['/', '\\'])
+            && !name.chars().any(char::is_control)
+    }
+}
+
+// NOTE: what is directly inside a folder, folder is None for the root of the customer
+#[derive(PartialEq, Clone, Debug)]
+pub struct FolderContent {
+    folder: Option<Folder>,
+    folder_list: Vec<Folder>,
+    file_meta_list: Vec<FileMeta>,
+}
+
+impl FolderContent {
+    pub fn new(folder: Option<Folder>, folder_list: Vec<Folder>, file_meta_list: Vec<FileMeta>) -> FolderContent {
+        FolderContent {
+            folder,
+            folder_list,
+            file_meta_list,
+        }
+    }
+
+    pub fn get_folder(&self) -> Option<Folder> {
+        self.folder.clone()
+    }
+
+    pub fn get_folder_list(&self) -> Vec<Folder> {
+        self.folder_list.clone()
+    }
+
+    pub fn get_file_meta_list(&self) -> Vec<FileMeta> {
+        self.file_meta_list.clone()
+    }
+}
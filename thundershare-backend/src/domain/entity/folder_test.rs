@@ -0,0 +1,25 @@
+use super::folder::Folder;
+
+#[test]
+fn test_folder_name_valid() {
+    let test_context = vec![
+        ("reports", true),
+        ("2024 Q1 報告", true),
+        (".config", true),
+        ("", false),
+        ("   ", false),
+        (" reports", false),
+        ("reports/2024", false),
+        ("reports\\2024", false),
+        (".", false),
+        ("..", false),
+        ("line\nbreak", false),
+    ];
+
+    for (name, expected) in test_context {
+        assert_eq!(Folder::is_name_valid(name), expected, "{}", name);
+    }
+
+    assert!(Folder::is_name_valid(&"a".repeat(255)));
+    assert!(!Folder::is_name_valid(&"a".repeat(256)));
+}
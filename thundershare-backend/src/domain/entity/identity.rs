@@ -1,8 +1,8 @@
 use crate::domain::entity::customer::*;
+use crate::domain::entity::jwt_key::JwtKeySet;
 use anyhow::Result;
 use chrono::prelude::*;
 use chrono::Duration;
-use jsonwebtoken::{decode, encode, DecodingKey, EncodingKey, Header, Validation};
 use log::info;
 use uuid::Uuid;
 
@@ -32,25 +32,14 @@ impl Identity {
         Identity { token }
     }
 
-    pub fn from_string(value: &str) -> Result<Identity> {
-        let result = decode::<CustomerJsonWebToken>(
-            value,
-            // TODO: move to configuration
-            &DecodingKey::from_secret("secret".as_ref()),
-            &Validation::default(),
-        );
-        let jwt = result?;
+    pub fn from_string(value: &str, key_set: &JwtKeySet) -> Result<Identity> {
+        let token = key_set.decode::<CustomerJsonWebToken>(value)?;
 
-        Ok(Identity { token: jwt.claims })
+        Ok(Identity { token })
     }
 
-    pub fn to_string(&self) -> Result<String> {
-        let session_str = encode(
-            &Header::default(),
-            &self.token,
-            // TODO: move to configuration
-            &EncodingKey::from_secret("secret".as_ref()),
-        )?;
+    pub fn to_string(&self, key_set: &JwtKeySet) -> Result<String> {
+        let session_str = key_set.encode(&self.token)?;
 
         Ok(session_str)
     }
@@ -0,0 +1,328 @@
+use std::collections::HashMap;
+
+use anyhow::{anyhow, bail, Result};
+use base64::engine::general_purpose::URL_SAFE_NO_PAD;
+use base64::Engine;
+use jsonwebtoken::jwk::{
+    AlgorithmParameters, CommonParameters, EllipticCurve, Jwk, JwkSet, KeyAlgorithm,
+    OctetKeyPairParameters, OctetKeyPairType, PublicKeyUse, RSAKeyParameters, RSAKeyType,
+};
+use jsonwebtoken::{
+    decode, decode_header, encode, Algorithm, DecodingKey, EncodingKey, Header, Validation,
+};
+use ring::signature::{Ed25519KeyPair, KeyPair, RsaKeyPair};
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+
+#[derive(Debug, PartialEq, Clone, Copy, serde::Deserialize)]
+pub enum JwtKeyAlgorithm {
+    HS256,
+    RS256,
+    EdDSA,
+}
+
+impl JwtKeyAlgorithm {
+    fn to_algorithm(self) -> Algorithm {
+        match self {
+            JwtKeyAlgorithm::HS256 => Algorithm::HS256,
+            JwtKeyAlgorithm::RS256 => Algorithm::RS256,
+            JwtKeyAlgorithm::EdDSA => Algorithm::EdDSA,
+        }
+    }
+}
+
+impl std::str::FromStr for JwtKeyAlgorithm {
+    type Err = anyhow::Error;
+
+    fn from_str(value: &str) -> Result<JwtKeyAlgorithm> {
+        match value {
+            "HS256" => Ok(JwtKeyAlgorithm::HS256),
+            "RS256" => Ok(JwtKeyAlgorithm::RS256),
+            "EdDSA" => Ok(JwtKeyAlgorithm::EdDSA),
+            _ => bail!("unsupported jwt algorithm {}", value),
+        }
+    }
+}
+
+// NOTE: HS256 keys are configured through `secret`, RS256 and EdDSA keys through `pem_file`.
+//       a retired asymmetric key may point at a public key pem since it is only used to verify.
+#[derive(Debug, Clone, serde::Deserialize)]
+pub struct JwtKeyConfig {
+    pub kid: String,
+    pub algorithm: JwtKeyAlgorithm,
+    pub secret: Option<String>,
+    pub pem_file: Option<String>,
+}
+
+impl JwtKeyConfig {
+    fn load_pem(&self) -> Result<(Vec<u8>, pem::Pem)> {
+        let path = match &self.pem_file {
+            Some(path) => path,
+            None => bail!("jwt key {} requires a pem_file", self.kid),
+        };
+        let content = std::fs::read(path).map_err(|err| {
+            anyhow!(
+                "failed to read pem file {} of jwt key {}: {}",
+                path,
+                self.kid,
+                err
+            )
+        })?;
+        let pem = pem::parse(&content).map_err(|err| {
+            anyhow!(
+                "failed to parse pem file {} of jwt key {}: {}",
+                path,
+                self.kid,
+                err
+            )
+        })?;
+
+        Ok((content, pem))
+    }
+}
+
+struct JwtKey {
+    algorithm: JwtKeyAlgorithm,
+    encoding_key: Option<EncodingKey>,
+    decoding_key: DecodingKey,
+    jwk: Option<Jwk>,
+}
+
+impl JwtKey {
+    fn load(config: &JwtKeyConfig) -> Result<JwtKey> {
+        match config.algorithm {
+            JwtKeyAlgorithm::HS256 => {
+                let secret = match &config.secret {
+                    Some(secret) if !secret.is_empty() => secret,
+                    _ => bail!("jwt key {} requires a non empty secret", config.kid),
+                };
+
+                Ok(JwtKey {
+                    algorithm: config.algorithm,
+                    encoding_key: Some(EncodingKey::from_secret(secret.as_bytes())),
+                    decoding_key: DecodingKey::from_secret(secret.as_bytes()),
+                    jwk: None,
+                })
+            }
+            JwtKeyAlgorithm::RS256 => {
+                let (content, pem) = config.load_pem()?;
+                let (encoding_key, public_key_der) = match pem.tag() {
+                    "PRIVATE KEY" | "RSA PRIVATE KEY" => {
+                        let key_pair = if pem.tag() == "PRIVATE KEY" {
+                            RsaKeyPair::from_pkcs8(pem.contents())
+                        } else {
+                            RsaKeyPair::from_der(pem.contents())
+                        }
+                        .map_err(|err| {
+                            anyhow!("invalid rsa private key of jwt key {}: {}", config.kid, err)
+                        })?;
+
+                        let encoding_key = EncodingKey::from_rsa_pem(&content)?;
+                        (Some(encoding_key), key_pair.public().as_ref().to_vec())
+                    }
+                    "PUBLIC KEY" => (None, der::subject_public_key(pem.contents())?.to_vec()),
+                    "RSA PUBLIC KEY" => (None, pem.contents().to_vec()),
+                    tag => bail!("unsupported pem tag {} of jwt key {}", tag, config.kid),
+                };
+
+                let (modulus, exponent) = der::rsa_public_key(&public_key_der)?;
+                let jwk = new_jwk(
+                    &config.kid,
+                    KeyAlgorithm::RS256,
+                    AlgorithmParameters::RSA(RSAKeyParameters {
+                        key_type: RSAKeyType::RSA,
+                        n: URL_SAFE_NO_PAD.encode(modulus),
+                        e: URL_SAFE_NO_PAD.encode(exponent),
+                    }),
+                );
+
+                Ok(JwtKey {
+                    algorithm: config.algorithm,
+                    encoding_key,
+                    decoding_key: DecodingKey::from_rsa_raw_components(modulus, exponent),
+                    jwk: Some(jwk),
+                })
+            }
+            JwtKeyAlgorithm::EdDSA => {
+                let (content, pem) = config.load_pem()?;
+                let (encoding_key, public_key) = match pem.tag() {
+                    "PRIVATE KEY" => {
+                        let key_pair = Ed25519KeyPair::from_pkcs8_maybe_unchecked(pem.contents())
+                            .map_err(|err| {
+                            anyhow!(
+                                "invalid ed25519 private key of jwt key {}: {}",
+                                config.kid,
+                                err
+                            )
+                        })?;
+
+                        let encoding_key = EncodingKey::from_ed_pem(&content)?;
+                        (Some(encoding_key), key_pair.public_key().as_ref().to_vec())
+                    }
+                    "PUBLIC KEY" => (None, der::subject_public_key(pem.contents())?.to_vec()),
+                    tag => bail!("unsupported pem tag {} of jwt key {}", tag, config.kid),
+                };
+
+                let jwk = new_jwk(
+                    &config.kid,
+                    KeyAlgorithm::EdDSA,
+                    AlgorithmParameters::OctetKeyPair(OctetKeyPairParameters {
+                        key_type: OctetKeyPairType::OctetKeyPair,
+                        curve: EllipticCurve::Ed25519,
+                        x: URL_SAFE_NO_PAD.encode(&public_key),
+                    }),
+                );
+
+                Ok(JwtKey {
+                    algorithm: config.algorithm,
+                    encoding_key,
+                    decoding_key: DecodingKey::from_ed_der(&public_key),
+                    jwk: Some(jwk),
+                })
+            }
+        }
+    }
+}
+
+fn new_jwk(kid: &str, key_algorithm: KeyAlgorithm, algorithm: AlgorithmParameters) -> Jwk {
+    Jwk {
+        common: CommonParameters {
+            public_key_use: Some(PublicKeyUse::Signature),
+            key_algorithm: Some(key_algorithm),
+            key_id: Some(kid.to_string()),
+            ..CommonParameters::default()
+        },
+        algorithm,
+    }
+}
+
+// NOTE: tokens are always signed with the active key, every other configured key is retired
+//       and only kept around so tokens issued before a rotation stay valid until they expire.
+pub struct JwtKeySet {
+    active_kid: String,
+    keys: HashMap<String, JwtKey>,
+}
+
+impl JwtKeySet {
+    pub fn new(active_kid: &str, configs: &[JwtKeyConfig]) -> Result<JwtKeySet> {
+        let mut keys = HashMap::new();
+        for config in configs {
+            if keys.contains_key(&config.kid) {
+                bail!("duplicated jwt key id {}", config.kid);
+            }
+            keys.insert(config.kid.clone(), JwtKey::load(config)?);
+        }
+
+        match keys.get(active_kid) {
+            Some(key) if key.encoding_key.is_some() => {}
+            Some(_) => bail!(
+                "active jwt key {} has no private key to sign with",
+                active_kid
+            ),
+            None => bail!("active jwt key {} is not configured", active_kid),
+        }
+
+        Ok(JwtKeySet {
+            active_kid: active_kid.to_string(),
+            keys,
+        })
+    }
+
+    pub fn encode<T: Serialize>(&self, claims: &T) -> Result<String> {
+        let key = &self.keys[&self.active_kid];
+        let mut header = Header::new(key.algorithm.to_algorithm());
+        header.kid = Some(self.active_kid.clone());
+
+        let encoding_key = key.encoding_key.as_ref().unwrap();
+        Ok(encode(&header, claims, encoding_key)?)
+    }
+
+    pub fn decode<T: DeserializeOwned>(&self, token: &str) -> Result<T> {
+        let header = decode_header(token)?;
+        let key = match header.kid.as_ref().and_then(|kid| self.keys.get(kid)) {
+            Some(key) => key,
+            None => bail!("token is signed with an unknown key"),
+        };
+
+        let validation = Validation::new(key.algorithm.to_algorithm());
+        let token_data = decode::<T>(token, &key.decoding_key, &validation)?;
+
+        Ok(token_data.claims)
+    }
+
+    pub fn jwks(&self) -> JwkSet {
+        let mut keys: Vec<Jwk> = self
+            .keys
+            .values()
+            .filter_map(|key| key.jwk.clone())
+            .collect();
+        keys.sort_by(|lhs, rhs| lhs.common.key_id.cmp(&rhs.common.key_id));
+
+        JwkSet { keys }
+    }
+}
+
+// NOTE: just enough DER to pull the public components out of SubjectPublicKeyInfo and
+//       RSAPublicKey structures, ring does not expose them.
+mod der {
+    use anyhow::{bail, Result};
+
+    const TAG_INTEGER: u8 = 0x02;
+    const TAG_BIT_STRING: u8 = 0x03;
+    const TAG_SEQUENCE: u8 = 0x30;
+
+    fn read_tlv(input: &[u8], expected_tag: u8) -> Result<(&[u8], &[u8])> {
+        if input.len() < 2 || input[0] != expected_tag {
+            bail!("malformed der: expected tag {:#x}", expected_tag);
+        }
+
+        let (length, header_len) = match input[1] {
+            len if len < 0x80 => (len as usize, 2),
+            len => {
+                let num_bytes = (len & 0x7f) as usize;
+                if num_bytes == 0 || num_bytes > 4 || input.len() < 2 + num_bytes {
+                    bail!("malformed der: invalid length");
+                }
+                let length = input[2..2 + num_bytes]
+                    .iter()
+                    .fold(0usize, |acc, byte| (acc << 8) | *byte as usize);
+                (length, 2 + num_bytes)
+            }
+        };
+
+        if input.len() < header_len + length {
+            bail!("malformed der: truncated value");
+        }
+
+        Ok((
+            &input[header_len..header_len + length],
+            &input[header_len + length..],
+        ))
+    }
+
+    fn strip_leading_zero(value: &[u8]) -> &[u8] {
+        match value {
+            [0, rest @ ..] if !rest.is_empty() => rest,
+            _ => value,
+        }
+    }
+
+    pub fn subject_public_key(spki: &[u8]) -> Result<&[u8]> {
+        let (content, _) = read_tlv(spki, TAG_SEQUENCE)?;
+        let (_algorithm, rest) = read_tlv(content, TAG_SEQUENCE)?;
+        let (bit_string, _) = read_tlv(rest, TAG_BIT_STRING)?;
+
+        match bit_string {
+            [0, public_key @ ..] => Ok(public_key),
+            _ => bail!("malformed der: unexpected unused bits in public key"),
+        }
+    }
+
+    pub fn rsa_public_key(der: &[u8]) -> Result<(&[u8], &[u8])> {
+        let (content, _) = read_tlv(der, TAG_SEQUENCE)?;
+        let (modulus, rest) = read_tlv(content, TAG_INTEGER)?;
+        let (exponent, _) = read_tlv(rest, TAG_INTEGER)?;
+
+        Ok((strip_leading_zero(modulus), strip_leading_zero(exponent)))
+    }
+}
@@ -3,4 +3,49 @@ pub mod customer;
 pub mod customer_test;
 
 pub mod identity;
+pub mod jwt_key;
+#[cfg(test)]
+pub mod jwt_key_test;
 pub mod file_meta;
+#[cfg(test)]
+pub mod file_meta_test;
+
+pub mod file_grant;
+#[cfg(test)]
+pub mod file_grant_test;
+
+pub mod file_list;
+#[cfg(test)]
+pub mod file_list_test;
+
+pub mod file_request;
+#[cfg(test)]
+pub mod file_request_test;
+
+pub mod folder;
+#[cfg(test)]
+pub mod folder_test;
+
+pub mod password;
+#[cfg(test)]
+pub mod password_test;
+
+pub mod refresh_token;
+
+pub mod sharing_access;
+#[cfg(test)]
+pub mod sharing_access_test;
+
+pub mod sharing_attempt;
+#[cfg(test)]
+pub mod sharing_attempt_test;
+
+pub mod sharing_unlock;
+#[cfg(test)]
+pub mod sharing_unlock_test;
+
+pub mod storage_object;
+
+pub mod upload;
+#[cfg(test)]
+pub mod upload_test;
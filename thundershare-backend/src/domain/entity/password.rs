@@ -0,0 +1,51 @@
+use anyhow::{anyhow, Result};
+use argon2::password_hash::{PasswordHash, PasswordHasher, PasswordVerifier, SaltString};
+use argon2::Argon2;
+use rand::rngs::OsRng;
+
+#[derive(Debug, PartialEq, Clone)]
+pub enum PasswordVerifyResult {
+    Matched,
+    // NOTE: the stored value is a plaintext password written before hashing was introduced,
+    //       the caller is expected to replace it with a fresh hash.
+    MatchedLegacy,
+    Mismatched,
+}
+
+pub fn hash_password(password: &str) -> Result<String> {
+    let salt = SaltString::generate(&mut OsRng);
+    let password_hash = Argon2::default()
+        .hash_password(password.as_bytes(), &salt)
+        .map_err(|err| anyhow!("failed to hash password: {}", err))?;
+
+    Ok(password_hash.to_string())
+}
+
+pub fn verify_password(password: &str, stored: &str) -> PasswordVerifyResult {
+    match PasswordHash::new(stored) {
+        Ok(password_hash) => {
+            match Argon2::default().verify_password(password.as_bytes(), &password_hash) {
+                Ok(_) => PasswordVerifyResult::Matched,
+                Err(_) => PasswordVerifyResult::Mismatched,
+            }
+        }
+        Err(_) => {
+            if constant_time_eq(password.as_bytes(), stored.as_bytes()) {
+                PasswordVerifyResult::MatchedLegacy
+            } else {
+                PasswordVerifyResult::Mismatched
+            }
+        }
+    }
+}
+
+fn constant_time_eq(lhs: &[u8], rhs: &[u8]) -> bool {
+    if lhs.len() != rhs.len() {
+        return false;
+    }
+
+    lhs.iter()
+        .zip(rhs.iter())
+        .fold(0u8, |acc, (l, r)| acc | (l ^ r))
+        == 0
+}
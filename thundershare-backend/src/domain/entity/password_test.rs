@@ -0,0 +1,32 @@
+use super::password::{hash_password, verify_password, PasswordVerifyResult};
+
+#[test]
+fn test_password_hash_is_phc_string() {
+    let hashed = hash_password("hunter2").unwrap();
+    assert!(hashed.starts_with("$argon2id$"));
+    assert_ne!(hashed, hash_password("hunter2").unwrap());
+}
+
+#[test]
+fn test_password_verify() {
+    let hashed = hash_password("hunter2").unwrap();
+
+    let test_context = vec![
+        ("hunter2", hashed.clone(), PasswordVerifyResult::Matched),
+        ("hunter3", hashed.clone(), PasswordVerifyResult::Mismatched),
+        (
+            "hunter2",
+            String::from("hunter2"),
+            PasswordVerifyResult::MatchedLegacy,
+        ),
+        (
+            "hunter3",
+            String::from("hunter2"),
+            PasswordVerifyResult::Mismatched,
+        ),
+    ];
+
+    for (password, stored, expected) in test_context {
+        assert_eq!(verify_password(password, &stored), expected);
+    }
+}
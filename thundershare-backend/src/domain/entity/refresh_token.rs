@@ -0,0 +1,77 @@
+use base64::engine::general_purpose::URL_SAFE_NO_PAD;
+use base64::Engine;
+use chrono::{DateTime, Utc};
+use rand::RngCore;
+use ring::digest::{digest, SHA256};
+use uuid::Uuid;
+
+#[derive(PartialEq, Clone, Debug)]
+pub struct RefreshToken {
+    id: Uuid,
+    customer_id: Uuid,
+    family_id: Uuid,
+    expireat: DateTime<Utc>,
+    used_at: Option<DateTime<Utc>>,
+    revoked: bool,
+}
+
+impl RefreshToken {
+    pub fn new_full(
+        id: &Uuid,
+        customer_id: &Uuid,
+        family_id: &Uuid,
+        expireat: &DateTime<Utc>,
+        used_at: &Option<DateTime<Utc>>,
+        revoked: bool,
+    ) -> RefreshToken {
+        RefreshToken {
+            id: *id,
+            customer_id: *customer_id,
+            family_id: *family_id,
+            expireat: *expireat,
+            used_at: *used_at,
+            revoked,
+        }
+    }
+
+    pub fn get_id(&self) -> Uuid {
+        self.id
+    }
+
+    pub fn get_customer_id(&self) -> Uuid {
+        self.customer_id
+    }
+
+    pub fn get_family_id(&self) -> Uuid {
+        self.family_id
+    }
+
+    pub fn is_expired(&self, curr_time: &DateTime<Utc>) -> bool {
+        self.expireat < *curr_time
+    }
+
+    // NOTE: a refresh token can be exchanged exactly once, presenting it again means it leaked
+    pub fn is_used(&self) -> bool {
+        self.used_at.is_some()
+    }
+
+    pub fn is_revoked(&self) -> bool {
+        self.revoked
+    }
+}
+
+// NOTE: the opaque value is only ever handed to the client, the server keeps its digest
+pub fn generate_refresh_token() -> String {
+    let mut buf = [0u8; 32];
+    rand::rngs::OsRng.fill_bytes(&mut buf);
+    URL_SAFE_NO_PAD.encode(buf)
+}
+
+pub fn hash_refresh_token(token: &str) -> String {
+    let hashed = digest(&SHA256, token.as_bytes());
+    hashed
+        .as_ref()
+        .iter()
+        .map(|byte| format!("{:02x}", byte))
+        .collect()
+}
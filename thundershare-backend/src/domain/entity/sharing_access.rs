@@ -0,0 +1,213 @@
+use std::collections::BTreeMap;
+
+use base64::{engine::general_purpose::URL_SAFE_NO_PAD, Engine};
+use chrono::{DateTime, Utc};
+use uuid::Uuid;
+
+// NOTE: user agents are cut to this many characters before they are stored
+pub const USER_AGENT_MAX_LENGTH: usize = 512;
+
+#[derive(PartialEq, Eq, PartialOrd, Ord, Clone, Copy, Debug, serde::Serialize, serde::Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SharingAccessOutcome {
+    Success,
+    Expired,
+    PasswordIncorrect,
+    NotFound,
+    Locked,
+    Exhausted,
+}
+
+impl SharingAccessOutcome {
+    pub fn from_name(name: &str) -> Option<SharingAccessOutcome> {
+        match name {
+            "success" => Some(SharingAccessOutcome::Success),
+            "expired" => Some(SharingAccessOutcome::Expired),
+            "password_incorrect" => Some(SharingAccessOutcome::PasswordIncorrect),
+            "not_found" => Some(SharingAccessOutcome::NotFound),
+            "locked" => Some(SharingAccessOutcome::Locked),
+            "exhausted" => Some(SharingAccessOutcome::Exhausted),
+            _ => None,
+        }
+    }
+
+    pub fn get_name(&self) -> &'static str {
+        match self {
+            SharingAccessOutcome::Success => "success",
+            SharingAccessOutcome::Expired => "expired",
+            SharingAccessOutcome::PasswordIncorrect => "password_incorrect",
+            SharingAccessOutcome::NotFound => "not_found",
+            SharingAccessOutcome::Locked => "locked",
+            SharingAccessOutcome::Exhausted => "exhausted",
+        }
+    }
+}
+
+// NOTE: who is opening a sharing link, as far as the request tells
+#[derive(PartialEq, Clone, Debug)]
+pub struct SharingClient {
+    addr: String,
+    user_agent: Option<String>,
+}
+
+impl SharingClient {
+    pub fn new(addr: &str, user_agent: &Option<String>) -> SharingClient {
+        SharingClient {
+            addr: addr.to_string(),
+            user_agent: user_agent
+                .as_ref()
+                .map(|user_agent| user_agent.chars().take(USER_AGENT_MAX_LENGTH).collect()),
+        }
+    }
+
+    pub fn get_addr(&self) -> String {
+        self.addr.clone()
+    }
+
+    pub fn get_user_agent(&self) -> Option<String> {
+        self.user_agent.clone()
+    }
+}
+
+#[derive(PartialEq, Clone, Debug)]
+pub struct SharingAccess {
+    id: Uuid,
+    sharing_id: Option<Uuid>,
+    accessed_at: DateTime<Utc>,
+    client: SharingClient,
+    outcome: SharingAccessOutcome,
+    bytes_served: i64,
+}
+
+impl SharingAccess {
+    pub fn new_full(
+        id: &Uuid,
+        sharing_id: &Option<Uuid>,
+        accessed_at: &DateTime<Utc>,
+        client: &SharingClient,
+        outcome: SharingAccessOutcome,
+        bytes_served: i64,
+    ) -> SharingAccess {
+        SharingAccess {
+            id: *id,
+            sharing_id: *sharing_id,
+            accessed_at: *accessed_at,
+            client: client.clone(),
+            outcome,
+            bytes_served,
+        }
+    }
+
+    pub fn get_id(&self) -> Uuid {
+        self.id
+    }
+
+    pub fn get_sharing_id(&self) -> Option<Uuid> {
+        self.sharing_id
+    }
+
+    pub fn get_accessed_at(&self) -> DateTime<Utc> {
+        self.accessed_at
+    }
+
+    pub fn get_client(&self) -> &SharingClient {
+        &self.client
+    }
+
+    pub fn get_outcome(&self) -> SharingAccessOutcome {
+        self.outcome
+    }
+
+    pub fn get_bytes_served(&self) -> i64 {
+        self.bytes_served
+    }
+}
+
+// NOTE: the access log is listed newest first, the cursor holds the last entry of a page
+#[derive(PartialEq, Clone, Debug, serde::Serialize, serde::Deserialize)]
+pub struct SharingAccessCursor {
+    accessed_at: DateTime<Utc>,
+    id: Uuid,
+}
+
+impl SharingAccessCursor {
+    pub fn new(sharing_access: &SharingAccess) -> SharingAccessCursor {
+        SharingAccessCursor {
+            accessed_at: sharing_access.get_accessed_at(),
+            id: sharing_access.get_id(),
+        }
+    }
+
+    pub fn get_accessed_at(&self) -> DateTime<Utc> {
+        self.accessed_at
+    }
+
+    pub fn get_id(&self) -> Uuid {
+        self.id
+    }
+
+    pub fn encode(&self) -> String {
+        URL_SAFE_NO_PAD.encode(serde_json::to_vec(self).unwrap_or_default())
+    }
+
+    pub fn decode(cursor: &str) -> Option<SharingAccessCursor> {
+        let json = URL_SAFE_NO_PAD.decode(cursor).ok()?;
+        serde_json::from_slice(&json).ok()
+    }
+}
+
+// NOTE: next_cursor is None on the last page
+#[derive(PartialEq, Clone, Debug)]
+pub struct SharingAccessPage {
+    sharing_access_list: Vec<SharingAccess>,
+    next_cursor: Option<SharingAccessCursor>,
+}
+
+impl SharingAccessPage {
+    pub fn new(sharing_access_list: Vec<SharingAccess>, next_cursor: Option<SharingAccessCursor>) -> SharingAccessPage {
+        SharingAccessPage {
+            sharing_access_list,
+            next_cursor,
+        }
+    }
+
+    pub fn get_sharing_access_list(&self) -> Vec<SharingAccess> {
+        self.sharing_access_list.clone()
+    }
+
+    pub fn get_next_cursor(&self) -> Option<SharingAccessCursor> {
+        self.next_cursor.clone()
+    }
+}
+
+// NOTE: totals over the whole access log of a link, whatever page is being looked at
+#[derive(PartialEq, Clone, Debug, Default)]
+pub struct SharingAccessStats {
+    accesses: BTreeMap<SharingAccessOutcome, i64>,
+    bytes_served: i64,
+    last_accessed_at: Option<DateTime<Utc>>,
+}
+
+impl SharingAccessStats {
+    pub fn add(&mut self, outcome: SharingAccessOutcome, accesses: i64, bytes_served: i64, last_accessed_at: &DateTime<Utc>) {
+        *self.accesses.entry(outcome).or_insert(0) += accesses;
+        self.bytes_served += bytes_served;
+        self.last_accessed_at = self.last_accessed_at.max(Some(*last_accessed_at));
+    }
+
+    pub fn get_accesses(&self) -> BTreeMap<SharingAccessOutcome, i64> {
+        self.accesses.clone()
+    }
+
+    pub fn get_total_accesses(&self) -> i64 {
+        self.accesses.values().sum()
+    }
+
+    pub fn get_bytes_served(&self) -> i64 {
+        self.bytes_served
+    }
+
+    pub fn get_last_accessed_at(&self) -> Option<DateTime<Utc>> {
+        self.last_accessed_at
+    }
+}
@@ -0,0 +1,65 @@
+use chrono::{TimeZone, Utc};
+use uuid::uuid;
+
+use super::sharing_access::{SharingAccess, SharingAccessCursor, SharingAccessOutcome, SharingAccessStats, SharingClient, USER_AGENT_MAX_LENGTH};
+
+#[test]
+fn test_sharing_access_outcome_name() {
+    let outcome_list = vec![
+        SharingAccessOutcome::Success,
+        SharingAccessOutcome::Expired,
+        SharingAccessOutcome::PasswordIncorrect,
+        SharingAccessOutcome::NotFound,
+        SharingAccessOutcome::Locked,
+        SharingAccessOutcome::Exhausted,
+    ];
+
+    for outcome in outcome_list {
+        assert_eq!(SharingAccessOutcome::from_name(outcome.get_name()), Some(outcome));
+        assert_eq!(serde_json::to_string(&outcome).unwrap(), format!("\"{}\"", outcome.get_name()));
+    }
+    assert_eq!(SharingAccessOutcome::from_name("unknown"), None);
+}
+
+#[test]
+fn test_sharing_client_user_agent_is_cut() {
+    let user_agent = "a".repeat(USER_AGENT_MAX_LENGTH + 10);
+    let client = SharingClient::new("127.0.0.1", &Some(user_agent));
+
+    assert_eq!(client.get_user_agent().unwrap().len(), USER_AGENT_MAX_LENGTH);
+    assert_eq!(SharingClient::new("127.0.0.1", &None).get_user_agent(), None);
+}
+
+#[test]
+fn test_sharing_access_cursor_roundtrip() {
+    let sharing_access = SharingAccess::new_full(
+        &uuid!("67e55044-10b1-426f-9247-bb680e5fe0c8"),
+        &None,
+        &Utc.with_ymd_and_hms(1990, 3, 3, 0, 0, 0).unwrap(),
+        &SharingClient::new("127.0.0.1", &None),
+        SharingAccessOutcome::NotFound,
+        0,
+    );
+    let cursor = SharingAccessCursor::new(&sharing_access);
+
+    assert_eq!(SharingAccessCursor::decode(&cursor.encode()), Some(cursor));
+    assert_eq!(SharingAccessCursor::decode("not a cursor"), None);
+}
+
+#[test]
+fn test_sharing_access_stats() {
+    let earlier = Utc.with_ymd_and_hms(1990, 3, 3, 0, 0, 0).unwrap();
+    let later = Utc.with_ymd_and_hms(1990, 3, 4, 0, 0, 0).unwrap();
+
+    let mut stats = SharingAccessStats::default();
+    assert_eq!(stats.get_total_accesses(), 0);
+    assert_eq!(stats.get_last_accessed_at(), None);
+
+    stats.add(SharingAccessOutcome::Success, 2, 2048, &later);
+    stats.add(SharingAccessOutcome::PasswordIncorrect, 3, 0, &earlier);
+
+    assert_eq!(stats.get_total_accesses(), 5);
+    assert_eq!(stats.get_accesses().get(&SharingAccessOutcome::Success), Some(&2));
+    assert_eq!(stats.get_bytes_served(), 2048);
+    assert_eq!(stats.get_last_accessed_at(), Some(later));
+}
@@ -0,0 +1,36 @@
+use chrono::{DateTime, Utc};
+use uuid::Uuid;
+
+// NOTE: the failed password attempts counted against a single throttling key, either a sharing
+//       link or the address of a client.
+#[derive(PartialEq, Clone, Debug)]
+pub struct SharingAttempt {
+    key: String,
+    failures: i32,
+    locked_until: Option<DateTime<Utc>>,
+}
+
+impl SharingAttempt {
+    pub fn new_full(key: &str, failures: i32, locked_until: &Option<DateTime<Utc>>) -> SharingAttempt {
+        SharingAttempt {
+            key: key.to_string(),
+            failures,
+            locked_until: *locked_until,
+        }
+    }
+
+    pub fn link_key(id: &Uuid) -> String {
+        format!("link:{}", id)
+    }
+
+    pub fn client_key(client_addr: &str) -> String {
+        format!("client:{}", client_addr)
+    }
+
+    pub fn is_locked(&self, curr_time: &DateTime<Utc>) -> bool {
+        match self.locked_until {
+            Some(locked_until) => *curr_time < locked_until,
+            None => false,
+        }
+    }
+}
@@ -0,0 +1,29 @@
+use chrono::{TimeZone, Utc};
+use uuid::uuid;
+
+use super::sharing_attempt::SharingAttempt;
+
+#[test]
+fn test_sharing_attempt_keys() {
+    let id = uuid!("67e55044-10b1-426f-9247-bb680e5fe0c8");
+
+    assert_eq!(SharingAttempt::link_key(&id), "link:67e55044-10b1-426f-9247-bb680e5fe0c8");
+    assert_eq!(SharingAttempt::client_key("127.0.0.1"), "client:127.0.0.1");
+}
+
+#[test]
+fn test_sharing_attempt_is_locked() {
+    let curr_time = Utc.with_ymd_and_hms(1990, 3, 3, 0, 0, 0).unwrap();
+
+    let test_context = vec![
+        (None, false),
+        (Some(Utc.with_ymd_and_hms(1990, 3, 2, 0, 0, 0).unwrap()), false),
+        (Some(curr_time), false),
+        (Some(Utc.with_ymd_and_hms(1990, 3, 4, 0, 0, 0).unwrap()), true),
+    ];
+
+    for (locked_until, expected) in test_context {
+        let attempt = SharingAttempt::new_full("link:test", 0, &locked_until);
+        assert_eq!(attempt.is_locked(&curr_time), expected);
+    }
+}
@@ -0,0 +1,58 @@
+use crate::domain::entity::jwt_key::JwtKeySet;
+use anyhow::Result;
+use chrono::prelude::*;
+use chrono::Duration;
+use uuid::Uuid;
+
+// NOTE: signed with the same keys as the customer tokens, the claims are named differently so
+//       neither kind of token decodes as the other one.
+#[derive(Debug, serde::Serialize, serde::Deserialize, PartialEq, Clone)]
+pub struct SharingUnlockJsonWebToken {
+    pub sharing_id: Uuid,
+    pub exp: i64,
+    pub iat: i64,
+}
+
+// NOTE: proves the password of a sharing link was given, so the download itself does not
+//       need to carry it.
+#[derive(Debug, serde::Serialize, serde::Deserialize, PartialEq, Clone)]
+pub struct SharingUnlock {
+    pub token: SharingUnlockJsonWebToken,
+}
+
+impl SharingUnlock {
+    pub fn new(sharing_id: &Uuid, issueat: &DateTime<Utc>, duration: Duration) -> SharingUnlock {
+        let iat = *issueat;
+        let exp = iat + duration;
+
+        let token = SharingUnlockJsonWebToken {
+            sharing_id: *sharing_id,
+            exp: exp.timestamp(),
+            iat: iat.timestamp(),
+        };
+
+        SharingUnlock { token }
+    }
+
+    pub fn from_string(value: &str, key_set: &JwtKeySet) -> Result<SharingUnlock> {
+        let token = key_set.decode::<SharingUnlockJsonWebToken>(value)?;
+
+        Ok(SharingUnlock { token })
+    }
+
+    pub fn to_string(&self, key_set: &JwtKeySet) -> Result<String> {
+        key_set.encode(&self.token)
+    }
+
+    pub fn get_sharing_id(&self) -> Uuid {
+        self.token.sharing_id
+    }
+
+    pub fn get_expireat(&self) -> DateTime<Utc> {
+        Utc.timestamp_opt(self.token.exp, 0).unwrap()
+    }
+
+    pub fn is_expired(&self, curr_time: &DateTime<Utc>) -> bool {
+        self.get_expireat() < *curr_time
+    }
+}
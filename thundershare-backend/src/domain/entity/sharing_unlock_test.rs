@@ -0,0 +1,50 @@
+use chrono::{Duration, Utc};
+use uuid::Uuid;
+
+use super::customer::Customer;
+use super::identity::Identity;
+use super::jwt_key::{JwtKeyAlgorithm, JwtKeyConfig, JwtKeySet};
+use super::sharing_unlock::SharingUnlock;
+
+fn key_set() -> JwtKeySet {
+    let config = JwtKeyConfig {
+        kid: String::from("2024-01"),
+        algorithm: JwtKeyAlgorithm::HS256,
+        secret: Some(String::from("secret")),
+        pem_file: None,
+    };
+
+    JwtKeySet::new("2024-01", &[config]).unwrap()
+}
+
+#[test]
+fn test_sharing_unlock_round_trip() {
+    let key_set = key_set();
+    let sharing_unlock = SharingUnlock::new(&Uuid::new_v4(), &Utc::now(), Duration::minutes(5));
+
+    let token = sharing_unlock.to_string(&key_set).unwrap();
+    assert_eq!(SharingUnlock::from_string(&token, &key_set).unwrap(), sharing_unlock);
+}
+
+#[test]
+fn test_sharing_unlock_is_not_an_identity() {
+    let key_set = key_set();
+
+    // NOTE: neither token may stand in for the other one
+    let sharing_unlock = SharingUnlock::new(&Uuid::new_v4(), &Utc::now(), Duration::minutes(5));
+    let token = sharing_unlock.to_string(&key_set).unwrap();
+    assert!(Identity::from_string(&token, &key_set).is_err());
+
+    let identity = Identity::new(&Customer::new("mikejiang"), &Utc::now(), Duration::minutes(5));
+    let token = identity.to_string(&key_set).unwrap();
+    assert!(SharingUnlock::from_string(&token, &key_set).is_err());
+}
+
+#[test]
+fn test_sharing_unlock_is_expired() {
+    let issueat = Utc::now();
+    let sharing_unlock = SharingUnlock::new(&Uuid::new_v4(), &issueat, Duration::minutes(5));
+
+    assert!(!sharing_unlock.is_expired(&issueat));
+    assert!(sharing_unlock.is_expired(&(issueat + Duration::minutes(6))));
+}
@@ -0,0 +1,101 @@
+use std::pin::Pin;
+
+use bytes::Bytes;
+use chrono::{DateTime, Utc};
+use futures_util::Stream;
+
+// NOTE: every storage backend speaks in byte streams so that neither side ever has to hold a
+//       whole file in memory.
+pub type StorageStream = Pin<Box<dyn Stream<Item = std::io::Result<Bytes>> + Send>>;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ByteRange {
+    pub start: u64,
+    pub length: u64,
+}
+
+impl ByteRange {
+    pub fn new(start: u64, length: u64) -> ByteRange {
+        ByteRange { start, length }
+    }
+
+    pub fn get_end(&self) -> u64 {
+        self.start + self.length - 1
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct StorageObjectStat {
+    size: u64,
+    etag: String,
+    last_modified: DateTime<Utc>,
+}
+
+impl StorageObjectStat {
+    pub fn new(size: u64, etag: &str, last_modified: &DateTime<Utc>) -> StorageObjectStat {
+        StorageObjectStat {
+            size,
+            etag: etag.to_string(),
+            last_modified: *last_modified,
+        }
+    }
+
+    pub fn get_size(&self) -> u64 {
+        self.size
+    }
+
+    pub fn get_etag(&self) -> String {
+        self.etag.clone()
+    }
+
+    pub fn get_last_modified(&self) -> DateTime<Utc> {
+        self.last_modified
+    }
+}
+
+pub struct StorageObject {
+    stat: StorageObjectStat,
+    range: Option<ByteRange>,
+    body: StorageStream,
+}
+
+impl StorageObject {
+    pub fn new(
+        stat: StorageObjectStat,
+        range: Option<ByteRange>,
+        body: StorageStream,
+    ) -> StorageObject {
+        StorageObject { stat, range, body }
+    }
+
+    pub fn get_stat(&self) -> &StorageObjectStat {
+        &self.stat
+    }
+
+    // NOTE: the part of the object the body holds, None when it holds all of it
+    pub fn get_range(&self) -> Option<ByteRange> {
+        self.range
+    }
+
+    pub fn into_body(self) -> StorageStream {
+        self.body
+    }
+}
+
+// NOTE: an object whose file is already deleted but which the backend has not confirmed to be gone
+#[derive(Debug, Clone, PartialEq)]
+pub struct PendingDelete {
+    storage_key: String,
+}
+
+impl PendingDelete {
+    pub fn new(storage_key: &str) -> PendingDelete {
+        PendingDelete {
+            storage_key: storage_key.to_string(),
+        }
+    }
+
+    pub fn get_storage_key(&self) -> String {
+        self.storage_key.clone()
+    }
+}
@@ -0,0 +1,139 @@
+use chrono::{DateTime, Utc};
+use ring::digest::{Algorithm, SHA1_FOR_LEGACY_USE_ONLY, SHA256};
+use uuid::Uuid;
+
+// NOTE: a resumable upload, the bytes received so far are kept as separate storage objects
+//       and only turned into a file once offset reaches length.
+#[derive(PartialEq, Clone, Debug)]
+pub struct Upload {
+    id: Uuid,
+    customer_id: Uuid,
+    name: String,
+    mime_type: Option<String>,
+    length: u64,
+    offset: u64,
+    parts: Vec<String>,
+    file_id: Option<Uuid>,
+    created_at: DateTime<Utc>,
+}
+
+impl Upload {
+    pub fn new_full(
+        id: &Uuid,
+        customer_id: &Uuid,
+        name: &str,
+        mime_type: &Option<String>,
+        length: u64,
+        created_at: &DateTime<Utc>,
+    ) -> Upload {
+        Upload {
+            id: *id,
+            customer_id: *customer_id,
+            name: name.to_string(),
+            mime_type: mime_type.clone(),
+            length,
+            offset: 0,
+            parts: vec![],
+            file_id: None,
+            created_at: *created_at,
+        }
+    }
+
+    pub fn get_id(&self) -> Uuid {
+        self.id
+    }
+
+    pub fn get_customer_id(&self) -> Uuid {
+        self.customer_id
+    }
+
+    pub fn get_name(&self) -> String {
+        self.name.clone()
+    }
+
+    pub fn get_mime_type(&self) -> Option<String> {
+        self.mime_type.clone()
+    }
+
+    pub fn get_length(&self) -> u64 {
+        self.length
+    }
+
+    pub fn get_offset(&self) -> u64 {
+        self.offset
+    }
+
+    pub fn get_parts(&self) -> Vec<String> {
+        self.parts.clone()
+    }
+
+    pub fn set_progress(&mut self, offset: u64, parts: &[String]) {
+        self.offset = offset;
+        self.parts = parts.to_vec();
+    }
+
+    pub fn get_file_id(&self) -> Option<Uuid> {
+        self.file_id
+    }
+
+    pub fn set_file_id(&mut self, file_id: Option<Uuid>) {
+        self.file_id = file_id;
+    }
+
+    pub fn is_complete(&self) -> bool {
+        self.offset == self.length
+    }
+
+    // NOTE: every part gets a key of its own, two clients racing for the same offset never
+    //       overwrite each other's data, the loser is dropped once the offset check fails.
+    pub fn new_part_key(&self) -> String {
+        format!("{}-{}", self.id.simple(), Uuid::new_v4().simple())
+    }
+}
+
+#[derive(PartialEq, Clone, Copy, Debug)]
+pub enum ChecksumAlgorithm {
+    Sha1,
+    Sha256,
+}
+
+impl ChecksumAlgorithm {
+    pub fn from_name(name: &str) -> Option<ChecksumAlgorithm> {
+        match name {
+            "sha1" => Some(ChecksumAlgorithm::Sha1),
+            "sha256" => Some(ChecksumAlgorithm::Sha256),
+            _ => None,
+        }
+    }
+
+    pub fn get_digest_algorithm(&self) -> &'static Algorithm {
+        match self {
+            ChecksumAlgorithm::Sha1 => &SHA1_FOR_LEGACY_USE_ONLY,
+            ChecksumAlgorithm::Sha256 => &SHA256,
+        }
+    }
+}
+
+// NOTE: the digest a client announced for the body of a single PATCH request
+#[derive(PartialEq, Clone, Debug)]
+pub struct UploadChecksum {
+    algorithm: ChecksumAlgorithm,
+    digest: Vec<u8>,
+}
+
+impl UploadChecksum {
+    pub fn new(algorithm: ChecksumAlgorithm, digest: &[u8]) -> UploadChecksum {
+        UploadChecksum {
+            algorithm,
+            digest: digest.to_vec(),
+        }
+    }
+
+    pub fn get_algorithm(&self) -> ChecksumAlgorithm {
+        self.algorithm
+    }
+
+    pub fn is_match(&self, digest: &[u8]) -> bool {
+        self.digest == digest
+    }
+}
@@ -0,0 +1,43 @@
+use chrono::{TimeZone, Utc};
+use ring::digest::digest;
+use uuid::uuid;
+
+use super::upload::{ChecksumAlgorithm, Upload, UploadChecksum};
+
+#[test]
+fn test_upload_progress() {
+    let id = uuid!("67e55044-10b1-426f-9247-bb680e5fe0c8");
+    let created_at = Utc.with_ymd_and_hms(1990, 3, 3, 0, 0, 0).unwrap();
+    let mut upload = Upload::new_full(&id, &id, "avatar.png", &None, 11, &created_at);
+    assert!(!upload.is_complete());
+
+    upload.set_progress(11, &[upload.new_part_key(), upload.new_part_key()]);
+    assert!(upload.is_complete());
+
+    let parts = upload.get_parts();
+    assert_ne!(parts[0], parts[1]);
+    for part in parts {
+        assert!(part.starts_with("67e5504410b1426f9247bb680e5fe0c8-"));
+    }
+}
+
+#[test]
+fn test_upload_checksum_is_match() {
+    let test_context = vec![
+        ("sha1", Some(ChecksumAlgorithm::Sha1)),
+        ("sha256", Some(ChecksumAlgorithm::Sha256)),
+        ("md5", None),
+        ("SHA1", None),
+    ];
+
+    for (name, expected) in test_context {
+        assert_eq!(ChecksumAlgorithm::from_name(name), expected);
+
+        if let Some(algorithm) = expected {
+            let expected_digest = digest(algorithm.get_digest_algorithm(), b"hello world");
+            let checksum = UploadChecksum::new(algorithm, expected_digest.as_ref());
+            assert!(checksum.is_match(expected_digest.as_ref()));
+            assert!(!checksum.is_match(digest(algorithm.get_digest_algorithm(), b"hello").as_ref()));
+        }
+    }
+}
@@ -10,4 +10,16 @@ pub enum CustomerError {
 
     #[error("customer not found")]
     CustomerNotFound,
+
+    #[error("invalid or expired token")]
+    CustomerInvalidToken,
+
+    #[error("the token has been revoked")]
+    CustomerTokenRevoked,
+
+    #[error("invalid or expired refresh token")]
+    CustomerInvalidRefreshToken,
+
+    #[error("refresh token reuse detected, the session has been revoked")]
+    CustomerRefreshTokenReused,
 }
@@ -13,4 +13,58 @@ pub enum FileError {
 
     #[error("file sharing link passowrd incorrect")]
     FileSharingLinkPasswordIncorrect,
+
+    #[error("file sharing link must expire in the future")]
+    FileSharingLinkExpireAtInvalid,
+
+    #[error("the uploaded file exceeds the maximum upload size")]
+    FileTooLarge,
+
+    #[error("the requested file is not in the trash")]
+    FileNotInTrash,
+
+    #[error("the requested sharing link not exist")]
+    FileSharingLinkNotFound,
+
+    #[error("the sharing link has no download left")]
+    FileSharingLinkExhausted,
+
+    #[error("the max downloads of a sharing link must be greater than 0")]
+    FileSharingLinkMaxDownloadsInvalid,
+
+    #[error("too many incorrect passwords, the sharing link is locked for a while")]
+    FileSharingLinkLocked,
+
+    #[error("the requested file request not exist")]
+    FileRequestNotFound,
+
+    #[error("file request is expired")]
+    FileRequestExpired,
+
+    #[error("file request must expire in the future")]
+    FileRequestExpireAtInvalid,
+
+    #[error("the file request takes no more files")]
+    FileRequestFull,
+
+    #[error("the max files of a file request must be greater than 0")]
+    FileRequestMaxFilesInvalid,
+
+    #[error("the max size of a file request must be greater than 0 and within the upload size of the customer")]
+    FileRequestMaxSizeInvalid,
+
+    #[error("the folder of a file request must be a folder of the customer")]
+    FileRequestFolderInvalid,
+
+    #[error("the file is shared with the customer without the needed permission")]
+    FilePermissionDenied,
+
+    #[error("the file is not shared with the customer")]
+    FileGrantNotFound,
+
+    #[error("a file can not be shared with its owner")]
+    FileGrantToOwner,
+
+    #[error("the unlock token is invalid, expired or made for another sharing link")]
+    FileSharingUnlockTokenInvalid,
 }
@@ -0,0 +1,26 @@
+use thiserror::Error;
+
+#[derive(Error, Debug, PartialEq, Clone)]
+#[allow(
+    clippy::enum_variant_names,
+    reason = "variants keep the domain prefix used by CustomerError and FileError"
+)]
+pub enum FolderError {
+    #[error("the requested folder not exist")]
+    FolderNotFound,
+
+    #[error("the requested folder is not belong to customer")]
+    FolderNotBelongToCustomer,
+
+    #[error("folder name must not be empty, padded or contain a path separator")]
+    FolderNameInvalid,
+
+    #[error("a folder with the same name already exists in the parent folder")]
+    FolderNameConflict,
+
+    #[error("a folder can not be moved into itself or one of its subfolders")]
+    FolderMoveCycle,
+
+    #[error("the folder still contains files or folders")]
+    FolderNotEmpty,
+}
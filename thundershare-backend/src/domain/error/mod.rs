@@ -1,2 +1,4 @@
 pub mod customer;
 pub mod file;
+pub mod folder;
+pub mod upload;
@@ -0,0 +1,32 @@
+use thiserror::Error;
+
+#[derive(Error, Debug, PartialEq, Clone)]
+#[allow(
+    clippy::enum_variant_names,
+    reason = "variants keep the domain prefix used by CustomerError and FileError"
+)]
+pub enum UploadError {
+    #[error("the requested upload not exist")]
+    UploadNotFound,
+
+    #[error("the requested upload is not belong to customer")]
+    UploadNotBelongToCustomer,
+
+    #[error("upload offset does not match the offset of the upload")]
+    UploadOffsetMismatch,
+
+    #[error("the uploaded data exceeds the declared upload length")]
+    UploadLengthExceeded,
+
+    #[error("upload checksum algorithm is not supported")]
+    UploadChecksumAlgorithmUnsupported,
+
+    #[error("upload checksum does not match the uploaded data")]
+    UploadChecksumMismatch,
+
+    #[error("tus protocol version is not supported")]
+    UploadVersionUnsupported,
+
+    #[error("upload content type must be application/offset+octet-stream")]
+    UploadContentTypeInvalid,
+}
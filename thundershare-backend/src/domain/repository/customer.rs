@@ -11,9 +11,9 @@ pub trait CustomerRepositoryTrait {
     async fn create_customer(&self, username: &str, password: &str) -> Result<Customer>;
     async fn get_customer_by_username(&self, username: &str) -> Result<Vec<Customer>>;
     async fn get_customer_by_id(&self, id: &Uuid) -> Result<Vec<Customer>>;
-    async fn get_customer_by_credential(
+    async fn get_customer_credential_by_username(
         &self,
         username: &str,
-        password: &str,
-    ) -> Result<Vec<Customer>>;
+    ) -> Result<Vec<(Customer, String)>>;
+    async fn update_customer_password(&self, id: &Uuid, password: &str) -> Result<()>;
 }
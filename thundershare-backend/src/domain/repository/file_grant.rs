@@ -0,0 +1,18 @@
+use anyhow::Result;
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use mockall::automock;
+use crate::domain::entity::file_grant::{FileGrant, FilePermission, SharedFile};
+use sqlx::types::Uuid;
+
+#[automock]
+#[async_trait]
+pub trait FileGrantRepositoryTrait {
+    // NOTE: a customer already holding a grant on the file gets the new permission instead
+    async fn upsert(&self, file_id: &Uuid, customer_id: &Uuid, permission: FilePermission, granted_at: &DateTime<Utc>) -> Result<FileGrant>;
+    async fn get(&self, file_id: &Uuid, customer_id: &Uuid) -> Result<Vec<FileGrant>>;
+    async fn list_by_file_id(&self, file_id: &Uuid) -> Result<Vec<FileGrant>>;
+    // NOTE: files in the trash of their owner are left out
+    async fn list_shared_with_customer_id(&self, customer_id: &Uuid) -> Result<Vec<SharedFile>>;
+    async fn revoke(&self, file_id: &Uuid, customer_id: &Uuid) -> Result<Vec<FileGrant>>;
+}
@@ -1,13 +1,28 @@
 use anyhow::Result;
 use async_trait::async_trait;
 use mockall::automock;
-use crate::domain::entity::file_meta::FileMeta;
+use chrono::{DateTime, Utc};
+use crate::domain::entity::{file_list::FileMetaListQuery, file_meta::{FileContent, FileMeta}};
 use sqlx::types::Uuid;
 
 #[automock]
 #[async_trait]
 pub trait FileMetaRepositoryTrait {
-    async fn create(&self, customer_id: &Uuid, url: &str) -> Result<FileMeta>;
+    // NOTE: a folder id of None puts the file at the root, a file request id attributes the file
+    //       to the request it was uploaded through
+    async fn create(&self, customer_id: &Uuid, url: &str, content: &FileContent, uploaded_at: &DateTime<Utc>, folder_id: &Option<Uuid>, file_request_id: &Option<Uuid>) -> Result<FileMeta>;
     async fn get_file_meta_by_id(&self, id: &Uuid) -> Result<Vec<FileMeta>>;
-    async fn list_file_meta_by_customer_id(&self, customer_id: &Uuid) -> Result<Vec<FileMeta>>;
+    // NOTE: returns at most limit files starting after the cursor of the query. files in the trash
+    //       are left out, they are only listed by list_trashed_file_meta_by_customer_id
+    async fn list_file_meta_by_customer_id(&self, customer_id: &Uuid, query: &FileMetaListQuery, limit: i64) -> Result<Vec<FileMeta>>;
+    async fn list_trashed_file_meta_by_customer_id(&self, customer_id: &Uuid) -> Result<Vec<FileMeta>>;
+    async fn list_trashed_file_meta_before(&self, deleted_before: &DateTime<Utc>, limit: i64) -> Result<Vec<FileMeta>>;
+    async fn trash(&self, id: &Uuid, deleted_at: &DateTime<Utc>) -> Result<()>;
+    async fn restore(&self, id: &Uuid) -> Result<()>;
+    // NOTE: a folder id of None stands for the root of the customer, trashed files are left out
+    async fn list_file_meta_by_folder_id(&self, customer_id: &Uuid, folder_id: &Option<Uuid>) -> Result<Vec<FileMeta>>;
+    async fn move_to_folder(&self, id: &Uuid, folder_id: &Option<Uuid>) -> Result<()>;
+    // NOTE: removes the file meta and every sharing link of it, and records its url as a pending
+    //       delete, all in one transaction. the stored object itself is left to the caller.
+    async fn delete(&self, id: &Uuid, deleted_at: &DateTime<Utc>) -> Result<()>;
 }
\ No newline at end of file
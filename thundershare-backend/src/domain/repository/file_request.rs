@@ -0,0 +1,31 @@
+use anyhow::Result;
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use mockall::automock;
+use crate::domain::entity::file_request::FileRequest;
+use sqlx::types::Uuid;
+
+pub struct FileRequestCreateParams {
+    pub customer_id: Uuid,
+    pub folder_id: Option<Uuid>,
+    pub code: String,
+    pub expireat: DateTime<Utc>,
+    pub password: Option<String>,
+    pub max_files: Option<i32>,
+    pub max_size_bytes: i64,
+}
+
+// NOTE: create refuses a folder the customer does not own and revoke any request of another
+//       customer, both by returning an empty list.
+#[automock]
+#[async_trait]
+pub trait FileRequestRepositoryTrait {
+    async fn create(&self, params: &FileRequestCreateParams) -> Result<Vec<FileRequest>>;
+    async fn get_by_code(&self, code: &str) -> Result<Vec<FileRequest>>;
+    async fn list_by_customer_id(&self, customer_id: &Uuid) -> Result<Vec<FileRequest>>;
+    // NOTE: counts one file before it is uploaded, an empty list means the request is full
+    async fn reserve_upload(&self, id: &Uuid) -> Result<Vec<FileRequest>>;
+    // NOTE: gives back a file reserved by an upload that failed
+    async fn release_upload(&self, id: &Uuid) -> Result<()>;
+    async fn revoke(&self, id: &Uuid, customer_id: &Uuid) -> Result<Vec<FileRequest>>;
+}
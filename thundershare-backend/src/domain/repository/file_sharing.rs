@@ -5,9 +5,23 @@ use mockall::automock;
 use crate::domain::entity::file_meta::{FileMeta, FileSharingMeta};
 use sqlx::types::Uuid;
 
+// NOTE: the customer methods only see links of files the customer owns, update and revoke return
+//       an empty list for any other link.
 #[automock]
 #[async_trait]
 pub trait FileSharingRepositoryTrait {
-    async fn create(&self, file_id: &Uuid, link: &str, expireat: &DateTime<Utc>, password: &Option<String>) -> Result<FileSharingMeta>;
+    async fn create(&self, file_id: &Uuid, code: &str, expireat: &DateTime<Utc>, password: &Option<String>, max_downloads: &Option<i32>) -> Result<FileSharingMeta>;
     async fn get_by_id(&self, id: &Uuid) -> Result<Vec<FileSharingMeta>>;
+    async fn get_by_code(&self, code: &str) -> Result<Vec<FileSharingMeta>>;
+    async fn get_by_id_and_customer_id(&self, id: &Uuid, customer_id: &Uuid) -> Result<Vec<FileSharingMeta>>;
+    async fn list_by_customer_id(&self, customer_id: &Uuid, file_id: &Option<Uuid>) -> Result<Vec<FileSharingMeta>>;
+    // NOTE: a None expireat keeps the current one, a None password keeps the current one while
+    //       Some(None) removes it
+    async fn update(&self, id: &Uuid, customer_id: &Uuid, expireat: &Option<DateTime<Utc>>, password: &Option<Option<String>>) -> Result<Vec<FileSharingMeta>>;
+    // NOTE: replaces the stored password without an owner check, used to upgrade a plaintext
+    //       password written before hashing was introduced
+    async fn update_password(&self, id: &Uuid, password: &str) -> Result<()>;
+    // NOTE: counts one download, an empty list means the link has no download left
+    async fn consume_download(&self, id: &Uuid) -> Result<Vec<FileSharingMeta>>;
+    async fn revoke(&self, id: &Uuid, customer_id: &Uuid) -> Result<Vec<FileSharingMeta>>;
 }
\ No newline at end of file
@@ -0,0 +1,21 @@
+use anyhow::Result;
+use async_trait::async_trait;
+use mockall::automock;
+use chrono::{DateTime, Utc};
+use crate::domain::entity::folder::Folder;
+use sqlx::types::Uuid;
+
+// NOTE: a parent id of None stands for the root of the customer. move_to and delete return an
+//       empty list when they were refused, move_to because the parent is inside the folder and
+//       delete because the folder is not empty.
+#[automock]
+#[async_trait]
+pub trait FolderRepositoryTrait {
+    async fn create(&self, customer_id: &Uuid, parent_id: &Option<Uuid>, name: &str, created_at: &DateTime<Utc>) -> Result<Folder>;
+    async fn get_by_id(&self, id: &Uuid) -> Result<Vec<Folder>>;
+    async fn get_by_name(&self, customer_id: &Uuid, parent_id: &Option<Uuid>, name: &str) -> Result<Vec<Folder>>;
+    async fn list_by_parent_id(&self, customer_id: &Uuid, parent_id: &Option<Uuid>) -> Result<Vec<Folder>>;
+    async fn rename(&self, id: &Uuid, name: &str) -> Result<Folder>;
+    async fn move_to(&self, customer_id: &Uuid, id: &Uuid, parent_id: &Option<Uuid>) -> Result<Vec<Folder>>;
+    async fn delete(&self, id: &Uuid) -> Result<Vec<Folder>>;
+}
@@ -1,5 +1,36 @@
 pub mod customer;
 pub mod used_token;
 pub mod file_meta;
+pub mod file_grant;
 pub mod file_sharing;
+pub mod file_request;
+pub mod folder;
+pub mod refresh_token;
+pub mod storage;
+pub mod upload;
+pub mod pending_delete;
+pub mod sharing_access;
+pub mod sharing_attempt;
 
+use std::sync::Arc;
+
+use tokio::sync::RwLock;
+
+use self::{customer::CustomerRepositoryTrait, file_grant::FileGrantRepositoryTrait, file_meta::FileMetaRepositoryTrait, file_request::FileRequestRepositoryTrait, file_sharing::FileSharingRepositoryTrait, folder::FolderRepositoryTrait, pending_delete::PendingDeleteRepositoryTrait, refresh_token::RefreshTokenRepositoryTrait, sharing_access::SharingAccessRepositoryTrait, sharing_attempt::SharingAttemptRepositoryTrait, upload::UploadRepositoryTrait, used_token::UsedTokenRepositoryTrait};
+
+// NOTE: every repository the services need, built by the storage layer (see pgsql) and handed
+//       over to ServerService as a whole.
+pub struct ServerRepositories {
+    pub customer_repository: Arc<RwLock<dyn CustomerRepositoryTrait>>,
+    pub used_token_repository: Arc<RwLock<dyn UsedTokenRepositoryTrait>>,
+    pub refresh_token_repository: Arc<RwLock<dyn RefreshTokenRepositoryTrait>>,
+    pub file_meta_repository: Arc<RwLock<dyn FileMetaRepositoryTrait>>,
+    pub file_sharing_meta_repository: Arc<RwLock<dyn FileSharingRepositoryTrait>>,
+    pub upload_repository: Arc<RwLock<dyn UploadRepositoryTrait>>,
+    pub pending_delete_repository: Arc<RwLock<dyn PendingDeleteRepositoryTrait>>,
+    pub folder_repository: Arc<RwLock<dyn FolderRepositoryTrait>>,
+    pub sharing_attempt_repository: Arc<RwLock<dyn SharingAttemptRepositoryTrait>>,
+    pub sharing_access_repository: Arc<RwLock<dyn SharingAccessRepositoryTrait>>,
+    pub file_request_repository: Arc<RwLock<dyn FileRequestRepositoryTrait>>,
+    pub file_grant_repository: Arc<RwLock<dyn FileGrantRepositoryTrait>>,
+}
@@ -0,0 +1,15 @@
+use anyhow::Result;
+use async_trait::async_trait;
+use mockall::automock;
+use chrono::{DateTime, Utc};
+use crate::domain::entity::storage_object::PendingDelete;
+
+// NOTE: rows are added by FileMetaRepositoryTrait::delete in the same transaction that removes
+//       the file meta, list returns the ones that were attempted the longest time ago first.
+#[automock]
+#[async_trait]
+pub trait PendingDeleteRepositoryTrait {
+    async fn list(&self, limit: i64) -> Result<Vec<PendingDelete>>;
+    async fn remove(&self, storage_key: &str) -> Result<()>;
+    async fn mark_failed(&self, storage_key: &str, error: &str, attempted_at: &DateTime<Utc>) -> Result<()>;
+}
@@ -0,0 +1,30 @@
+use anyhow::Result;
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use mockall::automock;
+use uuid::Uuid;
+
+use crate::domain::entity::refresh_token::RefreshToken;
+
+#[automock]
+#[async_trait]
+pub trait RefreshTokenRepositoryTrait {
+    async fn create(
+        &self,
+        customer_id: &Uuid,
+        family_id: &Uuid,
+        token_hash: &str,
+        expireat: &DateTime<Utc>,
+    ) -> Result<RefreshToken>;
+    async fn get_by_token_hash(&self, token_hash: &str) -> Result<Vec<RefreshToken>>;
+    // NOTE: marks the token used and creates the next one of its family at once, returns an
+    //       empty list when the token has already been used or its family revoked concurrently
+    async fn rotate(
+        &self,
+        id: &Uuid,
+        used_at: &DateTime<Utc>,
+        token_hash: &str,
+        expireat: &DateTime<Utc>,
+    ) -> Result<Vec<RefreshToken>>;
+    async fn revoke_family(&self, family_id: &Uuid) -> Result<()>;
+}
@@ -0,0 +1,17 @@
+use anyhow::Result;
+use async_trait::async_trait;
+use mockall::automock;
+use chrono::{DateTime, Utc};
+use sqlx::types::Uuid;
+use crate::domain::entity::sharing_access::{SharingAccess, SharingAccessCursor, SharingAccessOutcome, SharingAccessStats, SharingClient};
+
+// NOTE: the log is not owner checked, callers make sure the link belongs to the customer first
+#[automock]
+#[async_trait]
+pub trait SharingAccessRepositoryTrait {
+    async fn create(&self, sharing_id: &Option<Uuid>, accessed_at: &DateTime<Utc>, client: &SharingClient, outcome: SharingAccessOutcome) -> Result<SharingAccess>;
+    async fn set_bytes_served(&self, id: &Uuid, bytes_served: i64) -> Result<()>;
+    async fn set_outcome(&self, id: &Uuid, outcome: SharingAccessOutcome) -> Result<()>;
+    async fn list_by_sharing_id(&self, sharing_id: &Uuid, cursor: &Option<SharingAccessCursor>, limit: i64) -> Result<Vec<SharingAccess>>;
+    async fn stats_by_sharing_id(&self, sharing_id: &Uuid) -> Result<SharingAccessStats>;
+}
@@ -0,0 +1,19 @@
+use anyhow::Result;
+use async_trait::async_trait;
+use mockall::automock;
+use chrono::{DateTime, Utc};
+use crate::domain::entity::sharing_attempt::SharingAttempt;
+
+// NOTE: begin_attempt counts an attempt against a key before its password is checked, in a single
+//       statement so parallel guesses are counted one after another. It restarts the counter of a
+//       key whose window started at or before window_start_before, and a key that already holds
+//       max_failures attempts is locked until locked_until instead of being counted. The returned
+//       attempt tells whether the key is locked.
+#[automock]
+#[async_trait]
+pub trait SharingAttemptRepositoryTrait {
+    async fn begin_attempt(&self, key: &str, attempted_at: &DateTime<Utc>, window_start_before: &DateTime<Utc>, max_failures: i32, locked_until: &DateTime<Utc>) -> Result<SharingAttempt>;
+    // NOTE: takes back an attempt counted by begin_attempt that turned out not to be a failure
+    async fn forgive_attempt(&self, key: &str) -> Result<()>;
+    async fn purge(&self, window_start_before: &DateTime<Utc>, curr_time: &DateTime<Utc>) -> Result<u64>;
+}
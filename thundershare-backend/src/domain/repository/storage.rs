@@ -0,0 +1,18 @@
+use anyhow::Result;
+use async_trait::async_trait;
+use mockall::automock;
+use crate::domain::entity::storage_object::{ByteRange, StorageObject, StorageObjectStat, StorageStream};
+
+// NOTE: keys are opaque strings chosen by the file service, a backend decides where they end up.
+//       put consumes the body without knowing its size up front and stores nothing when the body
+//       fails. get of a missing key fails with FileError::FileNotFound, stat returns an empty list.
+//       assemble joins the objects of parts into key in order and leaves the parts in place.
+#[automock]
+#[async_trait]
+pub trait StorageBackendTrait: Send + Sync {
+    async fn put(&self, key: &str, body: StorageStream) -> Result<StorageObjectStat>;
+    async fn assemble(&self, key: &str, parts: &[String]) -> Result<StorageObjectStat>;
+    async fn get(&self, key: &str, range: Option<ByteRange>) -> Result<StorageObject>;
+    async fn delete(&self, key: &str) -> Result<()>;
+    async fn stat(&self, key: &str) -> Result<Vec<StorageObjectStat>>;
+}
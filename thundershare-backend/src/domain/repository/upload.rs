@@ -0,0 +1,18 @@
+use anyhow::Result;
+use async_trait::async_trait;
+use mockall::automock;
+use chrono::{DateTime, Utc};
+use crate::domain::entity::upload::Upload;
+use sqlx::types::Uuid;
+
+// NOTE: append_part only succeeds while the upload is still at offset and complete only while no
+//       file was made of it yet, an empty list means another request got there first.
+#[automock]
+#[async_trait]
+pub trait UploadRepositoryTrait {
+    async fn create(&self, customer_id: &Uuid, name: &str, mime_type: &Option<String>, length: u64, created_at: &DateTime<Utc>) -> Result<Upload>;
+    async fn get_by_id(&self, id: &Uuid) -> Result<Vec<Upload>>;
+    async fn append_part(&self, id: &Uuid, offset: u64, new_offset: u64, part_key: &str) -> Result<Vec<Upload>>;
+    async fn complete(&self, id: &Uuid, file_id: &Uuid) -> Result<Vec<Upload>>;
+    async fn delete(&self, id: &Uuid) -> Result<()>;
+}
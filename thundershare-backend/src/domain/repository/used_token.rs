@@ -7,4 +7,5 @@ use mockall::automock;
 #[async_trait]
 pub trait UsedTokenRepositoryTrait {
     async fn create_used_token(&self, token: &str, expire_time: DateTime<Utc>) -> Result<()>;
+    async fn is_token_revoked(&self, token: &str) -> Result<bool>;
 }
@@ -1,6 +1,10 @@
 use crate::domain::entity::identity::Identity;
+use crate::domain::entity::jwt_key::JwtKeySet;
+use crate::domain::entity::password::{hash_password, verify_password, PasswordVerifyResult};
+use crate::domain::entity::refresh_token::{generate_refresh_token, hash_refresh_token};
 use crate::domain::error::customer::CustomerError;
 use crate::domain::repository::customer::CustomerRepositoryTrait;
+use crate::domain::repository::refresh_token::RefreshTokenRepositoryTrait;
 use crate::domain::{entity::customer::Customer, repository::used_token::UsedTokenRepositoryTrait};
 use anyhow::{bail, Result};
 use async_trait::async_trait;
@@ -15,29 +19,73 @@ use uuid::Uuid;
 pub trait CustomerServiceTrait {
     async fn customer_signup(&self, username: &str, password: &str) -> Result<Identity>;
     async fn customer_signin(&self, username: &str, password: &str) -> Result<Identity>;
-    async fn customer_signout(&self, identity: &Identity) -> Result<()>;
+    async fn customer_signout(&self, token: &str) -> Result<()>;
+    async fn customer_authenticate(&self, token: &str) -> Result<Identity>;
+    async fn customer_issue_refresh_token(&self, identity: &Identity) -> Result<String>;
+    async fn customer_refresh(&self, refresh_token: &str) -> Result<(Identity, String)>;
+    async fn customer_revoke_refresh_token(&self, refresh_token: &str) -> Result<()>;
     async fn get_customer_by_username(&self, username: &str) -> Result<Customer>;
     async fn get_customer_by_id(&self, username: &Uuid) -> Result<Customer>;
 }
 
+#[derive(Clone, Copy, Debug)]
+pub struct CustomerSessionLifetime {
+    pub access_token: Duration,
+    pub refresh_token: Duration,
+}
+
+impl Default for CustomerSessionLifetime {
+    fn default() -> CustomerSessionLifetime {
+        CustomerSessionLifetime {
+            access_token: Duration::minutes(10),
+            refresh_token: Duration::days(30),
+        }
+    }
+}
+
 pub struct CustomerServiceImpl {
     issue_at_fn: Box<dyn Fn() -> DateTime<Utc>>,
+    jwt_key_set: Arc<JwtKeySet>,
+    session_lifetime: CustomerSessionLifetime,
     customer_repository: Arc<RwLock<dyn CustomerRepositoryTrait>>,
     used_token_repository: Arc<RwLock<dyn UsedTokenRepositoryTrait>>,
+    refresh_token_repository: Arc<RwLock<dyn RefreshTokenRepositoryTrait>>,
 }
 
 impl CustomerServiceImpl {
     pub fn new(
         issue_at_fn: impl Fn() -> DateTime<Utc> + 'static,
+        jwt_key_set: Arc<JwtKeySet>,
+        session_lifetime: CustomerSessionLifetime,
         customer_repository: Arc<RwLock<dyn CustomerRepositoryTrait>>,
         used_token_repository: Arc<RwLock<dyn UsedTokenRepositoryTrait>>,
+        refresh_token_repository: Arc<RwLock<dyn RefreshTokenRepositoryTrait>>,
     ) -> Arc<CustomerServiceImpl> {
         Arc::new(CustomerServiceImpl {
             issue_at_fn: Box::new(issue_at_fn),
+            jwt_key_set,
+            session_lifetime,
             customer_repository,
             used_token_repository,
+            refresh_token_repository,
         })
     }
+
+    async fn create_refresh_token(&self, customer_id: &Uuid, family_id: &Uuid) -> Result<String> {
+        let refresh_token = generate_refresh_token();
+        let expireat = (self.issue_at_fn)() + self.session_lifetime.refresh_token;
+
+        let repo = self.refresh_token_repository.write().await;
+        repo.create(
+            customer_id,
+            family_id,
+            &hash_refresh_token(&refresh_token),
+            &expireat,
+        )
+        .await?;
+
+        Ok(refresh_token)
+    }
 }
 
 #[async_trait(?Send)]
@@ -48,53 +96,177 @@ impl CustomerServiceTrait for CustomerServiceImpl {
             repo.get_customer_by_username(username).await?
         };
 
-        if customer_list.len() != 0 {
+        if !customer_list.is_empty() {
             bail!(CustomerError::CustomerAlreadyExist)
         }
 
+        let password_hash = hash_password(password)?;
         let customer = {
             let repo = self.customer_repository.write().await;
-            repo.create_customer(username, password).await?
+            repo.create_customer(username, &password_hash).await?
         };
 
         let issueat = (self.issue_at_fn)();
-        let duration = Duration::minutes(10);
+        let duration = self.session_lifetime.access_token;
         let identity = Identity::new(&customer, &issueat, duration);
 
         Ok(identity)
     }
 
     async fn customer_signin(&self, username: &str, password: &str) -> Result<Identity> {
-        let customer_list = {
+        let credential_list = {
             let repo = self.customer_repository.read().await;
-            repo.get_customer_by_credential(username, password).await?
+            repo.get_customer_credential_by_username(username).await?
         };
 
-        if customer_list.len() == 0 {
+        if credential_list.is_empty() {
             bail!(CustomerError::CustomerInvalidCredential)
         }
 
+        let (customer, stored_password) = credential_list[0].clone();
+        match verify_password(password, &stored_password) {
+            PasswordVerifyResult::Matched => {}
+            PasswordVerifyResult::MatchedLegacy => {
+                // NOTE: upgrade the plaintext password written before hashing was introduced
+                let password_hash = hash_password(password)?;
+                let repo = self.customer_repository.write().await;
+                repo.update_customer_password(&customer.get_id(), &password_hash)
+                    .await?;
+            }
+            PasswordVerifyResult::Mismatched => bail!(CustomerError::CustomerInvalidCredential),
+        }
+
         let issueat = (self.issue_at_fn)();
-        let duration = Duration::minutes(10);
-        let identity = Identity::new(&customer_list[0], &issueat, duration);
+        let duration = self.session_lifetime.access_token;
+        let identity = Identity::new(&customer, &issueat, duration);
 
         Ok(identity)
     }
 
-    async fn customer_signout(&self, identity: &Identity) -> Result<()> {
+    async fn customer_signout(&self, token: &str) -> Result<()> {
+        // NOTE: the token presented by the client is revoked as is, re-encoding the identity
+        //       would not reproduce it once the signing key has been rotated.
+        let identity = match Identity::from_string(token, &self.jwt_key_set) {
+            Ok(identity) => identity,
+            Err(_) => bail!(CustomerError::CustomerInvalidToken),
+        };
+
         let repo = self.used_token_repository.write().await;
-        repo.create_used_token(&identity.to_string()?, identity.get_expireat())
+        repo.create_used_token(token, identity.get_expireat())
             .await?;
         Ok(())
     }
 
+    async fn customer_authenticate(&self, token: &str) -> Result<Identity> {
+        let identity = match Identity::from_string(token, &self.jwt_key_set) {
+            Ok(identity) => identity,
+            Err(_) => bail!(CustomerError::CustomerInvalidToken),
+        };
+
+        let revoked = {
+            let repo = self.used_token_repository.read().await;
+            repo.is_token_revoked(token).await?
+        };
+
+        if revoked {
+            bail!(CustomerError::CustomerTokenRevoked)
+        }
+
+        Ok(identity)
+    }
+
+    async fn customer_issue_refresh_token(&self, identity: &Identity) -> Result<String> {
+        // NOTE: every signin starts a new token family, rotations stay in the same family
+        self.create_refresh_token(&identity.get_id(), &Uuid::new_v4())
+            .await
+    }
+
+    async fn customer_refresh(&self, refresh_token: &str) -> Result<(Identity, String)> {
+        let refresh_token_list = {
+            let repo = self.refresh_token_repository.read().await;
+            repo.get_by_token_hash(&hash_refresh_token(refresh_token))
+                .await?
+        };
+
+        if refresh_token_list.is_empty() {
+            bail!(CustomerError::CustomerInvalidRefreshToken)
+        }
+
+        let stored_token = refresh_token_list[0].clone();
+        if stored_token.is_revoked() {
+            bail!(CustomerError::CustomerInvalidRefreshToken)
+        }
+
+        let curr_time = (self.issue_at_fn)();
+        if stored_token.is_expired(&curr_time) {
+            bail!(CustomerError::CustomerInvalidRefreshToken)
+        }
+
+        let customer_list = {
+            let repo = self.customer_repository.read().await;
+            repo.get_customer_by_id(&stored_token.get_customer_id())
+                .await?
+        };
+
+        if customer_list.is_empty() {
+            bail!(CustomerError::CustomerInvalidRefreshToken)
+        }
+
+        // NOTE: the rotated token stays in the same family
+        let next_refresh_token = generate_refresh_token();
+        let rotated_list = if stored_token.is_used() {
+            vec![]
+        } else {
+            let expireat = curr_time + self.session_lifetime.refresh_token;
+            let repo = self.refresh_token_repository.write().await;
+            repo.rotate(
+                &stored_token.get_id(),
+                &curr_time,
+                &hash_refresh_token(&next_refresh_token),
+                &expireat,
+            )
+            .await?
+        };
+
+        if rotated_list.is_empty() {
+            // NOTE: someone replayed a token that was already rotated, since we can not tell
+            //       the legitimate client from the attacker the whole family is revoked.
+            let repo = self.refresh_token_repository.write().await;
+            repo.revoke_family(&stored_token.get_family_id()).await?;
+            bail!(CustomerError::CustomerRefreshTokenReused)
+        }
+
+        let identity = Identity::new(
+            &customer_list[0],
+            &curr_time,
+            self.session_lifetime.access_token,
+        );
+
+        Ok((identity, next_refresh_token))
+    }
+
+    async fn customer_revoke_refresh_token(&self, refresh_token: &str) -> Result<()> {
+        let refresh_token_list = {
+            let repo = self.refresh_token_repository.read().await;
+            repo.get_by_token_hash(&hash_refresh_token(refresh_token))
+                .await?
+        };
+
+        if let Some(stored_token) = refresh_token_list.first() {
+            let repo = self.refresh_token_repository.write().await;
+            repo.revoke_family(&stored_token.get_family_id()).await?;
+        }
+
+        Ok(())
+    }
+
     async fn get_customer_by_username(&self, username: &str) -> Result<Customer> {
         let customer_list = {
             let repo = self.customer_repository.read().await;
             repo.get_customer_by_username(username).await?
         };
 
-        if customer_list.len() == 0 {
+        if customer_list.is_empty() {
             bail!(CustomerError::CustomerNotFound)
         }
 
@@ -107,7 +279,7 @@ impl CustomerServiceTrait for CustomerServiceImpl {
             repo.get_customer_by_id(id).await?
         };
 
-        if customer_list.len() == 0 {
+        if customer_list.is_empty() {
             bail!(CustomerError::CustomerNotFound)
         }
 
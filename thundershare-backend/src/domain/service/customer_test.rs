@@ -2,14 +2,24 @@ use std::sync::Arc;
 
 use chrono::{DateTime, Duration, TimeZone, Utc};
 use tokio::sync::RwLock;
+use uuid::{uuid, Uuid};
 
 use crate::domain::{
-    entity::{customer::Customer, identity::Identity},
+    entity::{
+        customer::Customer,
+        identity::Identity,
+        jwt_key::{JwtKeyAlgorithm, JwtKeyConfig, JwtKeySet},
+        password::hash_password,
+        refresh_token::RefreshToken,
+    },
     error::customer::CustomerError,
-    repository::{customer::MockCustomerRepositoryTrait, used_token::MockUsedTokenRepositoryTrait},
+    repository::{
+        customer::MockCustomerRepositoryTrait, refresh_token::MockRefreshTokenRepositoryTrait,
+        used_token::MockUsedTokenRepositoryTrait,
+    },
 };
 
-use super::customer::{CustomerServiceImpl, CustomerServiceTrait};
+use super::customer::{CustomerServiceImpl, CustomerServiceTrait, CustomerSessionLifetime};
 
 enum CustomerTestContextExpectedResult {
     WithIdentityResult(Result<Identity, CustomerError>),
@@ -41,6 +51,17 @@ fn fake_issue_at() -> DateTime<Utc> {
     Utc.with_ymd_and_hms(1990, 3, 3, 0, 0, 0).unwrap()
 }
 
+fn fake_jwt_key_set() -> Arc<JwtKeySet> {
+    let config = JwtKeyConfig {
+        kid: String::from("test"),
+        algorithm: JwtKeyAlgorithm::HS256,
+        secret: Some(String::from("secret")),
+        pem_file: None,
+    };
+
+    Arc::new(JwtKeySet::new("test", &[config]).unwrap())
+}
+
 #[actix_rt::test]
 async fn test_customer_svc_signup() {
     let test_context = vec![
@@ -58,6 +79,7 @@ async fn test_customer_svc_signup() {
 
                     mock_repo
                         .expect_create_customer()
+                        .withf(|_username, password| password.starts_with("$argon2id$"))
                         .times(1)
                         .returning(|username, password| Ok(Customer::new(username)));
 
@@ -67,7 +89,14 @@ async fn test_customer_svc_signup() {
                 let svc = {
                     let customer_repo = Arc::new(RwLock::new(mock_customer_repo));
                     let used_token_repo = Arc::new(RwLock::new(mock_used_token_repo));
-                    CustomerServiceImpl::new(fake_issue_at, customer_repo, used_token_repo)
+                    CustomerServiceImpl::new(
+                        fake_issue_at,
+                        fake_jwt_key_set(),
+                        CustomerSessionLifetime::default(),
+                        customer_repo,
+                        used_token_repo,
+                        Arc::new(RwLock::new(MockRefreshTokenRepositoryTrait::new())),
+                    )
                 };
 
                 svc
@@ -95,7 +124,14 @@ async fn test_customer_svc_signup() {
                 let svc = {
                     let customer_repo = Arc::new(RwLock::new(mock_customer_repo));
                     let used_token_repo = Arc::new(RwLock::new(mock_used_token_repo));
-                    CustomerServiceImpl::new(fake_issue_at, customer_repo, used_token_repo)
+                    CustomerServiceImpl::new(
+                        fake_issue_at,
+                        fake_jwt_key_set(),
+                        CustomerSessionLifetime::default(),
+                        customer_repo,
+                        used_token_repo,
+                        Arc::new(RwLock::new(MockRefreshTokenRepositoryTrait::new())),
+                    )
                 };
 
                 svc
@@ -132,9 +168,11 @@ async fn test_customer_svc_signin() {
                     let mut mock_repo = MockCustomerRepositoryTrait::new();
 
                     mock_repo
-                        .expect_get_customer_by_credential()
+                        .expect_get_customer_credential_by_username()
                         .times(1)
-                        .returning(move |username, password| Ok(vec![Customer::new(username)]));
+                        .returning(move |username| {
+                            Ok(vec![(Customer::new(username), hash_password("").unwrap())])
+                        });
 
                     mock_repo
                 };
@@ -142,7 +180,14 @@ async fn test_customer_svc_signin() {
                 let svc = {
                     let customer_repo = Arc::new(RwLock::new(mock_customer_repo));
                     let used_token_repo = Arc::new(RwLock::new(mock_used_token_repo));
-                    CustomerServiceImpl::new(fake_issue_at, customer_repo, used_token_repo)
+                    CustomerServiceImpl::new(
+                        fake_issue_at,
+                        fake_jwt_key_set(),
+                        CustomerSessionLifetime::default(),
+                        customer_repo,
+                        used_token_repo,
+                        Arc::new(RwLock::new(MockRefreshTokenRepositoryTrait::new())),
+                    )
                 };
 
                 svc
@@ -161,16 +206,23 @@ async fn test_customer_svc_signin() {
                     let mut mock_repo = MockCustomerRepositoryTrait::new();
 
                     mock_repo
-                        .expect_get_customer_by_credential()
+                        .expect_get_customer_credential_by_username()
                         .times(1)
-                        .returning(move |_username, password| Ok(vec![]));
+                        .returning(move |_username| Ok(vec![]));
                     mock_repo
                 };
 
                 let svc = {
                     let customer_repo = Arc::new(RwLock::new(mock_customer_repo));
                     let used_token_repo = Arc::new(RwLock::new(mock_used_token_repo));
-                    CustomerServiceImpl::new(fake_issue_at, customer_repo, used_token_repo)
+                    CustomerServiceImpl::new(
+                        fake_issue_at,
+                        fake_jwt_key_set(),
+                        CustomerSessionLifetime::default(),
+                        customer_repo,
+                        used_token_repo,
+                        Arc::new(RwLock::new(MockRefreshTokenRepositoryTrait::new())),
+                    )
                 };
 
                 svc
@@ -179,6 +231,89 @@ async fn test_customer_svc_signin() {
                 CustomerError::CustomerInvalidCredential,
             )),
         ),
+        CustomerSvcTestContext::new(
+            Customer::new("brucewayne"),
+            || {
+                let mock_used_token_repo = MockUsedTokenRepositoryTrait::new();
+                let mock_customer_repo = {
+                    let mut mock_repo = MockCustomerRepositoryTrait::new();
+
+                    mock_repo
+                        .expect_get_customer_credential_by_username()
+                        .times(1)
+                        .returning(move |username| {
+                            Ok(vec![(
+                                Customer::new(username),
+                                hash_password("batman").unwrap(),
+                            )])
+                        });
+                    mock_repo
+                };
+
+                let svc = {
+                    let customer_repo = Arc::new(RwLock::new(mock_customer_repo));
+                    let used_token_repo = Arc::new(RwLock::new(mock_used_token_repo));
+                    CustomerServiceImpl::new(
+                        fake_issue_at,
+                        fake_jwt_key_set(),
+                        CustomerSessionLifetime::default(),
+                        customer_repo,
+                        used_token_repo,
+                        Arc::new(RwLock::new(MockRefreshTokenRepositoryTrait::new())),
+                    )
+                };
+
+                svc
+            },
+            CustomerTestContextExpectedResult::WithIdentityResult(Err(
+                CustomerError::CustomerInvalidCredential,
+            )),
+        ),
+        CustomerSvcTestContext::new(
+            Customer::new("mikejiang"),
+            || {
+                let mock_used_token_repo = MockUsedTokenRepositoryTrait::new();
+                let mock_customer_repo = {
+                    let mut mock_repo = MockCustomerRepositoryTrait::new();
+
+                    // NOTE: legacy plaintext row is rehashed on successful signin
+                    mock_repo
+                        .expect_get_customer_credential_by_username()
+                        .times(1)
+                        .returning(move |username| {
+                            Ok(vec![(Customer::new(username), String::new())])
+                        });
+
+                    mock_repo
+                        .expect_update_customer_password()
+                        .withf(|_id, password| password.starts_with("$argon2id$"))
+                        .times(1)
+                        .returning(|_id, _password| Ok(()));
+
+                    mock_repo
+                };
+
+                let svc = {
+                    let customer_repo = Arc::new(RwLock::new(mock_customer_repo));
+                    let used_token_repo = Arc::new(RwLock::new(mock_used_token_repo));
+                    CustomerServiceImpl::new(
+                        fake_issue_at,
+                        fake_jwt_key_set(),
+                        CustomerSessionLifetime::default(),
+                        customer_repo,
+                        used_token_repo,
+                        Arc::new(RwLock::new(MockRefreshTokenRepositoryTrait::new())),
+                    )
+                };
+
+                svc
+            },
+            CustomerTestContextExpectedResult::WithIdentityResult(Ok(Identity::new(
+                &Customer::new("mikejiang"),
+                &fake_issue_at(),
+                Duration::minutes(10),
+            ))),
+        ),
     ];
 
     for t in test_context {
@@ -206,6 +341,7 @@ async fn test_customer_svc_signout() {
 
                 mock_repo
                     .expect_create_used_token()
+                    .withf(|token, _expire_time| token.starts_with("ey"))
                     .times(1)
                     .returning(move |token, expire_time| Ok(()));
 
@@ -217,7 +353,14 @@ async fn test_customer_svc_signout() {
             let svc = {
                 let customer_repo = Arc::new(RwLock::new(mock_customer_repo));
                 let used_token_repo = Arc::new(RwLock::new(mock_used_token_repo));
-                CustomerServiceImpl::new(fake_issue_at, customer_repo, used_token_repo)
+                CustomerServiceImpl::new(
+                    fake_issue_at,
+                    fake_jwt_key_set(),
+                    CustomerSessionLifetime::default(),
+                    customer_repo,
+                    used_token_repo,
+                    Arc::new(RwLock::new(MockRefreshTokenRepositoryTrait::new())),
+                )
             };
 
             svc
@@ -229,9 +372,10 @@ async fn test_customer_svc_signout() {
         let svc = (t.setup_fn)();
         let duration = Duration::minutes(10);
 
-        let identity = Identity::new(&t.input_customer, &fake_issue_at(), duration);
+        let identity = Identity::new(&t.input_customer, &Utc::now(), duration);
+        let token = identity.to_string(&fake_jwt_key_set()).unwrap();
         let result = svc
-            .customer_signout(&identity)
+            .customer_signout(&token)
             .await
             .map_err(|err| err.downcast().unwrap());
 
@@ -266,7 +410,14 @@ async fn test_customer_svc_get_customer_by_username() {
                 let svc = {
                     let customer_repo = Arc::new(RwLock::new(mock_customer_repo));
                     let used_token_repo = Arc::new(RwLock::new(mock_used_token_repo));
-                    CustomerServiceImpl::new(fake_issue_at, customer_repo, used_token_repo)
+                    CustomerServiceImpl::new(
+                        fake_issue_at,
+                        fake_jwt_key_set(),
+                        CustomerSessionLifetime::default(),
+                        customer_repo,
+                        used_token_repo,
+                        Arc::new(RwLock::new(MockRefreshTokenRepositoryTrait::new())),
+                    )
                 };
 
                 svc
@@ -293,7 +444,14 @@ async fn test_customer_svc_get_customer_by_username() {
                 let svc = {
                     let customer_repo = Arc::new(RwLock::new(mock_customer_repo));
                     let used_token_repo = Arc::new(RwLock::new(mock_used_token_repo));
-                    CustomerServiceImpl::new(fake_issue_at, customer_repo, used_token_repo)
+                    CustomerServiceImpl::new(
+                        fake_issue_at,
+                        fake_jwt_key_set(),
+                        CustomerSessionLifetime::default(),
+                        customer_repo,
+                        used_token_repo,
+                        Arc::new(RwLock::new(MockRefreshTokenRepositoryTrait::new())),
+                    )
                 };
 
                 svc
@@ -342,7 +500,14 @@ async fn test_customer_svc_get_customer_by_id() {
                 let svc = {
                     let customer_repo = Arc::new(RwLock::new(mock_customer_repo));
                     let used_token_repo = Arc::new(RwLock::new(mock_used_token_repo));
-                    CustomerServiceImpl::new(fake_issue_at, customer_repo, used_token_repo)
+                    CustomerServiceImpl::new(
+                        fake_issue_at,
+                        fake_jwt_key_set(),
+                        CustomerSessionLifetime::default(),
+                        customer_repo,
+                        used_token_repo,
+                        Arc::new(RwLock::new(MockRefreshTokenRepositoryTrait::new())),
+                    )
                 };
 
                 svc
@@ -369,7 +534,14 @@ async fn test_customer_svc_get_customer_by_id() {
                 let svc = {
                     let customer_repo = Arc::new(RwLock::new(mock_customer_repo));
                     let used_token_repo = Arc::new(RwLock::new(mock_used_token_repo));
-                    CustomerServiceImpl::new(fake_issue_at, customer_repo, used_token_repo)
+                    CustomerServiceImpl::new(
+                        fake_issue_at,
+                        fake_jwt_key_set(),
+                        CustomerSessionLifetime::default(),
+                        customer_repo,
+                        used_token_repo,
+                        Arc::new(RwLock::new(MockRefreshTokenRepositoryTrait::new())),
+                    )
                 };
 
                 svc
@@ -394,3 +566,204 @@ async fn test_customer_svc_get_customer_by_id() {
         assert_eq!(result, expected_result);
     }
 }
+
+#[actix_rt::test]
+async fn test_customer_svc_authenticate() {
+    let identity = Identity::new(
+        &Customer::new("mikejiang"),
+        &Utc::now(),
+        Duration::minutes(10),
+    );
+    let token = identity.to_string(&fake_jwt_key_set()).unwrap();
+
+    let test_context = vec![
+        (
+            token.clone(),
+            {
+                let mut mock_repo = MockUsedTokenRepositoryTrait::new();
+                mock_repo
+                    .expect_is_token_revoked()
+                    .times(1)
+                    .returning(|_token| Ok(false));
+                mock_repo
+            },
+            Ok(identity.clone()),
+        ),
+        (
+            token.clone(),
+            {
+                let mut mock_repo = MockUsedTokenRepositoryTrait::new();
+                mock_repo
+                    .expect_is_token_revoked()
+                    .times(1)
+                    .returning(|_token| Ok(true));
+                mock_repo
+            },
+            Err(CustomerError::CustomerTokenRevoked),
+        ),
+        (
+            String::from("not-a-token"),
+            MockUsedTokenRepositoryTrait::new(),
+            Err(CustomerError::CustomerInvalidToken),
+        ),
+    ];
+
+    for (token, mock_used_token_repo, expected_result) in test_context {
+        let svc = {
+            let customer_repo = Arc::new(RwLock::new(MockCustomerRepositoryTrait::new()));
+            let used_token_repo = Arc::new(RwLock::new(mock_used_token_repo));
+            CustomerServiceImpl::new(
+                fake_issue_at,
+                fake_jwt_key_set(),
+                CustomerSessionLifetime::default(),
+                customer_repo,
+                used_token_repo,
+                Arc::new(RwLock::new(MockRefreshTokenRepositoryTrait::new())),
+            )
+        };
+
+        let result = svc
+            .customer_authenticate(&token)
+            .await
+            .map_err(|err| err.downcast().unwrap());
+
+        assert_eq!(result, expected_result);
+    }
+}
+
+fn fake_refresh_token(expireat: DateTime<Utc>, used_at: Option<DateTime<Utc>>) -> RefreshToken {
+    RefreshToken::new_full(
+        &Uuid::default(),
+        &Uuid::default(),
+        &uuid!("67e55044-10b1-426f-9247-bb680e5fe0c8"),
+        &expireat,
+        &used_at,
+        false,
+    )
+}
+
+#[actix_rt::test]
+async fn test_customer_svc_refresh() {
+    let valid_until = fake_issue_at() + Duration::days(1);
+    let expired_at = fake_issue_at() - Duration::seconds(1);
+
+    let test_context = vec![
+        (
+            {
+                let mut mock_repo = MockRefreshTokenRepositoryTrait::new();
+                mock_repo
+                    .expect_get_by_token_hash()
+                    .times(1)
+                    .returning(move |_hash| Ok(vec![fake_refresh_token(valid_until, None)]));
+                mock_repo
+                    .expect_rotate()
+                    .withf(|id, used_at, _hash, expireat| {
+                        *id == Uuid::default()
+                            && *used_at == fake_issue_at()
+                            && *expireat == fake_issue_at() + Duration::days(30)
+                    })
+                    .times(1)
+                    .returning(move |_id, _used_at, _hash, _expireat| {
+                        Ok(vec![fake_refresh_token(valid_until, None)])
+                    });
+                mock_repo
+            },
+            Ok(Identity::new(
+                &Customer::new("mikejiang"),
+                &fake_issue_at(),
+                Duration::minutes(10),
+            )),
+        ),
+        (
+            {
+                let mut mock_repo = MockRefreshTokenRepositoryTrait::new();
+                mock_repo
+                    .expect_get_by_token_hash()
+                    .times(1)
+                    .returning(move |_hash| {
+                        Ok(vec![fake_refresh_token(valid_until, Some(fake_issue_at()))])
+                    });
+                mock_repo
+                    .expect_revoke_family()
+                    .withf(|family_id| *family_id == uuid!("67e55044-10b1-426f-9247-bb680e5fe0c8"))
+                    .times(1)
+                    .returning(|_family_id| Ok(()));
+                mock_repo
+            },
+            Err(CustomerError::CustomerRefreshTokenReused),
+        ),
+        (
+            {
+                // NOTE: lost the race against a concurrent refresh with the same token, or the
+                //       family was revoked by a concurrent replay before the next token was created
+                let mut mock_repo = MockRefreshTokenRepositoryTrait::new();
+                mock_repo
+                    .expect_get_by_token_hash()
+                    .times(1)
+                    .returning(move |_hash| Ok(vec![fake_refresh_token(valid_until, None)]));
+                mock_repo
+                    .expect_rotate()
+                    .times(1)
+                    .returning(|_id, _used_at, _hash, _expireat| Ok(vec![]));
+                mock_repo
+                    .expect_revoke_family()
+                    .times(1)
+                    .returning(|_family_id| Ok(()));
+                mock_repo
+            },
+            Err(CustomerError::CustomerRefreshTokenReused),
+        ),
+        (
+            {
+                let mut mock_repo = MockRefreshTokenRepositoryTrait::new();
+                mock_repo
+                    .expect_get_by_token_hash()
+                    .times(1)
+                    .returning(move |_hash| Ok(vec![fake_refresh_token(expired_at, None)]));
+                mock_repo
+            },
+            Err(CustomerError::CustomerInvalidRefreshToken),
+        ),
+        (
+            {
+                let mut mock_repo = MockRefreshTokenRepositoryTrait::new();
+                mock_repo
+                    .expect_get_by_token_hash()
+                    .times(1)
+                    .returning(|_hash| Ok(vec![]));
+                mock_repo
+            },
+            Err(CustomerError::CustomerInvalidRefreshToken),
+        ),
+    ];
+
+    for (mock_refresh_token_repo, expected_result) in test_context {
+        let mock_customer_repo = {
+            let mut mock_repo = MockCustomerRepositoryTrait::new();
+            mock_repo
+                .expect_get_customer_by_id()
+                .returning(|_id| Ok(vec![Customer::new("mikejiang")]));
+            mock_repo
+        };
+
+        let svc = CustomerServiceImpl::new(
+            fake_issue_at,
+            fake_jwt_key_set(),
+            CustomerSessionLifetime::default(),
+            Arc::new(RwLock::new(mock_customer_repo)),
+            Arc::new(RwLock::new(MockUsedTokenRepositoryTrait::new())),
+            Arc::new(RwLock::new(mock_refresh_token_repo)),
+        );
+
+        let result = svc
+            .customer_refresh("opaque-refresh-token")
+            .await
+            .map(|(identity, refresh_token)| {
+                assert!(!refresh_token.is_empty());
+                identity
+            })
+            .map_err(|err| err.downcast().unwrap());
+
+        assert_eq!(result, expected_result);
+    }
+}
@@ -1,80 +1,223 @@
 
-use actix_files::NamedFile;
-use anyhow::{bail, Result};
+use anyhow::{anyhow, bail, Result};
 use async_trait::async_trait;
 use chrono::{DateTime, Duration, FixedOffset, Utc};
-use log::info;
 use mockall::automock;
-use std::{io::Bytes, sync::Arc};
-use tokio::{fs::rename, sync::RwLock};
+use futures_util::StreamExt;
+use std::sync::{Arc, Mutex};
+use ring::digest::{Context, SHA256};
+use tokio::sync::RwLock;
 use sqlx::types::Uuid;
 
-use crate::domain::{entity::file_meta::{FileMeta, FileSharingMeta}, error::file::FileError, repository::{file_meta::FileMetaRepositoryTrait, file_sharing::FileSharingRepositoryTrait}};
+use crate::domain::{entity::{customer::Customer, file_grant::{FileGrant, FilePermission, SharedFile}, file_list::{FileMetaCursor, FileMetaListQuery, FileMetaPage}, file_meta::{generate_sharing_code, sniff_mime_type, FileContent, FileMeta, FileSharingMeta}, file_request::FileRequest, password::{hash_password, PasswordVerifyResult}, sharing_access::{SharingAccess, SharingAccessCursor, SharingAccessOutcome, SharingAccessPage, SharingAccessStats, SharingClient}, sharing_attempt::SharingAttempt, sharing_unlock::SharingUnlock, storage_object::{ByteRange, StorageObject, StorageObjectStat, StorageStream}}, error::file::FileError, repository::{file_grant::FileGrantRepositoryTrait, file_meta::FileMetaRepositoryTrait, file_request::{FileRequestCreateParams, FileRequestRepositoryTrait}, file_sharing::FileSharingRepositoryTrait, pending_delete::PendingDeleteRepositoryTrait, sharing_access::SharingAccessRepositoryTrait, sharing_attempt::SharingAttemptRepositoryTrait, storage::StorageBackendTrait}};
 
-#[automock]
-#[async_trait(?Send)]
-pub trait FileUploaderTrait {
-    async fn upload(&self, src_filename: &str, dest_filename: &str) -> Result<()>;
-    async fn download(&self, file_meta: &FileMeta) -> Result<NamedFile>;
-}
+// NOTE: a collision between two random 22 character codes is practically impossible, the retry
+//       only guards against a broken random source looping forever.
+const SHARING_CODE_MAX_ATTEMPTS: usize = 5;
+
+const MIME_SNIFF_LENGTH: usize = 512;
+
+// NOTE: long enough to start a download after typing the password, an unlock token is not
+//       meant to be passed around like the link itself.
+const SHARING_UNLOCK_LIFETIME_SECONDS: i64 = 5 * 60;
+
+const STORAGE_CLEANUP_BATCH_SIZE: i64 = 100;
+const TRASH_PURGE_BATCH_SIZE: i64 = 100;
 
-pub struct LocalFileUploaderImpl {}
+// NOTE: a sharing link, or a client, whose password was wrong max_failures times within window is
+//       refused for lockout, whatever password it sends in the meantime.
+#[derive(Clone, Copy, Debug)]
+pub struct SharingThrottle {
+    pub max_failures: i32,
+    pub window: Duration,
+    pub lockout: Duration,
+}
 
-impl LocalFileUploaderImpl {
-    pub fn new() -> Arc<dyn FileUploaderTrait> {
-        Arc::new(LocalFileUploaderImpl{})
+impl Default for SharingThrottle {
+    fn default() -> SharingThrottle {
+        SharingThrottle {
+            max_failures: 5,
+            window: Duration::minutes(15),
+            lockout: Duration::minutes(15),
+        }
     }
 }
 
-#[async_trait(?Send)]
-impl FileUploaderTrait for LocalFileUploaderImpl {
-    async fn upload(&self, src_filename: &str, dest_filename: &str) -> Result<()> {
-        info!("[DEBUG] src: {:?}, dest: {:?}", src_filename, dest_filename);
-        rename(src_filename, dest_filename).await?;
-        Ok(())
+// NOTE: learns the size, digest and leading bytes of an upload while it streams into the storage
+//       backend, and fails the stream as soon as it grows past the allowed size.
+struct UploadInspector {
+    max_size: u64,
+    size: u64,
+    too_large: bool,
+    head: Vec<u8>,
+    context: Context,
+}
+
+impl UploadInspector {
+    fn new(max_size: u64) -> UploadInspector {
+        UploadInspector {
+            max_size,
+            size: 0,
+            too_large: false,
+            head: Vec::with_capacity(MIME_SNIFF_LENGTH),
+            context: Context::new(&SHA256),
+        }
     }
 
-    async fn download(&self, file_meta: &FileMeta) -> Result<NamedFile> {
-        info!("[DEBUG] file meta: {:?}", file_meta);
-        match NamedFile::open_async(file_meta.get_url()).await {
-            Ok(file) => {
-                Ok(file)
-            }
-            Err(_) => bail!(FileError::FileNotFound),
+    fn update(&mut self, chunk: &[u8]) -> std::io::Result<()> {
+        self.size += chunk.len() as u64;
+        if self.size > self.max_size {
+            self.too_large = true;
+            return Err(std::io::Error::other(FileError::FileTooLarge));
+        }
+
+        if self.head.len() < MIME_SNIFF_LENGTH {
+            let missing = (MIME_SNIFF_LENGTH - self.head.len()).min(chunk.len());
+            self.head.extend_from_slice(&chunk[..missing]);
         }
+        self.context.update(chunk);
+        Ok(())
+    }
+
+    fn inspect(inspector: Arc<Mutex<UploadInspector>>, body: StorageStream) -> StorageStream {
+        Box::pin(body.map(move |chunk| {
+            let chunk = chunk?;
+            inspector.lock().unwrap().update(&chunk)?;
+            Ok(chunk)
+        }))
+    }
+
+    fn finish(&self, name: &str, declared_mime_type: Option<&str>) -> FileContent {
+        let sha256: String = self
+            .context
+            .clone()
+            .finish()
+            .as_ref()
+            .iter()
+            .map(|byte| format!("{:02x}", byte))
+            .collect();
+        let mime_type = sniff_mime_type(&self.head, name, declared_mime_type);
+
+        FileContent::new(name, self.size as i64, &mime_type, &sha256)
+    }
+}
+
+// NOTE: where an upload is stored and how large it may grow, the customer's own uploads and the
+//       ones of outsiders through a file request differ only in this
+struct UploadDestination {
+    customer_id: Uuid,
+    max_size: u64,
+    folder_id: Option<Uuid>,
+    file_request_id: Option<Uuid>,
+}
+
+// NOTE: what the client opening a sharing link proves it knows the password with
+enum SharingCredential {
+    Password(Option<String>),
+    Unlocked,
+}
+
+fn sharing_access_outcome(err: &anyhow::Error) -> Option<SharingAccessOutcome> {
+    match err.downcast_ref::<FileError>()? {
+        FileError::FileNotFound => Some(SharingAccessOutcome::NotFound),
+        FileError::FileSharingLinkExpired => Some(SharingAccessOutcome::Expired),
+        FileError::FileSharingLinkPasswordIncorrect => Some(SharingAccessOutcome::PasswordIncorrect),
+        FileError::FileSharingLinkLocked => Some(SharingAccessOutcome::Locked),
+        FileError::FileSharingLinkExhausted => Some(SharingAccessOutcome::Exhausted),
+        _ => None,
     }
 }
 
 #[automock]
 #[async_trait(?Send)]
 pub trait FileServiceTrait {
-    async fn file_upload(&self, customer_id: &Uuid, filename: &str) -> Result<FileMeta>;
+    async fn file_upload(&self, customer: &Customer, name: &str, declared_mime_type: Option<String>, body: StorageStream) -> Result<FileMeta>;
+    async fn file_assemble(&self, customer: &Customer, name: &str, declared_mime_type: Option<String>, parts: &[String]) -> Result<FileMeta>;
     async fn file_read_by_id(&self, id: &Uuid, customer_id: &Uuid) -> Result<FileMeta>;
-    async fn file_list_by_customer_id(&self, customer_id: &Uuid) -> Result<Vec<FileMeta>>;
-    async fn file_create_sharing_link(&self, file_id: &Uuid, expireat: &DateTime<Utc>, password: &Option<String>) -> Result<FileSharingMeta>;
-    async fn file_get_sharing_link_by_id(&self, file_id: &Uuid, password: Option<String>) -> Result<NamedFile>;
+    async fn file_stat_content(&self, file_meta: &FileMeta) -> Result<StorageObjectStat>;
+    async fn file_open_content(&self, file_meta: &FileMeta, range: Option<ByteRange>) -> Result<StorageObject>;
+    async fn file_list_by_customer_id(&self, customer_id: &Uuid, query: &FileMetaListQuery, limit: u32) -> Result<FileMetaPage>;
+    async fn file_create_sharing_link(&self, file_id: &Uuid, customer_id: &Uuid, expireat: &DateTime<Utc>, password: &Option<String>, max_downloads: &Option<i32>) -> Result<FileSharingMeta>;
+    async fn file_get_sharing_link_by_id(&self, file_id: &Uuid, password: Option<String>, client: &SharingClient) -> Result<(FileMeta, SharingAccess)>;
+    async fn file_get_sharing_link_by_code(&self, code: &str, password: Option<String>, client: &SharingClient) -> Result<(FileMeta, SharingAccess)>;
+    async fn file_read_sharing_link_by_id(&self, id: &Uuid) -> Result<(FileSharingMeta, FileMeta)>;
+    async fn file_unlock_sharing_link(&self, id: &Uuid, password: Option<String>, client: &SharingClient) -> Result<SharingUnlock>;
+    async fn file_get_sharing_link_by_unlock(&self, id: &Uuid, sharing_unlock: &SharingUnlock, client: &SharingClient) -> Result<(FileMeta, SharingAccess)>;
+    async fn file_consume_sharing_download(&self, sharing_access: &SharingAccess) -> Result<()>;
+    async fn file_record_sharing_bytes_served(&self, access_id: &Uuid, bytes_served: i64) -> Result<()>;
+    async fn file_list_sharing_access(&self, id: &Uuid, customer_id: &Uuid, cursor: &Option<SharingAccessCursor>, limit: u32) -> Result<(SharingAccessStats, SharingAccessPage)>;
+    async fn file_list_sharing_links(&self, customer_id: &Uuid, file_id: &Option<Uuid>) -> Result<Vec<FileSharingMeta>>;
+    async fn file_update_sharing_link(&self, id: &Uuid, customer_id: &Uuid, expireat: &Option<DateTime<Utc>>, password: &Option<Option<String>>) -> Result<FileSharingMeta>;
+    async fn file_revoke_sharing_link(&self, id: &Uuid, customer_id: &Uuid) -> Result<()>;
+    async fn file_delete(&self, id: &Uuid, customer_id: &Uuid) -> Result<()>;
+    async fn file_list_trash_by_customer_id(&self, customer_id: &Uuid) -> Result<Vec<FileMeta>>;
+    async fn file_restore_by_id(&self, id: &Uuid, customer_id: &Uuid) -> Result<FileMeta>;
+    async fn file_purge_by_id(&self, id: &Uuid, customer_id: &Uuid) -> Result<()>;
+    async fn file_discard(&self, file_meta: &FileMeta) -> Result<()>;
+    async fn file_purge_trash(&self, retention: Duration) -> Result<usize>;
+    async fn file_cleanup_storage(&self) -> Result<usize>;
+    async fn file_cleanup_sharing_attempts(&self) -> Result<u64>;
+    async fn file_create_request(&self, customer: &Customer, folder_id: &Option<Uuid>, expireat: &DateTime<Utc>, password: &Option<String>, max_files: &Option<i32>, max_size_bytes: &Option<i64>) -> Result<FileRequest>;
+    async fn file_list_requests(&self, customer_id: &Uuid) -> Result<Vec<FileRequest>>;
+    async fn file_revoke_request(&self, id: &Uuid, customer_id: &Uuid) -> Result<()>;
+    async fn file_read_request_by_code(&self, code: &str) -> Result<FileRequest>;
+    async fn file_upload_by_request(&self, code: &str, password: Option<String>, client: &SharingClient, name: &str, declared_mime_type: Option<String>, body: StorageStream) -> Result<FileMeta>;
+    async fn file_grant(&self, file_id: &Uuid, customer_id: &Uuid, grantee: &Customer, permission: FilePermission) -> Result<FileGrant>;
+    async fn file_list_grants(&self, file_id: &Uuid, customer_id: &Uuid) -> Result<Vec<FileGrant>>;
+    async fn file_revoke_grant(&self, file_id: &Uuid, customer_id: &Uuid, grantee_id: &Uuid) -> Result<()>;
+    async fn file_list_shared_with(&self, customer_id: &Uuid) -> Result<Vec<SharedFile>>;
 }
 
 
+// NOTE: the repositories the file service works with, grouped so adding one does not grow the
+//       constructor again
+pub struct FileRepositories {
+    pub file_meta_repository: Arc<RwLock<dyn FileMetaRepositoryTrait>>,
+    pub file_sharing_meta_repository: Arc<RwLock<dyn FileSharingRepositoryTrait>>,
+    pub pending_delete_repository: Arc<RwLock<dyn PendingDeleteRepositoryTrait>>,
+    pub sharing_attempt_repository: Arc<RwLock<dyn SharingAttemptRepositoryTrait>>,
+    pub sharing_access_repository: Arc<RwLock<dyn SharingAccessRepositoryTrait>>,
+    pub file_request_repository: Arc<RwLock<dyn FileRequestRepositoryTrait>>,
+    pub file_grant_repository: Arc<RwLock<dyn FileGrantRepositoryTrait>>,
+}
+
 pub struct FileServiceImpl {
     curr_time_fn: Box<dyn Fn() -> DateTime<Utc>>,
-    file_uploader: Arc<dyn FileUploaderTrait>,
+    public_base_url: String,
+    max_upload_size_bytes: u64,
+    sharing_throttle: SharingThrottle,
+    storage_backend: Arc<dyn StorageBackendTrait>,
     file_meta_repository: Arc<RwLock<dyn FileMetaRepositoryTrait>>,
     file_sharing_meta_repository: Arc<RwLock<dyn FileSharingRepositoryTrait>>,
+    pending_delete_repository: Arc<RwLock<dyn PendingDeleteRepositoryTrait>>,
+    sharing_attempt_repository: Arc<RwLock<dyn SharingAttemptRepositoryTrait>>,
+    sharing_access_repository: Arc<RwLock<dyn SharingAccessRepositoryTrait>>,
+    file_request_repository: Arc<RwLock<dyn FileRequestRepositoryTrait>>,
+    file_grant_repository: Arc<RwLock<dyn FileGrantRepositoryTrait>>,
 }
 
 impl FileServiceImpl {
     pub fn new(
         curr_time_fn: impl Fn() -> DateTime<Utc> + 'static,
-        file_uploader: Arc<dyn FileUploaderTrait>,
-        file_meta_repository: Arc<RwLock<dyn FileMetaRepositoryTrait>>,
-        file_sharing_meta_repository: Arc<RwLock<dyn FileSharingRepositoryTrait>>,
+        public_base_url: &str,
+        max_upload_size_bytes: u64,
+        sharing_throttle: SharingThrottle,
+        storage_backend: Arc<dyn StorageBackendTrait>,
+        repositories: FileRepositories,
     ) -> Arc<FileServiceImpl> {
         let svc = FileServiceImpl {
             curr_time_fn: Box::new(curr_time_fn),
-            file_uploader: file_uploader.clone(),
-            file_meta_repository: file_meta_repository.clone(),
-            file_sharing_meta_repository: file_sharing_meta_repository.clone(),
+            public_base_url: public_base_url.to_string(),
+            max_upload_size_bytes,
+            sharing_throttle,
+            storage_backend,
+            file_meta_repository: repositories.file_meta_repository,
+            file_sharing_meta_repository: repositories.file_sharing_meta_repository,
+            pending_delete_repository: repositories.pending_delete_repository,
+            sharing_attempt_repository: repositories.sharing_attempt_repository,
+            sharing_access_repository: repositories.sharing_access_repository,
+            file_request_repository: repositories.file_request_repository,
+            file_grant_repository: repositories.file_grant_repository,
         };
 
         Arc::new(svc)
@@ -83,31 +226,250 @@ impl FileServiceImpl {
     fn fileid_generator(&self) -> String {
         Uuid::new_v4().to_string()
     }
-}
 
+    async fn sharing_code_generator(&self) -> Result<String> {
+        for _ in 0..SHARING_CODE_MAX_ATTEMPTS {
+            let code = generate_sharing_code();
+            let file_sharing_meta_list = {
+                let repo = self.file_sharing_meta_repository.read().await;
+                repo.get_by_code(&code).await?
+            };
 
-#[async_trait(?Send)]
-impl FileServiceTrait for FileServiceImpl {
-    async fn file_upload(&self, customer_id: &Uuid, filename: &str) -> Result<FileMeta> {
-        let dest_filename = self.fileid_generator();
-        let url = dest_filename.clone();
+            if file_sharing_meta_list.is_empty() {
+                return Ok(code);
+            }
+        }
+
+        bail!("failed to generate a unique sharing code")
+    }
+
+    async fn request_code_generator(&self) -> Result<String> {
+        for _ in 0..SHARING_CODE_MAX_ATTEMPTS {
+            let code = generate_sharing_code();
+            let file_request_list = {
+                let repo = self.file_request_repository.read().await;
+                repo.get_by_code(&code).await?
+            };
+
+            if file_request_list.is_empty() {
+                return Ok(code);
+            }
+        }
+
+        bail!("failed to generate a unique file request code")
+    }
+
+    // NOTE: a link that can not be opened anymore, whatever the password, is refused before the
+    //       password is looked at
+    fn usable_sharing_link(&self, file_sharing_meta_list: &[FileSharingMeta]) -> Result<FileSharingMeta> {
+        if file_sharing_meta_list.is_empty() {
+            bail!(FileError::FileNotFound)
+        }
+
+        let curr_time = (self.curr_time_fn)();
+        let file_sharing_meta = file_sharing_meta_list[0].clone();
+        if file_sharing_meta.is_expired(&curr_time) {
+            bail!(FileError::FileSharingLinkExpired)
+        }
+
+        if file_sharing_meta.is_exhausted() {
+            bail!(FileError::FileSharingLinkExhausted)
+        }
+
+        Ok(file_sharing_meta)
+    }
+
+    async fn read_shared_file_meta(&self, file_sharing_meta: &FileSharingMeta) -> Result<FileMeta> {
+        let file_meta_list = {
+            let repo = self.file_meta_repository.read().await;
+            repo.get_file_meta_by_id(&file_sharing_meta.get_file_id()).await?
+        };
+
+        if file_meta_list.is_empty() {
+            bail!(FileError::FileNotFound)
+        }
+
+        // NOTE: a trashed file keeps its sharing links, they work again once it is restored
+        let file_meta = file_meta_list[0].clone();
+        if file_meta.is_trashed() {
+            bail!(FileError::FileNotFound)
+        }
+
+        Ok(file_meta)
+    }
+
+    async fn open_sharing_link(&self, file_sharing_meta_list: &[FileSharingMeta], credential: SharingCredential, client: &SharingClient) -> Result<FileMeta> {
+        let file_sharing_meta = self.usable_sharing_link(file_sharing_meta_list)?;
+
+        if file_sharing_meta.has_password() {
+            if let SharingCredential::Password(password) = credential {
+                self.check_sharing_password(&file_sharing_meta, &password.unwrap_or_default(), client).await?;
+            }
+        }
+
+        self.read_shared_file_meta(&file_sharing_meta).await
+    }
+
+    async fn unlock_sharing_link(&self, file_sharing_meta_list: &[FileSharingMeta], password: Option<String>, client: &SharingClient) -> Result<()> {
+        let file_sharing_meta = self.usable_sharing_link(file_sharing_meta_list)?;
+        if file_sharing_meta.has_password() {
+            self.check_sharing_password(&file_sharing_meta, &password.unwrap_or_default(), client).await?;
+        }
+
+        Ok(())
+    }
+
+    // NOTE: both the link and the client are throttled, spreading the guesses over many links
+    //       or over many clients only buys an attacker the same number of attempts. file requests
+    //       share the throttle of sharing links, their ids never collide.
+    // NOTE: the attempt is counted before the password is checked and taken back when it turns out
+    //       correct, otherwise parallel guesses would all be checked before the first one is counted
+    async fn check_link_password(&self, link_id: &Uuid, client: &SharingClient, verify: impl FnOnce() -> PasswordVerifyResult) -> Result<PasswordVerifyResult> {
+        let curr_time = (self.curr_time_fn)();
+        let window_start_before = curr_time - self.sharing_throttle.window;
+        let locked_until = curr_time + self.sharing_throttle.lockout;
+        let keys = [
+            SharingAttempt::link_key(link_id),
+            SharingAttempt::client_key(&client.get_addr()),
+        ];
+
+        let mut counted_keys = vec![];
+        let mut locked = false;
+        {
+            let repo = self.sharing_attempt_repository.write().await;
+            for key in keys.iter() {
+                let sharing_attempt = repo
+                    .begin_attempt(key, &curr_time, &window_start_before, self.sharing_throttle.max_failures, &locked_until)
+                    .await?;
+                if sharing_attempt.is_locked(&curr_time) {
+                    locked = true;
+                    break;
+                }
+                counted_keys.push(key);
+            }
+        }
+
+        let verify_result = match locked {
+            true => None,
+            false => Some(verify()),
+        };
+        if verify_result == Some(PasswordVerifyResult::Mismatched) {
+            bail!(FileError::FileSharingLinkPasswordIncorrect)
+        }
+
+        {
+            let repo = self.sharing_attempt_repository.write().await;
+            for key in counted_keys {
+                repo.forgive_attempt(key).await?;
+            }
+        }
 
-        self.file_uploader.upload(filename, &dest_filename).await?;
+        match verify_result {
+            Some(verify_result) => Ok(verify_result),
+            None => bail!(FileError::FileSharingLinkLocked),
+        }
+    }
+
+    async fn check_sharing_password(&self, file_sharing_meta: &FileSharingMeta, password: &str, client: &SharingClient) -> Result<()> {
+        let verify_result = self
+            .check_link_password(&file_sharing_meta.get_id(), client, || file_sharing_meta.check_password(password))
+            .await?;
+
+        // NOTE: upgrade the plaintext password written before hashing was introduced
+        if verify_result == PasswordVerifyResult::MatchedLegacy {
+            let password_hash = hash_password(password)?;
+            let repo = self.file_sharing_meta_repository.write().await;
+            repo.update_password(&file_sharing_meta.get_id(), &password_hash).await?;
+        }
+
+        Ok(())
+    }
+
+    // NOTE: opens the link and logs the attempt against it, a failure that is not the client's
+    //       doing, such as an unreachable database, is not an access and is left out of the log
+    async fn open_and_log_sharing_link(&self, file_sharing_meta_list: Vec<FileSharingMeta>, credential: SharingCredential, client: &SharingClient) -> Result<(FileMeta, SharingAccess)> {
+        let result = self.open_sharing_link(&file_sharing_meta_list, credential, client).await;
+        let outcome = match result.as_ref().map_err(sharing_access_outcome) {
+            Ok(_) => SharingAccessOutcome::Success,
+            Err(Some(outcome)) => outcome,
+            Err(None) => return Err(result.err().unwrap_or_else(|| anyhow!("sharing link failed to open"))),
+        };
+
+        let sharing_access = self.log_sharing_access(&file_sharing_meta_list, client, outcome).await?;
+        Ok((result?, sharing_access))
+    }
+
+    async fn log_sharing_access(&self, file_sharing_meta_list: &[FileSharingMeta], client: &SharingClient, outcome: SharingAccessOutcome) -> Result<SharingAccess> {
+        let sharing_id = file_sharing_meta_list.first().map(|file_sharing_meta| file_sharing_meta.get_id());
+        let repo = self.sharing_access_repository.write().await;
+        repo.create(&sharing_id, &(self.curr_time_fn)(), client, outcome).await
+    }
+
+    async fn store_upload(&self, destination: &UploadDestination, name: &str, declared_mime_type: Option<String>, body: StorageStream) -> Result<FileMeta> {
+        let url = self.fileid_generator();
+
+        let inspector = Arc::new(Mutex::new(UploadInspector::new(destination.max_size)));
+        let result = self.storage_backend.put(&url, UploadInspector::inspect(inspector.clone(), body)).await;
+
+        // NOTE: the backend drops everything it received once the stream fails
+        let content = {
+            let inspector = inspector.lock().unwrap();
+            if inspector.too_large {
+                bail!(FileError::FileTooLarge)
+            }
+            result?;
+
+            inspector.finish(name, declared_mime_type.as_deref())
+        };
+
+        self.create_file_meta(destination, &url, &content).await
+    }
+
+    // NOTE: reads a stored object back for what UploadInspector would have learned while it was
+    //       written
+    async fn inspect_object(&self, url: &str, max_size: u64, name: &str, declared_mime_type: Option<&str>) -> Result<FileContent> {
+        let mut body = self.storage_backend.get(url, None).await?.into_body();
+        let mut inspector = UploadInspector::new(max_size);
+        while let Some(chunk) = body.next().await {
+            if inspector.update(&chunk?).is_err() {
+                bail!(FileError::FileTooLarge)
+            }
+        }
+
+        Ok(inspector.finish(name, declared_mime_type))
+    }
 
+    async fn create_file_meta(&self, destination: &UploadDestination, url: &str, content: &FileContent) -> Result<FileMeta> {
+        let uploaded_at = (self.curr_time_fn)();
         let file_meta = {
             let repo = self.file_meta_repository.write().await;
-            repo.create(customer_id, &url).await?
+            repo.create(&destination.customer_id, url, content, &uploaded_at, &destination.folder_id, &destination.file_request_id).await
         };
-        Ok(file_meta)
+
+        // NOTE: an object without a file meta can never be reached again, do not keep it around
+        if file_meta.is_err() {
+            self.storage_backend.delete(url).await?;
+        }
+        file_meta
     }
 
-    async fn file_read_by_id(&self, id: &Uuid, customer_id: &Uuid) -> Result<FileMeta> {
+    fn customer_destination(&self, customer: &Customer) -> UploadDestination {
+        UploadDestination {
+            customer_id: customer.get_id(),
+            max_size: customer.get_max_upload_size_bytes().unwrap_or(self.max_upload_size_bytes),
+            folder_id: None,
+            file_request_id: None,
+        }
+    }
+
+    // NOTE: finds a file of the customer whether it is in the trash or not
+    async fn read_owned_file_meta(&self, id: &Uuid, customer_id: &Uuid) -> Result<FileMeta> {
         let file_meta_list = {
             let repo = self.file_meta_repository.read().await;
             repo.get_file_meta_by_id(id).await?
         };
 
-        if file_meta_list.len() == 0 {
+        if file_meta_list.is_empty() {
             bail!(FileError::FileNotFound)
         }
 
@@ -116,60 +478,596 @@ impl FileServiceTrait for FileServiceImpl {
             bail!(FileError::FileNotBelongToCustomer)
         }
 
-
         Ok(file_meta)
     }
 
-    async fn file_list_by_customer_id(&self, customer_id: &Uuid) -> Result<Vec<FileMeta>> {
+    // NOTE: the owner may do anything with a file, any other customer needs a grant allowing
+    //       the permission. A file in the trash is not accessible to anyone but its owner.
+    async fn read_accessible_file_meta(&self, id: &Uuid, customer_id: &Uuid, permission: FilePermission) -> Result<FileMeta> {
         let file_meta_list = {
             let repo = self.file_meta_repository.read().await;
-            repo.list_file_meta_by_customer_id(customer_id).await?
+            repo.get_file_meta_by_id(id).await?
         };
-        Ok(file_meta_list)
+
+        if file_meta_list.is_empty() {
+            bail!(FileError::FileNotFound)
+        }
+
+        let file_meta = file_meta_list[0].clone();
+        if file_meta.get_customer_id() != *customer_id {
+            let file_grant_list = {
+                let repo = self.file_grant_repository.read().await;
+                repo.get(id, customer_id).await?
+            };
+
+            if file_grant_list.is_empty() {
+                bail!(FileError::FileNotBelongToCustomer)
+            }
+
+            if !file_grant_list[0].get_permission().allows(permission) {
+                bail!(FileError::FilePermissionDenied)
+            }
+        }
+
+        if file_meta.is_trashed() {
+            bail!(FileError::FileNotFound)
+        }
+
+        Ok(file_meta)
+    }
+
+    // NOTE: the file is gone for good once its meta is deleted, an object that can not be
+    //       removed right now is left to file_cleanup_storage.
+    async fn purge(&self, file_meta: &FileMeta) -> Result<()> {
+        let deleted_at = (self.curr_time_fn)();
+        {
+            let repo = self.file_meta_repository.write().await;
+            repo.delete(&file_meta.get_id(), &deleted_at).await?;
+        }
+
+        self.delete_pending_object(&file_meta.get_url()).await?;
+        Ok(())
+    }
+
+    // NOTE: a key stays pending until the backend confirmed the delete, a failure is only recorded
+    async fn delete_pending_object(&self, storage_key: &str) -> Result<bool> {
+        match self.storage_backend.delete(storage_key).await {
+            Ok(_) => {
+                let repo = self.pending_delete_repository.write().await;
+                repo.remove(storage_key).await?;
+                Ok(true)
+            }
+            Err(err) => {
+                log::warn!("failed to delete storage object {}: {}", storage_key, err);
+                let attempted_at = (self.curr_time_fn)();
+                let repo = self.pending_delete_repository.write().await;
+                repo.mark_failed(storage_key, &err.to_string(), &attempted_at).await?;
+                Ok(false)
+            }
+        }
+    }
+}
+
+
+#[async_trait(?Send)]
+impl FileServiceTrait for FileServiceImpl {
+    async fn file_upload(&self, customer: &Customer, name: &str, declared_mime_type: Option<String>, body: StorageStream) -> Result<FileMeta> {
+        let destination = self.customer_destination(customer);
+        self.store_upload(&destination, name, declared_mime_type, body).await
     }
 
-    async fn file_create_sharing_link(&self, id: &Uuid, expireat: &DateTime<Utc>, password: &Option<String>) -> Result<FileSharingMeta> {
-        let link = "TODO";
-        let file_sharing_meta = {
+    // NOTE: the storage backend joins the parts itself, the result is only read back once for its
+    //       digest and leading bytes. The parts are left for the caller to delete.
+    async fn file_assemble(&self, customer: &Customer, name: &str, declared_mime_type: Option<String>, parts: &[String]) -> Result<FileMeta> {
+        let destination = self.customer_destination(customer);
+        let url = self.fileid_generator();
+        self.storage_backend.assemble(&url, parts).await?;
+
+        let content = match self.inspect_object(&url, destination.max_size, name, declared_mime_type.as_deref()).await {
+            Ok(content) => content,
+            Err(err) => {
+                self.storage_backend.delete(&url).await?;
+                return Err(err);
+            }
+        };
+        self.create_file_meta(&destination, &url, &content).await
+    }
+
+    async fn file_read_by_id(&self, id: &Uuid, customer_id: &Uuid) -> Result<FileMeta> {
+        self.read_accessible_file_meta(id, customer_id, FilePermission::Read).await
+    }
+
+    // NOTE: callers are expected to have authorized the file meta, either through
+    //       file_read_by_id or through a sharing link.
+    async fn file_stat_content(&self, file_meta: &FileMeta) -> Result<StorageObjectStat> {
+        let stat_list = self.storage_backend.stat(&file_meta.get_url()).await?;
+        if stat_list.is_empty() {
+            bail!(FileError::FileNotFound)
+        }
+
+        Ok(stat_list[0].clone())
+    }
+
+    async fn file_open_content(&self, file_meta: &FileMeta, range: Option<ByteRange>) -> Result<StorageObject> {
+        self.storage_backend.get(&file_meta.get_url(), range).await
+    }
+
+    // NOTE: one file more than asked for is read, it tells whether there is a next page
+    async fn file_list_by_customer_id(&self, customer_id: &Uuid, query: &FileMetaListQuery, limit: u32) -> Result<FileMetaPage> {
+        let mut file_meta_list = {
+            let repo = self.file_meta_repository.read().await;
+            repo.list_file_meta_by_customer_id(customer_id, query, limit as i64 + 1).await?
+        };
+
+        let mut next_cursor = None;
+        if file_meta_list.len() > limit as usize {
+            file_meta_list.truncate(limit as usize);
+            next_cursor = file_meta_list
+                .last()
+                .map(|file_meta| FileMetaCursor::new(file_meta, query.get_sort_key(), query.get_order()));
+        }
+
+        Ok(FileMetaPage::new(file_meta_list, next_cursor))
+    }
+
+    async fn file_create_sharing_link(&self, id: &Uuid, customer_id: &Uuid, expireat: &DateTime<Utc>, password: &Option<String>, max_downloads: &Option<i32>) -> Result<FileSharingMeta> {
+        let curr_time = (self.curr_time_fn)();
+        if *expireat <= curr_time {
+            bail!(FileError::FileSharingLinkExpireAtInvalid)
+        }
+
+        if max_downloads.is_some_and(|max_downloads| max_downloads <= 0) {
+            bail!(FileError::FileSharingLinkMaxDownloadsInvalid)
+        }
+
+        // NOTE: only the owner of a file is allowed to share it, a grant is not enough
+        let file_meta = self.read_owned_file_meta(id, customer_id).await?;
+        if file_meta.is_trashed() {
+            bail!(FileError::FileNotFound)
+        }
+
+        let password_hash = match password {
+            Some(password) => Some(hash_password(password)?),
+            None => None,
+        };
+
+        let code = self.sharing_code_generator().await?;
+        let mut file_sharing_meta = {
             let repo = self.file_sharing_meta_repository.read().await;
-            repo.create(id, link, expireat, password).await?
+            repo.create(id, &code, expireat, &password_hash, max_downloads).await?
         };
+
+        file_sharing_meta.set_public_base_url(&self.public_base_url);
         Ok(file_sharing_meta)
     }
 
-    async fn file_get_sharing_link_by_id(&self, id: &Uuid, password: Option<String>) -> Result<NamedFile> {
+    async fn file_get_sharing_link_by_id(&self, id: &Uuid, password: Option<String>, client: &SharingClient) -> Result<(FileMeta, SharingAccess)> {
+        let file_sharing_meta_list = {
+            let repo = self.file_sharing_meta_repository.read().await;
+            repo.get_by_id(id).await?
+        };
+
+        self.open_and_log_sharing_link(file_sharing_meta_list, SharingCredential::Password(password), client).await
+    }
+
+    async fn file_get_sharing_link_by_code(&self, code: &str, password: Option<String>, client: &SharingClient) -> Result<(FileMeta, SharingAccess)> {
+        let file_sharing_meta_list = {
+            let repo = self.file_sharing_meta_repository.read().await;
+            repo.get_by_code(code).await?
+        };
+
+        self.open_and_log_sharing_link(file_sharing_meta_list, SharingCredential::Password(password), client).await
+    }
+
+    // NOTE: tells what the link leads to without opening it, nothing is logged or counted
+    async fn file_read_sharing_link_by_id(&self, id: &Uuid) -> Result<(FileSharingMeta, FileMeta)> {
+        let file_sharing_meta_list = {
+            let repo = self.file_sharing_meta_repository.read().await;
+            repo.get_by_id(id).await?
+        };
+
+        let mut file_sharing_meta = self.usable_sharing_link(&file_sharing_meta_list)?;
+        let file_meta = self.read_shared_file_meta(&file_sharing_meta).await?;
+
+        file_sharing_meta.set_public_base_url(&self.public_base_url);
+        Ok((file_sharing_meta, file_meta))
+    }
+
+    // NOTE: the password is checked and throttled the same way as when the link is opened and a
+    //       refused unlock is logged like a refused download, a successful one is only logged and
+    //       counted once the token is used
+    async fn file_unlock_sharing_link(&self, id: &Uuid, password: Option<String>, client: &SharingClient) -> Result<SharingUnlock> {
+        let file_sharing_meta_list = {
+            let repo = self.file_sharing_meta_repository.read().await;
+            repo.get_by_id(id).await?
+        };
+
+        let result = self.unlock_sharing_link(&file_sharing_meta_list, password, client).await;
+        if let Some(outcome) = result.as_ref().err().and_then(sharing_access_outcome) {
+            self.log_sharing_access(&file_sharing_meta_list, client, outcome).await?;
+        }
+        result?;
+
+        let issueat = (self.curr_time_fn)();
+        Ok(SharingUnlock::new(id, &issueat, Duration::seconds(SHARING_UNLOCK_LIFETIME_SECONDS)))
+    }
+
+    async fn file_get_sharing_link_by_unlock(&self, id: &Uuid, sharing_unlock: &SharingUnlock, client: &SharingClient) -> Result<(FileMeta, SharingAccess)> {
+        if sharing_unlock.get_sharing_id() != *id || sharing_unlock.is_expired(&(self.curr_time_fn)()) {
+            bail!(FileError::FileSharingUnlockTokenInvalid)
+        }
+
         let file_sharing_meta_list = {
             let repo = self.file_sharing_meta_repository.read().await;
             repo.get_by_id(id).await?
         };
 
-        if file_sharing_meta_list.len() == 0 {
+        self.open_and_log_sharing_link(file_sharing_meta_list, SharingCredential::Unlocked, client).await
+    }
+
+    // NOTE: is_exhausted only spares the work for a link known to be used up when it is opened, the
+    //       download is counted once the body is about to be sent and refused when a concurrent
+    //       request took the last one first
+    async fn file_consume_sharing_download(&self, sharing_access: &SharingAccess) -> Result<()> {
+        let Some(sharing_id) = sharing_access.get_sharing_id() else {
             bail!(FileError::FileNotFound)
+        };
+
+        let file_sharing_meta_list = {
+            let repo = self.file_sharing_meta_repository.write().await;
+            repo.consume_download(&sharing_id).await?
+        };
+
+        if file_sharing_meta_list.is_empty() {
+            let repo = self.sharing_access_repository.write().await;
+            repo.set_outcome(&sharing_access.get_id(), SharingAccessOutcome::Exhausted).await?;
+            bail!(FileError::FileSharingLinkExhausted)
         }
 
-        let curr_time = (self.curr_time_fn)();
-        let file_sharing_meta = file_sharing_meta_list[0].clone();
-        if (file_sharing_meta.is_expired(&curr_time)) {
-            bail!(FileError::FileSharingLinkExpired)
+        Ok(())
+    }
+
+    async fn file_record_sharing_bytes_served(&self, access_id: &Uuid, bytes_served: i64) -> Result<()> {
+        let repo = self.sharing_access_repository.write().await;
+        repo.set_bytes_served(access_id, bytes_served).await
+    }
+
+    async fn file_list_sharing_access(&self, id: &Uuid, customer_id: &Uuid, cursor: &Option<SharingAccessCursor>, limit: u32) -> Result<(SharingAccessStats, SharingAccessPage)> {
+        let file_sharing_meta_list = {
+            let repo = self.file_sharing_meta_repository.read().await;
+            repo.get_by_id_and_customer_id(id, customer_id).await?
+        };
+
+        if file_sharing_meta_list.is_empty() {
+            bail!(FileError::FileSharingLinkNotFound)
         }
 
-        if (!file_sharing_meta.is_password_correct(&password.unwrap_or(String::new()))) {
-            bail!(FileError::FileSharingLinkPasswordIncorrect)
+        let repo = self.sharing_access_repository.read().await;
+        let stats = repo.stats_by_sharing_id(id).await?;
+        let mut sharing_access_list = repo.list_by_sharing_id(id, cursor, limit as i64 + 1).await?;
+
+        let mut next_cursor = None;
+        if sharing_access_list.len() > limit as usize {
+            sharing_access_list.truncate(limit as usize);
+            next_cursor = sharing_access_list.last().map(SharingAccessCursor::new);
         }
 
+        Ok((stats, SharingAccessPage::new(sharing_access_list, next_cursor)))
+    }
+
+    async fn file_list_sharing_links(&self, customer_id: &Uuid, file_id: &Option<Uuid>) -> Result<Vec<FileSharingMeta>> {
+        let mut file_sharing_meta_list = {
+            let repo = self.file_sharing_meta_repository.read().await;
+            repo.list_by_customer_id(customer_id, file_id).await?
+        };
+
+        for file_sharing_meta in file_sharing_meta_list.iter_mut() {
+            file_sharing_meta.set_public_base_url(&self.public_base_url);
+        }
+        Ok(file_sharing_meta_list)
+    }
+
+    // NOTE: an expired link can be revived by moving its expireat into the future again
+    async fn file_update_sharing_link(&self, id: &Uuid, customer_id: &Uuid, expireat: &Option<DateTime<Utc>>, password: &Option<Option<String>>) -> Result<FileSharingMeta> {
+        if let Some(expireat) = expireat {
+            if *expireat <= (self.curr_time_fn)() {
+                bail!(FileError::FileSharingLinkExpireAtInvalid)
+            }
+        }
+
+        let password_hash = match password {
+            Some(Some(password)) => Some(Some(hash_password(password)?)),
+            Some(None) => Some(None),
+            None => None,
+        };
+
+        let file_sharing_meta_list = {
+            let repo = self.file_sharing_meta_repository.write().await;
+            repo.update(id, customer_id, expireat, &password_hash).await?
+        };
+
+        if file_sharing_meta_list.is_empty() {
+            bail!(FileError::FileSharingLinkNotFound)
+        }
+
+        let mut file_sharing_meta = file_sharing_meta_list[0].clone();
+        file_sharing_meta.set_public_base_url(&self.public_base_url);
+        Ok(file_sharing_meta)
+    }
+
+    async fn file_revoke_sharing_link(&self, id: &Uuid, customer_id: &Uuid) -> Result<()> {
+        let file_sharing_meta_list = {
+            let repo = self.file_sharing_meta_repository.write().await;
+            repo.revoke(id, customer_id).await?
+        };
+
+        if file_sharing_meta_list.is_empty() {
+            bail!(FileError::FileSharingLinkNotFound)
+        }
+
+        Ok(())
+    }
+
+    // NOTE: moves the file to the trash of its owner, nothing is removed until it is purged. a
+    //       read-write grant is what allows another customer to do so, only the owner can restore
+    //       or purge it afterwards.
+    async fn file_delete(&self, id: &Uuid, customer_id: &Uuid) -> Result<()> {
+        let file_meta = self.read_accessible_file_meta(id, customer_id, FilePermission::ReadWrite).await?;
+
+        let deleted_at = (self.curr_time_fn)();
+        let repo = self.file_meta_repository.write().await;
+        repo.trash(&file_meta.get_id(), &deleted_at).await
+    }
+
+    async fn file_list_trash_by_customer_id(&self, customer_id: &Uuid) -> Result<Vec<FileMeta>> {
+        let repo = self.file_meta_repository.read().await;
+        repo.list_trashed_file_meta_by_customer_id(customer_id).await
+    }
+
+    async fn file_restore_by_id(&self, id: &Uuid, customer_id: &Uuid) -> Result<FileMeta> {
+        let mut file_meta = self.read_owned_file_meta(id, customer_id).await?;
+        if !file_meta.is_trashed() {
+            bail!(FileError::FileNotInTrash)
+        }
+
+        {
+            let repo = self.file_meta_repository.write().await;
+            repo.restore(&file_meta.get_id()).await?;
+        }
+
+        file_meta.set_deleted_at(None);
+        Ok(file_meta)
+    }
+
+    // NOTE: only a file in the trash can be purged, a file in use has to be deleted first
+    async fn file_purge_by_id(&self, id: &Uuid, customer_id: &Uuid) -> Result<()> {
+        let file_meta = self.read_owned_file_meta(id, customer_id).await?;
+        if !file_meta.is_trashed() {
+            bail!(FileError::FileNotInTrash)
+        }
+
+        self.purge(&file_meta).await
+    }
+
+    // NOTE: for a file that was never handed out to anyone, it skips the trash
+    async fn file_discard(&self, file_meta: &FileMeta) -> Result<()> {
+        self.purge(file_meta).await
+    }
+
+    async fn file_purge_trash(&self, retention: Duration) -> Result<usize> {
+        let deleted_before = (self.curr_time_fn)() - retention;
         let file_meta_list = {
             let repo = self.file_meta_repository.read().await;
-            repo.get_file_meta_by_id(&file_sharing_meta.get_file_id()).await?
+            repo.list_trashed_file_meta_before(&deleted_before, TRASH_PURGE_BATCH_SIZE).await?
+        };
+
+        let mut purged = 0;
+        for file_meta in file_meta_list {
+            self.purge(&file_meta).await?;
+            purged += 1;
+        }
+
+        Ok(purged)
+    }
+
+    async fn file_cleanup_storage(&self) -> Result<usize> {
+        let pending_delete_list = {
+            let repo = self.pending_delete_repository.read().await;
+            repo.list(STORAGE_CLEANUP_BATCH_SIZE).await?
+        };
+
+        let mut deleted = 0;
+        for pending_delete in pending_delete_list {
+            if self.delete_pending_object(&pending_delete.get_storage_key()).await? {
+                deleted += 1;
+            }
+        }
+
+        Ok(deleted)
+    }
+
+    async fn file_cleanup_sharing_attempts(&self) -> Result<u64> {
+        let curr_time = (self.curr_time_fn)();
+        let window_start_before = curr_time - self.sharing_throttle.window;
+
+        let repo = self.sharing_attempt_repository.write().await;
+        repo.purge(&window_start_before, &curr_time).await
+    }
+
+    // NOTE: a request can never take larger files than the customer could upload, the limit in
+    //       effect at creation is kept with the request
+    async fn file_create_request(&self, customer: &Customer, folder_id: &Option<Uuid>, expireat: &DateTime<Utc>, password: &Option<String>, max_files: &Option<i32>, max_size_bytes: &Option<i64>) -> Result<FileRequest> {
+        let curr_time = (self.curr_time_fn)();
+        if *expireat <= curr_time {
+            bail!(FileError::FileRequestExpireAtInvalid)
+        }
+
+        if max_files.is_some_and(|max_files| max_files <= 0) {
+            bail!(FileError::FileRequestMaxFilesInvalid)
+        }
+
+        let customer_max_size = customer.get_max_upload_size_bytes().unwrap_or(self.max_upload_size_bytes);
+        let max_size_bytes = match max_size_bytes {
+            Some(max_size_bytes) if *max_size_bytes <= 0 || *max_size_bytes as u64 > customer_max_size => {
+                bail!(FileError::FileRequestMaxSizeInvalid)
+            }
+            Some(max_size_bytes) => *max_size_bytes,
+            None => customer_max_size as i64,
+        };
+
+        let password_hash = match password {
+            Some(password) => Some(hash_password(password)?),
+            None => None,
+        };
+
+        let code = self.request_code_generator().await?;
+        let file_request_list = {
+            let repo = self.file_request_repository.write().await;
+            let params = FileRequestCreateParams {
+                customer_id: customer.get_id(),
+                folder_id: *folder_id,
+                code,
+                expireat: *expireat,
+                password: password_hash,
+                max_files: *max_files,
+                max_size_bytes,
+            };
+            repo.create(&params).await?
+        };
+
+        // NOTE: the request is only created into a folder of the customer, whether the folder does
+        //       not exist or belongs to someone else is not told apart
+        if file_request_list.is_empty() {
+            bail!(FileError::FileRequestFolderInvalid)
+        }
+
+        let mut file_request = file_request_list[0].clone();
+        file_request.set_public_base_url(&self.public_base_url);
+        Ok(file_request)
+    }
+
+    async fn file_list_requests(&self, customer_id: &Uuid) -> Result<Vec<FileRequest>> {
+        let mut file_request_list = {
+            let repo = self.file_request_repository.read().await;
+            repo.list_by_customer_id(customer_id).await?
         };
 
-        if file_meta_list.len() == 0 {
+        for file_request in file_request_list.iter_mut() {
+            file_request.set_public_base_url(&self.public_base_url);
+        }
+        Ok(file_request_list)
+    }
+
+    async fn file_revoke_request(&self, id: &Uuid, customer_id: &Uuid) -> Result<()> {
+        let file_request_list = {
+            let repo = self.file_request_repository.write().await;
+            repo.revoke(id, customer_id).await?
+        };
+
+        if file_request_list.is_empty() {
+            bail!(FileError::FileRequestNotFound)
+        }
+
+        Ok(())
+    }
+
+    async fn file_read_request_by_code(&self, code: &str) -> Result<FileRequest> {
+        let file_request_list = {
+            let repo = self.file_request_repository.read().await;
+            repo.get_by_code(code).await?
+        };
+
+        if file_request_list.is_empty() {
+            bail!(FileError::FileRequestNotFound)
+        }
+
+        let mut file_request = file_request_list[0].clone();
+        if file_request.is_expired(&(self.curr_time_fn)()) {
+            bail!(FileError::FileRequestExpired)
+        }
+
+        file_request.set_public_base_url(&self.public_base_url);
+        Ok(file_request)
+    }
+
+    // NOTE: the file is counted before it is uploaded so concurrent uploads never overrun
+    //       max_files, the count is given back when the upload fails.
+    async fn file_upload_by_request(&self, code: &str, password: Option<String>, client: &SharingClient, name: &str, declared_mime_type: Option<String>, body: StorageStream) -> Result<FileMeta> {
+        let file_request = self.file_read_request_by_code(code).await?;
+        if file_request.is_full() {
+            bail!(FileError::FileRequestFull)
+        }
+
+        if file_request.has_password() {
+            let password = password.unwrap_or_default();
+            self.check_link_password(&file_request.get_id(), client, || file_request.check_password(&password)).await?;
+        }
+
+        let file_request_list = {
+            let repo = self.file_request_repository.write().await;
+            repo.reserve_upload(&file_request.get_id()).await?
+        };
+
+        if file_request_list.is_empty() {
+            bail!(FileError::FileRequestFull)
+        }
+
+        let destination = UploadDestination {
+            customer_id: file_request.get_customer_id(),
+            max_size: file_request.get_max_size_bytes() as u64,
+            folder_id: file_request.get_folder_id(),
+            file_request_id: Some(file_request.get_id()),
+        };
+        let file_meta = self.store_upload(&destination, name, declared_mime_type, body).await;
+
+        if file_meta.is_err() {
+            let repo = self.file_request_repository.write().await;
+            if let Err(err) = repo.release_upload(&file_request.get_id()).await {
+                log::warn!("failed to release the upload of file request {}: {}", file_request.get_id(), err);
+            }
+        }
+        file_meta
+    }
+
+    async fn file_grant(&self, file_id: &Uuid, customer_id: &Uuid, grantee: &Customer, permission: FilePermission) -> Result<FileGrant> {
+        let file_meta = self.read_owned_file_meta(file_id, customer_id).await?;
+        if file_meta.is_trashed() {
             bail!(FileError::FileNotFound)
         }
 
-        let file_meta = file_meta_list[0].clone();
+        if grantee.get_id() == *customer_id {
+            bail!(FileError::FileGrantToOwner)
+        }
 
-        let file_stream = self.file_uploader.download(&file_meta).await?;
-        Ok(file_stream)
+        let granted_at = (self.curr_time_fn)();
+        let repo = self.file_grant_repository.write().await;
+        repo.upsert(file_id, &grantee.get_id(), permission, &granted_at).await
     }
 
-}
\ No newline at end of file
+    async fn file_list_grants(&self, file_id: &Uuid, customer_id: &Uuid) -> Result<Vec<FileGrant>> {
+        self.read_owned_file_meta(file_id, customer_id).await?;
+
+        let repo = self.file_grant_repository.read().await;
+        repo.list_by_file_id(file_id).await
+    }
+
+    async fn file_revoke_grant(&self, file_id: &Uuid, customer_id: &Uuid, grantee_id: &Uuid) -> Result<()> {
+        self.read_owned_file_meta(file_id, customer_id).await?;
+
+        let file_grant_list = {
+            let repo = self.file_grant_repository.write().await;
+            repo.revoke(file_id, grantee_id).await?
+        };
+
+        if file_grant_list.is_empty() {
+            bail!(FileError::FileGrantNotFound)
+        }
+
+        Ok(())
+    }
+
+    async fn file_list_shared_with(&self, customer_id: &Uuid) -> Result<Vec<SharedFile>> {
+        let repo = self.file_grant_repository.read().await;
+        repo.list_shared_with_customer_id(customer_id).await
+    }
+}
@@ -1,16 +1,19 @@
 use std::sync::Arc;
 
-use chrono::{DateTime, TimeZone, Utc};
+use bytes::Bytes;
+use chrono::{DateTime, Duration, TimeZone, Utc};
+use futures_util::{FutureExt, StreamExt};
+use mockall::Sequence;
 use tokio::sync::RwLock;
 use uuid::{uuid, Uuid};
 
-use crate::domain::{entity::file_meta::FileMeta, error::file::FileError, repository::{file_meta::MockFileMetaRepositoryTrait, file_sharing::MockFileSharingRepositoryTrait}};
+use crate::domain::{entity::{file_grant::{FileGrant, FilePermission, SharedFile}, file_list::{FileMetaCursor, FileMetaFilter, FileMetaListQuery, FileMetaPage, FileMetaSortKey, SortOrder}, file_meta::{FileContent, FileMeta, FileSharingMeta, SHARING_CODE_LENGTH}, file_request::FileRequest, customer::Customer, password::{hash_password, PasswordVerifyResult}, sharing_access::{SharingAccess, SharingAccessCursor, SharingAccessOutcome, SharingAccessStats, SharingClient}, sharing_attempt::SharingAttempt, sharing_unlock::SharingUnlock, storage_object::{ByteRange, PendingDelete, StorageObject, StorageObjectStat, StorageStream}}, error::file::FileError, repository::{file_grant::MockFileGrantRepositoryTrait, file_meta::MockFileMetaRepositoryTrait, file_request::MockFileRequestRepositoryTrait, file_sharing::MockFileSharingRepositoryTrait, pending_delete::MockPendingDeleteRepositoryTrait, sharing_access::MockSharingAccessRepositoryTrait, sharing_attempt::MockSharingAttemptRepositoryTrait, storage::MockStorageBackendTrait}};
 
-use super::file::{FileServiceImpl, FileServiceTrait, MockFileUploaderTrait};
+use super::file::{FileRepositories, FileServiceImpl, FileServiceTrait, SharingThrottle};
 
 enum FileSvcTestContextExpectedResult {
     WithFileMetaResult(Result<FileMeta, FileError>),
-    WithFileMetaListResult(Result<Vec<FileMeta>, FileError>),
+    WithFileMetaPageResult(Result<FileMetaPage, FileError>),
 }
 
 struct FileSvcTestContext {
@@ -33,15 +36,50 @@ impl FileSvcTestContext {
     }
 }
 
+const FAKE_PUBLIC_BASE_URL: &str = "https://thundershare.test";
+const FAKE_MAX_UPLOAD_SIZE: u64 = 1024;
+
+const FAKE_UPLOAD_CONTENT: &[u8] = b"\x89PNG\r\n\x1a\nhello";
+const FAKE_UPLOAD_SHA256: &str = "0e8754cfeb908584f7a2f2ccd08e37fda8d3d540cbaf2e16ac559c7ea59a7151";
+
 fn fake_current_at() -> DateTime<Utc> {
     Utc.with_ymd_and_hms(1990, 3, 3, 0, 0, 0).unwrap()
 }
 
+fn fake_file_meta(id: &Uuid, customer_id: &Uuid, url: &str) -> FileMeta {
+    FileMeta::new_with_content(id, customer_id, url, &FileContent::default(), &DateTime::<Utc>::default())
+}
+
+fn fake_sharing_client() -> SharingClient {
+    SharingClient::new("127.0.0.1", &Some(String::from("curl/8.5.0")))
+}
+
+// NOTE: logs every access it is given, the outcome is checked by the tests that care about it
+fn fake_sharing_access_repo(times: usize) -> Arc<RwLock<MockSharingAccessRepositoryTrait>> {
+    let mut mock_repo = MockSharingAccessRepositoryTrait::new();
+    mock_repo
+        .expect_create()
+        .times(times)
+        .returning(|sharing_id, accessed_at, client, outcome| Ok(SharingAccess::new_full(&Uuid::default(), sharing_id, accessed_at, client, outcome, 0)));
+
+    Arc::new(RwLock::new(mock_repo))
+}
+
+// NOTE: grants the files to nobody unless given some
+fn fake_file_grant_repo(file_grant_list: Vec<FileGrant>) -> Arc<RwLock<MockFileGrantRepositoryTrait>> {
+    let mut mock_repo = MockFileGrantRepositoryTrait::new();
+    mock_repo
+        .expect_get()
+        .returning(move |_, _| Ok(file_grant_list.clone()));
+
+    Arc::new(RwLock::new(mock_repo))
+}
+
 #[actix_rt::test]
 async fn test_file_svc_read_by_id() {
     let test_context = vec![
         FileSvcTestContext::new(
-            FileMeta::new(""),
+            fake_file_meta(&Uuid::default(), &Uuid::default(), ""),
             || {
                 let mock_file_meta_repo = {
                     let mut mock_repo = MockFileMetaRepositoryTrait::new();
@@ -49,13 +87,13 @@ async fn test_file_svc_read_by_id() {
                     mock_repo
                         .expect_get_file_meta_by_id()
                         .times(1)
-                        .returning(move |_id| Ok(vec![FileMeta::new("")]));
+                        .returning(move |_id| Ok(vec![fake_file_meta(&Uuid::default(), &Uuid::default(), "")]));
 
                     mock_repo
                 };
 
-                let mock_file_uploader = {
-                    let mut mock_repo = MockFileUploaderTrait::new();
+                let mock_storage_backend = {
+                    let mut mock_repo = MockStorageBackendTrait::new();
 
                     mock_repo
                 };
@@ -67,18 +105,26 @@ async fn test_file_svc_read_by_id() {
                 };
 
                 let svc = {
-                    let file_uploader = Arc::new(mock_file_uploader);
+                    let storage_backend = Arc::new(mock_storage_backend);
                     let file_meta_repo = Arc::new(RwLock::new(mock_file_meta_repo));
                     let file_sharing_meta_repo = Arc::new(RwLock::new(mock_file_sharing_meta_repo));
-                    FileServiceImpl::new(fake_current_at, file_uploader, file_meta_repo, file_sharing_meta_repo)
+                    FileServiceImpl::new(fake_current_at, FAKE_PUBLIC_BASE_URL, FAKE_MAX_UPLOAD_SIZE, SharingThrottle::default(), storage_backend, FileRepositories {
+                        file_meta_repository: file_meta_repo,
+                        file_sharing_meta_repository: file_sharing_meta_repo,
+                        pending_delete_repository: Arc::new(RwLock::new(MockPendingDeleteRepositoryTrait::new())),
+                        sharing_attempt_repository: Arc::new(RwLock::new(MockSharingAttemptRepositoryTrait::new())),
+                        sharing_access_repository: Arc::new(RwLock::new(MockSharingAccessRepositoryTrait::new())),
+                        file_request_repository: Arc::new(RwLock::new(MockFileRequestRepositoryTrait::new())),
+                        file_grant_repository: fake_file_grant_repo(vec![]),
+                    })
                 };
 
                 svc
             },
-            FileSvcTestContextExpectedResult::WithFileMetaResult(Ok(FileMeta::new(""))),
+            FileSvcTestContextExpectedResult::WithFileMetaResult(Ok(fake_file_meta(&Uuid::default(), &Uuid::default(), ""))),
         ),
         FileSvcTestContext::new(
-            FileMeta::new(""),
+            fake_file_meta(&Uuid::default(), &Uuid::default(), ""),
             || {
                 let mock_file_meta_repo = {
                     let mut mock_repo = MockFileMetaRepositoryTrait::new();
@@ -91,8 +137,8 @@ async fn test_file_svc_read_by_id() {
                     mock_repo
                 };
 
-                let mock_file_uploader = {
-                    let mut mock_repo = MockFileUploaderTrait::new();
+                let mock_storage_backend = {
+                    let mut mock_repo = MockStorageBackendTrait::new();
 
                     mock_repo
                 };
@@ -104,10 +150,18 @@ async fn test_file_svc_read_by_id() {
                 };
 
                 let svc = {
-                    let file_uploader = Arc::new(mock_file_uploader);
+                    let storage_backend = Arc::new(mock_storage_backend);
                     let file_meta_repo = Arc::new(RwLock::new(mock_file_meta_repo));
                     let file_sharing_meta_repo = Arc::new(RwLock::new(mock_file_sharing_meta_repo));
-                    FileServiceImpl::new(fake_current_at, file_uploader, file_meta_repo, file_sharing_meta_repo)
+                    FileServiceImpl::new(fake_current_at, FAKE_PUBLIC_BASE_URL, FAKE_MAX_UPLOAD_SIZE, SharingThrottle::default(), storage_backend, FileRepositories {
+                        file_meta_repository: file_meta_repo,
+                        file_sharing_meta_repository: file_sharing_meta_repo,
+                        pending_delete_repository: Arc::new(RwLock::new(MockPendingDeleteRepositoryTrait::new())),
+                        sharing_attempt_repository: Arc::new(RwLock::new(MockSharingAttemptRepositoryTrait::new())),
+                        sharing_access_repository: Arc::new(RwLock::new(MockSharingAccessRepositoryTrait::new())),
+                        file_request_repository: Arc::new(RwLock::new(MockFileRequestRepositoryTrait::new())),
+                        file_grant_repository: fake_file_grant_repo(vec![]),
+                    })
                 };
 
                 svc
@@ -115,7 +169,7 @@ async fn test_file_svc_read_by_id() {
             FileSvcTestContextExpectedResult::WithFileMetaResult(Err(FileError::FileNotFound)),
         ),
         FileSvcTestContext::new(
-            FileMeta::new(""),
+            fake_file_meta(&Uuid::default(), &Uuid::default(), ""),
             || {
                 let mock_file_meta_repo = {
                     let mut mock_repo = MockFileMetaRepositoryTrait::new();
@@ -123,13 +177,13 @@ async fn test_file_svc_read_by_id() {
                     mock_repo
                         .expect_get_file_meta_by_id()
                         .times(1)
-                        .returning(move |_id| Ok(vec![FileMeta::new_full(&Uuid::default(), &uuid!("67e55044-10b1-426f-9247-bb680e5fe0c8"), "")]));
+                        .returning(move |_id| Ok(vec![fake_file_meta(&Uuid::default(), &uuid!("67e55044-10b1-426f-9247-bb680e5fe0c8"), "")]));
 
                     mock_repo
                 };
 
-                let mock_file_uploader = {
-                    let mut mock_repo = MockFileUploaderTrait::new();
+                let mock_storage_backend = {
+                    let mut mock_repo = MockStorageBackendTrait::new();
 
                     mock_repo
                 };
@@ -141,10 +195,18 @@ async fn test_file_svc_read_by_id() {
                 };
 
                 let svc = {
-                    let file_uploader = Arc::new(mock_file_uploader);
+                    let storage_backend = Arc::new(mock_storage_backend);
                     let file_meta_repo = Arc::new(RwLock::new(mock_file_meta_repo));
                     let file_sharing_meta_repo = Arc::new(RwLock::new(mock_file_sharing_meta_repo));
-                    FileServiceImpl::new(fake_current_at, file_uploader, file_meta_repo, file_sharing_meta_repo)
+                    FileServiceImpl::new(fake_current_at, FAKE_PUBLIC_BASE_URL, FAKE_MAX_UPLOAD_SIZE, SharingThrottle::default(), storage_backend, FileRepositories {
+                        file_meta_repository: file_meta_repo,
+                        file_sharing_meta_repository: file_sharing_meta_repo,
+                        pending_delete_repository: Arc::new(RwLock::new(MockPendingDeleteRepositoryTrait::new())),
+                        sharing_attempt_repository: Arc::new(RwLock::new(MockSharingAttemptRepositoryTrait::new())),
+                        sharing_access_repository: Arc::new(RwLock::new(MockSharingAccessRepositoryTrait::new())),
+                        file_request_repository: Arc::new(RwLock::new(MockFileRequestRepositoryTrait::new())),
+                        file_grant_repository: fake_file_grant_repo(vec![]),
+                    })
                 };
 
                 svc
@@ -172,7 +234,7 @@ async fn test_file_svc_read_by_id() {
 async fn test_file_svc_list_by_customer_id() {
     let test_context = vec![
         FileSvcTestContext::new(
-            FileMeta::new(""),
+            fake_file_meta(&Uuid::default(), &Uuid::default(), ""),
             || {
                 let mock_file_meta_repo = {
                     let mut mock_repo = MockFileMetaRepositoryTrait::new();
@@ -180,13 +242,14 @@ async fn test_file_svc_list_by_customer_id() {
                     mock_repo
                         .expect_list_file_meta_by_customer_id()
                         .times(1)
-                        .returning(move |_id| Ok(vec![FileMeta::new("")]));
+                        .withf(|_id, _query, limit| *limit == 3)
+                        .returning(move |_id, _query, _limit| Ok(vec![fake_file_meta(&Uuid::default(), &Uuid::default(), "")]));
 
                     mock_repo
                 };
 
-                let mock_file_uploader = {
-                    let mut mock_repo = MockFileUploaderTrait::new();
+                let mock_storage_backend = {
+                    let mut mock_repo = MockStorageBackendTrait::new();
 
                     mock_repo
                 };
@@ -198,18 +261,26 @@ async fn test_file_svc_list_by_customer_id() {
                 };
 
                 let svc = {
-                    let file_uploader = Arc::new(mock_file_uploader);
+                    let storage_backend = Arc::new(mock_storage_backend);
                     let file_meta_repo = Arc::new(RwLock::new(mock_file_meta_repo));
                     let file_sharing_meta_repo = Arc::new(RwLock::new(mock_file_sharing_meta_repo));
-                    FileServiceImpl::new(fake_current_at, file_uploader, file_meta_repo, file_sharing_meta_repo)
+                    FileServiceImpl::new(fake_current_at, FAKE_PUBLIC_BASE_URL, FAKE_MAX_UPLOAD_SIZE, SharingThrottle::default(), storage_backend, FileRepositories {
+                        file_meta_repository: file_meta_repo,
+                        file_sharing_meta_repository: file_sharing_meta_repo,
+                        pending_delete_repository: Arc::new(RwLock::new(MockPendingDeleteRepositoryTrait::new())),
+                        sharing_attempt_repository: Arc::new(RwLock::new(MockSharingAttemptRepositoryTrait::new())),
+                        sharing_access_repository: Arc::new(RwLock::new(MockSharingAccessRepositoryTrait::new())),
+                        file_request_repository: Arc::new(RwLock::new(MockFileRequestRepositoryTrait::new())),
+                        file_grant_repository: fake_file_grant_repo(vec![]),
+                    })
                 };
 
                 svc
             },
-            FileSvcTestContextExpectedResult::WithFileMetaListResult(Ok(vec![FileMeta::new("")])),
+            FileSvcTestContextExpectedResult::WithFileMetaPageResult(Ok(FileMetaPage::new(vec![fake_file_meta(&Uuid::default(), &Uuid::default(), "")], None))),
         ),
         FileSvcTestContext::new(
-            FileMeta::new(""),
+            fake_file_meta(&Uuid::default(), &Uuid::default(), ""),
             || {
                 let mock_file_meta_repo = {
                     let mut mock_repo = MockFileMetaRepositoryTrait::new();
@@ -217,13 +288,13 @@ async fn test_file_svc_list_by_customer_id() {
                     mock_repo
                         .expect_list_file_meta_by_customer_id()
                         .times(1)
-                        .returning(move |_id| Ok(vec![]));
+                        .returning(move |_id, _query, _limit| Ok(vec![]));
 
                     mock_repo
                 };
 
-                let mock_file_uploader = {
-                    let mut mock_repo = MockFileUploaderTrait::new();
+                let mock_storage_backend = {
+                    let mut mock_repo = MockStorageBackendTrait::new();
 
                     mock_repo
                 };
@@ -235,26 +306,34 @@ async fn test_file_svc_list_by_customer_id() {
                 };
 
                 let svc = {
-                    let file_uploader = Arc::new(mock_file_uploader);
+                    let storage_backend = Arc::new(mock_storage_backend);
                     let file_meta_repo = Arc::new(RwLock::new(mock_file_meta_repo));
                     let file_sharing_meta_repo = Arc::new(RwLock::new(mock_file_sharing_meta_repo));
-                    FileServiceImpl::new(fake_current_at, file_uploader, file_meta_repo, file_sharing_meta_repo)
+                    FileServiceImpl::new(fake_current_at, FAKE_PUBLIC_BASE_URL, FAKE_MAX_UPLOAD_SIZE, SharingThrottle::default(), storage_backend, FileRepositories {
+                        file_meta_repository: file_meta_repo,
+                        file_sharing_meta_repository: file_sharing_meta_repo,
+                        pending_delete_repository: Arc::new(RwLock::new(MockPendingDeleteRepositoryTrait::new())),
+                        sharing_attempt_repository: Arc::new(RwLock::new(MockSharingAttemptRepositoryTrait::new())),
+                        sharing_access_repository: Arc::new(RwLock::new(MockSharingAccessRepositoryTrait::new())),
+                        file_request_repository: Arc::new(RwLock::new(MockFileRequestRepositoryTrait::new())),
+                        file_grant_repository: fake_file_grant_repo(vec![]),
+                    })
                 };
 
                 svc
             },
-            FileSvcTestContextExpectedResult::WithFileMetaListResult(Ok(vec![])),
+            FileSvcTestContextExpectedResult::WithFileMetaPageResult(Ok(FileMetaPage::new(vec![], None))),
         ),
     ];
 
     for t in test_context {
         let svc = (t.setup_fn)();
         let result = svc
-            .file_list_by_customer_id(&Uuid::default())
+            .file_list_by_customer_id(&Uuid::default(), &FileMetaListQuery::default(), 2)
             .await
             .map_err(|err| err.downcast().unwrap());
 
-        let FileSvcTestContextExpectedResult::WithFileMetaListResult(expected_result) = t.expected
+        let FileSvcTestContextExpectedResult::WithFileMetaPageResult(expected_result) = t.expected
         else {
             return;
         };
@@ -262,61 +341,2138 @@ async fn test_file_svc_list_by_customer_id() {
     }
 }
 
+#[actix_rt::test]
+async fn test_file_svc_list_by_customer_id_next_page() {
+    let owner_id = uuid!("8a1f2c7e-4b3d-4e5f-9a6b-7c8d9e0f1a2b");
+    let file_meta_list: Vec<FileMeta> = ["a.txt", "b.txt", "c.txt"]
+        .iter()
+        .map(|name| {
+            let content = FileContent::new(name, 5, "text/plain", "");
+            FileMeta::new_with_content(&Uuid::new_v4(), &owner_id, name, &content, &fake_current_at())
+        })
+        .collect();
+
+    let mock_file_meta_repo = {
+        let mut mock_repo = MockFileMetaRepositoryTrait::new();
+        let file_meta_list = file_meta_list.clone();
+        mock_repo
+            .expect_list_file_meta_by_customer_id()
+            .times(1)
+            .withf(move |id, _query, limit| *id == owner_id && *limit == 3)
+            .returning(move |_id, _query, _limit| Ok(file_meta_list.clone()));
+
+        mock_repo
+    };
+
+    let svc = {
+        let storage_backend = Arc::new(MockStorageBackendTrait::new());
+        let file_meta_repo = Arc::new(RwLock::new(mock_file_meta_repo));
+        let file_sharing_meta_repo = Arc::new(RwLock::new(MockFileSharingRepositoryTrait::new()));
+        let pending_delete_repo = Arc::new(RwLock::new(MockPendingDeleteRepositoryTrait::new()));
+        FileServiceImpl::new(fake_current_at, FAKE_PUBLIC_BASE_URL, FAKE_MAX_UPLOAD_SIZE, SharingThrottle::default(), storage_backend, FileRepositories {
+            file_meta_repository: file_meta_repo,
+            file_sharing_meta_repository: file_sharing_meta_repo,
+            pending_delete_repository: pending_delete_repo,
+            sharing_attempt_repository: Arc::new(RwLock::new(MockSharingAttemptRepositoryTrait::new())),
+            sharing_access_repository: Arc::new(RwLock::new(MockSharingAccessRepositoryTrait::new())),
+            file_request_repository: Arc::new(RwLock::new(MockFileRequestRepositoryTrait::new())),
+            file_grant_repository: fake_file_grant_repo(vec![]),
+        })
+    };
+
+    // NOTE: the extra file only tells that there is a next page, the cursor points at the last file returned
+    let query = FileMetaListQuery::new(FileMetaSortKey::Name, SortOrder::Asc, FileMetaFilter::default(), None);
+    let page = svc.file_list_by_customer_id(&owner_id, &query, 2).await.unwrap();
+
+    assert_eq!(page.get_file_meta_list(), file_meta_list[..2].to_vec());
+    assert_eq!(page.get_next_cursor(), Some(FileMetaCursor::new(&file_meta_list[1], FileMetaSortKey::Name, SortOrder::Asc)));
+}
+
+fn fake_upload_body() -> StorageStream {
+    let chunks: Vec<std::io::Result<Bytes>> = FAKE_UPLOAD_CONTENT
+        .chunks(4)
+        .map(|chunk| Ok(Bytes::copy_from_slice(chunk)))
+        .collect();
+    Box::pin(futures_util::stream::iter(chunks))
+}
+
+// NOTE: reads the body the way a backend would, the fake bodies above never have to wait
+fn drain_body(mut body: StorageStream) -> std::io::Result<u64> {
+    let mut size = 0;
+    while let Some(Some(chunk)) = body.next().now_or_never() {
+        size += chunk?.len() as u64;
+    }
+    Ok(size)
+}
+
 #[actix_rt::test]
 async fn test_file_svc_file_upload() {
     let test_context = vec![
-        FileSvcTestContext::new(
-            FileMeta::new(""),
-            || {
-                let mock_file_meta_repo = {
-                    let mut mock_repo = MockFileMetaRepositoryTrait::new();
-                    mock_repo.expect_create()
-                    .times(1)
-                    .returning(|customer_id, _url| {Ok(FileMeta::new(""))});
+        (None, Ok(fake_file_meta(&Uuid::default(), &Uuid::default(), ""))),
+        (Some(13), Ok(fake_file_meta(&Uuid::default(), &Uuid::default(), ""))),
+        (Some(12), Err(FileError::FileTooLarge)),
+    ];
 
-                    mock_repo
-                };
+    for (max_upload_size_bytes, expected) in test_context {
+        let mock_file_meta_repo = {
+            let mut mock_repo = MockFileMetaRepositoryTrait::new();
+            mock_repo.expect_create()
+            .times(expected.iter().count())
+            .withf(|_customer_id, _url, content, uploaded_at, folder_id, file_request_id| {
+                *content == FileContent::new("avatar.png", 13, "image/png", FAKE_UPLOAD_SHA256) && *uploaded_at == fake_current_at() && folder_id.is_none() && file_request_id.is_none()
+            })
+            .returning(|_customer_id, _url, _content, _uploaded_at, _folder_id, _file_request_id| {Ok(fake_file_meta(&Uuid::default(), &Uuid::default(), ""))});
+
+            mock_repo
+        };
 
-                let mock_file_uploader = {
-                    let mut mock_repo = MockFileUploaderTrait::new();
+        let mock_storage_backend = {
+            let mut mock_repo = MockStorageBackendTrait::new();
 
-                    mock_repo.expect_upload()
-                    .times(1)
-                    .returning(|_src, _dest| {Ok(())});
+            mock_repo.expect_put()
+            .times(1)
+            .withf(|key, _body| !key.is_empty())
+            .returning(|_key, body| {
+                let size = drain_body(body)?;
+                Ok(StorageObjectStat::new(size, "", &fake_current_at()))
+            });
 
-                    mock_repo
-                };
+            mock_repo
+        };
 
-                let mock_file_sharing_meta_repo = {
-                    let mut mock_repo = MockFileSharingRepositoryTrait::new();
+        let svc = {
+            let storage_backend = Arc::new(mock_storage_backend);
+            let file_meta_repo = Arc::new(RwLock::new(mock_file_meta_repo));
+            let file_sharing_meta_repo = Arc::new(RwLock::new(MockFileSharingRepositoryTrait::new()));
+            FileServiceImpl::new(fake_current_at, FAKE_PUBLIC_BASE_URL, FAKE_MAX_UPLOAD_SIZE, SharingThrottle::default(), storage_backend, FileRepositories {
+                file_meta_repository: file_meta_repo,
+                file_sharing_meta_repository: file_sharing_meta_repo,
+                pending_delete_repository: Arc::new(RwLock::new(MockPendingDeleteRepositoryTrait::new())),
+                sharing_attempt_repository: Arc::new(RwLock::new(MockSharingAttemptRepositoryTrait::new())),
+                sharing_access_repository: Arc::new(RwLock::new(MockSharingAccessRepositoryTrait::new())),
+                file_request_repository: Arc::new(RwLock::new(MockFileRequestRepositoryTrait::new())),
+                file_grant_repository: fake_file_grant_repo(vec![]),
+            })
+        };
 
-                    mock_repo
-                };
+        let mut customer = Customer::new("mikejiang");
+        customer.set_max_upload_size_bytes(max_upload_size_bytes);
+        let result = svc
+            .file_upload(&customer, "avatar.png", Some(String::from("text/plain")), fake_upload_body())
+            .await
+            .map_err(|err| err.downcast().unwrap());
 
-                let svc = {
-                    let file_uploader = Arc::new(mock_file_uploader);
-                    let file_meta_repo = Arc::new(RwLock::new(mock_file_meta_repo));
-                    let file_sharing_meta_repo = Arc::new(RwLock::new(mock_file_sharing_meta_repo));
-                    FileServiceImpl::new(fake_current_at, file_uploader, file_meta_repo, file_sharing_meta_repo)
-                };
+        assert_eq!(result, expected);
+    }
+}
 
-                svc
-            },
-            FileSvcTestContextExpectedResult::WithFileMetaResult(Ok(FileMeta::new(""))),
+#[actix_rt::test]
+async fn test_file_svc_file_assemble() {
+    let test_context = vec![
+        (None, Ok(fake_file_meta(&Uuid::default(), &Uuid::default(), ""))),
+        (Some(12), Err(FileError::FileTooLarge)),
+    ];
+
+    for (max_upload_size_bytes, expected) in test_context {
+        let mock_file_meta_repo = {
+            let mut mock_repo = MockFileMetaRepositoryTrait::new();
+            mock_repo.expect_create()
+            .times(expected.iter().count())
+            .withf(|_customer_id, _url, content, uploaded_at, _folder_id, _file_request_id| {
+                *content == FileContent::new("avatar.png", 13, "image/png", FAKE_UPLOAD_SHA256) && *uploaded_at == fake_current_at()
+            })
+            .returning(|_customer_id, _url, _content, _uploaded_at, _folder_id, _file_request_id| {Ok(fake_file_meta(&Uuid::default(), &Uuid::default(), ""))});
+
+            mock_repo
+        };
+
+        // NOTE: the parts are joined by the backend and read back once, nothing is put again
+        let mock_storage_backend = {
+            let mut mock_repo = MockStorageBackendTrait::new();
+
+            mock_repo.expect_assemble()
+            .times(1)
+            .withf(|key, parts| !key.is_empty() && parts == ["first", "second"])
+            .returning(|_key, _parts| Ok(StorageObjectStat::new(FAKE_UPLOAD_CONTENT.len() as u64, "", &fake_current_at())));
+            mock_repo.expect_get()
+            .times(1)
+            .returning(|_key, _range| {
+                let stat = StorageObjectStat::new(FAKE_UPLOAD_CONTENT.len() as u64, "", &fake_current_at());
+                Ok(StorageObject::new(stat, None, fake_upload_body()))
+            });
+            mock_repo.expect_put().times(0);
+            mock_repo.expect_delete()
+            .times(if expected.is_err() { 1 } else { 0 })
+            .returning(|_key| Ok(()));
+
+            mock_repo
+        };
+
+        let svc = {
+            let storage_backend = Arc::new(mock_storage_backend);
+            let file_meta_repo = Arc::new(RwLock::new(mock_file_meta_repo));
+            let file_sharing_meta_repo = Arc::new(RwLock::new(MockFileSharingRepositoryTrait::new()));
+            FileServiceImpl::new(fake_current_at, FAKE_PUBLIC_BASE_URL, FAKE_MAX_UPLOAD_SIZE, SharingThrottle::default(), storage_backend, FileRepositories {
+                file_meta_repository: file_meta_repo,
+                file_sharing_meta_repository: file_sharing_meta_repo,
+                pending_delete_repository: Arc::new(RwLock::new(MockPendingDeleteRepositoryTrait::new())),
+                sharing_attempt_repository: Arc::new(RwLock::new(MockSharingAttemptRepositoryTrait::new())),
+                sharing_access_repository: Arc::new(RwLock::new(MockSharingAccessRepositoryTrait::new())),
+                file_request_repository: Arc::new(RwLock::new(MockFileRequestRepositoryTrait::new())),
+                file_grant_repository: fake_file_grant_repo(vec![]),
+            })
+        };
+
+        let mut customer = Customer::new("mikejiang");
+        customer.set_max_upload_size_bytes(max_upload_size_bytes);
+        let parts = [String::from("first"), String::from("second")];
+        let result = svc
+            .file_assemble(&customer, "avatar.png", Some(String::from("text/plain")), &parts)
+            .await
+            .map_err(|err| err.downcast().unwrap());
+
+        assert_eq!(result, expected);
+    }
+}
+
+#[actix_rt::test]
+async fn test_file_svc_file_upload_over_default_limit() {
+    let mock_storage_backend = {
+        let mut mock_repo = MockStorageBackendTrait::new();
+
+        mock_repo.expect_put()
+        .times(1)
+        .returning(|_key, body| {
+            let size = drain_body(body)?;
+            Ok(StorageObjectStat::new(size, "", &fake_current_at()))
+        });
+
+        mock_repo
+    };
+
+    let svc = {
+        let storage_backend = Arc::new(mock_storage_backend);
+        let file_meta_repo = Arc::new(RwLock::new(MockFileMetaRepositoryTrait::new()));
+        let file_sharing_meta_repo = Arc::new(RwLock::new(MockFileSharingRepositoryTrait::new()));
+        FileServiceImpl::new(fake_current_at, FAKE_PUBLIC_BASE_URL, 8, SharingThrottle::default(), storage_backend, FileRepositories {
+            file_meta_repository: file_meta_repo,
+            file_sharing_meta_repository: file_sharing_meta_repo,
+            pending_delete_repository: Arc::new(RwLock::new(MockPendingDeleteRepositoryTrait::new())),
+            sharing_attempt_repository: Arc::new(RwLock::new(MockSharingAttemptRepositoryTrait::new())),
+            sharing_access_repository: Arc::new(RwLock::new(MockSharingAccessRepositoryTrait::new())),
+            file_request_repository: Arc::new(RwLock::new(MockFileRequestRepositoryTrait::new())),
+            file_grant_repository: fake_file_grant_repo(vec![]),
+        })
+    };
+
+    let result = svc
+        .file_upload(&Customer::new("mikejiang"), "avatar.png", None, fake_upload_body())
+        .await
+        .map_err(|err| err.downcast().unwrap());
+    assert_eq!(result, Err(FileError::FileTooLarge));
+}
+
+#[actix_rt::test]
+async fn test_file_svc_file_upload_removes_orphan() {
+    let stored_key = Arc::new(std::sync::Mutex::new(String::new()));
+
+    let mock_file_meta_repo = {
+        let mut mock_repo = MockFileMetaRepositoryTrait::new();
+        mock_repo.expect_create()
+        .times(1)
+        .returning(|_customer_id, _url, _content, _uploaded_at, _folder_id, _file_request_id| {Err(sqlx::Error::PoolTimedOut.into())});
+
+        mock_repo
+    };
+
+    let mock_storage_backend = {
+        let mut mock_repo = MockStorageBackendTrait::new();
+
+        let put_key = stored_key.clone();
+        mock_repo.expect_put()
+        .times(1)
+        .returning(move |key, body| {
+            *put_key.lock().unwrap() = key.to_string();
+            let size = drain_body(body)?;
+            Ok(StorageObjectStat::new(size, "", &fake_current_at()))
+        });
+
+        let deleted_key = stored_key.clone();
+        mock_repo.expect_delete()
+        .times(1)
+        .withf(move |key| *key == *deleted_key.lock().unwrap())
+        .returning(|_key| {Ok(())});
+
+        mock_repo
+    };
+
+    let svc = {
+        let storage_backend = Arc::new(mock_storage_backend);
+        let file_meta_repo = Arc::new(RwLock::new(mock_file_meta_repo));
+        let file_sharing_meta_repo = Arc::new(RwLock::new(MockFileSharingRepositoryTrait::new()));
+        FileServiceImpl::new(fake_current_at, FAKE_PUBLIC_BASE_URL, FAKE_MAX_UPLOAD_SIZE, SharingThrottle::default(), storage_backend, FileRepositories {
+            file_meta_repository: file_meta_repo,
+            file_sharing_meta_repository: file_sharing_meta_repo,
+            pending_delete_repository: Arc::new(RwLock::new(MockPendingDeleteRepositoryTrait::new())),
+            sharing_attempt_repository: Arc::new(RwLock::new(MockSharingAttemptRepositoryTrait::new())),
+            sharing_access_repository: Arc::new(RwLock::new(MockSharingAccessRepositoryTrait::new())),
+            file_request_repository: Arc::new(RwLock::new(MockFileRequestRepositoryTrait::new())),
+            file_grant_repository: fake_file_grant_repo(vec![]),
+        })
+    };
+
+    let result = svc
+        .file_upload(&Customer::new("mikejiang"), "avatar.png", None, fake_upload_body())
+        .await;
+    assert!(result.unwrap_err().downcast::<sqlx::Error>().is_ok());
+}
+
+#[actix_rt::test]
+async fn test_file_svc_create_sharing_link() {
+    let expireat = Utc.with_ymd_and_hms(1990, 3, 4, 0, 0, 0).unwrap();
+    let customer_id = uuid!("67e55044-10b1-426f-9247-bb680e5fe0c8");
+
+    let mock_file_meta_repo = {
+        let mut mock_repo = MockFileMetaRepositoryTrait::new();
+
+        mock_repo
+            .expect_get_file_meta_by_id()
+            .times(1)
+            .returning(move |id| Ok(vec![fake_file_meta(id, &customer_id, "")]));
+
+        mock_repo
+    };
+    let mock_storage_backend = MockStorageBackendTrait::new();
+    let mock_file_sharing_meta_repo = {
+        let mut mock_repo = MockFileSharingRepositoryTrait::new();
+        let mut sequence = Sequence::new();
+
+        // NOTE: the first generated code collides with an existing link and has to be regenerated
+        mock_repo
+            .expect_get_by_code()
+            .times(1)
+            .in_sequence(&mut sequence)
+            .returning(move |code| Ok(vec![FileSharingMeta::new_full(&Uuid::default(), &Uuid::default(), code, &expireat, &None)]));
+        mock_repo
+            .expect_get_by_code()
+            .times(1)
+            .in_sequence(&mut sequence)
+            .returning(|_code| Ok(vec![]));
+        mock_repo
+            .expect_create()
+            .times(1)
+            .returning(|file_id, code, expireat, password, _max_downloads| Ok(FileSharingMeta::new_full(&Uuid::default(), file_id, code, expireat, password)));
+
+        mock_repo
+    };
+
+    let svc = {
+        let storage_backend = Arc::new(mock_storage_backend);
+        let file_meta_repo = Arc::new(RwLock::new(mock_file_meta_repo));
+        let file_sharing_meta_repo = Arc::new(RwLock::new(mock_file_sharing_meta_repo));
+        FileServiceImpl::new(fake_current_at, FAKE_PUBLIC_BASE_URL, FAKE_MAX_UPLOAD_SIZE, SharingThrottle::default(), storage_backend, FileRepositories {
+            file_meta_repository: file_meta_repo,
+            file_sharing_meta_repository: file_sharing_meta_repo,
+            pending_delete_repository: Arc::new(RwLock::new(MockPendingDeleteRepositoryTrait::new())),
+            sharing_attempt_repository: Arc::new(RwLock::new(MockSharingAttemptRepositoryTrait::new())),
+            sharing_access_repository: Arc::new(RwLock::new(MockSharingAccessRepositoryTrait::new())),
+            file_request_repository: Arc::new(RwLock::new(MockFileRequestRepositoryTrait::new())),
+            file_grant_repository: fake_file_grant_repo(vec![]),
+        })
+    };
+
+    let file_sharing_meta = svc
+        .file_create_sharing_link(&Uuid::default(), &customer_id, &expireat, &None, &None)
+        .await
+        .unwrap();
+
+    let link = file_sharing_meta.get_link();
+    let code = link.strip_prefix(&format!("{}/s/", FAKE_PUBLIC_BASE_URL)).unwrap();
+    assert_eq!(code.len(), SHARING_CODE_LENGTH);
+}
+
+#[actix_rt::test]
+async fn test_file_svc_create_sharing_link_rejected() {
+    let owner_id = uuid!("67e55044-10b1-426f-9247-bb680e5fe0c8");
+    let other_id = uuid!("936da01f-9abd-4d9d-80c7-02af85c822a8");
+    let tomorrow = Utc.with_ymd_and_hms(1990, 3, 4, 0, 0, 0).unwrap();
+    let yesterday = Utc.with_ymd_and_hms(1990, 3, 2, 0, 0, 0).unwrap();
+
+    let test_context = vec![
+        (
+            vec![fake_file_meta(&Uuid::default(), &owner_id, "")],
+            other_id,
+            tomorrow,
+            None,
+            1,
+            FileError::FileNotBelongToCustomer,
+        ),
+        (vec![], owner_id, tomorrow, None, 1, FileError::FileNotFound),
+        (
+            vec![fake_file_meta(&Uuid::default(), &owner_id, "")],
+            owner_id,
+            yesterday,
+            None,
+            0,
+            FileError::FileSharingLinkExpireAtInvalid,
+        ),
+        (
+            vec![fake_file_meta(&Uuid::default(), &owner_id, "")],
+            owner_id,
+            fake_current_at(),
+            None,
+            0,
+            FileError::FileSharingLinkExpireAtInvalid,
+        ),
+        (
+            vec![fake_file_meta(&Uuid::default(), &owner_id, "")],
+            owner_id,
+            tomorrow,
+            Some(0),
+            0,
+            FileError::FileSharingLinkMaxDownloadsInvalid,
         ),
     ];
 
-    for t in test_context {
-        let svc = (t.setup_fn)();
+    for (file_meta_list, customer_id, expireat, max_downloads, lookup_times, expected) in test_context {
+        let mock_file_meta_repo = {
+            let mut mock_repo = MockFileMetaRepositoryTrait::new();
+
+            mock_repo
+                .expect_get_file_meta_by_id()
+                .times(lookup_times)
+                .returning(move |_id| Ok(file_meta_list.clone()));
+
+            mock_repo
+        };
+        let mock_storage_backend = MockStorageBackendTrait::new();
+        let mock_file_sharing_meta_repo = {
+            let mut mock_repo = MockFileSharingRepositoryTrait::new();
+
+            mock_repo.expect_get_by_code().times(0);
+            mock_repo.expect_create().times(0);
+
+            mock_repo
+        };
+
+        let svc = {
+            let storage_backend = Arc::new(mock_storage_backend);
+            let file_meta_repo = Arc::new(RwLock::new(mock_file_meta_repo));
+            let file_sharing_meta_repo = Arc::new(RwLock::new(mock_file_sharing_meta_repo));
+            FileServiceImpl::new(fake_current_at, FAKE_PUBLIC_BASE_URL, FAKE_MAX_UPLOAD_SIZE, SharingThrottle::default(), storage_backend, FileRepositories {
+                file_meta_repository: file_meta_repo,
+                file_sharing_meta_repository: file_sharing_meta_repo,
+                pending_delete_repository: Arc::new(RwLock::new(MockPendingDeleteRepositoryTrait::new())),
+                sharing_attempt_repository: Arc::new(RwLock::new(MockSharingAttemptRepositoryTrait::new())),
+                sharing_access_repository: Arc::new(RwLock::new(MockSharingAccessRepositoryTrait::new())),
+                file_request_repository: Arc::new(RwLock::new(MockFileRequestRepositoryTrait::new())),
+                file_grant_repository: fake_file_grant_repo(vec![]),
+            })
+        };
+
+        let result: Result<FileSharingMeta, FileError> = svc
+            .file_create_sharing_link(&Uuid::default(), &customer_id, &expireat, &None, &max_downloads)
+            .await
+            .map_err(|err| err.downcast().unwrap());
+
+        assert_eq!(result, Err(expected));
+    }
+}
+
+#[actix_rt::test]
+async fn test_file_svc_open_content() {
+    let test_context = vec![
+        (Some(ByteRange::new(1, 3)), Ok(Bytes::from_static(b"ell"))),
+        (None, Err(FileError::FileNotFound)),
+    ];
+
+    for (range, expected) in test_context {
+        let mock_storage_backend = {
+            let mut mock_repo = MockStorageBackendTrait::new();
+
+            mock_repo
+                .expect_get()
+                .times(1)
+                .withf(move |key, requested_range| key == "stored-key" && *requested_range == range)
+                .returning(|_key, range| match range {
+                    Some(range) => {
+                        let stat = StorageObjectStat::new(5, "etag", &fake_current_at());
+                        let body = futures_util::stream::iter(vec![Ok(Bytes::from_static(b"ell"))]);
+                        Ok(StorageObject::new(stat, Some(range), Box::pin(body)))
+                    }
+                    None => Err(FileError::FileNotFound.into()),
+                });
+
+            mock_repo
+        };
+
+        let svc = {
+            let storage_backend = Arc::new(mock_storage_backend);
+            let file_meta_repo = Arc::new(RwLock::new(MockFileMetaRepositoryTrait::new()));
+            let file_sharing_meta_repo = Arc::new(RwLock::new(MockFileSharingRepositoryTrait::new()));
+            FileServiceImpl::new(fake_current_at, FAKE_PUBLIC_BASE_URL, FAKE_MAX_UPLOAD_SIZE, SharingThrottle::default(), storage_backend, FileRepositories {
+                file_meta_repository: file_meta_repo,
+                file_sharing_meta_repository: file_sharing_meta_repo,
+                pending_delete_repository: Arc::new(RwLock::new(MockPendingDeleteRepositoryTrait::new())),
+                sharing_attempt_repository: Arc::new(RwLock::new(MockSharingAttemptRepositoryTrait::new())),
+                sharing_access_repository: Arc::new(RwLock::new(MockSharingAccessRepositoryTrait::new())),
+                file_request_repository: Arc::new(RwLock::new(MockFileRequestRepositoryTrait::new())),
+                file_grant_repository: fake_file_grant_repo(vec![]),
+            })
+        };
+
+        let file_meta = fake_file_meta(&Uuid::default(), &Uuid::default(), "stored-key");
+        let result = match svc.file_open_content(&file_meta, range).await {
+            Ok(storage_object) => {
+                assert_eq!(storage_object.get_range(), range);
+                let chunks: Vec<std::io::Result<Bytes>> = storage_object.into_body().collect().await;
+                Ok(chunks.into_iter().map(|chunk| chunk.unwrap()).collect::<Vec<Bytes>>().concat().into())
+            }
+            Err(err) => Err(err.downcast().unwrap()),
+        };
+
+        assert_eq!(result, expected);
+    }
+}
+
+#[actix_rt::test]
+async fn test_file_svc_stat_content() {
+    let test_context = vec![
+        (vec![StorageObjectStat::new(5, "etag", &fake_current_at())], Ok(5)),
+        (vec![], Err(FileError::FileNotFound)),
+    ];
+
+    for (stat_list, expected) in test_context {
+        let mock_storage_backend = {
+            let mut mock_repo = MockStorageBackendTrait::new();
+
+            mock_repo
+                .expect_stat()
+                .times(1)
+                .returning(move |_key| Ok(stat_list.clone()));
+
+            mock_repo
+        };
+
+        let svc = {
+            let storage_backend = Arc::new(mock_storage_backend);
+            let file_meta_repo = Arc::new(RwLock::new(MockFileMetaRepositoryTrait::new()));
+            let file_sharing_meta_repo = Arc::new(RwLock::new(MockFileSharingRepositoryTrait::new()));
+            FileServiceImpl::new(fake_current_at, FAKE_PUBLIC_BASE_URL, FAKE_MAX_UPLOAD_SIZE, SharingThrottle::default(), storage_backend, FileRepositories {
+                file_meta_repository: file_meta_repo,
+                file_sharing_meta_repository: file_sharing_meta_repo,
+                pending_delete_repository: Arc::new(RwLock::new(MockPendingDeleteRepositoryTrait::new())),
+                sharing_attempt_repository: Arc::new(RwLock::new(MockSharingAttemptRepositoryTrait::new())),
+                sharing_access_repository: Arc::new(RwLock::new(MockSharingAccessRepositoryTrait::new())),
+                file_request_repository: Arc::new(RwLock::new(MockFileRequestRepositoryTrait::new())),
+                file_grant_repository: fake_file_grant_repo(vec![]),
+            })
+        };
+
+        let file_meta = fake_file_meta(&Uuid::default(), &Uuid::default(), "stored-key");
+        let result: Result<u64, FileError> = svc
+            .file_stat_content(&file_meta)
+            .await
+            .map(|stat| stat.get_size())
+            .map_err(|err| err.downcast().unwrap());
+
+        assert_eq!(result, expected);
+    }
+}
+
+fn fake_trashed_file_meta(id: &Uuid, customer_id: &Uuid, url: &str) -> FileMeta {
+    let mut file_meta = fake_file_meta(id, customer_id, url);
+    file_meta.set_deleted_at(Some(Utc.with_ymd_and_hms(1990, 2, 1, 0, 0, 0).unwrap()));
+    file_meta
+}
+
+#[actix_rt::test]
+async fn test_file_svc_delete() {
+    let file_id = uuid!("67e55044-10b1-426f-9247-bb680e5fe0c8");
+    let owner_id = uuid!("8a1f2c7e-4b3d-4e5f-9a6b-7c8d9e0f1a2b");
+    let grantee_id = uuid!("5d4c3b2a-1f0e-4d9c-8b7a-6f5e4d3c2b1a");
+    let fake_grant = move |permission| vec![FileGrant::new_full(&file_id, &grantee_id, "grantee", permission, &fake_current_at())];
+    let test_context = vec![
+        // NOTE: file meta list, customer id, grants of the file, expected result
+        (vec![], owner_id, vec![], Err(FileError::FileNotFound)),
+        (vec![fake_file_meta(&file_id, &owner_id, "stored-key")], Uuid::new_v4(), vec![], Err(FileError::FileNotBelongToCustomer)),
+        (vec![fake_trashed_file_meta(&file_id, &owner_id, "stored-key")], owner_id, vec![], Err(FileError::FileNotFound)),
+        (vec![fake_file_meta(&file_id, &owner_id, "stored-key")], owner_id, vec![], Ok(())),
+        // NOTE: read-write includes moving the file of the owner to the trash, read does not
+        (vec![fake_file_meta(&file_id, &owner_id, "stored-key")], grantee_id, fake_grant(FilePermission::Read), Err(FileError::FilePermissionDenied)),
+        (vec![fake_file_meta(&file_id, &owner_id, "stored-key")], grantee_id, fake_grant(FilePermission::ReadWrite), Ok(())),
+    ];
+
+    for (file_meta_list, customer_id, file_grant_list, expected) in test_context {
+        let trashed = expected.is_ok();
+
+        // NOTE: a delete only moves the file to the trash, neither the meta nor the object is removed
+        let mock_file_meta_repo = {
+            let mut mock_repo = MockFileMetaRepositoryTrait::new();
+            mock_repo
+                .expect_get_file_meta_by_id()
+                .times(1)
+                .returning(move |_id| Ok(file_meta_list.clone()));
+            mock_repo
+                .expect_trash()
+                .times(if trashed { 1 } else { 0 })
+                .withf(move |id, deleted_at| *id == file_id && *deleted_at == fake_current_at())
+                .returning(|_id, _deleted_at| Ok(()));
+            mock_repo.expect_delete().times(0);
+
+            mock_repo
+        };
+
+        let svc = {
+            let storage_backend = Arc::new(MockStorageBackendTrait::new());
+            let file_meta_repo = Arc::new(RwLock::new(mock_file_meta_repo));
+            let file_sharing_meta_repo = Arc::new(RwLock::new(MockFileSharingRepositoryTrait::new()));
+            let pending_delete_repo = Arc::new(RwLock::new(MockPendingDeleteRepositoryTrait::new()));
+            FileServiceImpl::new(fake_current_at, FAKE_PUBLIC_BASE_URL, FAKE_MAX_UPLOAD_SIZE, SharingThrottle::default(), storage_backend, FileRepositories {
+                file_meta_repository: file_meta_repo,
+                file_sharing_meta_repository: file_sharing_meta_repo,
+                pending_delete_repository: pending_delete_repo,
+                sharing_attempt_repository: Arc::new(RwLock::new(MockSharingAttemptRepositoryTrait::new())),
+                sharing_access_repository: Arc::new(RwLock::new(MockSharingAccessRepositoryTrait::new())),
+                file_request_repository: Arc::new(RwLock::new(MockFileRequestRepositoryTrait::new())),
+                file_grant_repository: fake_file_grant_repo(file_grant_list),
+            })
+        };
+
         let result = svc
-            .file_upload(&Uuid::default(), "")
+            .file_delete(&file_id, &customer_id)
             .await
             .map_err(|err| err.downcast().unwrap());
 
-        let FileSvcTestContextExpectedResult::WithFileMetaResult(expected_result) = t.expected
-        else {
-            return;
+        assert_eq!(result, expected);
+    }
+}
+
+#[actix_rt::test]
+async fn test_file_svc_read_trashed() {
+    let file_id = uuid!("67e55044-10b1-426f-9247-bb680e5fe0c8");
+    let owner_id = uuid!("8a1f2c7e-4b3d-4e5f-9a6b-7c8d9e0f1a2b");
+
+    let mock_file_meta_repo = {
+        let mut mock_repo = MockFileMetaRepositoryTrait::new();
+        mock_repo
+            .expect_get_file_meta_by_id()
+            .times(2)
+            .returning(move |_id| Ok(vec![fake_trashed_file_meta(&file_id, &owner_id, "stored-key")]));
+
+        mock_repo
+    };
+
+    let mock_file_sharing_meta_repo = {
+        let mut mock_repo = MockFileSharingRepositoryTrait::new();
+        mock_repo
+            .expect_get_by_id()
+            .times(1)
+            .returning(move |id| Ok(vec![FileSharingMeta::new_full(id, &file_id, "", &Utc.with_ymd_and_hms(2000, 1, 1, 0, 0, 0).unwrap(), &None)]));
+
+        mock_repo
+    };
+
+    let svc = {
+        let storage_backend = Arc::new(MockStorageBackendTrait::new());
+        let file_meta_repo = Arc::new(RwLock::new(mock_file_meta_repo));
+        let file_sharing_meta_repo = Arc::new(RwLock::new(mock_file_sharing_meta_repo));
+        let pending_delete_repo = Arc::new(RwLock::new(MockPendingDeleteRepositoryTrait::new()));
+        FileServiceImpl::new(fake_current_at, FAKE_PUBLIC_BASE_URL, FAKE_MAX_UPLOAD_SIZE, SharingThrottle::default(), storage_backend, FileRepositories {
+            file_meta_repository: file_meta_repo,
+            file_sharing_meta_repository: file_sharing_meta_repo,
+            pending_delete_repository: pending_delete_repo,
+            sharing_attempt_repository: Arc::new(RwLock::new(MockSharingAttemptRepositoryTrait::new())),
+            sharing_access_repository: fake_sharing_access_repo(1),
+            file_request_repository: Arc::new(RwLock::new(MockFileRequestRepositoryTrait::new())),
+            file_grant_repository: fake_file_grant_repo(vec![]),
+        })
+    };
+
+    // NOTE: a trashed file is hidden from its owner and from the sharing links pointing at it
+    let result = svc.file_read_by_id(&file_id, &owner_id).await.map_err(|err| err.downcast().unwrap());
+    assert_eq!(result, Err(FileError::FileNotFound));
+
+    let result = svc.file_get_sharing_link_by_id(&Uuid::new_v4(), None, &fake_sharing_client()).await.map(|(file_meta, _sharing_access)| file_meta).map_err(|err| err.downcast().unwrap());
+    assert_eq!(result, Err(FileError::FileNotFound));
+}
+
+#[actix_rt::test]
+async fn test_file_svc_restore() {
+    let file_id = uuid!("67e55044-10b1-426f-9247-bb680e5fe0c8");
+    let owner_id = uuid!("8a1f2c7e-4b3d-4e5f-9a6b-7c8d9e0f1a2b");
+    let test_context = vec![
+        // NOTE: file meta list, customer id, expected result
+        (vec![], owner_id, Err(FileError::FileNotFound)),
+        (vec![fake_trashed_file_meta(&file_id, &owner_id, "stored-key")], Uuid::new_v4(), Err(FileError::FileNotBelongToCustomer)),
+        (vec![fake_file_meta(&file_id, &owner_id, "stored-key")], owner_id, Err(FileError::FileNotInTrash)),
+        (vec![fake_trashed_file_meta(&file_id, &owner_id, "stored-key")], owner_id, Ok(fake_file_meta(&file_id, &owner_id, "stored-key"))),
+    ];
+
+    for (file_meta_list, customer_id, expected) in test_context {
+        let restored = expected.is_ok();
+
+        let mock_file_meta_repo = {
+            let mut mock_repo = MockFileMetaRepositoryTrait::new();
+            mock_repo
+                .expect_get_file_meta_by_id()
+                .times(1)
+                .returning(move |_id| Ok(file_meta_list.clone()));
+            mock_repo
+                .expect_restore()
+                .times(if restored { 1 } else { 0 })
+                .withf(move |id| *id == file_id)
+                .returning(|_id| Ok(()));
+
+            mock_repo
         };
-        assert_eq!(result, expected_result);
+
+        let svc = {
+            let storage_backend = Arc::new(MockStorageBackendTrait::new());
+            let file_meta_repo = Arc::new(RwLock::new(mock_file_meta_repo));
+            let file_sharing_meta_repo = Arc::new(RwLock::new(MockFileSharingRepositoryTrait::new()));
+            let pending_delete_repo = Arc::new(RwLock::new(MockPendingDeleteRepositoryTrait::new()));
+            FileServiceImpl::new(fake_current_at, FAKE_PUBLIC_BASE_URL, FAKE_MAX_UPLOAD_SIZE, SharingThrottle::default(), storage_backend, FileRepositories {
+                file_meta_repository: file_meta_repo,
+                file_sharing_meta_repository: file_sharing_meta_repo,
+                pending_delete_repository: pending_delete_repo,
+                sharing_attempt_repository: Arc::new(RwLock::new(MockSharingAttemptRepositoryTrait::new())),
+                sharing_access_repository: Arc::new(RwLock::new(MockSharingAccessRepositoryTrait::new())),
+                file_request_repository: Arc::new(RwLock::new(MockFileRequestRepositoryTrait::new())),
+                file_grant_repository: fake_file_grant_repo(vec![]),
+            })
+        };
+
+        let result = svc
+            .file_restore_by_id(&file_id, &customer_id)
+            .await
+            .map_err(|err| err.downcast().unwrap());
+
+        assert_eq!(result, expected);
+    }
+}
+
+#[actix_rt::test]
+async fn test_file_svc_purge() {
+    let file_id = uuid!("67e55044-10b1-426f-9247-bb680e5fe0c8");
+    let owner_id = uuid!("8a1f2c7e-4b3d-4e5f-9a6b-7c8d9e0f1a2b");
+    let test_context = vec![
+        // NOTE: file meta list, customer id, whether the storage delete succeeds, expected result
+        (vec![], owner_id, true, Err(FileError::FileNotFound)),
+        (vec![fake_trashed_file_meta(&file_id, &owner_id, "stored-key")], Uuid::new_v4(), true, Err(FileError::FileNotBelongToCustomer)),
+        (vec![fake_file_meta(&file_id, &owner_id, "stored-key")], owner_id, true, Err(FileError::FileNotInTrash)),
+        (vec![fake_trashed_file_meta(&file_id, &owner_id, "stored-key")], owner_id, true, Ok(())),
+        (vec![fake_trashed_file_meta(&file_id, &owner_id, "stored-key")], owner_id, false, Ok(())),
+    ];
+
+    for (file_meta_list, customer_id, storage_deleted, expected) in test_context {
+        let deleted = expected.is_ok();
+
+        let mock_file_meta_repo = {
+            let mut mock_repo = MockFileMetaRepositoryTrait::new();
+            mock_repo
+                .expect_get_file_meta_by_id()
+                .times(1)
+                .returning(move |_id| Ok(file_meta_list.clone()));
+            mock_repo
+                .expect_delete()
+                .times(if deleted { 1 } else { 0 })
+                .withf(move |id, deleted_at| *id == file_id && *deleted_at == fake_current_at())
+                .returning(|_id, _deleted_at| Ok(()));
+
+            mock_repo
+        };
+
+        let mock_storage_backend = {
+            let mut mock_repo = MockStorageBackendTrait::new();
+            mock_repo
+                .expect_delete()
+                .times(if deleted { 1 } else { 0 })
+                .withf(|key| key == "stored-key")
+                .returning(move |_key| match storage_deleted {
+                    true => Ok(()),
+                    false => Err(std::io::Error::from(std::io::ErrorKind::TimedOut).into()),
+                });
+
+            mock_repo
+        };
+
+        // NOTE: the pending delete recorded with the file meta is only dropped once the object is gone
+        let mock_pending_delete_repo = {
+            let mut mock_repo = MockPendingDeleteRepositoryTrait::new();
+            mock_repo
+                .expect_remove()
+                .times(if deleted && storage_deleted { 1 } else { 0 })
+                .withf(|key| key == "stored-key")
+                .returning(|_key| Ok(()));
+            mock_repo
+                .expect_mark_failed()
+                .times(if deleted && !storage_deleted { 1 } else { 0 })
+                .withf(|key, _error, attempted_at| key == "stored-key" && *attempted_at == fake_current_at())
+                .returning(|_key, _error, _attempted_at| Ok(()));
+
+            mock_repo
+        };
+
+        let svc = {
+            let storage_backend = Arc::new(mock_storage_backend);
+            let file_meta_repo = Arc::new(RwLock::new(mock_file_meta_repo));
+            let file_sharing_meta_repo = Arc::new(RwLock::new(MockFileSharingRepositoryTrait::new()));
+            let pending_delete_repo = Arc::new(RwLock::new(mock_pending_delete_repo));
+            FileServiceImpl::new(fake_current_at, FAKE_PUBLIC_BASE_URL, FAKE_MAX_UPLOAD_SIZE, SharingThrottle::default(), storage_backend, FileRepositories {
+                file_meta_repository: file_meta_repo,
+                file_sharing_meta_repository: file_sharing_meta_repo,
+                pending_delete_repository: pending_delete_repo,
+                sharing_attempt_repository: Arc::new(RwLock::new(MockSharingAttemptRepositoryTrait::new())),
+                sharing_access_repository: Arc::new(RwLock::new(MockSharingAccessRepositoryTrait::new())),
+                file_request_repository: Arc::new(RwLock::new(MockFileRequestRepositoryTrait::new())),
+                file_grant_repository: fake_file_grant_repo(vec![]),
+            })
+        };
+
+        let result = svc
+            .file_purge_by_id(&file_id, &customer_id)
+            .await
+            .map_err(|err| err.downcast().unwrap());
+
+        assert_eq!(result, expected);
+    }
+}
+
+#[actix_rt::test]
+async fn test_file_svc_purge_trash() {
+    let owner_id = uuid!("8a1f2c7e-4b3d-4e5f-9a6b-7c8d9e0f1a2b");
+
+    let mock_file_meta_repo = {
+        let mut mock_repo = MockFileMetaRepositoryTrait::new();
+        mock_repo
+            .expect_list_trashed_file_meta_before()
+            .times(1)
+            .withf(|deleted_before, _limit| *deleted_before == Utc.with_ymd_and_hms(1990, 2, 1, 0, 0, 0).unwrap())
+            .returning(move |_deleted_before, _limit| Ok(vec![
+                fake_trashed_file_meta(&Uuid::new_v4(), &owner_id, "first"),
+                fake_trashed_file_meta(&Uuid::new_v4(), &owner_id, "second"),
+            ]));
+        mock_repo
+            .expect_delete()
+            .times(2)
+            .returning(|_id, _deleted_at| Ok(()));
+
+        mock_repo
+    };
+
+    let mock_storage_backend = {
+        let mut mock_repo = MockStorageBackendTrait::new();
+        mock_repo
+            .expect_delete()
+            .times(2)
+            .withf(|key| key == "first" || key == "second")
+            .returning(|_key| Ok(()));
+
+        mock_repo
+    };
+
+    let mock_pending_delete_repo = {
+        let mut mock_repo = MockPendingDeleteRepositoryTrait::new();
+        mock_repo
+            .expect_remove()
+            .times(2)
+            .returning(|_key| Ok(()));
+
+        mock_repo
+    };
+
+    let svc = {
+        let storage_backend = Arc::new(mock_storage_backend);
+        let file_meta_repo = Arc::new(RwLock::new(mock_file_meta_repo));
+        let file_sharing_meta_repo = Arc::new(RwLock::new(MockFileSharingRepositoryTrait::new()));
+        let pending_delete_repo = Arc::new(RwLock::new(mock_pending_delete_repo));
+        FileServiceImpl::new(fake_current_at, FAKE_PUBLIC_BASE_URL, FAKE_MAX_UPLOAD_SIZE, SharingThrottle::default(), storage_backend, FileRepositories {
+            file_meta_repository: file_meta_repo,
+            file_sharing_meta_repository: file_sharing_meta_repo,
+            pending_delete_repository: pending_delete_repo,
+            sharing_attempt_repository: Arc::new(RwLock::new(MockSharingAttemptRepositoryTrait::new())),
+            sharing_access_repository: Arc::new(RwLock::new(MockSharingAccessRepositoryTrait::new())),
+            file_request_repository: Arc::new(RwLock::new(MockFileRequestRepositoryTrait::new())),
+            file_grant_repository: fake_file_grant_repo(vec![]),
+        })
+    };
+
+    assert_eq!(svc.file_purge_trash(Duration::days(30)).await.unwrap(), 2);
+}
+
+#[actix_rt::test]
+async fn test_file_svc_cleanup_storage() {
+    let mock_pending_delete_repo = {
+        let mut mock_repo = MockPendingDeleteRepositoryTrait::new();
+        mock_repo
+            .expect_list()
+            .times(1)
+            .returning(|_limit| Ok(vec![PendingDelete::new("first"), PendingDelete::new("second"), PendingDelete::new("third")]));
+        mock_repo
+            .expect_remove()
+            .times(2)
+            .withf(|key| key == "first" || key == "third")
+            .returning(|_key| Ok(()));
+        mock_repo
+            .expect_mark_failed()
+            .times(1)
+            .withf(|key, _error, _attempted_at| key == "second")
+            .returning(|_key, _error, _attempted_at| Ok(()));
+
+        mock_repo
+    };
+
+    let mock_storage_backend = {
+        let mut mock_repo = MockStorageBackendTrait::new();
+        mock_repo
+            .expect_delete()
+            .times(3)
+            .returning(|key| match key {
+                "second" => Err(std::io::Error::from(std::io::ErrorKind::TimedOut).into()),
+                _ => Ok(()),
+            });
+
+        mock_repo
+    };
+
+    let svc = {
+        let storage_backend = Arc::new(mock_storage_backend);
+        let file_meta_repo = Arc::new(RwLock::new(MockFileMetaRepositoryTrait::new()));
+        let file_sharing_meta_repo = Arc::new(RwLock::new(MockFileSharingRepositoryTrait::new()));
+        let pending_delete_repo = Arc::new(RwLock::new(mock_pending_delete_repo));
+        FileServiceImpl::new(fake_current_at, FAKE_PUBLIC_BASE_URL, FAKE_MAX_UPLOAD_SIZE, SharingThrottle::default(), storage_backend, FileRepositories {
+            file_meta_repository: file_meta_repo,
+            file_sharing_meta_repository: file_sharing_meta_repo,
+            pending_delete_repository: pending_delete_repo,
+            sharing_attempt_repository: Arc::new(RwLock::new(MockSharingAttemptRepositoryTrait::new())),
+            sharing_access_repository: Arc::new(RwLock::new(MockSharingAccessRepositoryTrait::new())),
+            file_request_repository: Arc::new(RwLock::new(MockFileRequestRepositoryTrait::new())),
+            file_grant_repository: fake_file_grant_repo(vec![]),
+        })
+    };
+
+    assert_eq!(svc.file_cleanup_storage().await.unwrap(), 2);
+}
+
+#[actix_rt::test]
+async fn test_file_svc_list_sharing_links() {
+    let customer_id = uuid!("67e55044-10b1-426f-9247-bb680e5fe0c8");
+    let file_id = uuid!("936da01f-9abd-4d9d-80c7-02af85c822a8");
+    let expireat = Utc.with_ymd_and_hms(1990, 3, 4, 0, 0, 0).unwrap();
+
+    let mock_file_sharing_meta_repo = {
+        let mut mock_repo = MockFileSharingRepositoryTrait::new();
+
+        mock_repo
+            .expect_list_by_customer_id()
+            .withf(move |id, file_id_filter| *id == customer_id && *file_id_filter == Some(file_id))
+            .times(1)
+            .returning(move |_customer_id, _file_id| Ok(vec![FileSharingMeta::new_full(&Uuid::default(), &file_id, "code", &expireat, &Some(String::from("password")))]));
+
+        mock_repo
+    };
+
+    let svc = {
+        let storage_backend = Arc::new(MockStorageBackendTrait::new());
+        let file_meta_repo = Arc::new(RwLock::new(MockFileMetaRepositoryTrait::new()));
+        let file_sharing_meta_repo = Arc::new(RwLock::new(mock_file_sharing_meta_repo));
+        FileServiceImpl::new(fake_current_at, FAKE_PUBLIC_BASE_URL, FAKE_MAX_UPLOAD_SIZE, SharingThrottle::default(), storage_backend, FileRepositories {
+            file_meta_repository: file_meta_repo,
+            file_sharing_meta_repository: file_sharing_meta_repo,
+            pending_delete_repository: Arc::new(RwLock::new(MockPendingDeleteRepositoryTrait::new())),
+            sharing_attempt_repository: Arc::new(RwLock::new(MockSharingAttemptRepositoryTrait::new())),
+            sharing_access_repository: Arc::new(RwLock::new(MockSharingAccessRepositoryTrait::new())),
+            file_request_repository: Arc::new(RwLock::new(MockFileRequestRepositoryTrait::new())),
+            file_grant_repository: fake_file_grant_repo(vec![]),
+        })
+    };
+
+    let file_sharing_meta_list = svc
+        .file_list_sharing_links(&customer_id, &Some(file_id))
+        .await
+        .unwrap();
+
+    assert_eq!(file_sharing_meta_list.len(), 1);
+    assert_eq!(file_sharing_meta_list[0].get_link(), format!("{}/s/code", FAKE_PUBLIC_BASE_URL));
+    assert!(file_sharing_meta_list[0].has_password());
+}
+
+#[actix_rt::test]
+async fn test_file_svc_update_sharing_link() {
+    let customer_id = uuid!("67e55044-10b1-426f-9247-bb680e5fe0c8");
+    let tomorrow = Utc.with_ymd_and_hms(1990, 3, 4, 0, 0, 0).unwrap();
+    let yesterday = Utc.with_ymd_and_hms(1990, 3, 2, 0, 0, 0).unwrap();
+
+    let test_context = vec![
+        (Some(tomorrow), Some(None), 1, true, Ok(tomorrow)),
+        (None, Some(Some(String::from("password"))), 1, true, Ok(yesterday)),
+        (Some(tomorrow), None, 1, false, Err(FileError::FileSharingLinkNotFound)),
+        (Some(yesterday), None, 0, true, Err(FileError::FileSharingLinkExpireAtInvalid)),
+        (Some(fake_current_at()), None, 0, true, Err(FileError::FileSharingLinkExpireAtInvalid)),
+    ];
+
+    for (expireat, password, update_times, is_owned, expected) in test_context {
+        let mock_file_sharing_meta_repo = {
+            let mut mock_repo = MockFileSharingRepositoryTrait::new();
+
+            mock_repo
+                .expect_update()
+                .times(update_times)
+                .returning(move |id, _customer_id, expireat, password| {
+                    if !is_owned {
+                        return Ok(vec![]);
+                    }
+                    let expireat = expireat.unwrap_or(yesterday);
+                    let password = password.clone().flatten();
+                    Ok(vec![FileSharingMeta::new_full(id, &Uuid::default(), "code", &expireat, &password)])
+                });
+
+            mock_repo
+        };
+
+        let svc = {
+            let storage_backend = Arc::new(MockStorageBackendTrait::new());
+            let file_meta_repo = Arc::new(RwLock::new(MockFileMetaRepositoryTrait::new()));
+            let file_sharing_meta_repo = Arc::new(RwLock::new(mock_file_sharing_meta_repo));
+            FileServiceImpl::new(fake_current_at, FAKE_PUBLIC_BASE_URL, FAKE_MAX_UPLOAD_SIZE, SharingThrottle::default(), storage_backend, FileRepositories {
+                file_meta_repository: file_meta_repo,
+                file_sharing_meta_repository: file_sharing_meta_repo,
+                pending_delete_repository: Arc::new(RwLock::new(MockPendingDeleteRepositoryTrait::new())),
+                sharing_attempt_repository: Arc::new(RwLock::new(MockSharingAttemptRepositoryTrait::new())),
+                sharing_access_repository: Arc::new(RwLock::new(MockSharingAccessRepositoryTrait::new())),
+                file_request_repository: Arc::new(RwLock::new(MockFileRequestRepositoryTrait::new())),
+                file_grant_repository: fake_file_grant_repo(vec![]),
+            })
+        };
+
+        let result: Result<DateTime<Utc>, FileError> = svc
+            .file_update_sharing_link(&Uuid::default(), &customer_id, &expireat, &password)
+            .await
+            .map(|file_sharing_meta| file_sharing_meta.get_expireat())
+            .map_err(|err| err.downcast().unwrap());
+
+        assert_eq!(result, expected);
+    }
+}
+
+#[actix_rt::test]
+async fn test_file_svc_revoke_sharing_link() {
+    let customer_id = uuid!("67e55044-10b1-426f-9247-bb680e5fe0c8");
+    let expireat = Utc.with_ymd_and_hms(1990, 3, 4, 0, 0, 0).unwrap();
+
+    let test_context = vec![
+        (vec![FileSharingMeta::new_full(&Uuid::default(), &Uuid::default(), "code", &expireat, &None)], Ok(())),
+        (vec![], Err(FileError::FileSharingLinkNotFound)),
+    ];
+
+    for (file_sharing_meta_list, expected) in test_context {
+        let mock_file_sharing_meta_repo = {
+            let mut mock_repo = MockFileSharingRepositoryTrait::new();
+
+            mock_repo
+                .expect_revoke()
+                .withf(move |_id, owner_id| *owner_id == customer_id)
+                .times(1)
+                .returning(move |_id, _customer_id| Ok(file_sharing_meta_list.clone()));
+
+            mock_repo
+        };
+
+        let svc = {
+            let storage_backend = Arc::new(MockStorageBackendTrait::new());
+            let file_meta_repo = Arc::new(RwLock::new(MockFileMetaRepositoryTrait::new()));
+            let file_sharing_meta_repo = Arc::new(RwLock::new(mock_file_sharing_meta_repo));
+            FileServiceImpl::new(fake_current_at, FAKE_PUBLIC_BASE_URL, FAKE_MAX_UPLOAD_SIZE, SharingThrottle::default(), storage_backend, FileRepositories {
+                file_meta_repository: file_meta_repo,
+                file_sharing_meta_repository: file_sharing_meta_repo,
+                pending_delete_repository: Arc::new(RwLock::new(MockPendingDeleteRepositoryTrait::new())),
+                sharing_attempt_repository: Arc::new(RwLock::new(MockSharingAttemptRepositoryTrait::new())),
+                sharing_access_repository: Arc::new(RwLock::new(MockSharingAccessRepositoryTrait::new())),
+                file_request_repository: Arc::new(RwLock::new(MockFileRequestRepositoryTrait::new())),
+                file_grant_repository: fake_file_grant_repo(vec![]),
+            })
+        };
+
+        let result: Result<(), FileError> = svc
+            .file_revoke_sharing_link(&Uuid::default(), &customer_id)
+            .await
+            .map_err(|err| err.downcast().unwrap());
+
+        assert_eq!(result, expected);
     }
-}
\ No newline at end of file
+}
+
+#[actix_rt::test]
+async fn test_file_svc_create_sharing_link_hashes_password() {
+    let expireat = Utc.with_ymd_and_hms(1990, 3, 4, 0, 0, 0).unwrap();
+    let customer_id = uuid!("67e55044-10b1-426f-9247-bb680e5fe0c8");
+
+    let mock_file_meta_repo = {
+        let mut mock_repo = MockFileMetaRepositoryTrait::new();
+        mock_repo
+            .expect_get_file_meta_by_id()
+            .times(1)
+            .returning(move |id| Ok(vec![fake_file_meta(id, &customer_id, "")]));
+
+        mock_repo
+    };
+    let mock_file_sharing_meta_repo = {
+        let mut mock_repo = MockFileSharingRepositoryTrait::new();
+        mock_repo
+            .expect_get_by_code()
+            .times(1)
+            .returning(|_code| Ok(vec![]));
+        mock_repo
+            .expect_create()
+            .withf(|_file_id, _code, _expireat, password, _max_downloads| password.as_ref().is_some_and(|password| password.starts_with("$argon2id$")))
+            .times(1)
+            .returning(|file_id, code, expireat, password, _max_downloads| Ok(FileSharingMeta::new_full(&Uuid::default(), file_id, code, expireat, password)));
+
+        mock_repo
+    };
+
+    let svc = {
+        let storage_backend = Arc::new(MockStorageBackendTrait::new());
+        let file_meta_repo = Arc::new(RwLock::new(mock_file_meta_repo));
+        let file_sharing_meta_repo = Arc::new(RwLock::new(mock_file_sharing_meta_repo));
+        FileServiceImpl::new(fake_current_at, FAKE_PUBLIC_BASE_URL, FAKE_MAX_UPLOAD_SIZE, SharingThrottle::default(), storage_backend, FileRepositories {
+            file_meta_repository: file_meta_repo,
+            file_sharing_meta_repository: file_sharing_meta_repo,
+            pending_delete_repository: Arc::new(RwLock::new(MockPendingDeleteRepositoryTrait::new())),
+            sharing_attempt_repository: Arc::new(RwLock::new(MockSharingAttemptRepositoryTrait::new())),
+            sharing_access_repository: Arc::new(RwLock::new(MockSharingAccessRepositoryTrait::new())),
+            file_request_repository: Arc::new(RwLock::new(MockFileRequestRepositoryTrait::new())),
+            file_grant_repository: fake_file_grant_repo(vec![]),
+        })
+    };
+
+    let file_sharing_meta = svc
+        .file_create_sharing_link(&Uuid::default(), &customer_id, &expireat, &Some(String::from("hunter2")), &None)
+        .await
+        .unwrap();
+
+    assert!(file_sharing_meta.has_password());
+    assert_eq!(file_sharing_meta.check_password("hunter2"), PasswordVerifyResult::Matched);
+}
+
+#[actix_rt::test]
+async fn test_file_svc_get_sharing_link_throttled() {
+    let link_id = uuid!("67e55044-10b1-426f-9247-bb680e5fe0c8");
+    let file_id = uuid!("936da01f-9abd-4d9d-80c7-02af85c822a8");
+    let expireat = Utc.with_ymd_and_hms(1990, 3, 4, 0, 0, 0).unwrap();
+    let hashed = hash_password("hunter2").unwrap();
+    let locked_until = Some(Utc.with_ymd_and_hms(1990, 3, 3, 0, 10, 0).unwrap());
+    let link_key = SharingAttempt::link_key(&link_id);
+    let client_key = SharingAttempt::client_key("127.0.0.1");
+
+    // NOTE: stored password, sent password, locked key, attempts counted, attempts forgiven, rehashes, expected
+    let test_context = vec![
+        (Some(hashed.clone()), "hunter2", None, 2, 2, 0, Ok(file_id)),
+        (Some(hashed.clone()), "hunter3", None, 2, 0, 0, Err(FileError::FileSharingLinkPasswordIncorrect)),
+        (Some(hashed.clone()), "hunter2", Some(link_key.clone()), 1, 0, 0, Err(FileError::FileSharingLinkLocked)),
+        (Some(hashed.clone()), "hunter2", Some(client_key.clone()), 2, 1, 0, Err(FileError::FileSharingLinkLocked)),
+        (Some(String::from("hunter2")), "hunter2", None, 2, 2, 1, Ok(file_id)),
+    ];
+
+    for (password, input, locked_key, begin_times, forgive_times, rehash_times, expected) in test_context {
+        let mock_file_meta_repo = {
+            let mut mock_repo = MockFileMetaRepositoryTrait::new();
+            mock_repo
+                .expect_get_file_meta_by_id()
+                .returning(move |id| Ok(vec![fake_file_meta(id, &Uuid::default(), "")]));
+
+            mock_repo
+        };
+        let mock_file_sharing_meta_repo = {
+            let mut mock_repo = MockFileSharingRepositoryTrait::new();
+            mock_repo
+                .expect_get_by_id()
+                .times(1)
+                .returning(move |id| Ok(vec![FileSharingMeta::new_full(id, &file_id, "code", &expireat, &password)]));
+            mock_repo
+                .expect_update_password()
+                .withf(|_id, password| password.starts_with("$argon2id$"))
+                .times(rehash_times)
+                .returning(|_id, _password| Ok(()));
+
+            mock_repo
+        };
+        let mock_sharing_attempt_repo = {
+            let mut mock_repo = MockSharingAttemptRepositoryTrait::new();
+            let mut seq = Sequence::new();
+            for key in [link_key.clone(), client_key.clone()].into_iter().take(begin_times) {
+                let locked = locked_key.as_ref() == Some(&key);
+                mock_repo
+                    .expect_begin_attempt()
+                    .withf(move |attempted_key, attempted_at, window_start_before, max_failures, next_locked_until| {
+                        *attempted_key == key
+                            && *attempted_at == fake_current_at()
+                            && *window_start_before == fake_current_at() - Duration::minutes(15)
+                            && *max_failures == 5
+                            && *next_locked_until == fake_current_at() + Duration::minutes(15)
+                    })
+                    .times(1)
+                    .in_sequence(&mut seq)
+                    .returning(move |key, _attempted_at, _window_start_before, _max_failures, _locked_until| {
+                        match locked {
+                            true => Ok(SharingAttempt::new_full(key, 0, &locked_until)),
+                            false => Ok(SharingAttempt::new_full(key, 1, &None)),
+                        }
+                    });
+            }
+            mock_repo
+                .expect_forgive_attempt()
+                .times(forgive_times)
+                .returning(|_key| Ok(()));
+
+            mock_repo
+        };
+
+        let svc = {
+            let storage_backend = Arc::new(MockStorageBackendTrait::new());
+            let file_meta_repo = Arc::new(RwLock::new(mock_file_meta_repo));
+            let file_sharing_meta_repo = Arc::new(RwLock::new(mock_file_sharing_meta_repo));
+            let sharing_attempt_repo = Arc::new(RwLock::new(mock_sharing_attempt_repo));
+            FileServiceImpl::new(fake_current_at, FAKE_PUBLIC_BASE_URL, FAKE_MAX_UPLOAD_SIZE, SharingThrottle::default(), storage_backend, FileRepositories {
+                file_meta_repository: file_meta_repo,
+                file_sharing_meta_repository: file_sharing_meta_repo,
+                pending_delete_repository: Arc::new(RwLock::new(MockPendingDeleteRepositoryTrait::new())),
+                sharing_attempt_repository: sharing_attempt_repo,
+                sharing_access_repository: fake_sharing_access_repo(1),
+                file_request_repository: Arc::new(RwLock::new(MockFileRequestRepositoryTrait::new())),
+                file_grant_repository: fake_file_grant_repo(vec![]),
+            })
+        };
+
+        let result: Result<Uuid, FileError> = svc
+            .file_get_sharing_link_by_id(&link_id, Some(input.to_string()), &fake_sharing_client())
+            .await
+            .map(|(file_meta, _sharing_access)| file_meta.get_id())
+            .map_err(|err| err.downcast().unwrap());
+
+        assert_eq!(result, expected);
+    }
+}
+
+#[actix_rt::test]
+async fn test_file_svc_get_sharing_link_without_password_not_throttled() {
+    let expireat = Utc.with_ymd_and_hms(1990, 3, 4, 0, 0, 0).unwrap();
+
+    let mock_file_meta_repo = {
+        let mut mock_repo = MockFileMetaRepositoryTrait::new();
+        mock_repo
+            .expect_get_file_meta_by_id()
+            .times(1)
+            .returning(move |id| Ok(vec![fake_file_meta(id, &Uuid::default(), "")]));
+
+        mock_repo
+    };
+    let mock_file_sharing_meta_repo = {
+        let mut mock_repo = MockFileSharingRepositoryTrait::new();
+        mock_repo
+            .expect_get_by_code()
+            .times(1)
+            .returning(move |code| Ok(vec![FileSharingMeta::new_full(&Uuid::default(), &Uuid::default(), code, &expireat, &None)]));
+
+        mock_repo
+    };
+    let mock_sharing_attempt_repo = {
+        let mut mock_repo = MockSharingAttemptRepositoryTrait::new();
+        mock_repo.expect_begin_attempt().times(0);
+        mock_repo.expect_forgive_attempt().times(0);
+
+        mock_repo
+    };
+
+    let svc = {
+        let storage_backend = Arc::new(MockStorageBackendTrait::new());
+        let file_meta_repo = Arc::new(RwLock::new(mock_file_meta_repo));
+        let file_sharing_meta_repo = Arc::new(RwLock::new(mock_file_sharing_meta_repo));
+        let sharing_attempt_repo = Arc::new(RwLock::new(mock_sharing_attempt_repo));
+        FileServiceImpl::new(fake_current_at, FAKE_PUBLIC_BASE_URL, FAKE_MAX_UPLOAD_SIZE, SharingThrottle::default(), storage_backend, FileRepositories {
+            file_meta_repository: file_meta_repo,
+            file_sharing_meta_repository: file_sharing_meta_repo,
+            pending_delete_repository: Arc::new(RwLock::new(MockPendingDeleteRepositoryTrait::new())),
+            sharing_attempt_repository: sharing_attempt_repo,
+            sharing_access_repository: fake_sharing_access_repo(1),
+            file_request_repository: Arc::new(RwLock::new(MockFileRequestRepositoryTrait::new())),
+            file_grant_repository: fake_file_grant_repo(vec![]),
+        })
+    };
+
+    let result = svc.file_get_sharing_link_by_code("code", Some(String::from("anything")), &fake_sharing_client()).await;
+    assert!(result.is_ok());
+}
+
+#[actix_rt::test]
+async fn test_file_svc_get_sharing_link_exhausted() {
+    let expireat = Utc.with_ymd_and_hms(1990, 3, 4, 0, 0, 0).unwrap();
+
+    // NOTE: downloads seen when the link is read, opening a link never counts a download
+    let test_context = vec![
+        (0, Ok(())),
+        (1, Err(FileError::FileSharingLinkExhausted)),
+    ];
+
+    for (downloads, expected) in test_context {
+        let mock_file_meta_repo = {
+            let mut mock_repo = MockFileMetaRepositoryTrait::new();
+            mock_repo
+                .expect_get_file_meta_by_id()
+                .returning(move |id| Ok(vec![fake_file_meta(id, &Uuid::default(), "")]));
+
+            mock_repo
+        };
+        let mock_file_sharing_meta_repo = {
+            let mut mock_repo = MockFileSharingRepositoryTrait::new();
+            mock_repo
+                .expect_get_by_code()
+                .times(1)
+                .returning(move |code| {
+                    let mut file_sharing_meta = FileSharingMeta::new_full(&Uuid::default(), &Uuid::default(), code, &expireat, &None);
+                    file_sharing_meta.set_downloads(&Some(1), downloads);
+                    Ok(vec![file_sharing_meta])
+                });
+            mock_repo.expect_consume_download().times(0);
+
+            mock_repo
+        };
+
+        let svc = {
+            let storage_backend = Arc::new(MockStorageBackendTrait::new());
+            let file_meta_repo = Arc::new(RwLock::new(mock_file_meta_repo));
+            let file_sharing_meta_repo = Arc::new(RwLock::new(mock_file_sharing_meta_repo));
+            FileServiceImpl::new(fake_current_at, FAKE_PUBLIC_BASE_URL, FAKE_MAX_UPLOAD_SIZE, SharingThrottle::default(), storage_backend, FileRepositories {
+                file_meta_repository: file_meta_repo,
+                file_sharing_meta_repository: file_sharing_meta_repo,
+                pending_delete_repository: Arc::new(RwLock::new(MockPendingDeleteRepositoryTrait::new())),
+                sharing_attempt_repository: Arc::new(RwLock::new(MockSharingAttemptRepositoryTrait::new())),
+                sharing_access_repository: fake_sharing_access_repo(1),
+                file_request_repository: Arc::new(RwLock::new(MockFileRequestRepositoryTrait::new())),
+                file_grant_repository: fake_file_grant_repo(vec![]),
+            })
+        };
+
+        let result: Result<(), FileError> = svc
+            .file_get_sharing_link_by_code("code", None, &fake_sharing_client())
+            .await
+            .map(|_result| ())
+            .map_err(|err| err.downcast().unwrap());
+
+        assert_eq!(result, expected);
+    }
+}
+
+#[actix_rt::test]
+async fn test_file_svc_consume_sharing_download() {
+    let link_id = uuid!("6f1c3d2a-8e4b-4f7a-9c1d-2b3e4f5a6b7c");
+    let access_id = uuid!("936da01f-9abd-4d9d-80c7-02af85c822a8");
+    let expireat = Utc.with_ymd_and_hms(1990, 3, 4, 0, 0, 0).unwrap();
+
+    // NOTE: whether a concurrent request took the last download first, access marked exhausted
+    let test_context = vec![
+        (false, 0, Ok(())),
+        (true, 1, Err(FileError::FileSharingLinkExhausted)),
+    ];
+
+    for (is_taken, set_outcome_times, expected) in test_context {
+        let mock_file_sharing_meta_repo = {
+            let mut mock_repo = MockFileSharingRepositoryTrait::new();
+            mock_repo
+                .expect_consume_download()
+                .withf(move |id| *id == link_id)
+                .times(1)
+                .returning(move |id| {
+                    if is_taken {
+                        return Ok(vec![]);
+                    }
+                    let mut file_sharing_meta = FileSharingMeta::new_full(id, &Uuid::default(), "code", &expireat, &None);
+                    file_sharing_meta.set_downloads(&Some(1), 1);
+                    Ok(vec![file_sharing_meta])
+                });
+
+            mock_repo
+        };
+        let mock_sharing_access_repo = {
+            let mut mock_repo = MockSharingAccessRepositoryTrait::new();
+            mock_repo
+                .expect_set_outcome()
+                .withf(move |id, outcome| *id == access_id && *outcome == SharingAccessOutcome::Exhausted)
+                .times(set_outcome_times)
+                .returning(|_id, _outcome| Ok(()));
+
+            mock_repo
+        };
+
+        let svc = {
+            let storage_backend = Arc::new(MockStorageBackendTrait::new());
+            let file_sharing_meta_repo = Arc::new(RwLock::new(mock_file_sharing_meta_repo));
+            let sharing_access_repo = Arc::new(RwLock::new(mock_sharing_access_repo));
+            FileServiceImpl::new(fake_current_at, FAKE_PUBLIC_BASE_URL, FAKE_MAX_UPLOAD_SIZE, SharingThrottle::default(), storage_backend, FileRepositories {
+                file_meta_repository: Arc::new(RwLock::new(MockFileMetaRepositoryTrait::new())),
+                file_sharing_meta_repository: file_sharing_meta_repo,
+                pending_delete_repository: Arc::new(RwLock::new(MockPendingDeleteRepositoryTrait::new())),
+                sharing_attempt_repository: Arc::new(RwLock::new(MockSharingAttemptRepositoryTrait::new())),
+                sharing_access_repository: sharing_access_repo,
+                file_request_repository: Arc::new(RwLock::new(MockFileRequestRepositoryTrait::new())),
+                file_grant_repository: fake_file_grant_repo(vec![]),
+            })
+        };
+
+        let sharing_access = SharingAccess::new_full(&access_id, &Some(link_id), &fake_current_at(), &fake_sharing_client(), SharingAccessOutcome::Success, 0);
+        let result: Result<(), FileError> = svc
+            .file_consume_sharing_download(&sharing_access)
+            .await
+            .map_err(|err| err.downcast().unwrap());
+
+        assert_eq!(result, expected);
+    }
+}
+
+#[actix_rt::test]
+async fn test_file_svc_get_sharing_link_logs_access() {
+    let link_id = uuid!("6f1c3d2a-8e4b-4f7a-9c1d-2b3e4f5a6b7c");
+    let expired_at = Utc.with_ymd_and_hms(1990, 3, 2, 0, 0, 0).unwrap();
+    let expireat = Utc.with_ymd_and_hms(1990, 3, 4, 0, 0, 0).unwrap();
+
+    // NOTE: link found under the code, its expiry, sharing id and outcome logged
+    let test_context = vec![
+        (false, expireat, None, SharingAccessOutcome::NotFound),
+        (true, expired_at, Some(link_id), SharingAccessOutcome::Expired),
+        (true, expireat, Some(link_id), SharingAccessOutcome::Success),
+    ];
+
+    for (is_found, link_expireat, expected_sharing_id, expected_outcome) in test_context {
+        let mock_file_meta_repo = {
+            let mut mock_repo = MockFileMetaRepositoryTrait::new();
+            mock_repo
+                .expect_get_file_meta_by_id()
+                .returning(move |id| Ok(vec![fake_file_meta(id, &Uuid::default(), "")]));
+
+            mock_repo
+        };
+        let mock_file_sharing_meta_repo = {
+            let mut mock_repo = MockFileSharingRepositoryTrait::new();
+            mock_repo
+                .expect_get_by_code()
+                .times(1)
+                .returning(move |code| {
+                    if !is_found {
+                        return Ok(vec![]);
+                    }
+                    Ok(vec![FileSharingMeta::new_full(&link_id, &Uuid::default(), code, &link_expireat, &None)])
+                });
+
+            mock_repo
+        };
+        let mock_sharing_access_repo = {
+            let mut mock_repo = MockSharingAccessRepositoryTrait::new();
+            mock_repo
+                .expect_create()
+                .withf(move |sharing_id, accessed_at, client, outcome| {
+                    *sharing_id == expected_sharing_id && *accessed_at == fake_current_at() && *client == fake_sharing_client() && *outcome == expected_outcome
+                })
+                .times(1)
+                .returning(|sharing_id, accessed_at, client, outcome| Ok(SharingAccess::new_full(&Uuid::default(), sharing_id, accessed_at, client, outcome, 0)));
+
+            mock_repo
+        };
+
+        let svc = {
+            let storage_backend = Arc::new(MockStorageBackendTrait::new());
+            let file_meta_repo = Arc::new(RwLock::new(mock_file_meta_repo));
+            let file_sharing_meta_repo = Arc::new(RwLock::new(mock_file_sharing_meta_repo));
+            let sharing_access_repo = Arc::new(RwLock::new(mock_sharing_access_repo));
+            FileServiceImpl::new(fake_current_at, FAKE_PUBLIC_BASE_URL, FAKE_MAX_UPLOAD_SIZE, SharingThrottle::default(), storage_backend, FileRepositories {
+                file_meta_repository: file_meta_repo,
+                file_sharing_meta_repository: file_sharing_meta_repo,
+                pending_delete_repository: Arc::new(RwLock::new(MockPendingDeleteRepositoryTrait::new())),
+                sharing_attempt_repository: Arc::new(RwLock::new(MockSharingAttemptRepositoryTrait::new())),
+                sharing_access_repository: sharing_access_repo,
+                file_request_repository: Arc::new(RwLock::new(MockFileRequestRepositoryTrait::new())),
+                file_grant_repository: fake_file_grant_repo(vec![]),
+            })
+        };
+
+        // NOTE: a refused attempt is only visible through the logged access, withf checks it
+        let result = svc
+            .file_get_sharing_link_by_code("code", None, &fake_sharing_client())
+            .await
+            .map(|(_file_meta, sharing_access)| sharing_access.get_outcome());
+
+        assert_eq!(result.ok(), Some(expected_outcome).filter(|outcome| *outcome == SharingAccessOutcome::Success));
+    }
+}
+
+#[actix_rt::test]
+async fn test_file_svc_list_sharing_access() {
+    let link_id = uuid!("6f1c3d2a-8e4b-4f7a-9c1d-2b3e4f5a6b7c");
+    let expireat = Utc.with_ymd_and_hms(1990, 3, 4, 0, 0, 0).unwrap();
+    let fake_sharing_access = move |n: u32| {
+        let accessed_at = fake_current_at() - Duration::minutes(n as i64);
+        SharingAccess::new_full(&Uuid::from_u128(n as u128), &Some(link_id), &accessed_at, &fake_sharing_client(), SharingAccessOutcome::Success, 1024)
+    };
+
+    // NOTE: whether the customer owns the link, accesses stored, expected page size and cursor
+    let test_context = vec![
+        (true, 2, Ok((2, None))),
+        (true, 3, Ok((2, Some(SharingAccessCursor::new(&fake_sharing_access(1)))))),
+        (false, 0, Err(FileError::FileSharingLinkNotFound)),
+    ];
+
+    for (is_owned, stored, expected) in test_context {
+        let mock_file_sharing_meta_repo = {
+            let mut mock_repo = MockFileSharingRepositoryTrait::new();
+            mock_repo
+                .expect_get_by_id_and_customer_id()
+                .times(1)
+                .returning(move |id, _customer_id| {
+                    if !is_owned {
+                        return Ok(vec![]);
+                    }
+                    Ok(vec![FileSharingMeta::new_full(id, &Uuid::default(), "code", &expireat, &None)])
+                });
+
+            mock_repo
+        };
+        let mock_sharing_access_repo = {
+            let mut mock_repo = MockSharingAccessRepositoryTrait::new();
+            mock_repo
+                .expect_stats_by_sharing_id()
+                .returning(|_sharing_id| Ok(SharingAccessStats::default()));
+            mock_repo
+                .expect_list_by_sharing_id()
+                .withf(move |sharing_id, cursor, limit| *sharing_id == link_id && cursor.is_none() && *limit == 3)
+                .returning(move |_sharing_id, _cursor, limit| Ok((0..stored.min(limit as u32)).map(fake_sharing_access).collect()));
+
+            mock_repo
+        };
+
+        let svc = {
+            let storage_backend = Arc::new(MockStorageBackendTrait::new());
+            let file_meta_repo = Arc::new(RwLock::new(MockFileMetaRepositoryTrait::new()));
+            let file_sharing_meta_repo = Arc::new(RwLock::new(mock_file_sharing_meta_repo));
+            let sharing_access_repo = Arc::new(RwLock::new(mock_sharing_access_repo));
+            FileServiceImpl::new(fake_current_at, FAKE_PUBLIC_BASE_URL, FAKE_MAX_UPLOAD_SIZE, SharingThrottle::default(), storage_backend, FileRepositories {
+                file_meta_repository: file_meta_repo,
+                file_sharing_meta_repository: file_sharing_meta_repo,
+                pending_delete_repository: Arc::new(RwLock::new(MockPendingDeleteRepositoryTrait::new())),
+                sharing_attempt_repository: Arc::new(RwLock::new(MockSharingAttemptRepositoryTrait::new())),
+                sharing_access_repository: sharing_access_repo,
+                file_request_repository: Arc::new(RwLock::new(MockFileRequestRepositoryTrait::new())),
+                file_grant_repository: fake_file_grant_repo(vec![]),
+            })
+        };
+
+        let result: Result<(usize, Option<SharingAccessCursor>), FileError> = svc
+            .file_list_sharing_access(&link_id, &Uuid::default(), &None, 2)
+            .await
+            .map(|(_stats, page)| (page.get_sharing_access_list().len(), page.get_next_cursor()))
+            .map_err(|err| err.downcast().unwrap());
+
+        assert_eq!(result, expected);
+    }
+}
+
+#[actix_rt::test]
+async fn test_file_svc_create_request() {
+    let expireat = Utc.with_ymd_and_hms(1990, 3, 4, 0, 0, 0).unwrap();
+    let expired_at = Utc.with_ymd_and_hms(1990, 3, 2, 0, 0, 0).unwrap();
+
+    // NOTE: expireat, max files, max size asked for, expected max size of the request
+    let test_context = vec![
+        (expireat, None, None, Ok(FAKE_MAX_UPLOAD_SIZE as i64)),
+        (expireat, Some(3), Some(512), Ok(512)),
+        (expired_at, None, None, Err(FileError::FileRequestExpireAtInvalid)),
+        (expireat, Some(0), None, Err(FileError::FileRequestMaxFilesInvalid)),
+        (expireat, None, Some(0), Err(FileError::FileRequestMaxSizeInvalid)),
+        (expireat, None, Some(FAKE_MAX_UPLOAD_SIZE as i64 + 1), Err(FileError::FileRequestMaxSizeInvalid)),
+    ];
+
+    for (expireat, max_files, max_size_bytes, expected) in test_context {
+        let mock_file_request_repo = {
+            let mut mock_repo = MockFileRequestRepositoryTrait::new();
+            mock_repo
+                .expect_get_by_code()
+                .returning(|_code| Ok(vec![]));
+            mock_repo
+                .expect_create()
+                .times(expected.iter().count())
+                .returning(|params| {
+                    let mut file_request = FileRequest::new_full(&Uuid::new_v4(), &params.customer_id, &params.folder_id, &params.code, &params.expireat, &params.password);
+                    file_request.set_limits(&params.max_files, params.max_size_bytes, 0);
+                    Ok(vec![file_request])
+                });
+
+            mock_repo
+        };
+
+        let svc = {
+            let storage_backend = Arc::new(MockStorageBackendTrait::new());
+            let file_meta_repo = Arc::new(RwLock::new(MockFileMetaRepositoryTrait::new()));
+            let file_sharing_meta_repo = Arc::new(RwLock::new(MockFileSharingRepositoryTrait::new()));
+            let file_request_repo = Arc::new(RwLock::new(mock_file_request_repo));
+            FileServiceImpl::new(fake_current_at, FAKE_PUBLIC_BASE_URL, FAKE_MAX_UPLOAD_SIZE, SharingThrottle::default(), storage_backend, FileRepositories {
+                file_meta_repository: file_meta_repo,
+                file_sharing_meta_repository: file_sharing_meta_repo,
+                pending_delete_repository: Arc::new(RwLock::new(MockPendingDeleteRepositoryTrait::new())),
+                sharing_attempt_repository: Arc::new(RwLock::new(MockSharingAttemptRepositoryTrait::new())),
+                sharing_access_repository: Arc::new(RwLock::new(MockSharingAccessRepositoryTrait::new())),
+                file_request_repository: file_request_repo,
+                file_grant_repository: fake_file_grant_repo(vec![]),
+            })
+        };
+
+        let result: Result<i64, FileError> = svc
+            .file_create_request(&Customer::new("mikejiang"), &None, &expireat, &None, &max_files, &max_size_bytes)
+            .await
+            .map(|file_request| {
+                assert!(file_request.get_link().starts_with(FAKE_PUBLIC_BASE_URL));
+                file_request.get_max_size_bytes()
+            })
+            .map_err(|err| err.downcast().unwrap());
+
+        assert_eq!(result, expected);
+    }
+}
+
+#[actix_rt::test]
+async fn test_file_svc_create_request_into_folder_of_another_customer() {
+    let expireat = Utc.with_ymd_and_hms(1990, 3, 4, 0, 0, 0).unwrap();
+
+    let mock_file_request_repo = {
+        let mut mock_repo = MockFileRequestRepositoryTrait::new();
+        mock_repo
+            .expect_get_by_code()
+            .returning(|_code| Ok(vec![]));
+        mock_repo
+            .expect_create()
+            .times(1)
+            .returning(|_params| Ok(vec![]));
+
+        mock_repo
+    };
+
+    let svc = {
+        let storage_backend = Arc::new(MockStorageBackendTrait::new());
+        let file_meta_repo = Arc::new(RwLock::new(MockFileMetaRepositoryTrait::new()));
+        let file_sharing_meta_repo = Arc::new(RwLock::new(MockFileSharingRepositoryTrait::new()));
+        let file_request_repo = Arc::new(RwLock::new(mock_file_request_repo));
+        FileServiceImpl::new(fake_current_at, FAKE_PUBLIC_BASE_URL, FAKE_MAX_UPLOAD_SIZE, SharingThrottle::default(), storage_backend, FileRepositories {
+            file_meta_repository: file_meta_repo,
+            file_sharing_meta_repository: file_sharing_meta_repo,
+            pending_delete_repository: Arc::new(RwLock::new(MockPendingDeleteRepositoryTrait::new())),
+            sharing_attempt_repository: Arc::new(RwLock::new(MockSharingAttemptRepositoryTrait::new())),
+            sharing_access_repository: Arc::new(RwLock::new(MockSharingAccessRepositoryTrait::new())),
+            file_request_repository: file_request_repo,
+            file_grant_repository: fake_file_grant_repo(vec![]),
+        })
+    };
+
+    let result: Result<FileRequest, FileError> = svc
+        .file_create_request(&Customer::new("mikejiang"), &Some(Uuid::new_v4()), &expireat, &None, &None, &None)
+        .await
+        .map_err(|err| err.downcast().unwrap());
+
+    assert_eq!(result, Err(FileError::FileRequestFolderInvalid));
+}
+
+#[actix_rt::test]
+async fn test_file_svc_upload_by_request() {
+    let owner_id = uuid!("2b7e6c1a-4d3f-4a8e-b5c9-0f1e2d3c4b5a");
+    let folder_id = uuid!("9a8b7c6d-5e4f-4a3b-8c2d-1e0f9a8b7c6d");
+    let request_id = uuid!("c3d2e1f0-a9b8-4c7d-8e6f-5a4b3c2d1e0f");
+    let expireat = Utc.with_ymd_and_hms(1990, 3, 4, 0, 0, 0).unwrap();
+    let expired_at = Utc.with_ymd_and_hms(1990, 3, 2, 0, 0, 0).unwrap();
+
+    // NOTE: expireat, max files, files uploaded so far, max size, whether a concurrent upload took
+    //       the last file, expected calls to reserve, put and release, expected result
+    let test_context = vec![
+        (expireat, Some(2), 0, 1024, false, 1, 1, 0, Ok(())),
+        (expireat, None, 9, 13, false, 1, 1, 0, Ok(())),
+        (expired_at, Some(2), 0, 1024, false, 0, 0, 0, Err(FileError::FileRequestExpired)),
+        (expireat, Some(1), 1, 1024, false, 0, 0, 0, Err(FileError::FileRequestFull)),
+        (expireat, Some(1), 0, 1024, true, 1, 0, 0, Err(FileError::FileRequestFull)),
+        (expireat, Some(2), 0, 12, false, 1, 1, 1, Err(FileError::FileTooLarge)),
+    ];
+
+    for (expireat, max_files, uploads, max_size_bytes, is_taken, reserve_times, put_times, release_times, expected) in test_context {
+        let fake_file_request = move || {
+            let mut file_request = FileRequest::new_full(&request_id, &owner_id, &Some(folder_id), "code", &expireat, &None);
+            file_request.set_limits(&max_files, max_size_bytes, uploads);
+            file_request
+        };
+
+        let mock_file_request_repo = {
+            let mut mock_repo = MockFileRequestRepositoryTrait::new();
+            mock_repo
+                .expect_get_by_code()
+                .times(1)
+                .returning(move |_code| Ok(vec![fake_file_request()]));
+            mock_repo
+                .expect_reserve_upload()
+                .times(reserve_times)
+                .returning(move |_id| {
+                    if is_taken {
+                        return Ok(vec![]);
+                    }
+                    Ok(vec![fake_file_request()])
+                });
+            mock_repo
+                .expect_release_upload()
+                .times(release_times)
+                .returning(|_id| Ok(()));
+
+            mock_repo
+        };
+        let mock_storage_backend = {
+            let mut mock_repo = MockStorageBackendTrait::new();
+            mock_repo
+                .expect_put()
+                .times(put_times)
+                .returning(|_key, body| {
+                    let size = drain_body(body)?;
+                    Ok(StorageObjectStat::new(size, "", &fake_current_at()))
+                });
+
+            mock_repo
+        };
+        let mock_file_meta_repo = {
+            let mut mock_repo = MockFileMetaRepositoryTrait::new();
+            mock_repo
+                .expect_create()
+                .times(expected.iter().count())
+                .withf(move |customer_id, _url, _content, _uploaded_at, file_folder_id, file_request_id| {
+                    *customer_id == owner_id && *file_folder_id == Some(folder_id) && *file_request_id == Some(request_id)
+                })
+                .returning(|_customer_id, _url, _content, _uploaded_at, _folder_id, _file_request_id| Ok(fake_file_meta(&Uuid::default(), &Uuid::default(), "")));
+
+            mock_repo
+        };
+
+        let svc = {
+            let storage_backend = Arc::new(mock_storage_backend);
+            let file_meta_repo = Arc::new(RwLock::new(mock_file_meta_repo));
+            let file_sharing_meta_repo = Arc::new(RwLock::new(MockFileSharingRepositoryTrait::new()));
+            let file_request_repo = Arc::new(RwLock::new(mock_file_request_repo));
+            FileServiceImpl::new(fake_current_at, FAKE_PUBLIC_BASE_URL, FAKE_MAX_UPLOAD_SIZE, SharingThrottle::default(), storage_backend, FileRepositories {
+                file_meta_repository: file_meta_repo,
+                file_sharing_meta_repository: file_sharing_meta_repo,
+                pending_delete_repository: Arc::new(RwLock::new(MockPendingDeleteRepositoryTrait::new())),
+                sharing_attempt_repository: Arc::new(RwLock::new(MockSharingAttemptRepositoryTrait::new())),
+                sharing_access_repository: Arc::new(RwLock::new(MockSharingAccessRepositoryTrait::new())),
+                file_request_repository: file_request_repo,
+                file_grant_repository: fake_file_grant_repo(vec![]),
+            })
+        };
+
+        let result: Result<(), FileError> = svc
+            .file_upload_by_request("code", None, &fake_sharing_client(), "avatar.png", None, fake_upload_body())
+            .await
+            .map(|_file_meta| ())
+            .map_err(|err| err.downcast().unwrap());
+
+        assert_eq!(result, expected);
+    }
+}
+
+#[actix_rt::test]
+async fn test_file_svc_access_by_grant() {
+    let file_id = uuid!("67e55044-10b1-426f-9247-bb680e5fe0c8");
+    let owner_id = uuid!("8a1f2c7e-4b3d-4e5f-9a6b-7c8d9e0f1a2b");
+    let grantee_id = uuid!("1d2c3b4a-5e6f-4a7b-8c9d-0e1f2a3b4c5d");
+    let fake_file_grant = |permission| FileGrant::new_full(&file_id, &grantee_id, "grantee", permission, &fake_current_at());
+
+    let test_context = vec![
+        // NOTE: file meta list, grant list, delete instead of read, expected result
+        (vec![fake_file_meta(&file_id, &owner_id, "stored-key")], vec![fake_file_grant(FilePermission::Read)], false, Ok(())),
+        (vec![fake_file_meta(&file_id, &owner_id, "stored-key")], vec![fake_file_grant(FilePermission::ReadWrite)], false, Ok(())),
+        (vec![fake_file_meta(&file_id, &owner_id, "stored-key")], vec![], false, Err(FileError::FileNotBelongToCustomer)),
+        (vec![fake_trashed_file_meta(&file_id, &owner_id, "stored-key")], vec![fake_file_grant(FilePermission::Read)], false, Err(FileError::FileNotFound)),
+        (vec![fake_file_meta(&file_id, &owner_id, "stored-key")], vec![fake_file_grant(FilePermission::Read)], true, Err(FileError::FilePermissionDenied)),
+        (vec![fake_file_meta(&file_id, &owner_id, "stored-key")], vec![fake_file_grant(FilePermission::ReadWrite)], true, Ok(())),
+    ];
+
+    for (file_meta_list, file_grant_list, delete, expected) in test_context {
+        let trashed = delete && expected.is_ok();
+
+        let mock_file_meta_repo = {
+            let mut mock_repo = MockFileMetaRepositoryTrait::new();
+            mock_repo
+                .expect_get_file_meta_by_id()
+                .times(1)
+                .returning(move |_id| Ok(file_meta_list.clone()));
+            // NOTE: the file goes to the trash of its owner, whoever deleted it
+            mock_repo
+                .expect_trash()
+                .times(if trashed { 1 } else { 0 })
+                .withf(move |id, _deleted_at| *id == file_id)
+                .returning(|_id, _deleted_at| Ok(()));
+
+            mock_repo
+        };
+
+        let svc = {
+            let storage_backend = Arc::new(MockStorageBackendTrait::new());
+            let file_meta_repo = Arc::new(RwLock::new(mock_file_meta_repo));
+            let file_sharing_meta_repo = Arc::new(RwLock::new(MockFileSharingRepositoryTrait::new()));
+            FileServiceImpl::new(fake_current_at, FAKE_PUBLIC_BASE_URL, FAKE_MAX_UPLOAD_SIZE, SharingThrottle::default(), storage_backend, FileRepositories {
+                file_meta_repository: file_meta_repo,
+                file_sharing_meta_repository: file_sharing_meta_repo,
+                pending_delete_repository: Arc::new(RwLock::new(MockPendingDeleteRepositoryTrait::new())),
+                sharing_attempt_repository: Arc::new(RwLock::new(MockSharingAttemptRepositoryTrait::new())),
+                sharing_access_repository: Arc::new(RwLock::new(MockSharingAccessRepositoryTrait::new())),
+                file_request_repository: Arc::new(RwLock::new(MockFileRequestRepositoryTrait::new())),
+                file_grant_repository: fake_file_grant_repo(file_grant_list),
+            })
+        };
+
+        let result = if delete {
+            svc.file_delete(&file_id, &grantee_id).await
+        } else {
+            svc.file_read_by_id(&file_id, &grantee_id).await.map(|_file_meta| ())
+        };
+
+        assert_eq!(result.map_err(|err| err.downcast().unwrap()), expected);
+    }
+}
+
+#[actix_rt::test]
+async fn test_file_svc_grant() {
+    let file_id = uuid!("67e55044-10b1-426f-9247-bb680e5fe0c8");
+    let owner_id = uuid!("8a1f2c7e-4b3d-4e5f-9a6b-7c8d9e0f1a2b");
+
+    // NOTE: the grantee keeps the default id of Customer::new
+    let test_context = vec![
+        // NOTE: file meta list, customer id, expected result
+        (vec![fake_file_meta(&file_id, &owner_id, "stored-key")], owner_id, Ok(())),
+        (vec![], owner_id, Err(FileError::FileNotFound)),
+        (vec![fake_trashed_file_meta(&file_id, &owner_id, "stored-key")], owner_id, Err(FileError::FileNotFound)),
+        (vec![fake_file_meta(&file_id, &owner_id, "stored-key")], Uuid::new_v4(), Err(FileError::FileNotBelongToCustomer)),
+        (vec![fake_file_meta(&file_id, &Uuid::default(), "stored-key")], Uuid::default(), Err(FileError::FileGrantToOwner)),
+    ];
+
+    for (file_meta_list, customer_id, expected) in test_context {
+        let granted = expected.is_ok();
+
+        let mock_file_meta_repo = {
+            let mut mock_repo = MockFileMetaRepositoryTrait::new();
+            mock_repo
+                .expect_get_file_meta_by_id()
+                .times(1)
+                .returning(move |_id| Ok(file_meta_list.clone()));
+
+            mock_repo
+        };
+        let mock_file_grant_repo = {
+            let mut mock_repo = MockFileGrantRepositoryTrait::new();
+            mock_repo
+                .expect_upsert()
+                .times(if granted { 1 } else { 0 })
+                .withf(move |id, grantee_id, permission, granted_at| {
+                    *id == file_id && *grantee_id == Uuid::default() && *permission == FilePermission::ReadWrite && *granted_at == fake_current_at()
+                })
+                .returning(|id, grantee_id, permission, granted_at| Ok(FileGrant::new_full(id, grantee_id, "mikejiang", permission, granted_at)));
+
+            mock_repo
+        };
+
+        let svc = {
+            let storage_backend = Arc::new(MockStorageBackendTrait::new());
+            let file_meta_repo = Arc::new(RwLock::new(mock_file_meta_repo));
+            let file_sharing_meta_repo = Arc::new(RwLock::new(MockFileSharingRepositoryTrait::new()));
+            FileServiceImpl::new(fake_current_at, FAKE_PUBLIC_BASE_URL, FAKE_MAX_UPLOAD_SIZE, SharingThrottle::default(), storage_backend, FileRepositories {
+                file_meta_repository: file_meta_repo,
+                file_sharing_meta_repository: file_sharing_meta_repo,
+                pending_delete_repository: Arc::new(RwLock::new(MockPendingDeleteRepositoryTrait::new())),
+                sharing_attempt_repository: Arc::new(RwLock::new(MockSharingAttemptRepositoryTrait::new())),
+                sharing_access_repository: Arc::new(RwLock::new(MockSharingAccessRepositoryTrait::new())),
+                file_request_repository: Arc::new(RwLock::new(MockFileRequestRepositoryTrait::new())),
+                file_grant_repository: Arc::new(RwLock::new(mock_file_grant_repo)),
+            })
+        };
+
+        let result = svc
+            .file_grant(&file_id, &customer_id, &Customer::new("mikejiang"), FilePermission::ReadWrite)
+            .await
+            .map(|file_grant| assert_eq!(file_grant.get_username(), "mikejiang"))
+            .map_err(|err| err.downcast().unwrap());
+
+        assert_eq!(result, expected);
+    }
+}
+
+#[actix_rt::test]
+async fn test_file_svc_shared_with() {
+    let owner_id = uuid!("8a1f2c7e-4b3d-4e5f-9a6b-7c8d9e0f1a2b");
+    let grantee_id = uuid!("1d2c3b4a-5e6f-4a7b-8c9d-0e1f2a3b4c5d");
+    let shared_file = SharedFile::new(fake_file_meta(&Uuid::new_v4(), &owner_id, "stored-key"), "owner", FilePermission::Read, &fake_current_at());
+
+    let mock_file_grant_repo = {
+        let expected = vec![shared_file.clone()];
+        let mut mock_repo = MockFileGrantRepositoryTrait::new();
+        mock_repo
+            .expect_list_shared_with_customer_id()
+            .times(1)
+            .withf(move |customer_id| *customer_id == grantee_id)
+            .returning(move |_customer_id| Ok(expected.clone()));
+
+        mock_repo
+    };
+
+    let svc = {
+        let storage_backend = Arc::new(MockStorageBackendTrait::new());
+        let file_meta_repo = Arc::new(RwLock::new(MockFileMetaRepositoryTrait::new()));
+        let file_sharing_meta_repo = Arc::new(RwLock::new(MockFileSharingRepositoryTrait::new()));
+        FileServiceImpl::new(fake_current_at, FAKE_PUBLIC_BASE_URL, FAKE_MAX_UPLOAD_SIZE, SharingThrottle::default(), storage_backend, FileRepositories {
+            file_meta_repository: file_meta_repo,
+            file_sharing_meta_repository: file_sharing_meta_repo,
+            pending_delete_repository: Arc::new(RwLock::new(MockPendingDeleteRepositoryTrait::new())),
+            sharing_attempt_repository: Arc::new(RwLock::new(MockSharingAttemptRepositoryTrait::new())),
+            sharing_access_repository: Arc::new(RwLock::new(MockSharingAccessRepositoryTrait::new())),
+            file_request_repository: Arc::new(RwLock::new(MockFileRequestRepositoryTrait::new())),
+            file_grant_repository: Arc::new(RwLock::new(mock_file_grant_repo)),
+        })
+    };
+
+    let result = svc.file_list_shared_with(&grantee_id).await.unwrap();
+    assert_eq!(result, vec![shared_file]);
+}
+
+#[actix_rt::test]
+async fn test_file_svc_read_sharing_link() {
+    let link_id = uuid!("6f1c3d2a-8e4b-4f7a-9c1d-2b3e4f5a6b7c");
+    let file_id = uuid!("67e55044-10b1-426f-9247-bb680e5fe0c8");
+    let expired_at = Utc.with_ymd_and_hms(1990, 3, 2, 0, 0, 0).unwrap();
+    let expireat = Utc.with_ymd_and_hms(1990, 3, 4, 0, 0, 0).unwrap();
+
+    let test_context = vec![
+        // NOTE: link found, its expiry, whether the file is trashed, expected result
+        (false, expireat, false, Err(FileError::FileNotFound)),
+        (true, expired_at, false, Err(FileError::FileSharingLinkExpired)),
+        (true, expireat, true, Err(FileError::FileNotFound)),
+        (true, expireat, false, Ok(())),
+    ];
+
+    for (is_found, link_expireat, is_trashed, expected) in test_context {
+        let mock_file_meta_repo = {
+            let mut mock_repo = MockFileMetaRepositoryTrait::new();
+            mock_repo
+                .expect_get_file_meta_by_id()
+                .returning(move |id| {
+                    if is_trashed {
+                        return Ok(vec![fake_trashed_file_meta(id, &Uuid::default(), "")]);
+                    }
+                    Ok(vec![fake_file_meta(id, &Uuid::default(), "")])
+                });
+
+            mock_repo
+        };
+        // NOTE: reading what a link leads to never counts as a download
+        let mock_file_sharing_meta_repo = {
+            let mut mock_repo = MockFileSharingRepositoryTrait::new();
+            mock_repo
+                .expect_get_by_id()
+                .times(1)
+                .returning(move |_id| {
+                    if !is_found {
+                        return Ok(vec![]);
+                    }
+                    Ok(vec![FileSharingMeta::new_full(&link_id, &file_id, "code", &link_expireat, &None)])
+                });
+            mock_repo.expect_consume_download().times(0);
+
+            mock_repo
+        };
+
+        let svc = {
+            let storage_backend = Arc::new(MockStorageBackendTrait::new());
+            let file_meta_repo = Arc::new(RwLock::new(mock_file_meta_repo));
+            let file_sharing_meta_repo = Arc::new(RwLock::new(mock_file_sharing_meta_repo));
+            FileServiceImpl::new(fake_current_at, FAKE_PUBLIC_BASE_URL, FAKE_MAX_UPLOAD_SIZE, SharingThrottle::default(), storage_backend, FileRepositories {
+                file_meta_repository: file_meta_repo,
+                file_sharing_meta_repository: file_sharing_meta_repo,
+                pending_delete_repository: Arc::new(RwLock::new(MockPendingDeleteRepositoryTrait::new())),
+                sharing_attempt_repository: Arc::new(RwLock::new(MockSharingAttemptRepositoryTrait::new())),
+                sharing_access_repository: Arc::new(RwLock::new(MockSharingAccessRepositoryTrait::new())),
+                file_request_repository: Arc::new(RwLock::new(MockFileRequestRepositoryTrait::new())),
+                file_grant_repository: fake_file_grant_repo(vec![]),
+            })
+        };
+
+        let result = svc
+            .file_read_sharing_link_by_id(&link_id)
+            .await
+            .map(|(file_sharing_meta, file_meta)| {
+                assert_eq!(file_sharing_meta.get_link(), format!("{}/s/code", FAKE_PUBLIC_BASE_URL));
+                assert_eq!(file_meta.get_id(), file_id);
+            })
+            .map_err(|err| err.downcast().unwrap());
+
+        assert_eq!(result, expected);
+    }
+}
+
+#[actix_rt::test]
+async fn test_file_svc_unlock_sharing_link() {
+    let link_id = uuid!("6f1c3d2a-8e4b-4f7a-9c1d-2b3e4f5a6b7c");
+    let expireat = Utc.with_ymd_and_hms(1990, 3, 4, 0, 0, 0).unwrap();
+    let password_hash = hash_password("secret").unwrap();
+
+    let test_context = vec![
+        // NOTE: link found, password given, expected result, outcome logged
+        (true, Some(String::from("secret")), Ok(()), None),
+        (true, Some(String::from("wrong")), Err(FileError::FileSharingLinkPasswordIncorrect), Some(SharingAccessOutcome::PasswordIncorrect)),
+        (true, None, Err(FileError::FileSharingLinkPasswordIncorrect), Some(SharingAccessOutcome::PasswordIncorrect)),
+        (false, Some(String::from("secret")), Err(FileError::FileNotFound), Some(SharingAccessOutcome::NotFound)),
+    ];
+
+    for (is_found, password, expected, expected_outcome) in test_context {
+        let forgiven = if expected.is_ok() { 2 } else { 0 };
+        let attempted = if is_found { 2 } else { 0 };
+
+        let mock_file_sharing_meta_repo = {
+            let password_hash = password_hash.clone();
+            let mut mock_repo = MockFileSharingRepositoryTrait::new();
+            mock_repo
+                .expect_get_by_id()
+                .times(1)
+                .returning(move |id| {
+                    if !is_found {
+                        return Ok(vec![]);
+                    }
+                    Ok(vec![FileSharingMeta::new_full(id, &Uuid::default(), "code", &expireat, &Some(password_hash.clone()))])
+                });
+            mock_repo.expect_consume_download().times(0);
+
+            mock_repo
+        };
+        let mock_sharing_attempt_repo = {
+            let mut mock_repo = MockSharingAttemptRepositoryTrait::new();
+            mock_repo
+                .expect_begin_attempt()
+                .times(attempted)
+                .returning(|key, _attempted_at, _window_start_before, _max_failures, _locked_until| Ok(SharingAttempt::new_full(key, 1, &None)));
+            mock_repo
+                .expect_forgive_attempt()
+                .times(forgiven)
+                .returning(|_key| Ok(()));
+
+            mock_repo
+        };
+        // NOTE: a refused unlock shows up in the access log, a successful one only once the token is used
+        let mock_sharing_access_repo = {
+            let mut mock_repo = MockSharingAccessRepositoryTrait::new();
+            mock_repo
+                .expect_create()
+                .withf(move |sharing_id, accessed_at, client, outcome| {
+                    *sharing_id == Some(link_id).filter(|_| is_found)
+                        && *accessed_at == fake_current_at()
+                        && *client == fake_sharing_client()
+                        && Some(*outcome) == expected_outcome
+                })
+                .times(usize::from(expected_outcome.is_some()))
+                .returning(|sharing_id, accessed_at, client, outcome| Ok(SharingAccess::new_full(&Uuid::default(), sharing_id, accessed_at, client, outcome, 0)));
+
+            mock_repo
+        };
+
+        let svc = {
+            let storage_backend = Arc::new(MockStorageBackendTrait::new());
+            let file_meta_repo = Arc::new(RwLock::new(MockFileMetaRepositoryTrait::new()));
+            let file_sharing_meta_repo = Arc::new(RwLock::new(mock_file_sharing_meta_repo));
+            let sharing_attempt_repo = Arc::new(RwLock::new(mock_sharing_attempt_repo));
+            FileServiceImpl::new(fake_current_at, FAKE_PUBLIC_BASE_URL, FAKE_MAX_UPLOAD_SIZE, SharingThrottle::default(), storage_backend, FileRepositories {
+                file_meta_repository: file_meta_repo,
+                file_sharing_meta_repository: file_sharing_meta_repo,
+                pending_delete_repository: Arc::new(RwLock::new(MockPendingDeleteRepositoryTrait::new())),
+                sharing_attempt_repository: sharing_attempt_repo,
+                sharing_access_repository: Arc::new(RwLock::new(mock_sharing_access_repo)),
+                file_request_repository: Arc::new(RwLock::new(MockFileRequestRepositoryTrait::new())),
+                file_grant_repository: fake_file_grant_repo(vec![]),
+            })
+        };
+
+        let result = svc
+            .file_unlock_sharing_link(&link_id, password, &fake_sharing_client())
+            .await
+            .map(|sharing_unlock| {
+                assert_eq!(sharing_unlock.get_sharing_id(), link_id);
+                assert_eq!(sharing_unlock.get_expireat(), fake_current_at() + Duration::minutes(5));
+            })
+            .map_err(|err| err.downcast().unwrap());
+
+        assert_eq!(result, expected);
+    }
+}
+
+#[actix_rt::test]
+async fn test_file_svc_get_sharing_link_by_unlock() {
+    let link_id = uuid!("6f1c3d2a-8e4b-4f7a-9c1d-2b3e4f5a6b7c");
+    let expireat = Utc.with_ymd_and_hms(1990, 3, 4, 0, 0, 0).unwrap();
+    let password_hash = hash_password("secret").unwrap();
+
+    let test_context = vec![
+        // NOTE: unlock token, expected result
+        (SharingUnlock::new(&link_id, &fake_current_at(), Duration::minutes(5)), Ok(())),
+        (SharingUnlock::new(&Uuid::new_v4(), &fake_current_at(), Duration::minutes(5)), Err(FileError::FileSharingUnlockTokenInvalid)),
+        (SharingUnlock::new(&link_id, &(fake_current_at() - Duration::minutes(10)), Duration::minutes(5)), Err(FileError::FileSharingUnlockTokenInvalid)),
+    ];
+
+    for (sharing_unlock, expected) in test_context {
+        let opened = if expected.is_ok() { 1 } else { 0 };
+
+        let mock_file_meta_repo = {
+            let mut mock_repo = MockFileMetaRepositoryTrait::new();
+            mock_repo
+                .expect_get_file_meta_by_id()
+                .times(opened)
+                .returning(move |id| Ok(vec![fake_file_meta(id, &Uuid::default(), "")]));
+
+            mock_repo
+        };
+        let mock_file_sharing_meta_repo = {
+            let password_hash = password_hash.clone();
+            let mut mock_repo = MockFileSharingRepositoryTrait::new();
+            mock_repo
+                .expect_get_by_id()
+                .times(opened)
+                .returning(move |id| Ok(vec![FileSharingMeta::new_full(id, &Uuid::default(), "code", &expireat, &Some(password_hash.clone()))]));
+
+            mock_repo
+        };
+        // NOTE: the token stands in for the password, the throttle is never consulted
+        let mock_sharing_attempt_repo = {
+            let mut mock_repo = MockSharingAttemptRepositoryTrait::new();
+            mock_repo.expect_begin_attempt().times(0);
+            mock_repo.expect_forgive_attempt().times(0);
+
+            mock_repo
+        };
+
+        let svc = {
+            let storage_backend = Arc::new(MockStorageBackendTrait::new());
+            let file_meta_repo = Arc::new(RwLock::new(mock_file_meta_repo));
+            let file_sharing_meta_repo = Arc::new(RwLock::new(mock_file_sharing_meta_repo));
+            let sharing_attempt_repo = Arc::new(RwLock::new(mock_sharing_attempt_repo));
+            FileServiceImpl::new(fake_current_at, FAKE_PUBLIC_BASE_URL, FAKE_MAX_UPLOAD_SIZE, SharingThrottle::default(), storage_backend, FileRepositories {
+                file_meta_repository: file_meta_repo,
+                file_sharing_meta_repository: file_sharing_meta_repo,
+                pending_delete_repository: Arc::new(RwLock::new(MockPendingDeleteRepositoryTrait::new())),
+                sharing_attempt_repository: sharing_attempt_repo,
+                sharing_access_repository: fake_sharing_access_repo(opened),
+                file_request_repository: Arc::new(RwLock::new(MockFileRequestRepositoryTrait::new())),
+                file_grant_repository: fake_file_grant_repo(vec![]),
+            })
+        };
+
+        let result = svc
+            .file_get_sharing_link_by_unlock(&link_id, &sharing_unlock, &fake_sharing_client())
+            .await
+            .map(|(_file_meta, sharing_access)| assert_eq!(sharing_access.get_outcome(), SharingAccessOutcome::Success))
+            .map_err(|err| err.downcast().unwrap());
+
+        assert_eq!(result, expected);
+    }
+}
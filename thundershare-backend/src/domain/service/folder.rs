@@ -0,0 +1,191 @@
+use anyhow::{bail, Result};
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use mockall::automock;
+use std::sync::Arc;
+use tokio::sync::RwLock;
+use sqlx::types::Uuid;
+
+use crate::domain::{entity::{file_meta::FileMeta, folder::{Folder, FolderContent}}, error::{file::FileError, folder::FolderError}, repository::{file_meta::FileMetaRepositoryTrait, folder::FolderRepositoryTrait}};
+
+use super::file::FileServiceTrait;
+
+// NOTE: a folder id of None stands for the root of the customer everywhere in this service
+#[automock]
+#[async_trait(?Send)]
+pub trait FolderServiceTrait {
+    async fn folder_create(&self, customer_id: &Uuid, parent_id: &Option<Uuid>, name: &str) -> Result<Folder>;
+    async fn folder_read_content(&self, id: &Option<Uuid>, customer_id: &Uuid) -> Result<FolderContent>;
+    async fn folder_rename(&self, id: &Uuid, customer_id: &Uuid, name: &str) -> Result<Folder>;
+    async fn folder_move(&self, id: &Uuid, customer_id: &Uuid, parent_id: &Option<Uuid>) -> Result<Folder>;
+    async fn folder_delete(&self, id: &Uuid, customer_id: &Uuid) -> Result<()>;
+    async fn folder_move_file(&self, file_id: &Uuid, customer_id: &Uuid, folder_id: &Option<Uuid>) -> Result<FileMeta>;
+}
+
+
+pub struct FolderServiceImpl {
+    curr_time_fn: Box<dyn Fn() -> DateTime<Utc>>,
+    file_service: Arc<dyn FileServiceTrait>,
+    folder_repository: Arc<RwLock<dyn FolderRepositoryTrait>>,
+    file_meta_repository: Arc<RwLock<dyn FileMetaRepositoryTrait>>,
+}
+
+impl FolderServiceImpl {
+    pub fn new(
+        curr_time_fn: impl Fn() -> DateTime<Utc> + 'static,
+        file_service: Arc<dyn FileServiceTrait>,
+        folder_repository: Arc<RwLock<dyn FolderRepositoryTrait>>,
+        file_meta_repository: Arc<RwLock<dyn FileMetaRepositoryTrait>>,
+    ) -> Arc<FolderServiceImpl> {
+        let svc = FolderServiceImpl {
+            curr_time_fn: Box::new(curr_time_fn),
+            file_service: file_service.clone(),
+            folder_repository: folder_repository.clone(),
+            file_meta_repository: file_meta_repository.clone(),
+        };
+
+        Arc::new(svc)
+    }
+
+    async fn read_owned_folder(&self, id: &Uuid, customer_id: &Uuid) -> Result<Folder> {
+        let folder_list = {
+            let repo = self.folder_repository.read().await;
+            repo.get_by_id(id).await?
+        };
+
+        if folder_list.is_empty() {
+            bail!(FolderError::FolderNotFound)
+        }
+
+        let folder = folder_list[0].clone();
+        if folder.get_customer_id() != *customer_id {
+            bail!(FolderError::FolderNotBelongToCustomer)
+        }
+
+        Ok(folder)
+    }
+
+    // NOTE: the root always exists, any other parent has to be a folder of the customer
+    async fn check_parent(&self, parent_id: &Option<Uuid>, customer_id: &Uuid) -> Result<()> {
+        if let Some(parent_id) = parent_id {
+            self.read_owned_folder(parent_id, customer_id).await?;
+        }
+
+        Ok(())
+    }
+
+    // NOTE: only spares a write that is bound to fail, a concurrent write of the same name can
+    //       still get in between and is refused by the repository with the same error
+    async fn check_name_available(&self, customer_id: &Uuid, parent_id: &Option<Uuid>, name: &str) -> Result<()> {
+        if !Folder::is_name_valid(name) {
+            bail!(FolderError::FolderNameInvalid)
+        }
+
+        let folder_list = {
+            let repo = self.folder_repository.read().await;
+            repo.get_by_name(customer_id, parent_id, name).await?
+        };
+
+        if !folder_list.is_empty() {
+            bail!(FolderError::FolderNameConflict)
+        }
+
+        Ok(())
+    }
+}
+
+#[async_trait(?Send)]
+impl FolderServiceTrait for FolderServiceImpl {
+    async fn folder_create(&self, customer_id: &Uuid, parent_id: &Option<Uuid>, name: &str) -> Result<Folder> {
+        self.check_parent(parent_id, customer_id).await?;
+        self.check_name_available(customer_id, parent_id, name).await?;
+
+        let created_at = (self.curr_time_fn)();
+        let repo = self.folder_repository.write().await;
+        repo.create(customer_id, parent_id, name, &created_at).await
+    }
+
+    async fn folder_read_content(&self, id: &Option<Uuid>, customer_id: &Uuid) -> Result<FolderContent> {
+        let folder = match id {
+            Some(id) => Some(self.read_owned_folder(id, customer_id).await?),
+            None => None,
+        };
+
+        let folder_list = {
+            let repo = self.folder_repository.read().await;
+            repo.list_by_parent_id(customer_id, id).await?
+        };
+
+        let file_meta_list = {
+            let repo = self.file_meta_repository.read().await;
+            repo.list_file_meta_by_folder_id(customer_id, id).await?
+        };
+
+        Ok(FolderContent::new(folder, folder_list, file_meta_list))
+    }
+
+    async fn folder_rename(&self, id: &Uuid, customer_id: &Uuid, name: &str) -> Result<Folder> {
+        let folder = self.read_owned_folder(id, customer_id).await?;
+        if folder.get_name() == name {
+            return Ok(folder);
+        }
+
+        self.check_name_available(customer_id, &folder.get_parent_id(), name).await?;
+
+        let repo = self.folder_repository.write().await;
+        repo.rename(id, name).await
+    }
+
+    async fn folder_move(&self, id: &Uuid, customer_id: &Uuid, parent_id: &Option<Uuid>) -> Result<Folder> {
+        let folder = self.read_owned_folder(id, customer_id).await?;
+        if folder.get_parent_id() == *parent_id {
+            return Ok(folder);
+        }
+
+        self.check_parent(parent_id, customer_id).await?;
+        self.check_name_available(customer_id, parent_id, &folder.get_name()).await?;
+
+        let folder_list = {
+            let repo = self.folder_repository.write().await;
+            repo.move_to(customer_id, id, parent_id).await?
+        };
+
+        if folder_list.is_empty() {
+            bail!(FolderError::FolderMoveCycle)
+        }
+
+        Ok(folder_list[0].clone())
+    }
+
+    async fn folder_delete(&self, id: &Uuid, customer_id: &Uuid) -> Result<()> {
+        self.read_owned_folder(id, customer_id).await?;
+
+        let folder_list = {
+            let repo = self.folder_repository.write().await;
+            repo.delete(id).await?
+        };
+
+        if folder_list.is_empty() {
+            bail!(FolderError::FolderNotEmpty)
+        }
+
+        Ok(())
+    }
+
+    async fn folder_move_file(&self, file_id: &Uuid, customer_id: &Uuid, folder_id: &Option<Uuid>) -> Result<FileMeta> {
+        let mut file_meta = self.file_service.file_read_by_id(file_id, customer_id).await?;
+        // NOTE: folders belong to one customer, a file shared with the customer can not be moved
+        if file_meta.get_customer_id() != *customer_id {
+            bail!(FileError::FileNotBelongToCustomer)
+        }
+        self.check_parent(folder_id, customer_id).await?;
+
+        {
+            let repo = self.file_meta_repository.write().await;
+            repo.move_to_folder(file_id, folder_id).await?;
+        }
+
+        file_meta.set_folder_id(*folder_id);
+        Ok(file_meta)
+    }
+}
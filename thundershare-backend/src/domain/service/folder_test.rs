@@ -0,0 +1,295 @@
+use std::sync::Arc;
+
+use anyhow::anyhow;
+use chrono::{DateTime, TimeZone, Utc};
+use tokio::sync::RwLock;
+use uuid::{uuid, Uuid};
+
+use crate::domain::{entity::{file_meta::{FileContent, FileMeta}, folder::{Folder, FolderContent}}, error::{file::FileError, folder::FolderError}, repository::{file_meta::MockFileMetaRepositoryTrait, folder::MockFolderRepositoryTrait}};
+
+use super::file::MockFileServiceTrait;
+use super::folder::{FolderServiceImpl, FolderServiceTrait};
+
+const FAKE_FOLDER_ID: Uuid = uuid!("67e55044-10b1-426f-9247-bb680e5fe0c8");
+const FAKE_PARENT_ID: Uuid = uuid!("3c9d2b1a-5e4f-4a6b-8c7d-9e0f1a2b3c4d");
+const FAKE_CUSTOMER_ID: Uuid = uuid!("8a1f2c7e-4b3d-4e5f-9a6b-7c8d9e0f1a2b");
+const FAKE_FILE_ID: Uuid = uuid!("0b7e6f3a-2c1d-4e8f-a9b0-c1d2e3f4a5b6");
+
+fn fake_current_at() -> DateTime<Utc> {
+    Utc.with_ymd_and_hms(1990, 3, 3, 0, 0, 0).unwrap()
+}
+
+fn fake_folder(id: &Uuid, parent_id: &Option<Uuid>, name: &str) -> Folder {
+    Folder::new_full(id, &FAKE_CUSTOMER_ID, parent_id, name, &fake_current_at())
+}
+
+fn folder_svc(
+    mock_file_svc: MockFileServiceTrait,
+    mock_folder_repo: MockFolderRepositoryTrait,
+    mock_file_meta_repo: MockFileMetaRepositoryTrait,
+) -> Arc<FolderServiceImpl> {
+    FolderServiceImpl::new(
+        fake_current_at,
+        Arc::new(mock_file_svc),
+        Arc::new(RwLock::new(mock_folder_repo)),
+        Arc::new(RwLock::new(mock_file_meta_repo)),
+    )
+}
+
+// NOTE: answers get_by_id with the given folders, every other id is unknown
+fn expect_get_by_id(mock_folder_repo: &mut MockFolderRepositoryTrait, folder_list: Vec<Folder>) {
+    mock_folder_repo
+        .expect_get_by_id()
+        .returning(move |id| Ok(folder_list.iter().filter(|folder| folder.get_id() == *id).cloned().collect()));
+}
+
+#[actix_rt::test]
+async fn test_folder_svc_create() {
+    let other_customer_folder = Folder::new_full(&FAKE_PARENT_ID, &Uuid::new_v4(), &None, "others", &fake_current_at());
+    let test_context = vec![
+        // NOTE: existing folders, parent id, name, whether the name is taken, expected result
+        (vec![], None, "reports", false, Ok(fake_folder(&FAKE_FOLDER_ID, &None, "reports"))),
+        (vec![fake_folder(&FAKE_PARENT_ID, &None, "work")], Some(FAKE_PARENT_ID), "reports", false, Ok(fake_folder(&FAKE_FOLDER_ID, &Some(FAKE_PARENT_ID), "reports"))),
+        (vec![], Some(FAKE_PARENT_ID), "reports", false, Err(FolderError::FolderNotFound)),
+        (vec![other_customer_folder], Some(FAKE_PARENT_ID), "reports", false, Err(FolderError::FolderNotBelongToCustomer)),
+        (vec![], None, "a/b", false, Err(FolderError::FolderNameInvalid)),
+        (vec![], None, "reports", true, Err(FolderError::FolderNameConflict)),
+    ];
+
+    for (folder_list, parent_id, name, name_taken, expected) in test_context {
+        let created = expected.is_ok();
+
+        let mut mock_folder_repo = MockFolderRepositoryTrait::new();
+        expect_get_by_id(&mut mock_folder_repo, folder_list);
+        mock_folder_repo
+            .expect_get_by_name()
+            .returning(move |_customer_id, parent_id, name| match name_taken {
+                true => Ok(vec![fake_folder(&Uuid::new_v4(), parent_id, name)]),
+                false => Ok(vec![]),
+            });
+        mock_folder_repo
+            .expect_create()
+            .times(if created { 1 } else { 0 })
+            .withf(move |customer_id, created_parent_id, created_name, created_at| {
+                *customer_id == FAKE_CUSTOMER_ID && *created_parent_id == parent_id && created_name == name && *created_at == fake_current_at()
+            })
+            .returning(|_customer_id, parent_id, name, _created_at| Ok(fake_folder(&FAKE_FOLDER_ID, parent_id, name)));
+
+        let svc = folder_svc(MockFileServiceTrait::new(), mock_folder_repo, MockFileMetaRepositoryTrait::new());
+        let result = svc
+            .folder_create(&FAKE_CUSTOMER_ID, &parent_id, name)
+            .await
+            .map_err(|err| err.downcast().unwrap());
+
+        assert_eq!(result, expected);
+    }
+}
+
+#[actix_rt::test]
+async fn test_folder_svc_create_name_taken_concurrently() {
+    // NOTE: the name is free when checked, a concurrent create takes it before this one is written
+    let mut mock_folder_repo = MockFolderRepositoryTrait::new();
+    mock_folder_repo
+        .expect_get_by_name()
+        .times(1)
+        .returning(|_customer_id, _parent_id, _name| Ok(vec![]));
+    mock_folder_repo
+        .expect_create()
+        .times(1)
+        .returning(|_customer_id, _parent_id, _name, _created_at| Err(anyhow::anyhow!(FolderError::FolderNameConflict)));
+
+    let svc = folder_svc(MockFileServiceTrait::new(), mock_folder_repo, MockFileMetaRepositoryTrait::new());
+    let result: Result<Folder, FolderError> = svc
+        .folder_create(&FAKE_CUSTOMER_ID, &None, "reports")
+        .await
+        .map_err(|err| err.downcast().unwrap());
+
+    assert_eq!(result, Err(FolderError::FolderNameConflict));
+}
+
+#[actix_rt::test]
+async fn test_folder_svc_read_content() {
+    let folder = fake_folder(&FAKE_FOLDER_ID, &None, "reports");
+    let child = fake_folder(&Uuid::new_v4(), &Some(FAKE_FOLDER_ID), "2024");
+    let file_meta = FileMeta::new_with_content(&FAKE_FILE_ID, &FAKE_CUSTOMER_ID, "stored-key", &FileContent::default(), &DateTime::<Utc>::default());
+
+    let mut mock_folder_repo = MockFolderRepositoryTrait::new();
+    expect_get_by_id(&mut mock_folder_repo, vec![folder.clone()]);
+    let folder_list = vec![child.clone()];
+    mock_folder_repo
+        .expect_list_by_parent_id()
+        .times(1)
+        .withf(|customer_id, parent_id| *customer_id == FAKE_CUSTOMER_ID && *parent_id == Some(FAKE_FOLDER_ID))
+        .returning(move |_customer_id, _parent_id| Ok(folder_list.clone()));
+
+    let mut mock_file_meta_repo = MockFileMetaRepositoryTrait::new();
+    let file_meta_list = vec![file_meta.clone()];
+    mock_file_meta_repo
+        .expect_list_file_meta_by_folder_id()
+        .times(1)
+        .withf(|customer_id, folder_id| *customer_id == FAKE_CUSTOMER_ID && *folder_id == Some(FAKE_FOLDER_ID))
+        .returning(move |_customer_id, _folder_id| Ok(file_meta_list.clone()));
+
+    let svc = folder_svc(MockFileServiceTrait::new(), mock_folder_repo, mock_file_meta_repo);
+    let result = svc.folder_read_content(&Some(FAKE_FOLDER_ID), &FAKE_CUSTOMER_ID).await.unwrap();
+
+    assert_eq!(result, FolderContent::new(Some(folder), vec![child], vec![file_meta]));
+}
+
+#[actix_rt::test]
+async fn test_folder_svc_rename() {
+    let test_context = vec![
+        // NOTE: name, whether the name is taken, expected result
+        ("archive", false, Ok(fake_folder(&FAKE_FOLDER_ID, &None, "archive"))),
+        ("reports", true, Ok(fake_folder(&FAKE_FOLDER_ID, &None, "reports"))),
+        ("archive", true, Err(FolderError::FolderNameConflict)),
+        ("..", false, Err(FolderError::FolderNameInvalid)),
+    ];
+
+    for (name, name_taken, expected) in test_context {
+        let renamed = expected.is_ok() && name != "reports";
+
+        let mut mock_folder_repo = MockFolderRepositoryTrait::new();
+        expect_get_by_id(&mut mock_folder_repo, vec![fake_folder(&FAKE_FOLDER_ID, &None, "reports")]);
+        mock_folder_repo
+            .expect_get_by_name()
+            .returning(move |_customer_id, parent_id, name| match name_taken {
+                true => Ok(vec![fake_folder(&Uuid::new_v4(), parent_id, name)]),
+                false => Ok(vec![]),
+            });
+        mock_folder_repo
+            .expect_rename()
+            .times(if renamed { 1 } else { 0 })
+            .returning(|id, name| Ok(fake_folder(id, &None, name)));
+
+        let svc = folder_svc(MockFileServiceTrait::new(), mock_folder_repo, MockFileMetaRepositoryTrait::new());
+        let result = svc
+            .folder_rename(&FAKE_FOLDER_ID, &FAKE_CUSTOMER_ID, name)
+            .await
+            .map_err(|err| err.downcast().unwrap());
+
+        assert_eq!(result, expected);
+    }
+}
+
+#[actix_rt::test]
+async fn test_folder_svc_move() {
+    let folder = fake_folder(&FAKE_FOLDER_ID, &None, "reports");
+    let parent = fake_folder(&FAKE_PARENT_ID, &Some(FAKE_FOLDER_ID), "2024");
+    let test_context = vec![
+        // NOTE: target parent id, whether the name is taken, whether the repository refuses the move, expected result
+        (Some(FAKE_PARENT_ID), false, false, Ok(fake_folder(&FAKE_FOLDER_ID, &Some(FAKE_PARENT_ID), "reports"))),
+        (Some(FAKE_PARENT_ID), false, true, Err(FolderError::FolderMoveCycle)),
+        (Some(FAKE_PARENT_ID), true, false, Err(FolderError::FolderNameConflict)),
+        (Some(Uuid::new_v4()), false, false, Err(FolderError::FolderNotFound)),
+        (None, false, false, Ok(folder.clone())),
+    ];
+
+    for (parent_id, name_taken, is_cycle, expected) in test_context {
+        let moved = parent_id.is_some() && !name_taken && (is_cycle || expected.is_ok());
+
+        let mut mock_folder_repo = MockFolderRepositoryTrait::new();
+        expect_get_by_id(&mut mock_folder_repo, vec![folder.clone(), parent.clone()]);
+        mock_folder_repo
+            .expect_get_by_name()
+            .returning(move |_customer_id, parent_id, name| match name_taken {
+                true => Ok(vec![fake_folder(&Uuid::new_v4(), parent_id, name)]),
+                false => Ok(vec![]),
+            });
+        mock_folder_repo
+            .expect_move_to()
+            .times(if moved { 1 } else { 0 })
+            .withf(|customer_id, id, _parent_id| *customer_id == FAKE_CUSTOMER_ID && *id == FAKE_FOLDER_ID)
+            .returning(move |_customer_id, id, parent_id| match is_cycle {
+                true => Ok(vec![]),
+                false => Ok(vec![fake_folder(id, parent_id, "reports")]),
+            });
+
+        let svc = folder_svc(MockFileServiceTrait::new(), mock_folder_repo, MockFileMetaRepositoryTrait::new());
+        let result = svc
+            .folder_move(&FAKE_FOLDER_ID, &FAKE_CUSTOMER_ID, &parent_id)
+            .await
+            .map_err(|err| err.downcast().unwrap());
+
+        assert_eq!(result, expected);
+    }
+}
+
+#[actix_rt::test]
+async fn test_folder_svc_delete() {
+    let test_context = vec![
+        // NOTE: whether the folder is empty, expected result
+        (true, Ok(())),
+        (false, Err(FolderError::FolderNotEmpty)),
+    ];
+
+    for (is_empty, expected) in test_context {
+        let mut mock_folder_repo = MockFolderRepositoryTrait::new();
+        expect_get_by_id(&mut mock_folder_repo, vec![fake_folder(&FAKE_FOLDER_ID, &None, "reports")]);
+        mock_folder_repo
+            .expect_delete()
+            .times(1)
+            .withf(|id| *id == FAKE_FOLDER_ID)
+            .returning(move |id| match is_empty {
+                true => Ok(vec![fake_folder(id, &None, "reports")]),
+                false => Ok(vec![]),
+            });
+
+        let svc = folder_svc(MockFileServiceTrait::new(), mock_folder_repo, MockFileMetaRepositoryTrait::new());
+        let result = svc
+            .folder_delete(&FAKE_FOLDER_ID, &FAKE_CUSTOMER_ID)
+            .await
+            .map_err(|err| err.downcast().unwrap());
+
+        assert_eq!(result, expected);
+    }
+}
+
+fn fake_file_meta(folder_id: Option<Uuid>) -> FileMeta {
+    let mut file_meta = FileMeta::new_with_content(&FAKE_FILE_ID, &FAKE_CUSTOMER_ID, "stored-key", &FileContent::default(), &DateTime::<Utc>::default());
+    file_meta.set_folder_id(folder_id);
+    file_meta
+}
+
+#[actix_rt::test]
+async fn test_folder_svc_move_file() {
+    let test_context = vec![
+        // NOTE: file lookup result, target folder id, expected result
+        (Ok(fake_file_meta(None)), Some(FAKE_FOLDER_ID), Ok(fake_file_meta(Some(FAKE_FOLDER_ID)))),
+        (Ok(fake_file_meta(Some(FAKE_FOLDER_ID))), None, Ok(fake_file_meta(None))),
+        (Ok(fake_file_meta(None)), Some(Uuid::new_v4()), Err(anyhow!(FolderError::FolderNotFound))),
+        (Err(FileError::FileNotFound), Some(FAKE_FOLDER_ID), Err(anyhow!(FileError::FileNotFound))),
+        // NOTE: a file shared with the customer is readable, but stays in the folders of its owner
+        (Ok(FileMeta::new_with_content(&FAKE_FILE_ID, &Uuid::new_v4(), "stored-key", &FileContent::default(), &DateTime::<Utc>::default())), Some(FAKE_FOLDER_ID), Err(anyhow!(FileError::FileNotBelongToCustomer))),
+    ];
+
+    for (file_lookup, folder_id, expected) in test_context {
+        let moved = expected.is_ok();
+
+        let mut mock_file_svc = MockFileServiceTrait::new();
+        mock_file_svc
+            .expect_file_read_by_id()
+            .times(1)
+            .withf(|id, customer_id| *id == FAKE_FILE_ID && *customer_id == FAKE_CUSTOMER_ID)
+            .returning(move |_id, _customer_id| file_lookup.clone().map_err(|err| anyhow!(err)));
+
+        let mut mock_folder_repo = MockFolderRepositoryTrait::new();
+        expect_get_by_id(&mut mock_folder_repo, vec![fake_folder(&FAKE_FOLDER_ID, &None, "reports")]);
+
+        let mut mock_file_meta_repo = MockFileMetaRepositoryTrait::new();
+        mock_file_meta_repo
+            .expect_move_to_folder()
+            .times(if moved { 1 } else { 0 })
+            .withf(move |id, moved_folder_id| *id == FAKE_FILE_ID && *moved_folder_id == folder_id)
+            .returning(|_id, _folder_id| Ok(()));
+
+        let svc = folder_svc(mock_file_svc, mock_folder_repo, mock_file_meta_repo);
+        let result = svc.folder_move_file(&FAKE_FILE_ID, &FAKE_CUSTOMER_ID, &folder_id).await;
+
+        // NOTE: the file and the folder errors are different types, so they are compared by message
+        match expected {
+            Ok(expected) => assert_eq!(result.unwrap(), expected),
+            Err(expected) => assert_eq!(result.err().unwrap().to_string(), expected.to_string()),
+        }
+    }
+}
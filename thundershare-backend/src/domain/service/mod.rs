@@ -7,14 +7,27 @@ pub mod file;
 #[cfg(test)]
 pub mod file_test;
 
+pub mod folder;
+
+#[cfg(test)]
+pub mod folder_test;
+
+pub mod upload;
+
+#[cfg(test)]
+pub mod upload_test;
+
 use std::sync::Arc;
 
 use chrono::{DateTime, Utc};
-use tokio::sync::RwLock;
 
-use self::{customer::CustomerServiceImpl, file::{FileServiceImpl, FileUploaderTrait}};
+use crate::config::ServerConfig;
+
+use super::entity::jwt_key::JwtKeySet;
+
+use self::{customer::{CustomerServiceImpl, CustomerSessionLifetime}, file::{FileRepositories, FileServiceImpl, SharingThrottle}, folder::FolderServiceImpl, upload::UploadServiceImpl};
 
-use super::repository::{customer::CustomerRepositoryTrait, file_meta::FileMetaRepositoryTrait, file_sharing::FileSharingRepositoryTrait, used_token::UsedTokenRepositoryTrait};
+use super::repository::{storage::StorageBackendTrait, ServerRepositories};
 
 fn issue_at_fn() -> DateTime<Utc> {
     chrono::Utc::now()
@@ -22,23 +35,59 @@ fn issue_at_fn() -> DateTime<Utc> {
 
 #[derive(Clone)]
 pub struct ServerService {
+    pub config: Arc<ServerConfig>,
+    pub jwt_key_set: Arc<JwtKeySet>,
+    pub session_lifetime: CustomerSessionLifetime,
     pub customer_service: Arc<CustomerServiceImpl>,
     pub file_service: Arc<FileServiceImpl>,
+    pub upload_service: Arc<UploadServiceImpl>,
+    pub folder_service: Arc<FolderServiceImpl>,
 }
 
 impl ServerService {
     pub fn new(
-        file_uploader: Arc<dyn FileUploaderTrait>,
-        customer_repository: Arc<RwLock<dyn CustomerRepositoryTrait>>,
-        used_token_repository: Arc<RwLock<dyn UsedTokenRepositoryTrait>>,
-        file_meta_repository: Arc<RwLock<dyn FileMetaRepositoryTrait>>,
-        file_sharing_meta_repository: Arc<RwLock<dyn FileSharingRepositoryTrait>>,
+        config: Arc<ServerConfig>,
+        jwt_key_set: Arc<JwtKeySet>,
+        storage_backend: Arc<dyn StorageBackendTrait>,
+        repositories: ServerRepositories,
     ) -> ServerService {
-        let customer_service =
-            CustomerServiceImpl::new(issue_at_fn, customer_repository, used_token_repository);
+        let session_lifetime = CustomerSessionLifetime::from(&config.session);
+        let customer_service = CustomerServiceImpl::new(
+            issue_at_fn,
+            jwt_key_set.clone(),
+            session_lifetime,
+            repositories.customer_repository,
+            repositories.used_token_repository,
+            repositories.refresh_token_repository,
+        );
 
-        let file_service = FileServiceImpl::new(issue_at_fn, file_uploader, file_meta_repository, file_sharing_meta_repository);
+        let file_service = FileServiceImpl::new(
+            issue_at_fn,
+            &config.http.public_base_url,
+            config.upload.max_size_bytes,
+            SharingThrottle::from(&config.sharing),
+            storage_backend.clone(),
+            FileRepositories {
+                file_meta_repository: repositories.file_meta_repository.clone(),
+                file_sharing_meta_repository: repositories.file_sharing_meta_repository,
+                pending_delete_repository: repositories.pending_delete_repository,
+                sharing_attempt_repository: repositories.sharing_attempt_repository,
+                sharing_access_repository: repositories.sharing_access_repository,
+                file_request_repository: repositories.file_request_repository,
+                file_grant_repository: repositories.file_grant_repository,
+            },
+        );
+        let upload_service = UploadServiceImpl::new(issue_at_fn, config.upload.max_size_bytes, storage_backend, file_service.clone(), repositories.upload_repository);
+        let folder_service = FolderServiceImpl::new(issue_at_fn, file_service.clone(), repositories.folder_repository, repositories.file_meta_repository);
 
-        ServerService { customer_service, file_service }
+        ServerService {
+            config,
+            jwt_key_set,
+            session_lifetime,
+            customer_service,
+            file_service,
+            upload_service,
+            folder_service,
+        }
     }
 }
@@ -0,0 +1,245 @@
+
+use anyhow::{bail, Result};
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use mockall::automock;
+use futures_util::StreamExt;
+use std::sync::{Arc, Mutex};
+use ring::digest::Context;
+use tokio::sync::RwLock;
+use sqlx::types::Uuid;
+
+use crate::domain::{entity::{customer::Customer, storage_object::StorageStream, upload::{ChecksumAlgorithm, Upload, UploadChecksum}}, error::{file::FileError, upload::UploadError}, repository::{storage::StorageBackendTrait, upload::UploadRepositoryTrait}};
+
+use super::file::FileServiceTrait;
+
+// NOTE: counts and digests the body of a single PATCH request while it streams into the
+//       storage backend, and fails the stream once it runs past the declared upload length.
+struct PartInspector {
+    max_size: u64,
+    size: u64,
+    too_large: bool,
+    context: Option<Context>,
+}
+
+impl PartInspector {
+    fn new(max_size: u64, algorithm: Option<ChecksumAlgorithm>) -> PartInspector {
+        PartInspector {
+            max_size,
+            size: 0,
+            too_large: false,
+            context: algorithm.map(|algorithm| Context::new(algorithm.get_digest_algorithm())),
+        }
+    }
+
+    fn update(&mut self, chunk: &[u8]) -> std::io::Result<()> {
+        self.size += chunk.len() as u64;
+        if self.size > self.max_size {
+            self.too_large = true;
+            return Err(std::io::Error::other(UploadError::UploadLengthExceeded));
+        }
+
+        if let Some(context) = self.context.as_mut() {
+            context.update(chunk);
+        }
+        Ok(())
+    }
+
+    fn inspect(inspector: Arc<Mutex<PartInspector>>, body: StorageStream) -> StorageStream {
+        Box::pin(body.map(move |chunk| {
+            let chunk = chunk?;
+            inspector.lock().unwrap().update(&chunk)?;
+            Ok(chunk)
+        }))
+    }
+
+    fn is_checksum_match(&self, checksum: &Option<UploadChecksum>) -> bool {
+        match (checksum, &self.context) {
+            (Some(checksum), Some(context)) => checksum.is_match(context.clone().finish().as_ref()),
+            _ => true,
+        }
+    }
+}
+
+#[automock]
+#[async_trait(?Send)]
+pub trait UploadServiceTrait {
+    async fn upload_create(&self, customer: &Customer, name: &str, declared_mime_type: Option<String>, length: u64) -> Result<Upload>;
+    async fn upload_read_by_id(&self, id: &Uuid, customer_id: &Uuid) -> Result<Upload>;
+    async fn upload_append(&self, customer: &Customer, id: &Uuid, offset: u64, checksum: Option<UploadChecksum>, body: StorageStream) -> Result<Upload>;
+    async fn upload_terminate(&self, id: &Uuid, customer_id: &Uuid) -> Result<()>;
+}
+
+
+pub struct UploadServiceImpl {
+    curr_time_fn: Box<dyn Fn() -> DateTime<Utc>>,
+    max_upload_size_bytes: u64,
+    storage_backend: Arc<dyn StorageBackendTrait>,
+    file_service: Arc<dyn FileServiceTrait>,
+    upload_repository: Arc<RwLock<dyn UploadRepositoryTrait>>,
+}
+
+impl UploadServiceImpl {
+    pub fn new(
+        curr_time_fn: impl Fn() -> DateTime<Utc> + 'static,
+        max_upload_size_bytes: u64,
+        storage_backend: Arc<dyn StorageBackendTrait>,
+        file_service: Arc<dyn FileServiceTrait>,
+        upload_repository: Arc<RwLock<dyn UploadRepositoryTrait>>,
+    ) -> Arc<UploadServiceImpl> {
+        let svc = UploadServiceImpl {
+            curr_time_fn: Box::new(curr_time_fn),
+            max_upload_size_bytes,
+            storage_backend: storage_backend.clone(),
+            file_service: file_service.clone(),
+            upload_repository: upload_repository.clone(),
+        };
+
+        Arc::new(svc)
+    }
+
+    // NOTE: two retries of the last PATCH may both get here, only the one that completes the
+    //       upload keeps its file. The other one drops its copy and leaves the parts alone.
+    async fn finalize(&self, customer: &Customer, upload: Upload) -> Result<Upload> {
+        let file_meta = self
+            .file_service
+            .file_assemble(customer, &upload.get_name(), upload.get_mime_type(), &upload.get_parts())
+            .await?;
+
+        let upload_list = {
+            let repo = self.upload_repository.write().await;
+            repo.complete(&upload.get_id(), &file_meta.get_id()).await
+        };
+
+        let completed_upload = match upload_list {
+            Ok(upload_list) if !upload_list.is_empty() => upload_list[0].clone(),
+            upload_list => {
+                self.file_service.file_discard(&file_meta).await?;
+                upload_list?;
+                return self.upload_read_by_id(&upload.get_id(), &customer.get_id()).await;
+            }
+        };
+
+        // NOTE: the file is complete at this point, a part left behind only wastes space
+        for key in upload.get_parts() {
+            if let Err(err) = self.storage_backend.delete(&key).await {
+                log::warn!("failed to delete part {} of upload {}: {}", key, upload.get_id(), err);
+            }
+        }
+
+        Ok(completed_upload)
+    }
+}
+
+
+#[async_trait(?Send)]
+impl UploadServiceTrait for UploadServiceImpl {
+    async fn upload_create(&self, customer: &Customer, name: &str, declared_mime_type: Option<String>, length: u64) -> Result<Upload> {
+        let max_size = customer.get_max_upload_size_bytes().unwrap_or(self.max_upload_size_bytes);
+        if length > max_size {
+            bail!(FileError::FileTooLarge)
+        }
+
+        let created_at = (self.curr_time_fn)();
+        let upload = {
+            let repo = self.upload_repository.write().await;
+            repo.create(&customer.get_id(), name, &declared_mime_type, length, &created_at).await?
+        };
+
+        // NOTE: an empty upload is complete as soon as it exists
+        if upload.is_complete() {
+            return self.finalize(customer, upload).await;
+        }
+        Ok(upload)
+    }
+
+    async fn upload_read_by_id(&self, id: &Uuid, customer_id: &Uuid) -> Result<Upload> {
+        let upload_list = {
+            let repo = self.upload_repository.read().await;
+            repo.get_by_id(id).await?
+        };
+
+        if upload_list.is_empty() {
+            bail!(UploadError::UploadNotFound)
+        }
+
+        let upload = upload_list[0].clone();
+        if upload.get_customer_id() != *customer_id {
+            bail!(UploadError::UploadNotBelongToCustomer)
+        }
+
+        Ok(upload)
+    }
+
+    async fn upload_append(&self, customer: &Customer, id: &Uuid, offset: u64, checksum: Option<UploadChecksum>, body: StorageStream) -> Result<Upload> {
+        let upload = self.upload_read_by_id(id, &customer.get_id()).await?;
+        if upload.get_offset() != offset {
+            bail!(UploadError::UploadOffsetMismatch)
+        }
+
+        // NOTE: all bytes arrived but the file could not be created last time, an empty PATCH at
+        //       the final offset tries again.
+        if upload.is_complete() {
+            return match upload.get_file_id() {
+                Some(_) => Ok(upload),
+                None => self.finalize(customer, upload).await,
+            };
+        }
+
+        let key = upload.new_part_key();
+        let algorithm = checksum.as_ref().map(|checksum| checksum.get_algorithm());
+        let inspector = Arc::new(Mutex::new(PartInspector::new(upload.get_length() - offset, algorithm)));
+        let result = self.storage_backend.put(&key, PartInspector::inspect(inspector.clone(), body)).await;
+
+        // NOTE: the backend drops everything it received once the stream fails
+        let (size, checksum_match) = {
+            let inspector = inspector.lock().unwrap();
+            if inspector.too_large {
+                bail!(UploadError::UploadLengthExceeded)
+            }
+            result?;
+
+            (inspector.size, inspector.is_checksum_match(&checksum))
+        };
+
+        if size == 0 || !checksum_match {
+            self.storage_backend.delete(&key).await?;
+            if !checksum_match {
+                bail!(UploadError::UploadChecksumMismatch)
+            }
+            return Ok(upload);
+        }
+
+        let upload_list = {
+            let repo = self.upload_repository.write().await;
+            repo.append_part(id, offset, offset + size, &key).await
+        };
+
+        // NOTE: a part that is not recorded can never be reached again, do not keep it around
+        let upload_list = match upload_list {
+            Ok(upload_list) if !upload_list.is_empty() => upload_list,
+            upload_list => {
+                self.storage_backend.delete(&key).await?;
+                upload_list?;
+                bail!(UploadError::UploadOffsetMismatch)
+            }
+        };
+
+        let upload = upload_list[0].clone();
+        if upload.is_complete() {
+            return self.finalize(customer, upload).await;
+        }
+        Ok(upload)
+    }
+
+    // NOTE: a finished upload only loses its record, the file it became is left alone
+    async fn upload_terminate(&self, id: &Uuid, customer_id: &Uuid) -> Result<()> {
+        let upload = self.upload_read_by_id(id, customer_id).await?;
+        for key in upload.get_parts() {
+            self.storage_backend.delete(&key).await?;
+        }
+
+        let repo = self.upload_repository.write().await;
+        repo.delete(id).await
+    }
+}
@@ -0,0 +1,367 @@
+use std::sync::Arc;
+
+use bytes::Bytes;
+use chrono::{DateTime, TimeZone, Utc};
+use futures_util::{FutureExt, StreamExt};
+use ring::digest::{digest, SHA1_FOR_LEGACY_USE_ONLY};
+use tokio::sync::RwLock;
+use uuid::{uuid, Uuid};
+
+use crate::domain::{entity::{customer::Customer, file_meta::{FileContent, FileMeta}, storage_object::{StorageObjectStat, StorageStream}, upload::{ChecksumAlgorithm, Upload, UploadChecksum}}, error::{file::FileError, upload::UploadError}, repository::{storage::MockStorageBackendTrait, upload::MockUploadRepositoryTrait}};
+
+use super::file::MockFileServiceTrait;
+use super::upload::{UploadServiceImpl, UploadServiceTrait};
+
+const FAKE_UPLOAD_ID: Uuid = uuid!("67e55044-10b1-426f-9247-bb680e5fe0c8");
+const FAKE_CUSTOMER_ID: Uuid = uuid!("8a1f2c7e-4b3d-4e5f-9a6b-7c8d9e0f1a2b");
+const FAKE_FILE_ID: Uuid = uuid!("0b7e6f3a-2c1d-4e8f-a9b0-c1d2e3f4a5b6");
+const FAKE_MAX_UPLOAD_SIZE: u64 = 1024;
+
+fn fake_current_at() -> DateTime<Utc> {
+    Utc.with_ymd_and_hms(1990, 3, 3, 0, 0, 0).unwrap()
+}
+
+fn fake_customer() -> Customer {
+    Customer::new_with_id(&FAKE_CUSTOMER_ID, "mikejiang")
+}
+
+fn fake_upload(offset: u64, parts: &[&str]) -> Upload {
+    let mut upload = Upload::new_full(&FAKE_UPLOAD_ID, &FAKE_CUSTOMER_ID, "hello.txt", &Some(String::from("text/plain")), 11, &fake_current_at());
+    upload.set_progress(offset, &parts.iter().map(|part| part.to_string()).collect::<Vec<String>>());
+    upload
+}
+
+fn body_of(content: &'static [u8]) -> StorageStream {
+    let chunks: Vec<std::io::Result<Bytes>> = content
+        .chunks(4)
+        .map(|chunk| Ok(Bytes::from_static(chunk)))
+        .collect();
+    Box::pin(futures_util::stream::iter(chunks))
+}
+
+// NOTE: reads the body the way a backend would, the fake bodies above never have to wait
+fn drain_body(mut body: StorageStream) -> std::io::Result<Vec<u8>> {
+    let mut content = vec![];
+    while let Some(Some(chunk)) = body.next().now_or_never() {
+        content.extend_from_slice(&chunk?);
+    }
+    Ok(content)
+}
+
+fn upload_svc(
+    mock_storage_backend: MockStorageBackendTrait,
+    mock_file_svc: MockFileServiceTrait,
+    mock_upload_repo: MockUploadRepositoryTrait,
+) -> Arc<UploadServiceImpl> {
+    UploadServiceImpl::new(
+        fake_current_at,
+        FAKE_MAX_UPLOAD_SIZE,
+        Arc::new(mock_storage_backend),
+        Arc::new(mock_file_svc),
+        Arc::new(RwLock::new(mock_upload_repo)),
+    )
+}
+
+#[actix_rt::test]
+async fn test_upload_svc_create() {
+    let test_context = vec![
+        (None, Ok(fake_upload(0, &[]))),
+        (Some(11), Ok(fake_upload(0, &[]))),
+        (Some(10), Err(FileError::FileTooLarge)),
+    ];
+
+    for (max_upload_size_bytes, expected) in test_context {
+        let mut mock_upload_repo = MockUploadRepositoryTrait::new();
+        mock_upload_repo
+            .expect_create()
+            .times(expected.iter().count())
+            .withf(|customer_id, name, mime_type, length, created_at| {
+                *customer_id == FAKE_CUSTOMER_ID && name == "hello.txt" && *mime_type == Some(String::from("text/plain")) && *length == 11 && *created_at == fake_current_at()
+            })
+            .returning(|_customer_id, _name, _mime_type, _length, _created_at| Ok(fake_upload(0, &[])));
+
+        let svc = upload_svc(MockStorageBackendTrait::new(), MockFileServiceTrait::new(), mock_upload_repo);
+
+        let mut customer = fake_customer();
+        customer.set_max_upload_size_bytes(max_upload_size_bytes);
+        let result = svc
+            .upload_create(&customer, "hello.txt", Some(String::from("text/plain")), 11)
+            .await
+            .map_err(|err| err.downcast().unwrap());
+
+        assert_eq!(result, expected);
+    }
+}
+
+#[actix_rt::test]
+async fn test_upload_svc_create_empty() {
+    let mut mock_upload_repo = MockUploadRepositoryTrait::new();
+    mock_upload_repo
+        .expect_create()
+        .times(1)
+        .returning(|customer_id, name, mime_type, length, created_at| Ok(Upload::new_full(&FAKE_UPLOAD_ID, customer_id, name, mime_type, length, created_at)));
+    mock_upload_repo
+        .expect_complete()
+        .times(1)
+        .withf(|id, file_id| *id == FAKE_UPLOAD_ID && *file_id == FAKE_FILE_ID)
+        .returning(|_id, file_id| {
+            let mut upload = Upload::new_full(&FAKE_UPLOAD_ID, &FAKE_CUSTOMER_ID, "empty.txt", &None, 0, &fake_current_at());
+            upload.set_file_id(Some(*file_id));
+            Ok(vec![upload])
+        });
+
+    let mut mock_file_svc = MockFileServiceTrait::new();
+    mock_file_svc
+        .expect_file_assemble()
+        .times(1)
+        .withf(|_customer, _name, _mime_type, parts| parts.is_empty())
+        .returning(|customer, _name, _mime_type, _parts| Ok(FileMeta::new_with_content(&FAKE_FILE_ID, &customer.get_id(), "", &FileContent::default(), &DateTime::<Utc>::default())));
+
+    let svc = upload_svc(MockStorageBackendTrait::new(), mock_file_svc, mock_upload_repo);
+    let upload = svc.upload_create(&fake_customer(), "empty.txt", None, 0).await.unwrap();
+
+    assert!(upload.is_complete());
+    assert_eq!(upload.get_file_id(), Some(FAKE_FILE_ID));
+}
+
+#[actix_rt::test]
+async fn test_upload_svc_read_by_id() {
+    let other_customer_id = Uuid::new_v4();
+    let test_context = vec![
+        (vec![], FAKE_CUSTOMER_ID, Err(UploadError::UploadNotFound)),
+        (vec![fake_upload(5, &[])], other_customer_id, Err(UploadError::UploadNotBelongToCustomer)),
+        (vec![fake_upload(5, &[])], FAKE_CUSTOMER_ID, Ok(fake_upload(5, &[]))),
+    ];
+
+    for (upload_list, customer_id, expected) in test_context {
+        let mut mock_upload_repo = MockUploadRepositoryTrait::new();
+        mock_upload_repo
+            .expect_get_by_id()
+            .times(1)
+            .returning(move |_id| Ok(upload_list.clone()));
+
+        let svc = upload_svc(MockStorageBackendTrait::new(), MockFileServiceTrait::new(), mock_upload_repo);
+        let result = svc
+            .upload_read_by_id(&FAKE_UPLOAD_ID, &customer_id)
+            .await
+            .map_err(|err| err.downcast().unwrap());
+
+        assert_eq!(result, expected);
+    }
+}
+
+fn sha1_checksum(content: &[u8]) -> Option<UploadChecksum> {
+    let sha1 = digest(&SHA1_FOR_LEGACY_USE_ONLY, content);
+    Some(UploadChecksum::new(ChecksumAlgorithm::Sha1, sha1.as_ref()))
+}
+
+#[actix_rt::test]
+async fn test_upload_svc_append() {
+    let test_context = vec![
+        // NOTE: offset, body, checksum, whether the part is recorded, expected result
+        (0, &b"hello"[..], None, true, Ok(5)),
+        (0, &b"hello"[..], sha1_checksum(b"hello"), true, Ok(5)),
+        (0, &b"hello"[..], sha1_checksum(b"world"), false, Err(UploadError::UploadChecksumMismatch)),
+        (0, &b""[..], None, false, Ok(0)),
+        (0, &b"hello world!"[..], None, false, Err(UploadError::UploadLengthExceeded)),
+        (3, &b"hello"[..], None, false, Err(UploadError::UploadOffsetMismatch)),
+    ];
+
+    for (offset, content, checksum, recorded, expected) in test_context {
+        let mut mock_upload_repo = MockUploadRepositoryTrait::new();
+        mock_upload_repo
+            .expect_get_by_id()
+            .times(1)
+            .returning(|_id| Ok(vec![fake_upload(0, &[])]));
+        mock_upload_repo
+            .expect_append_part()
+            .times(if recorded { 1 } else { 0 })
+            .withf(|id, offset, new_offset, part_key| *id == FAKE_UPLOAD_ID && *offset == 0 && *new_offset == 5 && part_key.starts_with("67e5504410b1426f9247bb680e5fe0c8-"))
+            .returning(|_id, _offset, new_offset, part_key| Ok(vec![fake_upload(new_offset, &[part_key])]));
+
+        let expect_put = offset == 0;
+        let discarded = expect_put && !recorded && content.len() <= 11;
+        let mut mock_storage_backend = MockStorageBackendTrait::new();
+        mock_storage_backend
+            .expect_put()
+            .times(if expect_put { 1 } else { 0 })
+            .returning(|_key, body| {
+                let size = drain_body(body)?.len() as u64;
+                Ok(StorageObjectStat::new(size, "", &fake_current_at()))
+            });
+        mock_storage_backend
+            .expect_delete()
+            .times(if discarded { 1 } else { 0 })
+            .returning(|_key| Ok(()));
+
+        let svc = upload_svc(mock_storage_backend, MockFileServiceTrait::new(), mock_upload_repo);
+        let result = svc
+            .upload_append(&fake_customer(), &FAKE_UPLOAD_ID, offset, checksum, body_of(content))
+            .await
+            .map(|upload| upload.get_offset())
+            .map_err(|err| err.downcast().unwrap());
+
+        assert_eq!(result, expected);
+    }
+}
+
+#[actix_rt::test]
+async fn test_upload_svc_append_lost_race() {
+    let mut mock_upload_repo = MockUploadRepositoryTrait::new();
+    mock_upload_repo
+        .expect_get_by_id()
+        .times(1)
+        .returning(|_id| Ok(vec![fake_upload(0, &[])]));
+    mock_upload_repo
+        .expect_append_part()
+        .times(1)
+        .returning(|_id, _offset, _new_offset, _part_key| Ok(vec![]));
+
+    let stored_key = Arc::new(std::sync::Mutex::new(String::new()));
+    let mut mock_storage_backend = MockStorageBackendTrait::new();
+    let put_key = stored_key.clone();
+    mock_storage_backend
+        .expect_put()
+        .times(1)
+        .returning(move |key, body| {
+            *put_key.lock().unwrap() = key.to_string();
+            let size = drain_body(body)?.len() as u64;
+            Ok(StorageObjectStat::new(size, "", &fake_current_at()))
+        });
+    let deleted_key = stored_key.clone();
+    mock_storage_backend
+        .expect_delete()
+        .times(1)
+        .withf(move |key| *key == *deleted_key.lock().unwrap())
+        .returning(|_key| Ok(()));
+
+    let svc = upload_svc(mock_storage_backend, MockFileServiceTrait::new(), mock_upload_repo);
+    let result = svc
+        .upload_append(&fake_customer(), &FAKE_UPLOAD_ID, 0, None, body_of(b"hello"))
+        .await
+        .map_err(|err| err.downcast().unwrap());
+
+    assert_eq!(result, Err(UploadError::UploadOffsetMismatch));
+}
+
+#[actix_rt::test]
+async fn test_upload_svc_append_finalize() {
+    let mut mock_upload_repo = MockUploadRepositoryTrait::new();
+    mock_upload_repo
+        .expect_get_by_id()
+        .times(1)
+        .returning(|_id| Ok(vec![fake_upload(5, &["first"])]));
+    mock_upload_repo
+        .expect_append_part()
+        .times(1)
+        .withf(|_id, offset, new_offset, _part_key| *offset == 5 && *new_offset == 11)
+        .returning(|_id, _offset, new_offset, part_key| Ok(vec![fake_upload(new_offset, &["first", part_key])]));
+    mock_upload_repo
+        .expect_complete()
+        .times(1)
+        .withf(|id, file_id| *id == FAKE_UPLOAD_ID && *file_id == FAKE_FILE_ID)
+        .returning(|_id, file_id| {
+            let mut upload = fake_upload(11, &[]);
+            upload.set_file_id(Some(*file_id));
+            Ok(vec![upload])
+        });
+
+    let mut mock_storage_backend = MockStorageBackendTrait::new();
+    mock_storage_backend
+        .expect_put()
+        .times(1)
+        .returning(|_key, body| {
+            let size = drain_body(body)?.len() as u64;
+            Ok(StorageObjectStat::new(size, "", &fake_current_at()))
+        });
+    mock_storage_backend
+        .expect_delete()
+        .times(2)
+        .returning(|_key| Ok(()));
+
+    let mut mock_file_svc = MockFileServiceTrait::new();
+    mock_file_svc
+        .expect_file_assemble()
+        .times(1)
+        .withf(|customer, name, mime_type, parts| customer.get_id() == FAKE_CUSTOMER_ID && name == "hello.txt" && *mime_type == Some(String::from("text/plain")) && parts.len() == 2 && parts[0] == "first")
+        .returning(|customer, _name, _mime_type, _parts| Ok(FileMeta::new_with_content(&FAKE_FILE_ID, &customer.get_id(), "", &FileContent::default(), &DateTime::<Utc>::default())));
+
+    let svc = upload_svc(mock_storage_backend, mock_file_svc, mock_upload_repo);
+    let upload = svc
+        .upload_append(&fake_customer(), &FAKE_UPLOAD_ID, 5, None, body_of(b" world"))
+        .await
+        .unwrap();
+
+    assert!(upload.is_complete());
+    assert_eq!(upload.get_parts(), Vec::<String>::new());
+    assert_eq!(upload.get_file_id(), Some(FAKE_FILE_ID));
+}
+
+// NOTE: another retry of the last PATCH completed the upload while this one was assembling
+#[actix_rt::test]
+async fn test_upload_svc_append_finalize_lost_race() {
+    let mut completed_upload = fake_upload(11, &[]);
+    completed_upload.set_file_id(Some(Uuid::new_v4()));
+
+    let mut mock_upload_repo = MockUploadRepositoryTrait::new();
+    let get_by_id_list = [vec![fake_upload(11, &["first", "second"])], vec![completed_upload.clone()]];
+    let mut get_by_id_call = 0;
+    mock_upload_repo
+        .expect_get_by_id()
+        .times(2)
+        .returning(move |_id| {
+            get_by_id_call += 1;
+            Ok(get_by_id_list[get_by_id_call - 1].clone())
+        });
+    mock_upload_repo
+        .expect_complete()
+        .times(1)
+        .returning(|_id, _file_id| Ok(vec![]));
+
+    let mut mock_file_svc = MockFileServiceTrait::new();
+    mock_file_svc
+        .expect_file_assemble()
+        .times(1)
+        .returning(|customer, _name, _mime_type, _parts| Ok(FileMeta::new_with_content(&FAKE_FILE_ID, &customer.get_id(), "", &FileContent::default(), &DateTime::<Utc>::default())));
+    mock_file_svc
+        .expect_file_discard()
+        .times(1)
+        .withf(|file_meta| file_meta.get_id() == FAKE_FILE_ID)
+        .returning(|_file_meta| Ok(()));
+
+    // NOTE: the parts belong to the request that completed the upload
+    let mut mock_storage_backend = MockStorageBackendTrait::new();
+    mock_storage_backend.expect_delete().times(0);
+
+    let svc = upload_svc(mock_storage_backend, mock_file_svc, mock_upload_repo);
+    let upload = svc
+        .upload_append(&fake_customer(), &FAKE_UPLOAD_ID, 11, None, body_of(b""))
+        .await
+        .unwrap();
+
+    assert_eq!(upload, completed_upload);
+}
+
+#[actix_rt::test]
+async fn test_upload_svc_terminate() {
+    let mut mock_upload_repo = MockUploadRepositoryTrait::new();
+    mock_upload_repo
+        .expect_get_by_id()
+        .times(1)
+        .returning(|_id| Ok(vec![fake_upload(8, &["first", "second"])]));
+    mock_upload_repo
+        .expect_delete()
+        .times(1)
+        .withf(|id| *id == FAKE_UPLOAD_ID)
+        .returning(|_id| Ok(()));
+
+    let mut mock_storage_backend = MockStorageBackendTrait::new();
+    mock_storage_backend
+        .expect_delete()
+        .times(2)
+        .withf(|key| key == "first" || key == "second")
+        .returning(|_key| Ok(()));
+
+    let svc = upload_svc(mock_storage_backend, MockFileServiceTrait::new(), mock_upload_repo);
+    svc.upload_terminate(&FAKE_UPLOAD_ID, &FAKE_CUSTOMER_ID).await.unwrap();
+}
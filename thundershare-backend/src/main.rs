@@ -1,18 +1,38 @@
+mod config;
 mod domain;
 mod pgsql;
 mod presentation;
+mod storage;
 
-use actix_web::middleware::Logger;
+use std::sync::Arc;
+use std::time::Duration;
+
+use actix_web::http::Method;
+use actix_web::middleware::{DefaultHeaders, Logger};
 use actix_web::web::{self, Data};
 use actix_web::{App, HttpServer};
-use domain::service::file::LocalFileUploaderImpl;
+use config::ServerConfig;
+use domain::entity::jwt_key::JwtKeySet;
+use domain::repository::storage::StorageBackendTrait;
+use domain::repository::ServerRepositories;
+use domain::service::file::FileServiceTrait;
 use domain::service::ServerService;
 use env_logger::Env;
-use pgsql::{connection_builder, ServerRepositories};
-use presentation::customer::view::{customer_get_by_id_v1, customer_signin_v1, customer_signout_v1, customer_signup_v1};
-use presentation::file::view::{file_list_by_customer_id_v1, file_read_by_id_v1, file_sharing_create_v1, file_sharing_get_by_id_v1, file_upload_v1};
+use pgsql::used_token::UsedTokenCache;
+use pgsql::{connection_builder, DbPool};
+use presentation::auth::RequireAuthentication;
+use presentation::error::invalid_request_handler;
+use presentation::customer::view::{customer_get_by_id_v1, customer_refresh_v1, customer_signin_v1, customer_signout_v1, customer_signup_v1};
+use presentation::jwks::view::jwks_get;
+use storage::storage_backend_builder;
+use presentation::folder::view::{folder_create_v1, folder_delete_by_id_v1, folder_move_v1, folder_read_by_id_v1, folder_read_root_v1, folder_rename_v1};
+use presentation::upload::dto::{TUS_RESUMABLE_HEADER_NAME, TUS_VERSION, TUS_VERSION_HEADER_NAME};
+use presentation::upload::view::{upload_append_v1, upload_create_v1, upload_options_v1, upload_read_by_id_v1, upload_terminate_v1};
+use presentation::file::view::{file_delete_by_id_v1, file_download_by_id_v1, file_grant_list_v1, file_grant_revoke_v1, file_grant_v1, file_list_by_customer_id_v1, file_move_by_id_v1, file_read_by_id_v1, file_request_create_v1, file_request_get_by_code, file_request_list_v1, file_request_revoke_v1, file_request_upload_by_code, file_sharing_access_list_v1, file_sharing_create_v1, file_sharing_download_by_id_v1, file_sharing_get_by_code, file_sharing_get_by_id_v1, file_sharing_list_v1, file_sharing_read_by_id_v1, file_sharing_revoke_v1, file_sharing_unlock_v1, file_sharing_update_v1, file_shared_with_me_v1, file_upload_v1, trash_list_by_customer_id_v1, trash_purge_by_id_v1, trash_restore_by_id_v1};
 
 pub fn register_routes(cfg: &mut actix_web::web::ServiceConfig) {
+    cfg.route("/.well-known/jwks.json", web::get().to(jwks_get));
+
     // NOTE: customer auth related endpoints
     cfg.route(
         "/api/v1/customer/signup",
@@ -26,35 +46,145 @@ pub fn register_routes(cfg: &mut actix_web::web::ServiceConfig) {
         "/api/v1/customer/signout",
         web::post().to(customer_signout_v1),
     )
+    .route(
+        "/api/v1/customer/refresh",
+        web::post().to(customer_refresh_v1),
+    )
     .route(
         "/api/v1/customer/{id}",
         web::get().to(customer_get_by_id_v1),
 
     );
 
-    // NOTE: file meta related endpoints
+    // NOTE: file meta related endpoints, every one of them requires an authenticated customer.
+    //       /shared-with-me has to come before /{id} to not be taken for a file id
+    cfg.service(
+        web::scope("/api/v1/file")
+            .wrap(RequireAuthentication)
+            .route("", web::get().to(file_list_by_customer_id_v1))
+            .route("/shared-with-me", web::get().to(file_shared_with_me_v1))
+            .route("/{id}", web::get().to(file_read_by_id_v1))
+            .route("/{id}/content", web::get().to(file_download_by_id_v1))
+            .route("/{id}/content", web::head().to(file_download_by_id_v1))
+            .route("/{id}", web::delete().to(file_delete_by_id_v1))
+            .route("/{id}/move", web::post().to(file_move_by_id_v1))
+            .route("/{id}/grants", web::put().to(file_grant_v1))
+            .route("/{id}/grants", web::get().to(file_grant_list_v1))
+            .route("/{id}/grants/{customer_id}", web::delete().to(file_grant_revoke_v1))
+            .route("", web::post().to(file_upload_v1)),
+    );
+
+    // NOTE: the folder tree of the customer, a folder has to be empty before it can be deleted
+    cfg.service(
+        web::scope("/api/v1/folder")
+            .wrap(RequireAuthentication)
+            .route("", web::get().to(folder_read_root_v1))
+            .route("", web::post().to(folder_create_v1))
+            .route("/{id}", web::get().to(folder_read_by_id_v1))
+            .route("/{id}/rename", web::post().to(folder_rename_v1))
+            .route("/{id}/move", web::post().to(folder_move_v1))
+            .route("/{id}", web::delete().to(folder_delete_by_id_v1)),
+    );
+
+    // NOTE: deleted files stay in the trash of their customer until restored or purged
+    cfg.service(
+        web::scope("/api/v1/trash")
+            .wrap(RequireAuthentication)
+            .route("", web::get().to(trash_list_by_customer_id_v1))
+            .route("/{id}/restore", web::post().to(trash_restore_by_id_v1))
+            .route("/{id}", web::delete().to(trash_purge_by_id_v1)),
+    );
+
+    // NOTE: GET on a link tells anyone what it leads to, POST, /unlock and /content open it for
+    //       anyone holding the password. the other methods are for the owner of the link only
     cfg.route(
-        "/api/v1/file",
-        web::get().to(file_list_by_customer_id_v1),
+        "/api/v1/file-sharing",
+        web::post().to(file_sharing_create_v1),
+    )
+    .route(
+        "/api/v1/file-sharing",
+        web::get().to(file_sharing_list_v1),
+    )
+    .route(
+        "/api/v1/file-sharing/{id}",
+        web::get().to(file_sharing_read_by_id_v1),
+    )
+    .route(
+        "/api/v1/file-sharing/{id}",
+        web::post().to(file_sharing_get_by_id_v1),
+    )
+    .route(
+        "/api/v1/file-sharing/{id}/unlock",
+        web::post().to(file_sharing_unlock_v1),
     )
     .route(
-        "/api/v1/file/{id}",
-        web::get().to(file_read_by_id_v1),
+        "/api/v1/file-sharing/{id}/content",
+        web::get().to(file_sharing_download_by_id_v1),
     )
     .route(
-        "/api/v1/file",
-        web::post().to(file_upload_v1),
+        "/api/v1/file-sharing/{id}",
+        web::patch().to(file_sharing_update_v1),
+    )
+    .route(
+        "/api/v1/file-sharing/{id}",
+        web::delete().to(file_sharing_revoke_v1),
+    )
+    .route(
+        "/api/v1/file-sharing/{id}/access",
+        web::get().to(file_sharing_access_list_v1),
     );
 
     cfg.route(
-        "/api/v1/file-sharing",
-        web::post().to(file_sharing_create_v1),
+        "/api/v1/file-request",
+        web::post().to(file_request_create_v1),
     )
     .route(
-        "/api/v1/file-sharing/{id}",
-        web::post().to(file_sharing_get_by_id_v1),
+        "/api/v1/file-request",
+        web::get().to(file_request_list_v1),
+    )
+    .route(
+        "/api/v1/file-request/{id}",
+        web::delete().to(file_request_revoke_v1),
     );
 
+    // NOTE: tus resumable uploads, every response carries the protocol version even when it
+    //       is an error. OPTIONS is left unauthenticated so clients can discover the server.
+    cfg.service(
+        web::scope("/api/v1/uploads")
+            .wrap(
+                DefaultHeaders::new()
+                    .add((TUS_RESUMABLE_HEADER_NAME, TUS_VERSION))
+                    .add((TUS_VERSION_HEADER_NAME, TUS_VERSION)),
+            )
+            .route("", web::method(Method::OPTIONS).to(upload_options_v1))
+            .route("", web::post().to(upload_create_v1))
+            .route("/{id}", web::head().to(upload_read_by_id_v1))
+            .route("/{id}", web::patch().to(upload_append_v1))
+            .route("/{id}", web::delete().to(upload_terminate_v1)),
+    );
+
+    // NOTE: public short links handed out by file-sharing, resolved by code
+    cfg.route("/s/{code}", web::get().to(file_sharing_get_by_code));
+
+    // NOTE: public upload links handed out by file-request, GET tells an outsider what it takes
+    cfg.route("/r/{code}", web::get().to(file_request_get_by_code))
+        .route("/r/{code}", web::post().to(file_request_upload_by_code));
+
+}
+
+fn server_services_builder(
+    config: &Arc<ServerConfig>,
+    jwt_key_set: &Arc<JwtKeySet>,
+    storage_backend: &Arc<dyn StorageBackendTrait>,
+    db_pool: &DbPool,
+    used_token_cache: &UsedTokenCache,
+) -> ServerService {
+    ServerService::new(
+        config.clone(),
+        jwt_key_set.clone(),
+        storage_backend.clone(),
+        ServerRepositories::new(db_pool.clone(), used_token_cache.clone()),
+    )
 }
 
 #[actix_web::main]
@@ -62,25 +192,69 @@ async fn main() -> std::io::Result<()> {
     dotenv::dotenv().ok();
     env_logger::Builder::from_env(Env::default().default_filter_or("info")).init();
 
-    let server_host = std::env::var("SERVER_HOST").unwrap();
-    let server_port = std::env::var("SERVER_PORT").unwrap();
-    let server_location = server_host + ":" + &server_port;
+    let config = match ServerConfig::load() {
+        Ok(config) => Arc::new(config),
+        Err(err) => {
+            log::error!("{}", err);
+            std::process::exit(1);
+        }
+    };
+
+    // NOTE: key files are read once here so a broken key fails the startup instead of a request
+    let jwt_key_set = match JwtKeySet::new(&config.jwt.active_key_id, &config.jwt.keys) {
+        Ok(jwt_key_set) => Arc::new(jwt_key_set),
+        Err(err) => {
+            log::error!("invalid jwt key configuration: {}", err);
+            std::process::exit(1);
+        }
+    };
+
+    let server_location = format!("{}:{}", config.http.host, config.http.port);
 
-    let db_pool = connection_builder().await.unwrap();
+    let db_pool = connection_builder(&config.database).await.unwrap();
     sqlx::migrate!("./migrations").run(&db_pool).await.unwrap();
+    let used_token_cache = UsedTokenCache::new();
+
+    let storage_backend = match storage_backend_builder(&config.storage) {
+        Ok(storage_backend) => storage_backend,
+        Err(err) => {
+            log::error!("invalid storage configuration: {}", err);
+            std::process::exit(1);
+        }
+    };
+
+    // NOTE: purges the files that outlived the trash retention, then retries the objects of
+    //       purged files the storage backend could not remove at the time
+    let cleanup_services = server_services_builder(&config, &jwt_key_set, &storage_backend, &db_pool, &used_token_cache);
+    let cleanup_interval = Duration::from_secs(config.storage.cleanup_interval_seconds);
+    let trash_retention = chrono::Duration::seconds(config.trash.retention_seconds);
+    actix_rt::spawn(async move {
+        let mut interval = actix_rt::time::interval(cleanup_interval);
+        loop {
+            interval.tick().await;
+            match cleanup_services.file_service.file_purge_trash(trash_retention).await {
+                Ok(0) => {}
+                Ok(purged) => log::info!("purged {} files from the trash", purged),
+                Err(err) => log::warn!("trash purge failed: {}", err),
+            }
+            match cleanup_services.file_service.file_cleanup_storage().await {
+                Ok(0) => {}
+                Ok(deleted) => log::info!("deleted {} pending storage objects", deleted),
+                Err(err) => log::warn!("storage cleanup failed: {}", err),
+            }
+            if let Err(err) = cleanup_services.file_service.file_cleanup_sharing_attempts().await {
+                log::warn!("sharing attempt cleanup failed: {}", err);
+            }
+        }
+    });
 
     HttpServer::new(move || {
-        let file_uploader = LocalFileUploaderImpl::new();
-        let server_repositories = ServerRepositories::new(db_pool.clone());
-        let server_domain_services = ServerService::new(
-            file_uploader,
-            server_repositories.customer_repository,
-            server_repositories.used_token_repository,
-            server_repositories.file_meta_repository,
-            server_repositories.file_sharing_meta_repository,
-        );
+        let server_domain_services = server_services_builder(&config, &jwt_key_set, &storage_backend, &db_pool, &used_token_cache);
         App::new()
             .wrap(Logger::default())
+            .app_data(web::JsonConfig::default().error_handler(invalid_request_handler))
+            .app_data(web::PathConfig::default().error_handler(invalid_request_handler))
+            .app_data(web::QueryConfig::default().error_handler(invalid_request_handler))
             .app_data(Data::new(server_domain_services))
             .configure(register_routes)
     })
@@ -14,11 +14,27 @@ use super::DbPool;
 struct CustomerDAO {
     id: Uuid,
     username: String,
+    maxuploadsizebytes: Option<i64>,
 }
 
 impl From<CustomerDAO> for Customer {
     fn from(dao: CustomerDAO) -> Customer {
-        Customer::new_with_id(&dao.id, &dao.username)
+        let mut customer = Customer::new_with_id(&dao.id, &dao.username);
+        customer.set_max_upload_size_bytes(dao.maxuploadsizebytes.map(|size| size as u64));
+        customer
+    }
+}
+
+#[derive(Debug, FromRow, Clone)]
+struct CustomerCredentialDAO {
+    id: Uuid,
+    username: String,
+    password: String,
+}
+
+impl From<CustomerCredentialDAO> for (Customer, String) {
+    fn from(dao: CustomerCredentialDAO) -> (Customer, String) {
+        (Customer::new_with_id(&dao.id, &dao.username), dao.password)
     }
 }
 
@@ -51,7 +67,7 @@ impl CustomerRepositoryTrait for CustomerRepository {
 
         let customer: CustomerDAO = sqlx::query_as(
             r#"
-                SELECT id, username FROM
+                SELECT id, username, maxuploadsizebytes FROM
                     customer
                 WHERE
                     username = $1
@@ -67,7 +83,7 @@ impl CustomerRepositoryTrait for CustomerRepository {
     async fn get_customer_by_username(&self, username: &str) -> Result<Vec<Customer>> {
         let customer_list: Vec<CustomerDAO> = sqlx::query_as(
             r#"
-                SELECT id, username FROM
+                SELECT id, username, maxuploadsizebytes FROM
                     customer
                 WHERE
                     username = $1
@@ -83,7 +99,7 @@ impl CustomerRepositoryTrait for CustomerRepository {
     async fn get_customer_by_id(&self, id: &Uuid) -> Result<Vec<Customer>> {
         let customer_list: Vec<CustomerDAO> = sqlx::query_as(
             r#"
-                SELECT id, username FROM
+                SELECT id, username, maxuploadsizebytes FROM
                     customer
                 WHERE
                     id = $1
@@ -96,26 +112,41 @@ impl CustomerRepositoryTrait for CustomerRepository {
         Ok(customer_list.into_iter().map(|dao| dao.into()).collect())
     }
 
-    async fn get_customer_by_credential(
+    async fn get_customer_credential_by_username(
         &self,
         username: &str,
-        password: &str,
-    ) -> Result<Vec<Customer>> {
-        let customer_list: Vec<CustomerDAO> = sqlx::query_as(
+    ) -> Result<Vec<(Customer, String)>> {
+        let credential_list: Vec<CustomerCredentialDAO> = sqlx::query_as(
             r#"
-                SELECT id, username FROM
+                SELECT id, username, password FROM
                     customer
                 WHERE
                     username = $1
-                    AND
-                    password = $2
             "#,
         )
         .bind(username)
-        .bind(password)
         .fetch_all(&self.db_conn)
         .await?;
 
-        Ok(customer_list.into_iter().map(|dao| dao.into()).collect())
+        Ok(credential_list.into_iter().map(|dao| dao.into()).collect())
+    }
+
+    async fn update_customer_password(&self, id: &Uuid, password: &str) -> Result<()> {
+        sqlx::query(
+            r#"
+                UPDATE
+                    customer
+                SET
+                    password = $2
+                WHERE
+                    id = $1
+            "#,
+        )
+        .bind(id)
+        .bind(password)
+        .execute(&self.db_conn)
+        .await?;
+
+        Ok(())
     }
 }
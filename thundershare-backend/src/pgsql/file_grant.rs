@@ -0,0 +1,208 @@
+use std::sync::Arc;
+
+use anyhow::{anyhow, Result};
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use sqlx::prelude::FromRow;
+use tokio::sync::RwLock;
+use uuid::Uuid;
+
+use crate::domain::{entity::file_grant::{FileGrant, FilePermission, SharedFile}, repository::file_grant::FileGrantRepositoryTrait};
+
+use super::{file_meta::FileMetaDAO, DbPool};
+
+fn file_permission(name: &str) -> Result<FilePermission> {
+    FilePermission::from_name(name).ok_or_else(|| anyhow!("unknown file permission {}", name))
+}
+
+#[derive(Debug, FromRow, Clone)]
+struct FileGrantDAO {
+    file_id: Uuid,
+    customer_id: Uuid,
+    username: String,
+    permission: String,
+    grantedat: DateTime<Utc>,
+}
+
+impl TryFrom<FileGrantDAO> for FileGrant {
+    type Error = anyhow::Error;
+
+    fn try_from(dao: FileGrantDAO) -> Result<FileGrant> {
+        Ok(FileGrant::new_full(
+            &dao.file_id,
+            &dao.customer_id,
+            &dao.username,
+            file_permission(&dao.permission)?,
+            &dao.grantedat,
+        ))
+    }
+}
+
+#[derive(Debug, FromRow, Clone)]
+struct SharedFileDAO {
+    #[sqlx(flatten)]
+    file_meta: FileMetaDAO,
+    ownerusername: String,
+    permission: String,
+    grantedat: DateTime<Utc>,
+}
+
+impl TryFrom<SharedFileDAO> for SharedFile {
+    type Error = anyhow::Error;
+
+    fn try_from(dao: SharedFileDAO) -> Result<SharedFile> {
+        Ok(SharedFile::new(
+            dao.file_meta.into(),
+            &dao.ownerusername,
+            file_permission(&dao.permission)?,
+            &dao.grantedat,
+        ))
+    }
+}
+
+#[derive(Clone)]
+pub struct FileGrantRepository {
+    db_conn: DbPool,
+}
+
+impl FileGrantRepository {
+    pub fn new(db_conn: DbPool) -> Arc<RwLock<dyn FileGrantRepositoryTrait>> {
+        Arc::new(RwLock::new(FileGrantRepository { db_conn }))
+    }
+}
+
+#[async_trait]
+impl FileGrantRepositoryTrait for FileGrantRepository {
+    async fn upsert(&self, file_id: &Uuid, customer_id: &Uuid, permission: FilePermission, granted_at: &DateTime<Utc>) -> Result<FileGrant> {
+        let file_grant: FileGrantDAO = sqlx::query_as(
+            r#"
+                WITH granted AS (
+                    INSERT INTO
+                        filegrant (file_id, customer_id, permission, grantedat)
+                    VALUES
+                        ($1, $2, $3, $4)
+                    ON CONFLICT (file_id, customer_id) DO UPDATE SET
+                        permission = EXCLUDED.permission,
+                        grantedat = EXCLUDED.grantedat
+                    RETURNING file_id, customer_id, permission, grantedat
+                )
+                SELECT granted.file_id, granted.customer_id, customer.username, granted.permission, granted.grantedat FROM
+                    granted
+                JOIN
+                    customer ON customer.id = granted.customer_id
+            "#,
+        )
+        .bind(file_id)
+        .bind(customer_id)
+        .bind(permission.get_name())
+        .bind(granted_at)
+        .fetch_one(&self.db_conn)
+        .await?;
+
+        file_grant.try_into()
+    }
+
+    async fn get(&self, file_id: &Uuid, customer_id: &Uuid) -> Result<Vec<FileGrant>> {
+        let file_grant_list = sqlx::query_as(
+            r#"
+                SELECT filegrant.file_id, filegrant.customer_id, customer.username, filegrant.permission, filegrant.grantedat FROM
+                    filegrant
+                JOIN
+                    customer ON customer.id = filegrant.customer_id
+                WHERE
+                    filegrant.file_id = $1
+                    AND
+                    filegrant.customer_id = $2
+            "#,
+        )
+        .bind(file_id)
+        .bind(customer_id)
+        .fetch_all(&self.db_conn)
+        .await?
+        .into_iter()
+        .map(|dao: FileGrantDAO| dao.try_into())
+        .collect::<Result<Vec<FileGrant>>>()?;
+
+        Ok(file_grant_list)
+    }
+
+    async fn list_by_file_id(&self, file_id: &Uuid) -> Result<Vec<FileGrant>> {
+        let file_grant_list = sqlx::query_as(
+            r#"
+                SELECT filegrant.file_id, filegrant.customer_id, customer.username, filegrant.permission, filegrant.grantedat FROM
+                    filegrant
+                JOIN
+                    customer ON customer.id = filegrant.customer_id
+                WHERE
+                    filegrant.file_id = $1
+                ORDER BY
+                    customer.username
+            "#,
+        )
+        .bind(file_id)
+        .fetch_all(&self.db_conn)
+        .await?
+        .into_iter()
+        .map(|dao: FileGrantDAO| dao.try_into())
+        .collect::<Result<Vec<FileGrant>>>()?;
+
+        Ok(file_grant_list)
+    }
+
+    async fn list_shared_with_customer_id(&self, customer_id: &Uuid) -> Result<Vec<SharedFile>> {
+        let shared_file_list = sqlx::query_as(
+            r#"
+                SELECT filemeta.id, filemeta.customer_id, filemeta.url, filemeta.name, filemeta.size, filemeta.mimetype, filemeta.sha256, filemeta.uploadedat, filemeta.folder_id, filemeta.deletedat, filemeta.filerequest_id,
+                    customer.username AS ownerusername, filegrant.permission, filegrant.grantedat FROM
+                    filegrant
+                JOIN
+                    filemeta ON filemeta.id = filegrant.file_id
+                JOIN
+                    customer ON customer.id = filemeta.customer_id
+                WHERE
+                    filegrant.customer_id = $1
+                    AND
+                    filemeta.deletedat IS NULL
+                ORDER BY
+                    filegrant.grantedat DESC, filemeta.id DESC
+            "#,
+        )
+        .bind(customer_id)
+        .fetch_all(&self.db_conn)
+        .await?
+        .into_iter()
+        .map(|dao: SharedFileDAO| dao.try_into())
+        .collect::<Result<Vec<SharedFile>>>()?;
+
+        Ok(shared_file_list)
+    }
+
+    async fn revoke(&self, file_id: &Uuid, customer_id: &Uuid) -> Result<Vec<FileGrant>> {
+        let file_grant_list = sqlx::query_as(
+            r#"
+                WITH revoked AS (
+                    DELETE FROM
+                        filegrant
+                    WHERE
+                        file_id = $1
+                        AND
+                        customer_id = $2
+                    RETURNING file_id, customer_id, permission, grantedat
+                )
+                SELECT revoked.file_id, revoked.customer_id, customer.username, revoked.permission, revoked.grantedat FROM
+                    revoked
+                JOIN
+                    customer ON customer.id = revoked.customer_id
+            "#,
+        )
+        .bind(file_id)
+        .bind(customer_id)
+        .fetch_all(&self.db_conn)
+        .await?
+        .into_iter()
+        .map(|dao: FileGrantDAO| dao.try_into())
+        .collect::<Result<Vec<FileGrant>>>()?;
+
+        Ok(file_grant_list)
+    }
+}
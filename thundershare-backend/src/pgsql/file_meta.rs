@@ -3,26 +3,50 @@ use std::sync::Arc;
 
 use anyhow::Result;
 use async_trait::async_trait;
-use sqlx::prelude::FromRow;
+use chrono::{DateTime, Utc};
+use sqlx::{prelude::FromRow, Postgres, QueryBuilder};
 use tokio::sync::RwLock;
 use uuid::Uuid;
-use crate::domain::{entity::file_meta::FileMeta, repository::file_meta::FileMetaRepositoryTrait};
+use crate::domain::{entity::{file_list::{FileMetaCursorValue, FileMetaListQuery, FileMetaSortKey, SortOrder}, file_meta::{FileContent, FileMeta}}, repository::file_meta::FileMetaRepositoryTrait};
 
 use super::DbPool;
 
+// NOTE: shared with the repositories that read files along with rows of their own
 #[derive(Debug, FromRow, Clone)]
-struct FileMetaDAO {
+pub(super) struct FileMetaDAO {
     id: Uuid,
     customer_id: Uuid,
     url: String,
+    name: String,
+    size: i64,
+    mimetype: String,
+    sha256: String,
+    uploadedat: DateTime<Utc>,
+    folder_id: Option<Uuid>,
+    deletedat: Option<DateTime<Utc>>,
+    filerequest_id: Option<Uuid>,
 }
 
 impl From<FileMetaDAO> for FileMeta {
     fn from(dao: FileMetaDAO) -> FileMeta {
-        FileMeta::new_full(&dao.id, &dao.customer_id, &dao.url)
+        let content = FileContent::new(&dao.name, dao.size, &dao.mimetype, &dao.sha256);
+        let mut file_meta = FileMeta::new_with_content(&dao.id, &dao.customer_id, &dao.url, &content, &dao.uploadedat);
+        file_meta.set_folder_id(dao.folder_id);
+        file_meta.set_deleted_at(dao.deletedat);
+        file_meta.set_file_request_id(dao.filerequest_id);
+        file_meta
     }
 }
 
+// NOTE: the prefix is matched literally, LIKE wildcards sent by a client are escaped
+fn like_prefix(prefix: &str) -> String {
+    let escaped = prefix
+        .replace('\\', "\\\\")
+        .replace('%', "\\%")
+        .replace('_', "\\_");
+    format!("{}%", escaped)
+}
+
 #[derive(Clone)]
 pub struct FileMetaRepository {
     db_conn: DbPool,
@@ -36,24 +60,31 @@ impl FileMetaRepository {
 
 #[async_trait]
 impl FileMetaRepositoryTrait for FileMetaRepository {
-    async fn create(&self, customer_id: &Uuid, url: &str) -> Result<FileMeta> {
+    async fn create(&self, customer_id: &Uuid, url: &str, content: &FileContent, uploaded_at: &DateTime<Utc>, folder_id: &Option<Uuid>, file_request_id: &Option<Uuid>) -> Result<FileMeta> {
         let (id, ): (Uuid,) = sqlx::query_as(
             r#"
                 INSERT INTO
-                    filemeta (customer_id, url)
+                    filemeta (customer_id, url, name, size, mimetype, sha256, uploadedat, folder_id, filerequest_id)
                 VALUES
-                    ($1, $2)
+                    ($1, $2, $3, $4, $5, $6, $7, $8, $9)
                 RETURNING id;
             "#,
         )
         .bind(customer_id)
         .bind(url)
+        .bind(content.get_name())
+        .bind(content.get_size())
+        .bind(content.get_mime_type())
+        .bind(content.get_sha256())
+        .bind(uploaded_at)
+        .bind(folder_id)
+        .bind(file_request_id)
         .fetch_one(&self.db_conn)
         .await?;
 
         let filemeta: FileMetaDAO = sqlx::query_as(
             r#"
-                SELECT id, customer_id, url FROM
+                SELECT id, customer_id, url, name, size, mimetype, sha256, uploadedat, folder_id, deletedat, filerequest_id FROM
                     filemeta
                 WHERE
                     id = $1
@@ -69,7 +100,7 @@ impl FileMetaRepositoryTrait for FileMetaRepository {
     async fn get_file_meta_by_id(&self, id: &Uuid) -> Result<Vec<FileMeta>> {
         let filemeta: Vec<FileMeta> = sqlx::query_as(
             r#"
-                SELECT id, customer_id, url FROM
+                SELECT id, customer_id, url, name, size, mimetype, sha256, uploadedat, folder_id, deletedat, filerequest_id FROM
                     filemeta
                 WHERE
                     id = $1
@@ -85,13 +116,81 @@ impl FileMetaRepositoryTrait for FileMetaRepository {
         Ok(filemeta)
     }
 
-    async fn list_file_meta_by_customer_id(&self, customer_id: &Uuid) -> Result<Vec<FileMeta>> {
+    async fn list_file_meta_by_customer_id(&self, customer_id: &Uuid, query: &FileMetaListQuery, limit: i64) -> Result<Vec<FileMeta>> {
+        let mut builder: QueryBuilder<Postgres> = QueryBuilder::new(
+            r#"
+                SELECT id, customer_id, url, name, size, mimetype, sha256, uploadedat, folder_id, deletedat, filerequest_id FROM
+                    filemeta
+                WHERE
+                    deletedat IS NULL
+                    AND
+                    customer_id = "#,
+        );
+        builder.push_bind(customer_id);
+
+        let filter = query.get_filter();
+        if let Some(mime_type_prefix) = filter.get_mime_type_prefix() {
+            builder.push(" AND mimetype LIKE ").push_bind(like_prefix(&mime_type_prefix));
+        }
+        if let Some(min_size) = filter.get_min_size() {
+            builder.push(" AND size >= ").push_bind(min_size);
+        }
+        if let Some(max_size) = filter.get_max_size() {
+            builder.push(" AND size <= ").push_bind(max_size);
+        }
+        if let Some(uploaded_after) = filter.get_uploaded_after() {
+            builder.push(" AND uploadedat >= ").push_bind(uploaded_after);
+        }
+        if let Some(uploaded_before) = filter.get_uploaded_before() {
+            builder.push(" AND uploadedat < ").push_bind(uploaded_before);
+        }
+
+        let column = match query.get_sort_key() {
+            FileMetaSortKey::Name => "name",
+            FileMetaSortKey::Size => "size",
+            FileMetaSortKey::UploadedAt => "uploadedat",
+        };
+        let (comparison, direction) = match query.get_order() {
+            SortOrder::Asc => (">", "ASC"),
+            SortOrder::Desc => ("<", "DESC"),
+        };
+
+        // NOTE: id breaks ties between equal sort values, so a page never repeats or skips a file
+        if let Some(cursor) = query.get_cursor() {
+            builder.push(format!(" AND ({}, id) {} (", column, comparison));
+            match cursor.get_value() {
+                FileMetaCursorValue::Name(name) => builder.push_bind(name.clone()),
+                FileMetaCursorValue::Size(size) => builder.push_bind(*size),
+                FileMetaCursorValue::UploadedAt(uploaded_at) => builder.push_bind(*uploaded_at),
+            };
+            builder.push(", ").push_bind(cursor.get_id()).push(")");
+        }
+
+        builder.push(format!(" ORDER BY {} {}, id {} LIMIT ", column, direction, direction));
+        builder.push_bind(limit);
+
+        let filemeta: Vec<FileMeta> = builder
+            .build_query_as::<FileMetaDAO>()
+            .fetch_all(&self.db_conn)
+            .await?
+            .into_iter()
+            .map(|dao: FileMetaDAO| dao.into())
+            .collect();
+
+        Ok(filemeta)
+    }
+
+    async fn list_trashed_file_meta_by_customer_id(&self, customer_id: &Uuid) -> Result<Vec<FileMeta>> {
         let filemeta: Vec<FileMeta> = sqlx::query_as(
             r#"
-                SELECT id, customer_id, url FROM
+                SELECT id, customer_id, url, name, size, mimetype, sha256, uploadedat, folder_id, deletedat, filerequest_id FROM
                     filemeta
                 WHERE
                     customer_id = $1
+                    AND
+                    deletedat IS NOT NULL
+                ORDER BY
+                    deletedat DESC
             "#,
         )
         .bind(customer_id)
@@ -103,4 +202,157 @@ impl FileMetaRepositoryTrait for FileMetaRepository {
 
         Ok(filemeta)
     }
+
+    async fn list_trashed_file_meta_before(&self, deleted_before: &DateTime<Utc>, limit: i64) -> Result<Vec<FileMeta>> {
+        let filemeta: Vec<FileMeta> = sqlx::query_as(
+            r#"
+                SELECT id, customer_id, url, name, size, mimetype, sha256, uploadedat, folder_id, deletedat, filerequest_id FROM
+                    filemeta
+                WHERE
+                    deletedat < $1
+                ORDER BY
+                    deletedat ASC
+                LIMIT $2
+            "#,
+        )
+        .bind(deleted_before)
+        .bind(limit)
+        .fetch_all(&self.db_conn)
+        .await?
+        .into_iter()
+        .map(|dao: FileMetaDAO| dao.into())
+        .collect();
+
+        Ok(filemeta)
+    }
+
+    async fn trash(&self, id: &Uuid, deleted_at: &DateTime<Utc>) -> Result<()> {
+        sqlx::query(
+            r#"
+                UPDATE
+                    filemeta
+                SET
+                    deletedat = $2
+                WHERE
+                    id = $1
+                    AND
+                    deletedat IS NULL
+            "#,
+        )
+        .bind(id)
+        .bind(deleted_at)
+        .execute(&self.db_conn)
+        .await?;
+
+        Ok(())
+    }
+
+    async fn restore(&self, id: &Uuid) -> Result<()> {
+        sqlx::query(
+            r#"
+                UPDATE
+                    filemeta
+                SET
+                    deletedat = NULL
+                WHERE
+                    id = $1
+            "#,
+        )
+        .bind(id)
+        .execute(&self.db_conn)
+        .await?;
+
+        Ok(())
+    }
+
+    async fn list_file_meta_by_folder_id(&self, customer_id: &Uuid, folder_id: &Option<Uuid>) -> Result<Vec<FileMeta>> {
+        let filemeta: Vec<FileMeta> = sqlx::query_as(
+            r#"
+                SELECT id, customer_id, url, name, size, mimetype, sha256, uploadedat, folder_id, deletedat, filerequest_id FROM
+                    filemeta
+                WHERE
+                    customer_id = $1
+                    AND
+                    folder_id IS NOT DISTINCT FROM $2
+                    AND
+                    deletedat IS NULL
+            "#,
+        )
+        .bind(customer_id)
+        .bind(folder_id)
+        .fetch_all(&self.db_conn)
+        .await?
+        .into_iter()
+        .map(|dao: FileMetaDAO| dao.into())
+        .collect();
+
+        Ok(filemeta)
+    }
+
+    async fn move_to_folder(&self, id: &Uuid, folder_id: &Option<Uuid>) -> Result<()> {
+        sqlx::query(
+            r#"
+                UPDATE
+                    filemeta
+                SET
+                    folder_id = $2
+                WHERE
+                    id = $1
+            "#,
+        )
+        .bind(id)
+        .bind(folder_id)
+        .execute(&self.db_conn)
+        .await?;
+
+        Ok(())
+    }
+
+    async fn delete(&self, id: &Uuid, deleted_at: &DateTime<Utc>) -> Result<()> {
+        let mut tx = self.db_conn.begin().await?;
+
+        sqlx::query(
+            r#"
+                DELETE FROM
+                    filesharingmeta
+                WHERE
+                    file_id = $1
+            "#,
+        )
+        .bind(id)
+        .execute(&mut *tx)
+        .await?;
+
+        let url_list: Vec<(String,)> = sqlx::query_as(
+            r#"
+                DELETE FROM
+                    filemeta
+                WHERE
+                    id = $1
+                RETURNING url;
+            "#,
+        )
+        .bind(id)
+        .fetch_all(&mut *tx)
+        .await?;
+
+        for (url,) in url_list {
+            sqlx::query(
+                r#"
+                    INSERT INTO
+                        pendingdelete (storagekey, createdat)
+                    VALUES
+                        ($1, $2)
+                    ON CONFLICT (storagekey) DO NOTHING;
+                "#,
+            )
+            .bind(url)
+            .bind(deleted_at)
+            .execute(&mut *tx)
+            .await?;
+        }
+
+        tx.commit().await?;
+        Ok(())
+    }
 }
@@ -0,0 +1,195 @@
+use std::sync::Arc;
+
+use anyhow::Result;
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use sqlx::prelude::FromRow;
+use tokio::sync::RwLock;
+use uuid::Uuid;
+
+use crate::domain::{entity::file_request::FileRequest, repository::file_request::{FileRequestCreateParams, FileRequestRepositoryTrait}};
+
+use super::DbPool;
+
+#[derive(Debug, FromRow, Clone)]
+struct FileRequestDAO {
+    id: Uuid,
+    customer_id: Uuid,
+    folder_id: Option<Uuid>,
+    code: String,
+    expireat: DateTime<Utc>,
+    password: Option<String>,
+    maxfiles: Option<i32>,
+    maxsizebytes: i64,
+    uploads: i32,
+}
+
+impl From<FileRequestDAO> for FileRequest {
+    fn from(dao: FileRequestDAO) -> FileRequest {
+        let mut file_request = FileRequest::new_full(
+            &dao.id,
+            &dao.customer_id,
+            &dao.folder_id,
+            &dao.code,
+            &dao.expireat,
+            &dao.password,
+        );
+        file_request.set_limits(&dao.maxfiles, dao.maxsizebytes, dao.uploads);
+        file_request
+    }
+}
+
+#[derive(Clone)]
+pub struct FileRequestRepository {
+    db_conn: DbPool,
+}
+
+impl FileRequestRepository {
+    pub fn new(db_conn: DbPool) -> Arc<RwLock<dyn FileRequestRepositoryTrait>> {
+        Arc::new(RwLock::new(FileRequestRepository { db_conn }))
+    }
+}
+
+#[async_trait]
+impl FileRequestRepositoryTrait for FileRequestRepository {
+    // NOTE: the folder is checked by the insert itself, nothing is written for a folder of another customer
+    async fn create(&self, params: &FileRequestCreateParams) -> Result<Vec<FileRequest>> {
+        let file_request_list: Vec<FileRequest> = sqlx::query_as(
+            r#"
+                INSERT INTO
+                    filerequest
+                (customer_id, folder_id, code, expireat, password, maxfiles, maxsizebytes)
+                SELECT
+                    $1, $2, $3, $4, $5, $6, $7
+                WHERE
+                    $2::uuid IS NULL
+                    OR
+                    EXISTS (SELECT 1 FROM folder WHERE folder.id = $2 AND folder.customer_id = $1)
+                RETURNING id, customer_id, folder_id, code, expireat, password, maxfiles, maxsizebytes, uploads;
+            "#,
+        )
+        .bind(params.customer_id)
+        .bind(params.folder_id)
+        .bind(&params.code)
+        .bind(params.expireat)
+        .bind(&params.password)
+        .bind(params.max_files)
+        .bind(params.max_size_bytes)
+        .fetch_all(&self.db_conn)
+        .await?
+        .into_iter()
+        .map(|dao: FileRequestDAO| dao.into())
+        .collect();
+
+        Ok(file_request_list)
+    }
+
+    async fn get_by_code(&self, code: &str) -> Result<Vec<FileRequest>> {
+        let file_request_list: Vec<FileRequest> = sqlx::query_as(
+            r#"
+                SELECT id, customer_id, folder_id, code, expireat, password, maxfiles, maxsizebytes, uploads FROM
+                    filerequest
+                WHERE
+                    code = $1
+            "#,
+        )
+        .bind(code)
+        .fetch_all(&self.db_conn)
+        .await?
+        .into_iter()
+        .map(|dao: FileRequestDAO| dao.into())
+        .collect();
+
+        Ok(file_request_list)
+    }
+
+    async fn list_by_customer_id(&self, customer_id: &Uuid) -> Result<Vec<FileRequest>> {
+        let file_request_list: Vec<FileRequest> = sqlx::query_as(
+            r#"
+                SELECT id, customer_id, folder_id, code, expireat, password, maxfiles, maxsizebytes, uploads FROM
+                    filerequest
+                WHERE
+                    customer_id = $1
+                ORDER BY
+                    createdat DESC, id DESC
+            "#,
+        )
+        .bind(customer_id)
+        .fetch_all(&self.db_conn)
+        .await?
+        .into_iter()
+        .map(|dao: FileRequestDAO| dao.into())
+        .collect();
+
+        Ok(file_request_list)
+    }
+
+    // NOTE: the row lock taken by the update serializes concurrent uploads, the condition is
+    //       checked again once the lock is granted so the limit is never overrun
+    async fn reserve_upload(&self, id: &Uuid) -> Result<Vec<FileRequest>> {
+        let file_request_list: Vec<FileRequest> = sqlx::query_as(
+            r#"
+                UPDATE
+                    filerequest
+                SET
+                    uploads = uploads + 1
+                WHERE
+                    id = $1
+                    AND
+                    (maxfiles IS NULL OR uploads < maxfiles)
+                RETURNING id, customer_id, folder_id, code, expireat, password, maxfiles, maxsizebytes, uploads;
+            "#,
+        )
+        .bind(id)
+        .fetch_all(&self.db_conn)
+        .await?
+        .into_iter()
+        .map(|dao: FileRequestDAO| dao.into())
+        .collect();
+
+        Ok(file_request_list)
+    }
+
+    async fn release_upload(&self, id: &Uuid) -> Result<()> {
+        sqlx::query(
+            r#"
+                UPDATE
+                    filerequest
+                SET
+                    uploads = uploads - 1
+                WHERE
+                    id = $1
+                    AND
+                    uploads > 0
+            "#,
+        )
+        .bind(id)
+        .execute(&self.db_conn)
+        .await?;
+
+        Ok(())
+    }
+
+    async fn revoke(&self, id: &Uuid, customer_id: &Uuid) -> Result<Vec<FileRequest>> {
+        let file_request_list: Vec<FileRequest> = sqlx::query_as(
+            r#"
+                DELETE FROM
+                    filerequest
+                WHERE
+                    id = $1
+                    AND
+                    customer_id = $2
+                RETURNING id, customer_id, folder_id, code, expireat, password, maxfiles, maxsizebytes, uploads;
+            "#,
+        )
+        .bind(id)
+        .bind(customer_id)
+        .fetch_all(&self.db_conn)
+        .await?
+        .into_iter()
+        .map(|dao: FileRequestDAO| dao.into())
+        .collect();
+
+        Ok(file_request_list)
+    }
+}
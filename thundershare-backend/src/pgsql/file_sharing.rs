@@ -15,20 +15,24 @@ use super::DbPool;
 struct FileSharingMetaDAO {
     id: Uuid,
     file_id: Uuid,
-    link: String,
+    code: String,
     expireat: DateTime<Utc>,
     password: Option<String>,
+    maxdownloads: Option<i32>,
+    downloads: i32,
 }
 
 impl From<FileSharingMetaDAO> for FileSharingMeta {
     fn from(dao: FileSharingMetaDAO) -> FileSharingMeta {
-        FileSharingMeta::new_full(
+        let mut file_sharing_meta = FileSharingMeta::new_full(
             &dao.id,
             &dao.file_id,
-            &dao.link,
+            &dao.code,
             &dao.expireat,
             &dao.password,
-        )
+        );
+        file_sharing_meta.set_downloads(&dao.maxdownloads, dao.downloads);
+        file_sharing_meta
     }
 }
 
@@ -45,27 +49,28 @@ impl FileSharingRepository {
 
 #[async_trait]
 impl FileSharingRepositoryTrait for FileSharingRepository {
-    async fn create(&self, file_id: &Uuid, link: &str, expireat: &DateTime<Utc>, password: &Option<String>) -> Result<FileSharingMeta> {
+    async fn create(&self, file_id: &Uuid, code: &str, expireat: &DateTime<Utc>, password: &Option<String>, max_downloads: &Option<i32>) -> Result<FileSharingMeta> {
         let (id, ): (Uuid,) = sqlx::query_as(
             r#"
                 INSERT INTO
                     filesharingmeta
-                (file_id, link, expireat, password)
+                (file_id, code, expireat, password, maxdownloads)
                 VALUES
-                    ($1, $2, $3, $4)
+                    ($1, $2, $3, $4, $5)
                 RETURNING id;
             "#,
         )
         .bind(file_id)
-        .bind(link)
+        .bind(code)
         .bind(expireat)
         .bind(password)
+        .bind(max_downloads)
         .fetch_one(&self.db_conn)
         .await?;
 
         let filemeta: FileSharingMetaDAO = sqlx::query_as(
             r#"
-                SELECT id, file_id, link, expireat, password FROM
+                SELECT id, file_id, code, expireat, password, maxdownloads, downloads FROM
                     filesharingmeta
                 WHERE
                     id = $1
@@ -82,7 +87,7 @@ impl FileSharingRepositoryTrait for FileSharingRepository {
     async fn get_by_id(&self, id: &Uuid) -> Result<Vec<FileSharingMeta>> {
         let filemeta_list: Vec<FileSharingMeta> = sqlx::query_as(
             r#"
-                SELECT id, file_id, link, expireat, password FROM
+                SELECT id, file_id, code, expireat, password, maxdownloads, downloads FROM
                     filesharingmeta
                 WHERE
                     id = $1
@@ -97,4 +102,178 @@ impl FileSharingRepositoryTrait for FileSharingRepository {
 
         Ok(filemeta_list)
     }
+
+    async fn get_by_code(&self, code: &str) -> Result<Vec<FileSharingMeta>> {
+        let filemeta_list: Vec<FileSharingMeta> = sqlx::query_as(
+            r#"
+                SELECT id, file_id, code, expireat, password, maxdownloads, downloads FROM
+                    filesharingmeta
+                WHERE
+                    code = $1
+            "#,
+        )
+        .bind(code)
+        .fetch_all(&self.db_conn)
+        .await?
+        .into_iter()
+        .map(|dao: FileSharingMetaDAO| dao.into())
+        .collect();
+
+        Ok(filemeta_list)
+    }
+
+    async fn get_by_id_and_customer_id(&self, id: &Uuid, customer_id: &Uuid) -> Result<Vec<FileSharingMeta>> {
+        let filemeta_list: Vec<FileSharingMeta> = sqlx::query_as(
+            r#"
+                SELECT filesharingmeta.id, filesharingmeta.file_id, filesharingmeta.code, filesharingmeta.expireat, filesharingmeta.password, filesharingmeta.maxdownloads, filesharingmeta.downloads FROM
+                    filesharingmeta
+                JOIN
+                    filemeta ON filemeta.id = filesharingmeta.file_id
+                WHERE
+                    filesharingmeta.id = $1
+                    AND
+                    filemeta.customer_id = $2
+            "#,
+        )
+        .bind(id)
+        .bind(customer_id)
+        .fetch_all(&self.db_conn)
+        .await?
+        .into_iter()
+        .map(|dao: FileSharingMetaDAO| dao.into())
+        .collect();
+
+        Ok(filemeta_list)
+    }
+
+    async fn list_by_customer_id(&self, customer_id: &Uuid, file_id: &Option<Uuid>) -> Result<Vec<FileSharingMeta>> {
+        let filemeta_list: Vec<FileSharingMeta> = sqlx::query_as(
+            r#"
+                SELECT filesharingmeta.id, filesharingmeta.file_id, filesharingmeta.code, filesharingmeta.expireat, filesharingmeta.password, filesharingmeta.maxdownloads, filesharingmeta.downloads FROM
+                    filesharingmeta
+                JOIN
+                    filemeta ON filemeta.id = filesharingmeta.file_id
+                WHERE
+                    filemeta.customer_id = $1
+                    AND
+                    ($2::uuid IS NULL OR filesharingmeta.file_id = $2)
+                ORDER BY
+                    filesharingmeta.expireat DESC
+            "#,
+        )
+        .bind(customer_id)
+        .bind(file_id)
+        .fetch_all(&self.db_conn)
+        .await?
+        .into_iter()
+        .map(|dao: FileSharingMetaDAO| dao.into())
+        .collect();
+
+        Ok(filemeta_list)
+    }
+
+    async fn update(&self, id: &Uuid, customer_id: &Uuid, expireat: &Option<DateTime<Utc>>, password: &Option<Option<String>>) -> Result<Vec<FileSharingMeta>> {
+        let filemeta_list: Vec<FileSharingMeta> = sqlx::query_as(
+            r#"
+                UPDATE
+                    filesharingmeta
+                SET
+                    expireat = COALESCE($3, filesharingmeta.expireat),
+                    password = CASE WHEN $4 THEN $5 ELSE filesharingmeta.password END
+                FROM
+                    filemeta
+                WHERE
+                    filesharingmeta.id = $1
+                    AND
+                    filemeta.id = filesharingmeta.file_id
+                    AND
+                    filemeta.customer_id = $2
+                RETURNING filesharingmeta.id, filesharingmeta.file_id, filesharingmeta.code, filesharingmeta.expireat, filesharingmeta.password, filesharingmeta.maxdownloads, filesharingmeta.downloads;
+            "#,
+        )
+        .bind(id)
+        .bind(customer_id)
+        .bind(expireat)
+        .bind(password.is_some())
+        .bind(password.clone().flatten())
+        .fetch_all(&self.db_conn)
+        .await?
+        .into_iter()
+        .map(|dao: FileSharingMetaDAO| dao.into())
+        .collect();
+
+        Ok(filemeta_list)
+    }
+
+    async fn update_password(&self, id: &Uuid, password: &str) -> Result<()> {
+        sqlx::query(
+            r#"
+                UPDATE
+                    filesharingmeta
+                SET
+                    password = $2
+                WHERE
+                    id = $1;
+            "#,
+        )
+        .bind(id)
+        .bind(password)
+        .execute(&self.db_conn)
+        .await?;
+
+        Ok(())
+    }
+
+    // NOTE: the row lock taken by the update serializes concurrent downloads, the condition is
+    //       checked again once the lock is granted so the limit is never overrun
+    async fn consume_download(&self, id: &Uuid) -> Result<Vec<FileSharingMeta>> {
+        let filemeta_list: Vec<FileSharingMeta> = sqlx::query_as(
+            r#"
+                UPDATE
+                    filesharingmeta
+                SET
+                    downloads = downloads + 1
+                WHERE
+                    id = $1
+                    AND
+                    (maxdownloads IS NULL OR downloads < maxdownloads)
+                RETURNING id, file_id, code, expireat, password, maxdownloads, downloads;
+            "#,
+        )
+        .bind(id)
+        .fetch_all(&self.db_conn)
+        .await?
+        .into_iter()
+        .map(|dao: FileSharingMetaDAO| dao.into())
+        .collect();
+
+        Ok(filemeta_list)
+    }
+
+    async fn revoke(&self, id: &Uuid, customer_id: &Uuid) -> Result<Vec<FileSharingMeta>> {
+        let filemeta_list: Vec<FileSharingMeta> = sqlx::query_as(
+            r#"
+                DELETE FROM
+                    filesharingmeta
+                USING
+                    filemeta
+                WHERE
+                    filesharingmeta.id = $1
+                    AND
+                    filemeta.id = filesharingmeta.file_id
+                    AND
+                    filemeta.customer_id = $2
+                RETURNING filesharingmeta.id, filesharingmeta.file_id, filesharingmeta.code, filesharingmeta.expireat, filesharingmeta.password, filesharingmeta.maxdownloads, filesharingmeta.downloads;
+            "#,
+        )
+        .bind(id)
+        .bind(customer_id)
+        .fetch_all(&self.db_conn)
+        .await?
+        .into_iter()
+        .map(|dao: FileSharingMetaDAO| dao.into())
+        .collect();
+
+        Ok(filemeta_list)
+    }
 }
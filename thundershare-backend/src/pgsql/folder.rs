@@ -0,0 +1,252 @@
+use std::sync::Arc;
+
+use anyhow::{anyhow, Result};
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use sqlx::prelude::FromRow;
+use tokio::sync::RwLock;
+use uuid::Uuid;
+
+use crate::domain::{entity::folder::Folder, error::folder::FolderError, repository::folder::FolderRepositoryTrait};
+
+use super::DbPool;
+
+const FOLDER_NAME_INDEX: &str = "folder_parent_name_idx";
+
+// NOTE: a name taken by a concurrent write between the check of the service and this one is
+//       only noticed by the unique index
+fn name_conflict(err: sqlx::Error) -> anyhow::Error {
+    match &err {
+        sqlx::Error::Database(db_err) if db_err.is_unique_violation() && db_err.constraint() == Some(FOLDER_NAME_INDEX) => {
+            anyhow!(FolderError::FolderNameConflict)
+        }
+        _ => err.into(),
+    }
+}
+
+#[derive(Debug, FromRow, Clone)]
+struct FolderDAO {
+    id: Uuid,
+    customer_id: Uuid,
+    parent_id: Option<Uuid>,
+    name: String,
+    createdat: DateTime<Utc>,
+}
+
+impl From<FolderDAO> for Folder {
+    fn from(dao: FolderDAO) -> Folder {
+        Folder::new_full(&dao.id, &dao.customer_id, &dao.parent_id, &dao.name, &dao.createdat)
+    }
+}
+
+#[derive(Clone)]
+pub struct FolderRepository {
+    db_conn: DbPool,
+}
+
+impl FolderRepository {
+    pub fn new(db_conn: DbPool) -> Arc<RwLock<dyn FolderRepositoryTrait>> {
+        Arc::new(RwLock::new(FolderRepository { db_conn }))
+    }
+}
+
+#[async_trait]
+impl FolderRepositoryTrait for FolderRepository {
+    async fn create(&self, customer_id: &Uuid, parent_id: &Option<Uuid>, name: &str, created_at: &DateTime<Utc>) -> Result<Folder> {
+        let folder: FolderDAO = sqlx::query_as(
+            r#"
+                INSERT INTO
+                    folder (customer_id, parent_id, name, createdat)
+                VALUES
+                    ($1, $2, $3, $4)
+                RETURNING id, customer_id, parent_id, name, createdat;
+            "#,
+        )
+        .bind(customer_id)
+        .bind(parent_id)
+        .bind(name)
+        .bind(created_at)
+        .fetch_one(&self.db_conn)
+        .await
+        .map_err(name_conflict)?;
+
+        Ok(folder.into())
+    }
+
+    async fn get_by_id(&self, id: &Uuid) -> Result<Vec<Folder>> {
+        let folder_list: Vec<Folder> = sqlx::query_as(
+            r#"
+                SELECT id, customer_id, parent_id, name, createdat FROM
+                    folder
+                WHERE
+                    id = $1
+            "#,
+        )
+        .bind(id)
+        .fetch_all(&self.db_conn)
+        .await?
+        .into_iter()
+        .map(|dao: FolderDAO| dao.into())
+        .collect();
+
+        Ok(folder_list)
+    }
+
+    async fn get_by_name(&self, customer_id: &Uuid, parent_id: &Option<Uuid>, name: &str) -> Result<Vec<Folder>> {
+        let folder_list: Vec<Folder> = sqlx::query_as(
+            r#"
+                SELECT id, customer_id, parent_id, name, createdat FROM
+                    folder
+                WHERE
+                    customer_id = $1
+                    AND
+                    parent_id IS NOT DISTINCT FROM $2
+                    AND
+                    name = $3
+            "#,
+        )
+        .bind(customer_id)
+        .bind(parent_id)
+        .bind(name)
+        .fetch_all(&self.db_conn)
+        .await?
+        .into_iter()
+        .map(|dao: FolderDAO| dao.into())
+        .collect();
+
+        Ok(folder_list)
+    }
+
+    async fn list_by_parent_id(&self, customer_id: &Uuid, parent_id: &Option<Uuid>) -> Result<Vec<Folder>> {
+        let folder_list: Vec<Folder> = sqlx::query_as(
+            r#"
+                SELECT id, customer_id, parent_id, name, createdat FROM
+                    folder
+                WHERE
+                    customer_id = $1
+                    AND
+                    parent_id IS NOT DISTINCT FROM $2
+                ORDER BY
+                    name
+            "#,
+        )
+        .bind(customer_id)
+        .bind(parent_id)
+        .fetch_all(&self.db_conn)
+        .await?
+        .into_iter()
+        .map(|dao: FolderDAO| dao.into())
+        .collect();
+
+        Ok(folder_list)
+    }
+
+    async fn rename(&self, id: &Uuid, name: &str) -> Result<Folder> {
+        let folder: FolderDAO = sqlx::query_as(
+            r#"
+                UPDATE
+                    folder
+                SET
+                    name = $2
+                WHERE
+                    id = $1
+                RETURNING id, customer_id, parent_id, name, createdat;
+            "#,
+        )
+        .bind(id)
+        .bind(name)
+        .fetch_one(&self.db_conn)
+        .await
+        .map_err(name_conflict)?;
+
+        Ok(folder.into())
+    }
+
+    // NOTE: the customer row is locked so that two moves of the same tree are checked one after
+    //       the other, otherwise each could pass the check and together form a cycle.
+    async fn move_to(&self, customer_id: &Uuid, id: &Uuid, parent_id: &Option<Uuid>) -> Result<Vec<Folder>> {
+        let mut tx = self.db_conn.begin().await?;
+
+        sqlx::query(
+            r#"
+                SELECT id FROM
+                    customer
+                WHERE
+                    id = $1
+                FOR UPDATE
+            "#,
+        )
+        .bind(customer_id)
+        .execute(&mut *tx)
+        .await?;
+
+        if let Some(parent_id) = parent_id {
+            let (is_cycle,): (bool,) = sqlx::query_as(
+                r#"
+                    WITH RECURSIVE ancestor AS (
+                        SELECT id, parent_id FROM folder WHERE id = $1
+                        UNION ALL
+                        SELECT folder.id, folder.parent_id FROM folder JOIN ancestor ON folder.id = ancestor.parent_id
+                    )
+                    SELECT EXISTS (SELECT 1 FROM ancestor WHERE id = $2);
+                "#,
+            )
+            .bind(parent_id)
+            .bind(id)
+            .fetch_one(&mut *tx)
+            .await?;
+
+            if is_cycle {
+                return Ok(vec![]);
+            }
+        }
+
+        let folder_list: Vec<Folder> = sqlx::query_as(
+            r#"
+                UPDATE
+                    folder
+                SET
+                    parent_id = $2
+                WHERE
+                    id = $1
+                RETURNING id, customer_id, parent_id, name, createdat;
+            "#,
+        )
+        .bind(id)
+        .bind(parent_id)
+        .fetch_all(&mut *tx)
+        .await
+        .map_err(name_conflict)?
+        .into_iter()
+        .map(|dao: FolderDAO| dao.into())
+        .collect();
+
+        tx.commit().await?;
+        Ok(folder_list)
+    }
+
+    // NOTE: files in the trash do not keep a folder alive, they are restored to the root instead
+    async fn delete(&self, id: &Uuid) -> Result<Vec<Folder>> {
+        let folder_list: Vec<Folder> = sqlx::query_as(
+            r#"
+                DELETE FROM
+                    folder
+                WHERE
+                    id = $1
+                    AND
+                    NOT EXISTS (SELECT 1 FROM folder AS child WHERE child.parent_id = $1)
+                    AND
+                    NOT EXISTS (SELECT 1 FROM filemeta WHERE folder_id = $1 AND deletedat IS NULL)
+                RETURNING id, customer_id, parent_id, name, createdat;
+            "#,
+        )
+        .bind(id)
+        .fetch_all(&self.db_conn)
+        .await?
+        .into_iter()
+        .map(|dao: FolderDAO| dao.into())
+        .collect();
+
+        Ok(folder_list)
+    }
+}
@@ -1,55 +1,70 @@
 pub mod customer;
 pub mod file_meta;
+pub mod file_grant;
 pub mod file_sharing;
+pub mod file_request;
+pub mod folder;
+pub mod pending_delete;
+pub mod refresh_token;
+pub mod sharing_access;
+pub mod sharing_attempt;
 pub mod used_token;
+pub mod upload;
 
 pub type DbPool = sqlx::postgres::PgPool;
-use std::sync::Arc;
 
-use tokio::sync::RwLock;
+use sqlx::postgres::PgPoolOptions;
 use urlencoding::encode;
 
-use crate::domain::repository::{
-    customer::CustomerRepositoryTrait, file_meta::FileMetaRepositoryTrait, file_sharing::FileSharingRepositoryTrait, used_token::UsedTokenRepositoryTrait
-};
+use crate::config::DatabaseConfig;
 
-use self::{customer::CustomerRepository, file_meta::FileMetaRepository, file_sharing::FileSharingRepository, used_token::UsedTokenRepository};
+use crate::domain::repository::ServerRepositories;
 
-pub async fn connection_builder() -> Result<DbPool, sqlx::Error> {
-    let db_user = std::env::var("DB_USER").unwrap();
-    let db_host = std::env::var("DB_HOST").unwrap();
-    let db_name = std::env::var("DB_NAME").unwrap();
-    let db_pass = std::env::var("DB_PASS").unwrap();
+use self::{customer::CustomerRepository, file_meta::FileMetaRepository, file_grant::FileGrantRepository, file_sharing::FileSharingRepository, file_request::FileRequestRepository, folder::FolderRepository, pending_delete::PendingDeleteRepository, refresh_token::RefreshTokenRepository, sharing_access::SharingAccessRepository, sharing_attempt::SharingAttemptRepository, upload::UploadRepository, used_token::{UsedTokenCache, UsedTokenRepository}};
 
-    let encoded_db_pass = encode(&db_pass);
+pub async fn connection_builder(config: &DatabaseConfig) -> Result<DbPool, sqlx::Error> {
+    let encoded_db_user = encode(&config.user);
+    let encoded_db_pass = encode(&config.password);
 
     let connectspec = format!(
-        "postgres://{}:{}@{}/{}",
-        db_user, encoded_db_pass, db_host, db_name
+        "postgres://{}:{}@{}:{}/{}",
+        encoded_db_user, encoded_db_pass, config.host, config.port, config.name
     );
 
-    sqlx::postgres::PgPool::connect(&connectspec).await
-}
-
-pub struct ServerRepositories {
-    pub customer_repository: Arc<RwLock<dyn CustomerRepositoryTrait>>,
-    pub used_token_repository: Arc<RwLock<dyn UsedTokenRepositoryTrait>>,
-    pub file_meta_repository: Arc<RwLock<dyn FileMetaRepositoryTrait>>,
-    pub file_sharing_meta_repository: Arc<RwLock<dyn FileSharingRepositoryTrait>>,
+    PgPoolOptions::new()
+        .max_connections(config.max_connections)
+        .connect(&connectspec)
+        .await
 }
 
 impl ServerRepositories {
-    pub fn new(db_pool: DbPool) -> ServerRepositories {
+    pub fn new(db_pool: DbPool, used_token_cache: UsedTokenCache) -> ServerRepositories {
         let customer_repository = CustomerRepository::new(db_pool.clone());
-        let used_token_repository = UsedTokenRepository::new(db_pool.clone());
+        let used_token_repository = UsedTokenRepository::new(db_pool.clone(), used_token_cache);
+        let refresh_token_repository = RefreshTokenRepository::new(db_pool.clone());
         let file_meta_repository = FileMetaRepository::new(db_pool.clone());
-        let file_sharing_meta_repository = FileSharingRepository::new(db_pool);
+        let file_sharing_meta_repository = FileSharingRepository::new(db_pool.clone());
+        let upload_repository = UploadRepository::new(db_pool.clone());
+        let pending_delete_repository = PendingDeleteRepository::new(db_pool.clone());
+        let folder_repository = FolderRepository::new(db_pool.clone());
+        let sharing_attempt_repository = SharingAttemptRepository::new(db_pool.clone());
+        let sharing_access_repository = SharingAccessRepository::new(db_pool.clone());
+        let file_request_repository = FileRequestRepository::new(db_pool.clone());
+        let file_grant_repository = FileGrantRepository::new(db_pool);
 
         ServerRepositories {
             customer_repository,
             used_token_repository,
+            refresh_token_repository,
             file_meta_repository,
             file_sharing_meta_repository,
+            upload_repository,
+            pending_delete_repository,
+            folder_repository,
+            sharing_attempt_repository,
+            sharing_access_repository,
+            file_request_repository,
+            file_grant_repository,
         }
     }
 }
@@ -0,0 +1,94 @@
+use std::sync::Arc;
+
+use anyhow::Result;
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use sqlx::prelude::FromRow;
+use tokio::sync::RwLock;
+
+use crate::domain::{entity::storage_object::PendingDelete, repository::pending_delete::PendingDeleteRepositoryTrait};
+
+use super::DbPool;
+
+#[derive(Debug, FromRow, Clone)]
+struct PendingDeleteDAO {
+    storagekey: String,
+}
+
+impl From<PendingDeleteDAO> for PendingDelete {
+    fn from(dao: PendingDeleteDAO) -> PendingDelete {
+        PendingDelete::new(&dao.storagekey)
+    }
+}
+
+#[derive(Clone)]
+pub struct PendingDeleteRepository {
+    db_conn: DbPool,
+}
+
+impl PendingDeleteRepository {
+    pub fn new(db_conn: DbPool) -> Arc<RwLock<dyn PendingDeleteRepositoryTrait>> {
+        Arc::new(RwLock::new(PendingDeleteRepository { db_conn }))
+    }
+}
+
+#[async_trait]
+impl PendingDeleteRepositoryTrait for PendingDeleteRepository {
+    async fn list(&self, limit: i64) -> Result<Vec<PendingDelete>> {
+        let pending_delete_list: Vec<PendingDelete> = sqlx::query_as(
+            r#"
+                SELECT storagekey FROM
+                    pendingdelete
+                ORDER BY
+                    lastattemptat ASC NULLS FIRST, createdat ASC
+                LIMIT $1
+            "#,
+        )
+        .bind(limit)
+        .fetch_all(&self.db_conn)
+        .await?
+        .into_iter()
+        .map(|dao: PendingDeleteDAO| dao.into())
+        .collect();
+
+        Ok(pending_delete_list)
+    }
+
+    async fn remove(&self, storage_key: &str) -> Result<()> {
+        sqlx::query(
+            r#"
+                DELETE FROM
+                    pendingdelete
+                WHERE
+                    storagekey = $1
+            "#,
+        )
+        .bind(storage_key)
+        .execute(&self.db_conn)
+        .await?;
+
+        Ok(())
+    }
+
+    async fn mark_failed(&self, storage_key: &str, error: &str, attempted_at: &DateTime<Utc>) -> Result<()> {
+        sqlx::query(
+            r#"
+                UPDATE
+                    pendingdelete
+                SET
+                    attempts = attempts + 1,
+                    lastattemptat = $2,
+                    lasterror = $3
+                WHERE
+                    storagekey = $1
+            "#,
+        )
+        .bind(storage_key)
+        .bind(attempted_at)
+        .bind(error)
+        .execute(&self.db_conn)
+        .await?;
+
+        Ok(())
+    }
+}
@@ -0,0 +1,218 @@
+use std::sync::Arc;
+
+use anyhow::Result;
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use sqlx::prelude::FromRow;
+use tokio::sync::RwLock;
+use uuid::Uuid;
+
+use crate::domain::{
+    entity::refresh_token::RefreshToken, repository::refresh_token::RefreshTokenRepositoryTrait,
+};
+
+use super::DbPool;
+
+#[derive(Debug, FromRow, Clone)]
+struct RefreshTokenDAO {
+    id: Uuid,
+    customer_id: Uuid,
+    family_id: Uuid,
+    expireat: DateTime<Utc>,
+    usedat: Option<DateTime<Utc>>,
+    revoked: bool,
+}
+
+impl From<RefreshTokenDAO> for RefreshToken {
+    fn from(dao: RefreshTokenDAO) -> RefreshToken {
+        RefreshToken::new_full(
+            &dao.id,
+            &dao.customer_id,
+            &dao.family_id,
+            &dao.expireat,
+            &dao.usedat,
+            dao.revoked,
+        )
+    }
+}
+
+#[derive(Clone)]
+pub struct RefreshTokenRepository {
+    db_conn: DbPool,
+}
+
+impl RefreshTokenRepository {
+    pub fn new(db_conn: DbPool) -> Arc<RwLock<dyn RefreshTokenRepositoryTrait>> {
+        Arc::new(RwLock::new(RefreshTokenRepository { db_conn }))
+    }
+}
+
+#[async_trait]
+impl RefreshTokenRepositoryTrait for RefreshTokenRepository {
+    async fn create(
+        &self,
+        customer_id: &Uuid,
+        family_id: &Uuid,
+        token_hash: &str,
+        expireat: &DateTime<Utc>,
+    ) -> Result<RefreshToken> {
+        let refresh_token: RefreshTokenDAO = sqlx::query_as(
+            r#"
+                INSERT INTO
+                    refreshtoken (customer_id, family_id, token_hash, expireat)
+                VALUES
+                    ($1, $2, $3, $4)
+                RETURNING id, customer_id, family_id, expireat, usedat, revoked;
+            "#,
+        )
+        .bind(customer_id)
+        .bind(family_id)
+        .bind(token_hash)
+        .bind(expireat)
+        .fetch_one(&self.db_conn)
+        .await?;
+
+        Ok(refresh_token.into())
+    }
+
+    async fn get_by_token_hash(&self, token_hash: &str) -> Result<Vec<RefreshToken>> {
+        let refresh_token_list: Vec<RefreshToken> = sqlx::query_as(
+            r#"
+                SELECT id, customer_id, family_id, expireat, usedat, revoked FROM
+                    refreshtoken
+                WHERE
+                    token_hash = $1
+            "#,
+        )
+        .bind(token_hash)
+        .fetch_all(&self.db_conn)
+        .await?
+        .into_iter()
+        .map(|dao: RefreshTokenDAO| dao.into())
+        .collect();
+
+        Ok(refresh_token_list)
+    }
+
+    // NOTE: the rows of the family are locked so that a rotation and a revocation of the same
+    //       family run one after the other, a token created by the rotation is either seen by the
+    //       revocation or not created at all.
+    async fn rotate(
+        &self,
+        id: &Uuid,
+        used_at: &DateTime<Utc>,
+        token_hash: &str,
+        expireat: &DateTime<Utc>,
+    ) -> Result<Vec<RefreshToken>> {
+        let mut tx = self.db_conn.begin().await?;
+
+        sqlx::query(
+            r#"
+                SELECT id FROM
+                    refreshtoken
+                WHERE
+                    family_id = (SELECT family_id FROM refreshtoken WHERE id = $1)
+                ORDER BY id
+                FOR UPDATE
+            "#,
+        )
+        .bind(id)
+        .execute(&mut *tx)
+        .await?;
+
+        let result = sqlx::query(
+            r#"
+                UPDATE
+                    refreshtoken
+                SET
+                    usedat = $2
+                WHERE
+                    id = $1
+                    AND
+                    usedat IS NULL
+                    AND
+                    revoked = FALSE
+            "#,
+        )
+        .bind(id)
+        .bind(used_at)
+        .execute(&mut *tx)
+        .await?;
+
+        if result.rows_affected() != 1 {
+            tx.rollback().await?;
+            return Ok(vec![]);
+        }
+
+        let refresh_token: Option<RefreshTokenDAO> = sqlx::query_as(
+            r#"
+                INSERT INTO
+                    refreshtoken (customer_id, family_id, token_hash, expireat)
+                SELECT
+                    used.customer_id, used.family_id, $2, $3
+                FROM
+                    refreshtoken AS used
+                WHERE
+                    used.id = $1
+                    AND
+                    NOT EXISTS (
+                        SELECT 1 FROM
+                            refreshtoken AS sibling
+                        WHERE
+                            sibling.family_id = used.family_id
+                            AND
+                            sibling.revoked = TRUE
+                    )
+                RETURNING id, customer_id, family_id, expireat, usedat, revoked;
+            "#,
+        )
+        .bind(id)
+        .bind(token_hash)
+        .bind(expireat)
+        .fetch_optional(&mut *tx)
+        .await?;
+
+        let Some(refresh_token) = refresh_token else {
+            tx.rollback().await?;
+            return Ok(vec![]);
+        };
+
+        tx.commit().await?;
+        Ok(vec![refresh_token.into()])
+    }
+
+    async fn revoke_family(&self, family_id: &Uuid) -> Result<()> {
+        let mut tx = self.db_conn.begin().await?;
+
+        sqlx::query(
+            r#"
+                SELECT id FROM
+                    refreshtoken
+                WHERE
+                    family_id = $1
+                ORDER BY id
+                FOR UPDATE
+            "#,
+        )
+        .bind(family_id)
+        .execute(&mut *tx)
+        .await?;
+
+        sqlx::query(
+            r#"
+                UPDATE
+                    refreshtoken
+                SET
+                    revoked = TRUE
+                WHERE
+                    family_id = $1
+            "#,
+        )
+        .bind(family_id)
+        .execute(&mut *tx)
+        .await?;
+
+        tx.commit().await?;
+        Ok(())
+    }
+}
@@ -0,0 +1,180 @@
+use std::sync::Arc;
+
+use anyhow::{anyhow, Result};
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use sqlx::prelude::FromRow;
+use tokio::sync::RwLock;
+use uuid::Uuid;
+
+use crate::domain::{entity::sharing_access::{SharingAccess, SharingAccessCursor, SharingAccessOutcome, SharingAccessStats, SharingClient}, repository::sharing_access::SharingAccessRepositoryTrait};
+
+use super::DbPool;
+
+#[derive(Debug, FromRow, Clone)]
+struct SharingAccessDAO {
+    id: Uuid,
+    sharing_id: Option<Uuid>,
+    accessedat: DateTime<Utc>,
+    clientaddr: String,
+    useragent: Option<String>,
+    outcome: String,
+    bytesserved: i64,
+}
+
+impl TryFrom<SharingAccessDAO> for SharingAccess {
+    type Error = anyhow::Error;
+
+    fn try_from(dao: SharingAccessDAO) -> Result<SharingAccess> {
+        let outcome = SharingAccessOutcome::from_name(&dao.outcome)
+            .ok_or_else(|| anyhow!("unknown sharing access outcome {}", dao.outcome))?;
+
+        Ok(SharingAccess::new_full(
+            &dao.id,
+            &dao.sharing_id,
+            &dao.accessedat,
+            &SharingClient::new(&dao.clientaddr, &dao.useragent),
+            outcome,
+            dao.bytesserved,
+        ))
+    }
+}
+
+#[derive(Debug, FromRow, Clone)]
+struct SharingAccessStatsDAO {
+    outcome: String,
+    accesses: i64,
+    bytesserved: i64,
+    lastaccessedat: DateTime<Utc>,
+}
+
+#[derive(Clone)]
+pub struct SharingAccessRepository {
+    db_conn: DbPool,
+}
+
+impl SharingAccessRepository {
+    pub fn new(db_conn: DbPool) -> Arc<RwLock<dyn SharingAccessRepositoryTrait>> {
+        Arc::new(RwLock::new(SharingAccessRepository { db_conn }))
+    }
+}
+
+#[async_trait]
+impl SharingAccessRepositoryTrait for SharingAccessRepository {
+    async fn create(&self, sharing_id: &Option<Uuid>, accessed_at: &DateTime<Utc>, client: &SharingClient, outcome: SharingAccessOutcome) -> Result<SharingAccess> {
+        let dao: SharingAccessDAO = sqlx::query_as(
+            r#"
+                INSERT INTO
+                    sharingaccess
+                (sharing_id, accessedat, clientaddr, useragent, outcome)
+                VALUES
+                    ($1, $2, $3, $4, $5)
+                RETURNING id, sharing_id, accessedat, clientaddr, useragent, outcome, bytesserved;
+            "#,
+        )
+        .bind(sharing_id)
+        .bind(accessed_at)
+        .bind(client.get_addr())
+        .bind(client.get_user_agent())
+        .bind(outcome.get_name())
+        .fetch_one(&self.db_conn)
+        .await?;
+
+        dao.try_into()
+    }
+
+    async fn set_bytes_served(&self, id: &Uuid, bytes_served: i64) -> Result<()> {
+        sqlx::query(
+            r#"
+                UPDATE
+                    sharingaccess
+                SET
+                    bytesserved = $2
+                WHERE
+                    id = $1;
+            "#,
+        )
+        .bind(id)
+        .bind(bytes_served)
+        .execute(&self.db_conn)
+        .await?;
+
+        Ok(())
+    }
+
+    async fn set_outcome(&self, id: &Uuid, outcome: SharingAccessOutcome) -> Result<()> {
+        sqlx::query(
+            r#"
+                UPDATE
+                    sharingaccess
+                SET
+                    outcome = $2
+                WHERE
+                    id = $1;
+            "#,
+        )
+        .bind(id)
+        .bind(outcome.get_name())
+        .execute(&self.db_conn)
+        .await?;
+
+        Ok(())
+    }
+
+    async fn list_by_sharing_id(&self, sharing_id: &Uuid, cursor: &Option<SharingAccessCursor>, limit: i64) -> Result<Vec<SharingAccess>> {
+        let sharing_access_list = sqlx::query_as(
+            r#"
+                SELECT id, sharing_id, accessedat, clientaddr, useragent, outcome, bytesserved FROM
+                    sharingaccess
+                WHERE
+                    sharing_id = $1
+                    AND
+                    ($2::timestamptz IS NULL OR (accessedat, id) < ($2, $3))
+                ORDER BY
+                    accessedat DESC, id DESC
+                LIMIT $4
+            "#,
+        )
+        .bind(sharing_id)
+        .bind(cursor.as_ref().map(|cursor| cursor.get_accessed_at()))
+        .bind(cursor.as_ref().map(|cursor| cursor.get_id()))
+        .bind(limit)
+        .fetch_all(&self.db_conn)
+        .await?
+        .into_iter()
+        .map(|dao: SharingAccessDAO| dao.try_into())
+        .collect::<Result<Vec<SharingAccess>>>()?;
+
+        Ok(sharing_access_list)
+    }
+
+    async fn stats_by_sharing_id(&self, sharing_id: &Uuid) -> Result<SharingAccessStats> {
+        let stats_list: Vec<SharingAccessStatsDAO> = sqlx::query_as(
+            r#"
+                SELECT
+                    outcome,
+                    COUNT(*) AS accesses,
+                    COALESCE(SUM(bytesserved), 0)::BIGINT AS bytesserved,
+                    MAX(accessedat) AS lastaccessedat
+                FROM
+                    sharingaccess
+                WHERE
+                    sharing_id = $1
+                GROUP BY
+                    outcome
+            "#,
+        )
+        .bind(sharing_id)
+        .fetch_all(&self.db_conn)
+        .await?;
+
+        let mut stats = SharingAccessStats::default();
+        for dao in stats_list {
+            let outcome = SharingAccessOutcome::from_name(&dao.outcome)
+                .ok_or_else(|| anyhow!("unknown sharing access outcome {}", dao.outcome))?;
+            stats.add(outcome, dao.accesses, dao.bytesserved, &dao.lastaccessedat);
+        }
+
+        Ok(stats)
+    }
+}
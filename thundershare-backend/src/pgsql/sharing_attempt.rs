@@ -0,0 +1,117 @@
+use std::sync::Arc;
+
+use anyhow::Result;
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use sqlx::prelude::FromRow;
+use tokio::sync::RwLock;
+
+use crate::domain::{entity::sharing_attempt::SharingAttempt, repository::sharing_attempt::SharingAttemptRepositoryTrait};
+
+use super::DbPool;
+
+#[derive(Debug, FromRow, Clone)]
+struct SharingAttemptDAO {
+    key: String,
+    failures: i32,
+    lockeduntil: Option<DateTime<Utc>>,
+}
+
+impl From<SharingAttemptDAO> for SharingAttempt {
+    fn from(dao: SharingAttemptDAO) -> SharingAttempt {
+        SharingAttempt::new_full(&dao.key, dao.failures, &dao.lockeduntil)
+    }
+}
+
+#[derive(Clone)]
+pub struct SharingAttemptRepository {
+    db_conn: DbPool,
+}
+
+impl SharingAttemptRepository {
+    pub fn new(db_conn: DbPool) -> Arc<RwLock<dyn SharingAttemptRepositoryTrait>> {
+        Arc::new(RwLock::new(SharingAttemptRepository { db_conn }))
+    }
+}
+
+#[async_trait]
+impl SharingAttemptRepositoryTrait for SharingAttemptRepository {
+    async fn begin_attempt(&self, key: &str, attempted_at: &DateTime<Utc>, window_start_before: &DateTime<Utc>, max_failures: i32, locked_until: &DateTime<Utc>) -> Result<SharingAttempt> {
+        let sharing_attempt: SharingAttemptDAO = sqlx::query_as(
+            r#"
+                INSERT INTO sharingattempt
+                    (key, failures, windowstartat)
+                VALUES
+                    ($1, 1, $2)
+                ON CONFLICT (key) DO UPDATE SET
+                    failures = CASE
+                        WHEN sharingattempt.lockeduntil > $2 THEN sharingattempt.failures
+                        WHEN sharingattempt.windowstartat <= $3 THEN 1
+                        WHEN sharingattempt.failures >= $4 THEN 0
+                        ELSE sharingattempt.failures + 1
+                    END,
+                    windowstartat = CASE
+                        WHEN sharingattempt.lockeduntil > $2 THEN sharingattempt.windowstartat
+                        WHEN sharingattempt.windowstartat <= $3 THEN $2
+                        WHEN sharingattempt.failures >= $4 THEN $2
+                        ELSE sharingattempt.windowstartat
+                    END,
+                    lockeduntil = CASE
+                        WHEN sharingattempt.lockeduntil > $2 THEN sharingattempt.lockeduntil
+                        WHEN sharingattempt.windowstartat <= $3 THEN sharingattempt.lockeduntil
+                        WHEN sharingattempt.failures >= $4 THEN $5
+                        ELSE sharingattempt.lockeduntil
+                    END
+                RETURNING key, failures, lockeduntil;
+            "#,
+        )
+        .bind(key)
+        .bind(attempted_at)
+        .bind(window_start_before)
+        .bind(max_failures)
+        .bind(locked_until)
+        .fetch_one(&self.db_conn)
+        .await?;
+
+        Ok(sharing_attempt.into())
+    }
+
+    async fn forgive_attempt(&self, key: &str) -> Result<()> {
+        sqlx::query(
+            r#"
+                UPDATE
+                    sharingattempt
+                SET
+                    failures = failures - 1
+                WHERE
+                    key = $1
+                    AND
+                    failures > 0;
+            "#,
+        )
+        .bind(key)
+        .execute(&self.db_conn)
+        .await?;
+
+        Ok(())
+    }
+
+    async fn purge(&self, window_start_before: &DateTime<Utc>, curr_time: &DateTime<Utc>) -> Result<u64> {
+        let result = sqlx::query(
+            r#"
+                DELETE FROM
+                    sharingattempt
+                WHERE
+                    windowstartat <= $1
+                    AND
+                    (lockeduntil IS NULL OR lockeduntil <= $2);
+            "#,
+        )
+        .bind(window_start_before)
+        .bind(curr_time)
+        .execute(&self.db_conn)
+        .await?;
+
+        Ok(result.rows_affected())
+    }
+}
@@ -0,0 +1,165 @@
+use std::sync::Arc;
+
+use anyhow::Result;
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use sqlx::prelude::FromRow;
+use tokio::sync::RwLock;
+use uuid::Uuid;
+
+use crate::domain::{entity::upload::Upload, repository::upload::UploadRepositoryTrait};
+
+use super::DbPool;
+
+#[derive(Debug, FromRow, Clone)]
+struct UploadDAO {
+    id: Uuid,
+    customer_id: Uuid,
+    name: String,
+    mimetype: Option<String>,
+    uploadlength: i64,
+    uploadoffset: i64,
+    parts: Vec<String>,
+    file_id: Option<Uuid>,
+    createdat: DateTime<Utc>,
+}
+
+impl From<UploadDAO> for Upload {
+    fn from(dao: UploadDAO) -> Upload {
+        let mut upload = Upload::new_full(
+            &dao.id,
+            &dao.customer_id,
+            &dao.name,
+            &dao.mimetype,
+            dao.uploadlength as u64,
+            &dao.createdat,
+        );
+        upload.set_progress(dao.uploadoffset as u64, &dao.parts);
+        upload.set_file_id(dao.file_id);
+        upload
+    }
+}
+
+#[derive(Clone)]
+pub struct UploadRepository {
+    db_conn: DbPool,
+}
+
+impl UploadRepository {
+    pub fn new(db_conn: DbPool) -> Arc<RwLock<dyn UploadRepositoryTrait>> {
+        Arc::new(RwLock::new(UploadRepository { db_conn }))
+    }
+}
+
+#[async_trait]
+impl UploadRepositoryTrait for UploadRepository {
+    async fn create(&self, customer_id: &Uuid, name: &str, mime_type: &Option<String>, length: u64, created_at: &DateTime<Utc>) -> Result<Upload> {
+        let upload: UploadDAO = sqlx::query_as(
+            r#"
+                INSERT INTO
+                    upload (customer_id, name, mimetype, uploadlength, createdat)
+                VALUES
+                    ($1, $2, $3, $4, $5)
+                RETURNING id, customer_id, name, mimetype, uploadlength, uploadoffset, parts, file_id, createdat;
+            "#,
+        )
+        .bind(customer_id)
+        .bind(name)
+        .bind(mime_type)
+        .bind(length as i64)
+        .bind(created_at)
+        .fetch_one(&self.db_conn)
+        .await?;
+
+        Ok(upload.into())
+    }
+
+    async fn get_by_id(&self, id: &Uuid) -> Result<Vec<Upload>> {
+        let upload_list: Vec<Upload> = sqlx::query_as(
+            r#"
+                SELECT id, customer_id, name, mimetype, uploadlength, uploadoffset, parts, file_id, createdat FROM
+                    upload
+                WHERE
+                    id = $1
+            "#,
+        )
+        .bind(id)
+        .fetch_all(&self.db_conn)
+        .await?
+        .into_iter()
+        .map(|dao: UploadDAO| dao.into())
+        .collect();
+
+        Ok(upload_list)
+    }
+
+    async fn append_part(&self, id: &Uuid, offset: u64, new_offset: u64, part_key: &str) -> Result<Vec<Upload>> {
+        let upload_list: Vec<Upload> = sqlx::query_as(
+            r#"
+                UPDATE
+                    upload
+                SET
+                    uploadoffset = $3,
+                    parts = array_append(parts, $4)
+                WHERE
+                    id = $1
+                    AND
+                    uploadoffset = $2
+                RETURNING id, customer_id, name, mimetype, uploadlength, uploadoffset, parts, file_id, createdat;
+            "#,
+        )
+        .bind(id)
+        .bind(offset as i64)
+        .bind(new_offset as i64)
+        .bind(part_key)
+        .fetch_all(&self.db_conn)
+        .await?
+        .into_iter()
+        .map(|dao: UploadDAO| dao.into())
+        .collect();
+
+        Ok(upload_list)
+    }
+
+    async fn complete(&self, id: &Uuid, file_id: &Uuid) -> Result<Vec<Upload>> {
+        let upload_list: Vec<Upload> = sqlx::query_as(
+            r#"
+                UPDATE
+                    upload
+                SET
+                    file_id = $2,
+                    parts = '{}'
+                WHERE
+                    id = $1
+                    AND
+                    file_id IS NULL
+                RETURNING id, customer_id, name, mimetype, uploadlength, uploadoffset, parts, file_id, createdat;
+            "#,
+        )
+        .bind(id)
+        .bind(file_id)
+        .fetch_all(&self.db_conn)
+        .await?
+        .into_iter()
+        .map(|dao: UploadDAO| dao.into())
+        .collect();
+
+        Ok(upload_list)
+    }
+
+    async fn delete(&self, id: &Uuid) -> Result<()> {
+        sqlx::query(
+            r#"
+                DELETE FROM
+                    upload
+                WHERE
+                    id = $1
+            "#,
+        )
+        .bind(id)
+        .execute(&self.db_conn)
+        .await?;
+
+        Ok(())
+    }
+}
@@ -2,18 +2,80 @@ use super::DbPool;
 use crate::domain::repository::used_token::UsedTokenRepositoryTrait;
 use anyhow::Result;
 use async_trait::async_trait;
-use chrono::{DateTime, Utc};
+use chrono::{DateTime, Duration, Utc};
+use std::collections::HashMap;
 use std::sync::Arc;
 use tokio::sync::RwLock;
 
+// NOTE: how long a "not revoked" answer is trusted before asking postgres again.
+//       revocations issued by this process are visible immediately, the ttl only
+//       bounds the delay for revocations issued by other server instances.
+const NOT_REVOKED_CACHE_TTL_SECONDS: i64 = 30;
+const CACHE_PRUNE_THRESHOLD: usize = 10_000;
+// NOTE: when pruning expired entries is not enough, this many live entries are dropped at once so
+//       the O(n) scan runs once per batch of inserts instead of on every insert.
+const CACHE_EVICT_BATCH: usize = 1_000;
+
+#[derive(Clone, Copy)]
+struct UsedTokenCacheEntry {
+    revoked: bool,
+    valid_until: DateTime<Utc>,
+}
+
+// NOTE: shared by every worker of the http server, so it is created once in main and
+//       handed over to each ServerRepositories instance.
+#[derive(Clone, Default)]
+pub struct UsedTokenCache {
+    entries: Arc<std::sync::RwLock<HashMap<String, UsedTokenCacheEntry>>>,
+}
+
+impl UsedTokenCache {
+    pub fn new() -> UsedTokenCache {
+        UsedTokenCache::default()
+    }
+
+    fn get(&self, token: &str, curr_time: &DateTime<Utc>) -> Option<bool> {
+        let entries = self.entries.read().unwrap();
+        match entries.get(token) {
+            Some(entry) if entry.valid_until > *curr_time => Some(entry.revoked),
+            _ => None,
+        }
+    }
+
+    fn insert(&self, token: &str, revoked: bool, valid_until: DateTime<Utc>) {
+        let mut entries = self.entries.write().unwrap();
+        if entries.len() >= CACHE_PRUNE_THRESHOLD && !entries.contains_key(token) {
+            let curr_time = Utc::now();
+            entries.retain(|_, entry| entry.valid_until > curr_time);
+
+            let keep = CACHE_PRUNE_THRESHOLD - CACHE_EVICT_BATCH;
+            if entries.len() > keep {
+                let evicted: Vec<String> = entries.keys().take(entries.len() - keep).cloned().collect();
+                for key in evicted {
+                    entries.remove(&key);
+                }
+            }
+        }
+
+        entries.insert(
+            token.to_string(),
+            UsedTokenCacheEntry {
+                revoked,
+                valid_until,
+            },
+        );
+    }
+}
+
 #[derive(Clone)]
 pub struct UsedTokenRepository {
     db_conn: DbPool,
+    cache: UsedTokenCache,
 }
 
 impl UsedTokenRepository {
-    pub fn new(db_conn: DbPool) -> Arc<RwLock<UsedTokenRepository>> {
-        Arc::new(RwLock::new(UsedTokenRepository { db_conn }))
+    pub fn new(db_conn: DbPool, cache: UsedTokenCache) -> Arc<RwLock<UsedTokenRepository>> {
+        Arc::new(RwLock::new(UsedTokenRepository { db_conn, cache }))
     }
 }
 
@@ -26,6 +88,7 @@ impl UsedTokenRepositoryTrait for UsedTokenRepository {
                     signouttoken (token, expireat)
                 VALUES
                     ($1, $2)
+                ON CONFLICT (token) DO NOTHING
             "#,
         )
         .bind(token.to_string())
@@ -33,6 +96,37 @@ impl UsedTokenRepositoryTrait for UsedTokenRepository {
         .execute(&self.db_conn)
         .await?;
 
+        self.cache.insert(token, true, expire_time);
+
         Ok(())
     }
+
+    async fn is_token_revoked(&self, token: &str) -> Result<bool> {
+        let curr_time = Utc::now();
+        if let Some(revoked) = self.cache.get(token, &curr_time) {
+            return Ok(revoked);
+        }
+
+        let revoked_list: Vec<(Option<DateTime<Utc>>,)> = sqlx::query_as(
+            r#"
+                SELECT expireat FROM
+                    signouttoken
+                WHERE
+                    token = $1
+            "#,
+        )
+        .bind(token)
+        .fetch_all(&self.db_conn)
+        .await?;
+
+        let revoked = !revoked_list.is_empty();
+        let valid_until = match revoked_list.first() {
+            Some((Some(expireat),)) => *expireat,
+            Some((None,)) => curr_time + Duration::days(1),
+            None => curr_time + Duration::seconds(NOT_REVOKED_CACHE_TTL_SECONDS),
+        };
+        self.cache.insert(token, revoked, valid_until);
+
+        Ok(revoked)
+    }
 }
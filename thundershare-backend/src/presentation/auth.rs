@@ -0,0 +1,133 @@
+use std::future::{ready, Ready};
+use std::rc::Rc;
+
+use actix_web::body::EitherBody;
+use actix_web::dev::{forward_ready, Payload, Service, ServiceRequest, ServiceResponse, Transform};
+use actix_web::http::header::AUTHORIZATION;
+use actix_web::{web, Error, FromRequest, HttpMessage, HttpRequest, ResponseError};
+use futures_util::future::LocalBoxFuture;
+
+use crate::domain::entity::identity::Identity;
+use crate::domain::service::customer::CustomerServiceTrait;
+use crate::domain::service::ServerService;
+use crate::presentation::error::ApiError;
+
+pub const TOKEN_COOKIE_NAME: &str = "token";
+
+#[derive(Clone, Debug)]
+pub struct AuthenticatedCustomer {
+    identity: Identity,
+    token: String,
+}
+
+impl AuthenticatedCustomer {
+    pub fn get_identity(&self) -> &Identity {
+        &self.identity
+    }
+
+    pub fn get_token(&self) -> &str {
+        &self.token
+    }
+}
+
+// NOTE: the Authorization header takes precedence over the cookie so that non-browser
+//       clients are not affected by a stale cookie.
+fn extract_token(request: &HttpRequest) -> Option<String> {
+    let bearer_token = request
+        .headers()
+        .get(AUTHORIZATION)
+        .and_then(|value| value.to_str().ok())
+        .and_then(|value| value.strip_prefix("Bearer "))
+        .map(|value| value.trim().to_string());
+
+    match bearer_token {
+        Some(token) => Some(token),
+        None => request
+            .cookie(TOKEN_COOKIE_NAME)
+            .map(|cookie| cookie.value().to_string()),
+    }
+}
+
+async fn authenticate(request: HttpRequest) -> Result<AuthenticatedCustomer, ApiError> {
+    if let Some(customer) = request.extensions().get::<AuthenticatedCustomer>() {
+        return Ok(customer.clone());
+    }
+
+    let token = extract_token(&request).ok_or(ApiError::Unauthenticated)?;
+    let server_services = request
+        .app_data::<web::Data<ServerService>>()
+        .ok_or_else(|| ApiError::Internal(anyhow::anyhow!("ServerService is not registered")))?
+        .clone();
+
+    let identity = server_services
+        .customer_service
+        .customer_authenticate(&token)
+        .await?;
+
+    let customer = AuthenticatedCustomer { identity, token };
+    request.extensions_mut().insert(customer.clone());
+
+    Ok(customer)
+}
+
+impl FromRequest for AuthenticatedCustomer {
+    type Error = ApiError;
+    type Future = LocalBoxFuture<'static, Result<Self, Self::Error>>;
+
+    fn from_request(request: &HttpRequest, _payload: &mut Payload) -> Self::Future {
+        Box::pin(authenticate(request.clone()))
+    }
+}
+
+// NOTE: rejects unauthenticated requests for a whole scope before any handler runs,
+//       handlers can still take AuthenticatedCustomer which is then served from the
+//       request extensions without validating the token twice.
+pub struct RequireAuthentication;
+
+impl<S, B> Transform<S, ServiceRequest> for RequireAuthentication
+where
+    S: Service<ServiceRequest, Response = ServiceResponse<B>, Error = Error> + 'static,
+    B: 'static,
+{
+    type Response = ServiceResponse<EitherBody<B>>;
+    type Error = Error;
+    type Transform = RequireAuthenticationMiddleware<S>;
+    type InitError = ();
+    type Future = Ready<Result<Self::Transform, Self::InitError>>;
+
+    fn new_transform(&self, service: S) -> Self::Future {
+        ready(Ok(RequireAuthenticationMiddleware {
+            service: Rc::new(service),
+        }))
+    }
+}
+
+pub struct RequireAuthenticationMiddleware<S> {
+    service: Rc<S>,
+}
+
+impl<S, B> Service<ServiceRequest> for RequireAuthenticationMiddleware<S>
+where
+    S: Service<ServiceRequest, Response = ServiceResponse<B>, Error = Error> + 'static,
+    B: 'static,
+{
+    type Response = ServiceResponse<EitherBody<B>>;
+    type Error = Error;
+    type Future = LocalBoxFuture<'static, Result<Self::Response, Self::Error>>;
+
+    forward_ready!(service);
+
+    fn call(&self, request: ServiceRequest) -> Self::Future {
+        let service = self.service.clone();
+
+        Box::pin(async move {
+            if let Err(err) = authenticate(request.request().clone()).await {
+                let response = err.error_response().map_into_right_body();
+                return Ok(request.into_response(response));
+            }
+
+            let response = service.call(request).await?;
+            Ok(response.map_into_left_body())
+        })
+    }
+}
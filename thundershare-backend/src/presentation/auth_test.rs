@@ -0,0 +1,155 @@
+use std::sync::Arc;
+
+use actix_web::body::to_bytes;
+use actix_web::cookie::Cookie;
+use actix_web::http::{header, StatusCode};
+use actix_web::test::{self, TestRequest};
+use actix_web::{web, App, HttpResponse};
+use chrono::{Duration, Utc};
+use tokio::sync::RwLock;
+
+use super::auth::{AuthenticatedCustomer, RequireAuthentication, TOKEN_COOKIE_NAME};
+use crate::config::ServerConfig;
+use crate::domain::entity::customer::Customer;
+use crate::domain::entity::identity::Identity;
+use crate::domain::entity::jwt_key::{JwtKeyAlgorithm, JwtKeyConfig, JwtKeySet};
+use crate::domain::repository::{
+    customer::MockCustomerRepositoryTrait, file_grant::MockFileGrantRepositoryTrait,
+    file_meta::MockFileMetaRepositoryTrait, file_request::MockFileRequestRepositoryTrait,
+    file_sharing::MockFileSharingRepositoryTrait, folder::MockFolderRepositoryTrait,
+    pending_delete::MockPendingDeleteRepositoryTrait,
+    refresh_token::MockRefreshTokenRepositoryTrait,
+    sharing_access::MockSharingAccessRepositoryTrait,
+    sharing_attempt::MockSharingAttemptRepositoryTrait, storage::MockStorageBackendTrait,
+    upload::MockUploadRepositoryTrait, used_token::MockUsedTokenRepositoryTrait,
+    ServerRepositories,
+};
+use crate::domain::service::ServerService;
+
+fn fake_jwt_key_set() -> Arc<JwtKeySet> {
+    let config = JwtKeyConfig {
+        kid: String::from("test"),
+        algorithm: JwtKeyAlgorithm::HS256,
+        secret: Some(String::from("secret")),
+        pem_file: None,
+    };
+
+    Arc::new(JwtKeySet::new("test", &[config]).unwrap())
+}
+
+fn fake_token(issueat_offset: Duration) -> String {
+    let identity = Identity::new(
+        &Customer::new("mikejiang"),
+        &(Utc::now() + issueat_offset),
+        Duration::minutes(10),
+    );
+    identity.to_string(&fake_jwt_key_set()).unwrap()
+}
+
+// NOTE: only the used token repository is reached while authenticating
+fn fake_server_service(revoked_token: String) -> ServerService {
+    let mut mock_used_token_repo = MockUsedTokenRepositoryTrait::new();
+    mock_used_token_repo
+        .expect_is_token_revoked()
+        .returning(move |token| Ok(token == revoked_token));
+
+    let repositories = ServerRepositories {
+        customer_repository: Arc::new(RwLock::new(MockCustomerRepositoryTrait::new())),
+        used_token_repository: Arc::new(RwLock::new(mock_used_token_repo)),
+        refresh_token_repository: Arc::new(RwLock::new(MockRefreshTokenRepositoryTrait::new())),
+        file_meta_repository: Arc::new(RwLock::new(MockFileMetaRepositoryTrait::new())),
+        file_sharing_meta_repository: Arc::new(RwLock::new(MockFileSharingRepositoryTrait::new())),
+        upload_repository: Arc::new(RwLock::new(MockUploadRepositoryTrait::new())),
+        pending_delete_repository: Arc::new(RwLock::new(MockPendingDeleteRepositoryTrait::new())),
+        folder_repository: Arc::new(RwLock::new(MockFolderRepositoryTrait::new())),
+        sharing_attempt_repository: Arc::new(RwLock::new(MockSharingAttemptRepositoryTrait::new())),
+        sharing_access_repository: Arc::new(RwLock::new(MockSharingAccessRepositoryTrait::new())),
+        file_request_repository: Arc::new(RwLock::new(MockFileRequestRepositoryTrait::new())),
+        file_grant_repository: Arc::new(RwLock::new(MockFileGrantRepositoryTrait::new())),
+    };
+
+    ServerService::new(
+        Arc::new(ServerConfig::default()),
+        fake_jwt_key_set(),
+        Arc::new(MockStorageBackendTrait::new()),
+        repositories,
+    )
+}
+
+async fn whoami(customer: AuthenticatedCustomer) -> HttpResponse {
+    HttpResponse::Ok().body(customer.get_identity().get_id().to_string())
+}
+
+#[actix_rt::test]
+async fn test_authenticated_customer_and_require_authentication() {
+    let valid_token = fake_token(Duration::zero());
+    let revoked_token = fake_token(Duration::minutes(-1));
+    let expired_token = fake_token(Duration::days(-1));
+    let customer_id = Customer::new("mikejiang").get_id().to_string();
+
+    let bearer = |token: &str| Some(format!("Bearer {}", token));
+    let test_context = vec![
+        (bearer(&valid_token), None, StatusCode::OK, ""),
+        (None, Some(valid_token.clone()), StatusCode::OK, ""),
+        (
+            bearer(&revoked_token),
+            None,
+            StatusCode::UNAUTHORIZED,
+            "CUSTOMER_TOKEN_REVOKED",
+        ),
+        (
+            bearer(&expired_token),
+            None,
+            StatusCode::UNAUTHORIZED,
+            "CUSTOMER_INVALID_TOKEN",
+        ),
+        (None, None, StatusCode::UNAUTHORIZED, "UNAUTHENTICATED"),
+        (
+            Some(format!("Basic {}", valid_token)),
+            None,
+            StatusCode::UNAUTHORIZED,
+            "UNAUTHENTICATED",
+        ),
+        (
+            bearer("not-a-jwt"),
+            None,
+            StatusCode::UNAUTHORIZED,
+            "CUSTOMER_INVALID_TOKEN",
+        ),
+    ];
+
+    let app = test::init_service(
+        App::new()
+            .app_data(web::Data::new(fake_server_service(revoked_token.clone())))
+            .route("/extractor", web::get().to(whoami))
+            .service(
+                web::scope("/middleware")
+                    .wrap(RequireAuthentication)
+                    .route("", web::get().to(whoami)),
+            ),
+    )
+    .await;
+
+    // NOTE: the extractor alone and the middleware in front of it must agree on every request
+    for (authorization, cookie, expected_status, expected_error_code) in test_context {
+        for path in ["/extractor", "/middleware"] {
+            let mut request = TestRequest::get().uri(path);
+            if let Some(authorization) = authorization.clone() {
+                request = request.insert_header((header::AUTHORIZATION, authorization));
+            }
+            if let Some(cookie) = cookie.clone() {
+                request = request.cookie(Cookie::new(TOKEN_COOKIE_NAME, cookie));
+            }
+
+            let response = test::call_service(&app, request.to_request()).await;
+            assert_eq!(response.status(), expected_status, "{}", path);
+
+            let body = to_bytes(response.into_body()).await.unwrap();
+            let body = String::from_utf8(body.to_vec()).unwrap();
+            match expected_status {
+                StatusCode::OK => assert_eq!(body, customer_id),
+                _ => assert!(body.contains(expected_error_code), "{} {}", path, body),
+            }
+        }
+    }
+}
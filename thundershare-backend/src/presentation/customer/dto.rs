@@ -1,10 +1,8 @@
+use chrono::{DateTime, Utc};
 use uuid::Uuid;
 
 use crate::{
-    domain::{
-        entity::{customer::Customer, identity::Identity},
-        error::customer::CustomerError,
-    },
+    domain::entity::{customer::Customer, identity::Identity},
     presentation::ResponseData,
 };
 
@@ -24,12 +22,6 @@ impl From<Identity> for ResponseData<CustomerSignupV1RespDTO> {
     }
 }
 
-impl From<CustomerError> for ResponseData<CustomerSignupV1RespDTO> {
-    fn from(error: CustomerError) -> ResponseData<CustomerSignupV1RespDTO> {
-        ResponseData::new(false, error.to_string(), None)
-    }
-}
-
 #[derive(serde::Deserialize)]
 pub struct CustomerSigninV1ReqDTO {
     pub username: String,
@@ -46,9 +38,18 @@ impl From<Identity> for ResponseData<CustomerSigninV1RespDTO> {
     }
 }
 
-impl From<CustomerError> for ResponseData<CustomerSigninV1RespDTO> {
-    fn from(error: CustomerError) -> ResponseData<CustomerSigninV1RespDTO> {
-        ResponseData::new(false, error.to_string(), None)
+#[derive(serde::Serialize)]
+pub struct CustomerRefreshV1RespDTO {
+    #[serde(with = "chrono::serde::ts_seconds")]
+    expireat: DateTime<Utc>,
+}
+
+impl From<Identity> for ResponseData<CustomerRefreshV1RespDTO> {
+    fn from(svc_data: Identity) -> ResponseData<CustomerRefreshV1RespDTO> {
+        let resp = CustomerRefreshV1RespDTO {
+            expireat: svc_data.get_expireat(),
+        };
+        ResponseData::new(true, String::new(), Some(resp))
     }
 }
 
@@ -68,9 +69,3 @@ impl From<Customer> for ResponseData<CustomerGetByIdV1RespDTO> {
         ResponseData::new(true, String::new(), Some(resp))
     }
 }
-
-impl From<CustomerError> for ResponseData<CustomerGetByIdV1RespDTO> {
-    fn from(error: CustomerError) -> ResponseData<CustomerGetByIdV1RespDTO> {
-        ResponseData::new(false, error.to_string(), None)
-    }
-}
\ No newline at end of file
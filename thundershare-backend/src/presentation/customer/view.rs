@@ -1,137 +1,192 @@
-use crate::domain::entity::identity::Identity;
+use crate::config::CookieConfig;
 use crate::domain::error::customer::CustomerError;
 use crate::domain::service::customer::CustomerServiceTrait;
 use crate::domain::service::ServerService;
+use crate::domain::entity::identity::Identity;
+use crate::presentation::auth::{AuthenticatedCustomer, TOKEN_COOKIE_NAME};
+use crate::presentation::error::ApiError;
 use crate::presentation::ResponseData;
 
-use super::dto::{CustomerGetByIdV1RespDTO, CustomerSigninV1ReqDTO, CustomerSignupV1ReqDTO, CustomerSignupV1RespDTO};
+use super::dto::{CustomerGetByIdV1RespDTO, CustomerRefreshV1RespDTO, CustomerSigninV1ReqDTO, CustomerSigninV1RespDTO, CustomerSignupV1ReqDTO, CustomerSignupV1RespDTO};
 use actix_web::cookie::time::{Duration, OffsetDateTime};
-use actix_web::cookie::{Cookie, SameSite};
-use actix_web::Responder;
-use actix_web::{web, HttpRequest, HttpResponse};
+use actix_web::cookie::Cookie;
+use actix_web::{web, HttpRequest, HttpResponse, ResponseError};
+
+pub const REFRESH_TOKEN_COOKIE_NAME: &str = "refresh_token";
+
+// NOTE: the refresh token is only ever sent back to the customer endpoints and is never
+//       readable from javascript, unlike the access token cookie.
+const REFRESH_TOKEN_COOKIE_PATH: &str = "/api/v1/customer";
 
-fn new_cookie(token: &str) -> Cookie {
-    let mut now = OffsetDateTime::now_utc();
-    now += Duration::days(180);
+fn new_cookie(token: &str, lifetime: chrono::Duration, config: &CookieConfig) -> Cookie<'static> {
+    let max_age = Duration::seconds(lifetime.num_seconds());
 
-    let mut cookie = Cookie::new("token", token);
+    let mut cookie = Cookie::new(TOKEN_COOKIE_NAME, token.to_string());
     cookie.set_path("/");
-    cookie.set_expires(now);
+    cookie.set_max_age(max_age);
+    cookie.set_expires(OffsetDateTime::now_utc() + max_age);
     cookie.set_http_only(false);
-    cookie.set_secure(true);
-    cookie.set_same_site(SameSite::None);
+    cookie.set_secure(config.secure);
+    cookie.set_same_site(config.get_same_site());
+    if let Some(domain) = &config.domain {
+        cookie.set_domain(domain.clone());
+    }
 
     cookie
 }
 
+fn new_refresh_cookie(refresh_token: &str, lifetime: chrono::Duration, config: &CookieConfig) -> Cookie<'static> {
+    let max_age = Duration::seconds(lifetime.num_seconds());
+
+    let mut cookie = Cookie::new(REFRESH_TOKEN_COOKIE_NAME, refresh_token.to_string());
+    cookie.set_path(REFRESH_TOKEN_COOKIE_PATH);
+    cookie.set_max_age(max_age);
+    cookie.set_expires(OffsetDateTime::now_utc() + max_age);
+    cookie.set_http_only(true);
+    cookie.set_secure(config.secure);
+    cookie.set_same_site(config.get_same_site());
+    if let Some(domain) = &config.domain {
+        cookie.set_domain(domain.clone());
+    }
+
+    cookie
+}
+
+async fn new_session_cookies(
+    server_services: &ServerService,
+    identity: &Identity,
+    refresh_token: Option<String>,
+) -> anyhow::Result<(Cookie<'static>, Cookie<'static>)> {
+    let refresh_token = match refresh_token {
+        Some(refresh_token) => refresh_token,
+        None => {
+            server_services
+                .customer_service
+                .customer_issue_refresh_token(identity)
+                .await?
+        }
+    };
+
+    let lifetime = server_services.session_lifetime;
+    let token = identity.to_string(&server_services.jwt_key_set)?;
+    let cookie_config = &server_services.config.cookie;
+    let cookie = new_cookie(&token, lifetime.access_token, cookie_config);
+    let refresh_cookie = new_refresh_cookie(&refresh_token, lifetime.refresh_token, cookie_config);
+
+    Ok((cookie, refresh_cookie))
+}
+
 pub async fn customer_signup_v1(
     server_services: web::Data<ServerService>,
     user_data: web::Json<CustomerSignupV1ReqDTO>,
-) -> impl Responder {
+) -> Result<HttpResponse, ApiError> {
     let svc = server_services.customer_service.clone();
-    let result = svc
+    let identity = svc
         .customer_signup(&user_data.username, &user_data.password)
-        .await;
+        .await?;
 
-    match result {
-        Ok(identity) => {
-            let resp: ResponseData<CustomerSignupV1RespDTO> = identity.clone().into();
-            let token = identity.to_string().unwrap();
-            let cookie = new_cookie(&token);
-            HttpResponse::Created().cookie(cookie).json(resp)
-        }
-        Err(err) => {
-            let domain_error: CustomerError = err.downcast().unwrap();
-            let resp: ResponseData<CustomerSignupV1RespDTO> = domain_error.into();
-            HttpResponse::BadRequest().json(resp)
-        }
-    }
+    let (cookie, refresh_cookie) = new_session_cookies(&server_services, &identity, None).await?;
+
+    let resp: ResponseData<CustomerSignupV1RespDTO> = identity.into();
+    Ok(HttpResponse::Created()
+        .cookie(cookie)
+        .cookie(refresh_cookie)
+        .json(resp))
 }
 
 pub async fn customer_signin_v1(
     server_services: web::Data<ServerService>,
     user_data: web::Json<CustomerSigninV1ReqDTO>,
-) -> impl Responder {
+) -> Result<HttpResponse, ApiError> {
     let svc = server_services.customer_service.clone();
-    let svc_result = svc
+    let identity = svc
         .customer_signin(&user_data.username, &user_data.password)
-        .await;
-
-    match svc_result {
-        Ok(identity) => {
-            let resp: ResponseData<CustomerSignupV1RespDTO> = identity.clone().into();
-            let token = identity.to_string().unwrap();
-            let cookie = new_cookie(&token);
-            HttpResponse::Created().cookie(cookie).json(resp)
-        }
-        Err(err) => {
-            let domain_error: CustomerError = err.downcast().unwrap();
-            let resp: ResponseData<CustomerSignupV1RespDTO> = domain_error.into();
-            HttpResponse::Unauthorized().json(resp)
-        }
-    }
+        .await?;
+
+    let (cookie, refresh_cookie) = new_session_cookies(&server_services, &identity, None).await?;
+
+    let resp: ResponseData<CustomerSigninV1RespDTO> = identity.into();
+    Ok(HttpResponse::Created()
+        .cookie(cookie)
+        .cookie(refresh_cookie)
+        .json(resp))
 }
 
 pub async fn customer_signout_v1(
     server_services: web::Data<ServerService>,
+    customer: AuthenticatedCustomer,
     request: HttpRequest,
-) -> impl Responder {
-    let token = match request.cookie("token") {
-        Some(token) => token,
-        None => {
-            return HttpResponse::Unauthorized().finish();
-        }
-    };
-
+) -> Result<HttpResponse, ApiError> {
     let svc = server_services.customer_service.clone();
-    let identity = Identity::from_string(token.value()).unwrap();
-    let result = svc.customer_signout(&identity).await;
+    svc.customer_signout(customer.get_token()).await?;
+
+    if let Some(refresh_cookie) = request.cookie(REFRESH_TOKEN_COOKIE_NAME) {
+        let _ = svc.customer_revoke_refresh_token(refresh_cookie.value()).await;
+    }
 
-    let cookie = new_cookie("");
-    HttpResponse::Ok().cookie(cookie).finish()
+    let cookie = new_cookie("", chrono::Duration::zero(), &server_services.config.cookie);
+    let refresh_cookie = new_refresh_cookie("", chrono::Duration::zero(), &server_services.config.cookie);
+    Ok(HttpResponse::Ok()
+        .cookie(cookie)
+        .cookie(refresh_cookie)
+        .finish())
 }
 
-pub async fn customer_get_by_id_v1(
+pub async fn customer_refresh_v1(
     server_services: web::Data<ServerService>,
     request: HttpRequest,
-    id: web::Path<String>,
-) -> impl Responder {
-    let user_id: &str = id.as_str();
-    if user_id != "self" {
-        return HttpResponse::BadRequest().finish();
-    }
-
-    let token = match request.cookie("token") {
-        Some(token) => token,
-        None => {
-            return HttpResponse::Unauthorized().finish();
-        }
-    };
-
-    let identity = match Identity::from_string(&token.value()) {
-        Ok(identity) => identity,
-        Err(_err) => {
-            return HttpResponse::Unauthorized().finish();
-        }
+) -> Result<HttpResponse, ApiError> {
+    let refresh_token = match request.cookie(REFRESH_TOKEN_COOKIE_NAME) {
+        Some(refresh_token) => refresh_token,
+        None => return Err(CustomerError::CustomerInvalidRefreshToken.into()),
     };
 
     let svc = server_services.customer_service.clone();
-    let result = svc.get_customer_by_id(&identity.get_id()).await;
+    let result = svc.customer_refresh(refresh_token.value()).await;
 
     match result {
-        Ok(file_meta) => {
-            let resp: ResponseData<CustomerGetByIdV1RespDTO> = file_meta.into();
-            HttpResponse::Ok().json(resp)
-        },
+        Ok((identity, next_refresh_token)) => {
+            let (cookie, refresh_cookie) = new_session_cookies(&server_services, &identity, Some(next_refresh_token)).await?;
+
+            let resp: ResponseData<CustomerRefreshV1RespDTO> = identity.into();
+            Ok(HttpResponse::Ok()
+                .cookie(cookie)
+                .cookie(refresh_cookie)
+                .json(resp))
+        }
         Err(err) => {
-            let domain_err: CustomerError = err.downcast().unwrap();
-            let resp: ResponseData<CustomerGetByIdV1RespDTO> = domain_err.clone().into();
-
-            match domain_err {
-                CustomerError::CustomerNotFound => HttpResponse::NotFound().json(resp),
-                _ => HttpResponse::InternalServerError().json(resp),
+            let api_error = ApiError::from(err);
+            if !matches!(api_error, ApiError::Customer(_)) {
+                return Err(api_error);
             }
+
+            // NOTE: the refresh token is unusable from now on, drop both cookies so the client
+            //       does not keep retrying with it.
+            let mut response = api_error.error_response();
+            let cookie = new_cookie("", chrono::Duration::zero(), &server_services.config.cookie);
+            let refresh_cookie = new_refresh_cookie("", chrono::Duration::zero(), &server_services.config.cookie);
+            response.add_cookie(&cookie).map_err(|err| ApiError::Internal(err.into()))?;
+            response.add_cookie(&refresh_cookie).map_err(|err| ApiError::Internal(err.into()))?;
+
+            Ok(response)
         }
     }
 }
 
+pub async fn customer_get_by_id_v1(
+    server_services: web::Data<ServerService>,
+    customer: AuthenticatedCustomer,
+    id: web::Path<String>,
+) -> Result<HttpResponse, ApiError> {
+    let user_id: &str = id.as_str();
+    if user_id != "self" {
+        return Err(ApiError::InvalidRequest(String::from("only the self customer can be read")));
+    }
+
+    let identity = customer.get_identity();
+    let svc = server_services.customer_service.clone();
+    let customer = svc.get_customer_by_id(&identity.get_id()).await?;
+
+    let resp: ResponseData<CustomerGetByIdV1RespDTO> = customer.into();
+    Ok(HttpResponse::Ok().json(resp))
+}
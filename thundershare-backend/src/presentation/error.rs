@@ -0,0 +1,226 @@
+use actix_web::http::StatusCode;
+use actix_web::{HttpRequest, HttpResponse, ResponseError};
+use thiserror::Error;
+
+use crate::domain::error::customer::CustomerError;
+use crate::domain::error::file::FileError;
+use crate::domain::error::folder::FolderError;
+use crate::domain::error::upload::UploadError;
+use crate::presentation::ResponseData;
+
+// NOTE: the single error type every handler returns. domain errors are shown to the client as is,
+//       everything else is logged and replaced by a generic message so that sql, io or jwt details
+//       never end up in a response body.
+#[derive(Error, Debug)]
+pub enum ApiError {
+    #[error(transparent)]
+    Customer(#[from] CustomerError),
+
+    #[error(transparent)]
+    File(#[from] FileError),
+
+    #[error(transparent)]
+    Upload(#[from] UploadError),
+
+    #[error(transparent)]
+    Folder(#[from] FolderError),
+
+    #[error("{0}")]
+    InvalidRequest(String),
+
+    #[error("authentication required")]
+    Unauthenticated,
+
+    #[error("database failure")]
+    Database(#[source] sqlx::Error),
+
+    #[error("storage failure")]
+    Storage(#[source] std::io::Error),
+
+    #[error("internal server error")]
+    Internal(#[source] anyhow::Error),
+}
+
+impl From<anyhow::Error> for ApiError {
+    fn from(err: anyhow::Error) -> ApiError {
+        let err = match err.downcast::<CustomerError>() {
+            Ok(err) => return ApiError::Customer(err),
+            Err(err) => err,
+        };
+        let err = match err.downcast::<FileError>() {
+            Ok(err) => return ApiError::File(err),
+            Err(err) => err,
+        };
+        let err = match err.downcast::<UploadError>() {
+            Ok(err) => return ApiError::Upload(err),
+            Err(err) => err,
+        };
+        let err = match err.downcast::<FolderError>() {
+            Ok(err) => return ApiError::Folder(err),
+            Err(err) => err,
+        };
+        let err = match err.downcast::<sqlx::Error>() {
+            Ok(err) => return ApiError::Database(err),
+            Err(err) => err,
+        };
+        match err.downcast::<std::io::Error>() {
+            Ok(err) => ApiError::Storage(err),
+            Err(err) => ApiError::Internal(err),
+        }
+    }
+}
+
+impl ApiError {
+    // NOTE: part of the public api, clients match on these so existing values must never change
+    pub fn error_code(&self) -> &'static str {
+        match self {
+            ApiError::Customer(err) => match err {
+                CustomerError::CustomerAlreadyExist => "CUSTOMER_ALREADY_EXIST",
+                CustomerError::CustomerInvalidCredential => "CUSTOMER_INVALID_CREDENTIAL",
+                CustomerError::CustomerNotFound => "CUSTOMER_NOT_FOUND",
+                CustomerError::CustomerInvalidToken => "CUSTOMER_INVALID_TOKEN",
+                CustomerError::CustomerTokenRevoked => "CUSTOMER_TOKEN_REVOKED",
+                CustomerError::CustomerInvalidRefreshToken => "CUSTOMER_INVALID_REFRESH_TOKEN",
+                CustomerError::CustomerRefreshTokenReused => "CUSTOMER_REFRESH_TOKEN_REUSED",
+            },
+            ApiError::File(err) => match err {
+                FileError::FileNotFound => "FILE_NOT_FOUND",
+                FileError::FileNotBelongToCustomer => "FILE_NOT_BELONG_TO_CUSTOMER",
+                FileError::FileSharingLinkExpired => "FILE_SHARING_LINK_EXPIRED",
+                FileError::FileSharingLinkPasswordIncorrect => {
+                    "FILE_SHARING_LINK_PASSWORD_INCORRECT"
+                }
+                FileError::FileSharingLinkExpireAtInvalid => "FILE_SHARING_LINK_EXPIREAT_INVALID",
+                FileError::FileTooLarge => "FILE_TOO_LARGE",
+                FileError::FileNotInTrash => "FILE_NOT_IN_TRASH",
+                FileError::FileSharingLinkNotFound => "FILE_SHARING_LINK_NOT_FOUND",
+                FileError::FileSharingLinkLocked => "FILE_SHARING_LINK_LOCKED",
+                FileError::FileSharingLinkExhausted => "FILE_SHARING_LINK_EXHAUSTED",
+                FileError::FileSharingLinkMaxDownloadsInvalid => "FILE_SHARING_LINK_MAX_DOWNLOADS_INVALID",
+                FileError::FileRequestNotFound => "FILE_REQUEST_NOT_FOUND",
+                FileError::FileRequestExpired => "FILE_REQUEST_EXPIRED",
+                FileError::FileRequestExpireAtInvalid => "FILE_REQUEST_EXPIREAT_INVALID",
+                FileError::FileRequestFull => "FILE_REQUEST_FULL",
+                FileError::FileRequestMaxFilesInvalid => "FILE_REQUEST_MAX_FILES_INVALID",
+                FileError::FileRequestMaxSizeInvalid => "FILE_REQUEST_MAX_SIZE_INVALID",
+                FileError::FileRequestFolderInvalid => "FILE_REQUEST_FOLDER_INVALID",
+                FileError::FilePermissionDenied => "FILE_PERMISSION_DENIED",
+                FileError::FileGrantNotFound => "FILE_GRANT_NOT_FOUND",
+                FileError::FileGrantToOwner => "FILE_GRANT_TO_OWNER",
+                FileError::FileSharingUnlockTokenInvalid => "FILE_SHARING_UNLOCK_TOKEN_INVALID",
+            },
+            ApiError::Upload(err) => match err {
+                UploadError::UploadNotFound => "UPLOAD_NOT_FOUND",
+                UploadError::UploadNotBelongToCustomer => "UPLOAD_NOT_BELONG_TO_CUSTOMER",
+                UploadError::UploadOffsetMismatch => "UPLOAD_OFFSET_MISMATCH",
+                UploadError::UploadLengthExceeded => "UPLOAD_LENGTH_EXCEEDED",
+                UploadError::UploadChecksumAlgorithmUnsupported => {
+                    "UPLOAD_CHECKSUM_ALGORITHM_UNSUPPORTED"
+                }
+                UploadError::UploadChecksumMismatch => "UPLOAD_CHECKSUM_MISMATCH",
+                UploadError::UploadVersionUnsupported => "UPLOAD_VERSION_UNSUPPORTED",
+                UploadError::UploadContentTypeInvalid => "UPLOAD_CONTENT_TYPE_INVALID",
+            },
+            ApiError::Folder(err) => match err {
+                FolderError::FolderNotFound => "FOLDER_NOT_FOUND",
+                FolderError::FolderNotBelongToCustomer => "FOLDER_NOT_BELONG_TO_CUSTOMER",
+                FolderError::FolderNameInvalid => "FOLDER_NAME_INVALID",
+                FolderError::FolderNameConflict => "FOLDER_NAME_CONFLICT",
+                FolderError::FolderMoveCycle => "FOLDER_MOVE_CYCLE",
+                FolderError::FolderNotEmpty => "FOLDER_NOT_EMPTY",
+            },
+            ApiError::InvalidRequest(_) => "INVALID_REQUEST",
+            ApiError::Unauthenticated => "UNAUTHENTICATED",
+            ApiError::Database(_) => "DATABASE_ERROR",
+            ApiError::Storage(_) => "STORAGE_ERROR",
+            ApiError::Internal(_) => "INTERNAL_ERROR",
+        }
+    }
+}
+
+// NOTE: "460 Checksum Mismatch" is defined by the tus checksum extension, not by http itself
+fn upload_checksum_mismatch_status() -> StatusCode {
+    StatusCode::from_u16(460).unwrap_or(StatusCode::BAD_REQUEST)
+}
+
+impl ResponseError for ApiError {
+    fn status_code(&self) -> StatusCode {
+        match self {
+            ApiError::Customer(err) => match err {
+                CustomerError::CustomerAlreadyExist => StatusCode::BAD_REQUEST,
+                CustomerError::CustomerNotFound => StatusCode::NOT_FOUND,
+                CustomerError::CustomerInvalidCredential
+                | CustomerError::CustomerInvalidToken
+                | CustomerError::CustomerTokenRevoked
+                | CustomerError::CustomerInvalidRefreshToken
+                | CustomerError::CustomerRefreshTokenReused => StatusCode::UNAUTHORIZED,
+            },
+            ApiError::File(err) => match err {
+                FileError::FileNotFound => StatusCode::NOT_FOUND,
+                FileError::FileNotBelongToCustomer => StatusCode::FORBIDDEN,
+                FileError::FileSharingLinkExpired => StatusCode::FORBIDDEN,
+                FileError::FileSharingLinkPasswordIncorrect => StatusCode::UNAUTHORIZED,
+                FileError::FileSharingLinkExpireAtInvalid => StatusCode::BAD_REQUEST,
+                FileError::FileTooLarge => StatusCode::PAYLOAD_TOO_LARGE,
+                FileError::FileNotInTrash => StatusCode::NOT_FOUND,
+                FileError::FileSharingLinkNotFound => StatusCode::NOT_FOUND,
+                FileError::FileSharingLinkLocked => StatusCode::TOO_MANY_REQUESTS,
+                FileError::FileSharingLinkExhausted => StatusCode::FORBIDDEN,
+                FileError::FileSharingLinkMaxDownloadsInvalid => StatusCode::BAD_REQUEST,
+                FileError::FileRequestNotFound => StatusCode::NOT_FOUND,
+                FileError::FileRequestExpired => StatusCode::FORBIDDEN,
+                FileError::FileRequestExpireAtInvalid => StatusCode::BAD_REQUEST,
+                FileError::FileRequestFull => StatusCode::FORBIDDEN,
+                FileError::FileRequestMaxFilesInvalid => StatusCode::BAD_REQUEST,
+                FileError::FileRequestMaxSizeInvalid => StatusCode::BAD_REQUEST,
+                FileError::FileRequestFolderInvalid => StatusCode::BAD_REQUEST,
+                FileError::FilePermissionDenied => StatusCode::FORBIDDEN,
+                FileError::FileGrantNotFound => StatusCode::NOT_FOUND,
+                FileError::FileGrantToOwner => StatusCode::BAD_REQUEST,
+                FileError::FileSharingUnlockTokenInvalid => StatusCode::UNAUTHORIZED,
+            },
+            // NOTE: the status codes are the ones the tus protocol prescribes
+            ApiError::Upload(err) => match err {
+                UploadError::UploadNotFound => StatusCode::NOT_FOUND,
+                UploadError::UploadNotBelongToCustomer => StatusCode::FORBIDDEN,
+                UploadError::UploadOffsetMismatch => StatusCode::CONFLICT,
+                UploadError::UploadLengthExceeded => StatusCode::PAYLOAD_TOO_LARGE,
+                UploadError::UploadChecksumAlgorithmUnsupported => StatusCode::BAD_REQUEST,
+                UploadError::UploadChecksumMismatch => upload_checksum_mismatch_status(),
+                UploadError::UploadVersionUnsupported => StatusCode::PRECONDITION_FAILED,
+                UploadError::UploadContentTypeInvalid => StatusCode::UNSUPPORTED_MEDIA_TYPE,
+            },
+            ApiError::Folder(err) => match err {
+                FolderError::FolderNotFound => StatusCode::NOT_FOUND,
+                FolderError::FolderNotBelongToCustomer => StatusCode::FORBIDDEN,
+                FolderError::FolderNameInvalid => StatusCode::BAD_REQUEST,
+                FolderError::FolderNameConflict
+                | FolderError::FolderMoveCycle
+                | FolderError::FolderNotEmpty => StatusCode::CONFLICT,
+            },
+            ApiError::InvalidRequest(_) => StatusCode::BAD_REQUEST,
+            ApiError::Unauthenticated => StatusCode::UNAUTHORIZED,
+            ApiError::Database(_) | ApiError::Storage(_) | ApiError::Internal(_) => {
+                StatusCode::INTERNAL_SERVER_ERROR
+            }
+        }
+    }
+
+    fn error_response(&self) -> HttpResponse {
+        if let ApiError::Database(_) | ApiError::Storage(_) | ApiError::Internal(_) = self {
+            log::error!("{}: {:?}", self.error_code(), self);
+        }
+
+        let resp: ResponseData<()> = ResponseData::new_error(self.error_code(), self.to_string());
+        HttpResponse::build(self.status_code()).json(resp)
+    }
+}
+
+// NOTE: used as the error handler of the json and path extractors so malformed
+//       requests get the same response body as every other error.
+pub fn invalid_request_handler<E: std::fmt::Display>(
+    err: E,
+    _request: &HttpRequest,
+) -> actix_web::Error {
+    ApiError::InvalidRequest(err.to_string()).into()
+}
@@ -0,0 +1,149 @@
+use actix_web::body::to_bytes;
+use actix_web::http::StatusCode;
+use actix_web::ResponseError;
+use anyhow::anyhow;
+
+use super::error::ApiError;
+use crate::domain::error::customer::CustomerError;
+use crate::domain::error::file::FileError;
+use crate::domain::error::folder::FolderError;
+use crate::domain::error::upload::UploadError;
+
+#[test]
+fn test_api_error_from_anyhow() {
+    let test_context = vec![
+        (
+            anyhow!(CustomerError::CustomerNotFound),
+            StatusCode::NOT_FOUND,
+            "CUSTOMER_NOT_FOUND",
+        ),
+        (
+            anyhow!(CustomerError::CustomerInvalidCredential),
+            StatusCode::UNAUTHORIZED,
+            "CUSTOMER_INVALID_CREDENTIAL",
+        ),
+        (
+            anyhow!(FileError::FileNotBelongToCustomer),
+            StatusCode::FORBIDDEN,
+            "FILE_NOT_BELONG_TO_CUSTOMER",
+        ),
+        (
+            anyhow!(FileError::FileTooLarge),
+            StatusCode::PAYLOAD_TOO_LARGE,
+            "FILE_TOO_LARGE",
+        ),
+        (
+            anyhow!(FileError::FileNotInTrash),
+            StatusCode::NOT_FOUND,
+            "FILE_NOT_IN_TRASH",
+        ),
+        (
+            anyhow!(FileError::FileSharingLinkNotFound),
+            StatusCode::NOT_FOUND,
+            "FILE_SHARING_LINK_NOT_FOUND",
+        ),
+        (
+            anyhow!(FileError::FileSharingLinkLocked),
+            StatusCode::TOO_MANY_REQUESTS,
+            "FILE_SHARING_LINK_LOCKED",
+        ),
+        (
+            anyhow!(FileError::FileSharingLinkExhausted),
+            StatusCode::FORBIDDEN,
+            "FILE_SHARING_LINK_EXHAUSTED",
+        ),
+        (
+            anyhow!(FileError::FileSharingLinkMaxDownloadsInvalid),
+            StatusCode::BAD_REQUEST,
+            "FILE_SHARING_LINK_MAX_DOWNLOADS_INVALID",
+        ),
+        (
+            anyhow!(FileError::FileRequestFolderInvalid),
+            StatusCode::BAD_REQUEST,
+            "FILE_REQUEST_FOLDER_INVALID",
+        ),
+        (
+            anyhow!(FileError::FileRequestFull),
+            StatusCode::FORBIDDEN,
+            "FILE_REQUEST_FULL",
+        ),
+        (
+            anyhow!(FileError::FilePermissionDenied),
+            StatusCode::FORBIDDEN,
+            "FILE_PERMISSION_DENIED",
+        ),
+        (
+            anyhow!(FileError::FileSharingUnlockTokenInvalid),
+            StatusCode::UNAUTHORIZED,
+            "FILE_SHARING_UNLOCK_TOKEN_INVALID",
+        ),
+        (
+            anyhow!(FolderError::FolderMoveCycle),
+            StatusCode::CONFLICT,
+            "FOLDER_MOVE_CYCLE",
+        ),
+        (
+            anyhow!(UploadError::UploadOffsetMismatch),
+            StatusCode::CONFLICT,
+            "UPLOAD_OFFSET_MISMATCH",
+        ),
+        (
+            anyhow!(UploadError::UploadChecksumMismatch),
+            StatusCode::from_u16(460).unwrap(),
+            "UPLOAD_CHECKSUM_MISMATCH",
+        ),
+        (
+            anyhow!(sqlx::Error::RowNotFound),
+            StatusCode::INTERNAL_SERVER_ERROR,
+            "DATABASE_ERROR",
+        ),
+        (
+            anyhow!(std::io::Error::from(std::io::ErrorKind::NotFound)),
+            StatusCode::INTERNAL_SERVER_ERROR,
+            "STORAGE_ERROR",
+        ),
+        (
+            anyhow!("InvalidSignature"),
+            StatusCode::INTERNAL_SERVER_ERROR,
+            "INTERNAL_ERROR",
+        ),
+    ];
+
+    for (err, expected_status, expected_code) in test_context {
+        let api_error = ApiError::from(err);
+        assert_eq!(api_error.status_code(), expected_status);
+        assert_eq!(api_error.error_code(), expected_code);
+    }
+}
+
+#[actix_rt::test]
+async fn test_api_error_response_hides_internal_details() {
+    let api_error = ApiError::from(anyhow!("password authentication failed for user pgsql"));
+    let body = to_bytes(api_error.error_response().into_body())
+        .await
+        .unwrap();
+    let body: serde_json::Value = serde_json::from_slice(&body).unwrap();
+
+    assert_eq!(
+        body,
+        serde_json::json!({
+            "success": false,
+            "error_code": "INTERNAL_ERROR",
+            "error_msg": "internal server error",
+            "data": null,
+        })
+    );
+}
+
+#[actix_rt::test]
+async fn test_api_error_response_shows_domain_error() {
+    let api_error = ApiError::from(anyhow!(FileError::FileSharingLinkExpired));
+    let response = api_error.error_response();
+    assert_eq!(response.status(), StatusCode::FORBIDDEN);
+
+    let body = to_bytes(response.into_body()).await.unwrap();
+    let body: serde_json::Value = serde_json::from_slice(&body).unwrap();
+
+    assert_eq!(body["error_code"], "FILE_SHARING_LINK_EXPIRED");
+    assert_eq!(body["error_msg"], "file sharing link is expired");
+}
@@ -1,71 +1,210 @@
+use std::collections::BTreeMap;
 use std::sync::Arc;
 
-use actix_multipart::form::{tempfile::TempFile, MultipartForm};
-use actix_web::{HttpResponse, Responder};
-use chrono::{DateTime, Utc};
+use actix_multipart::Field;
+use actix_web::Responder;
+use chrono::{DateTime, Duration, Utc};
 use uuid::Uuid;
 
-use crate::{domain::{entity::file_meta::{FileMeta, FileSharingMeta}, error::file::FileError}, presentation::ResponseData};
+use crate::{domain::entity::{file_grant::{FileGrant, FilePermission, SharedFile}, file_list::{FileMetaCursor, FileMetaFilter, FileMetaListQuery, FileMetaPage, FileMetaSortKey, SortOrder}, file_meta::{FileMeta, FileSharingMeta}, file_request::FileRequest, sharing_access::{SharingAccess, SharingAccessCursor, SharingAccessOutcome, SharingAccessPage, SharingAccessStats}}, presentation::{error::ApiError, ResponseData}};
 
 #[derive(serde::Serialize)]
 pub struct FileReadByIdV1RespDTO {
     id: Uuid,
+    name: String,
+    size: i64,
+    mime_type: String,
+    sha256: String,
+    folder_id: Option<Uuid>,
+    #[serde(with = "chrono::serde::ts_seconds")]
+    uploaded_at: DateTime<Utc>,
+    file_request_id: Option<Uuid>,
+}
+
+impl From<FileMeta> for FileReadByIdV1RespDTO {
+    fn from(data: FileMeta) -> FileReadByIdV1RespDTO {
+        let content = data.get_content();
+        FileReadByIdV1RespDTO {
+            id: data.get_id(),
+            name: content.get_name(),
+            size: content.get_size(),
+            mime_type: content.get_mime_type(),
+            sha256: content.get_sha256(),
+            folder_id: data.get_folder_id(),
+            uploaded_at: data.get_uploaded_at(),
+            file_request_id: data.get_file_request_id(),
+        }
+    }
 }
 
 impl From<FileMeta> for ResponseData<FileReadByIdV1RespDTO> {
     fn from(data: FileMeta) -> ResponseData<FileReadByIdV1RespDTO> {
-        let resp_data = Some(FileReadByIdV1RespDTO{id: data.get_id()});
+        let resp_data = Some(data.into());
         ResponseData::new(true, String::new(), resp_data)
     }
 }
 
-impl From<FileError> for ResponseData<FileReadByIdV1RespDTO> {
-    fn from(error: FileError) -> ResponseData<FileReadByIdV1RespDTO> {
-        ResponseData::new(false, error.to_string(), None)
-    }
-}
+// NOTE: list items carry the same fields as a single read
+type FileMetaListItemV1RespDTO = FileReadByIdV1RespDTO;
 
-#[derive(serde::Serialize)]
-struct FileMetaListItemV1RespDTO {
-    id: Uuid,
+pub const FILE_LIST_DEFAULT_LIMIT: u32 = 50;
+pub const FILE_LIST_MAX_LIMIT: u32 = 200;
+
+// NOTE: the query string of the file listing, times are unix timestamps like everywhere else in the api
+#[derive(serde::Deserialize)]
+pub struct FileListByCustomerIdV1ReqDTO {
+    cursor: Option<String>,
+    limit: Option<u32>,
+    sort: Option<FileMetaSortKey>,
+    order: Option<SortOrder>,
+    mime_type: Option<String>,
+    min_size: Option<i64>,
+    max_size: Option<i64>,
+    #[serde(default, with = "chrono::serde::ts_seconds_option")]
+    uploaded_after: Option<DateTime<Utc>>,
+    #[serde(default, with = "chrono::serde::ts_seconds_option")]
+    uploaded_before: Option<DateTime<Utc>>,
 }
 
-impl From<FileMeta> for FileMetaListItemV1RespDTO {
-    fn from(data: FileMeta) -> FileMetaListItemV1RespDTO {
-        FileMetaListItemV1RespDTO {
-            id: data.get_id()
+impl FileListByCustomerIdV1ReqDTO {
+    pub fn get_limit(&self) -> Result<u32, ApiError> {
+        match self.limit {
+            None => Ok(FILE_LIST_DEFAULT_LIMIT),
+            Some(limit) if (1..=FILE_LIST_MAX_LIMIT).contains(&limit) => Ok(limit),
+            Some(_) => Err(ApiError::InvalidRequest(format!("limit must be between 1 and {}", FILE_LIST_MAX_LIMIT))),
         }
     }
+
+    // NOTE: a cursor only makes sense for the sort it was issued for
+    pub fn get_query(&self) -> Result<FileMetaListQuery, ApiError> {
+        let sort_key = self.sort.unwrap_or_default();
+        let order = self.order.unwrap_or_default();
+
+        let cursor = match &self.cursor {
+            Some(cursor) => {
+                let cursor = FileMetaCursor::decode(cursor)
+                    .ok_or_else(|| ApiError::InvalidRequest(String::from("cursor is invalid")))?;
+                if cursor.get_sort_key() != sort_key || cursor.get_order() != order {
+                    return Err(ApiError::InvalidRequest(String::from("cursor was issued for another sort")));
+                }
+                Some(cursor)
+            }
+            None => None,
+        };
+
+        if let (Some(min_size), Some(max_size)) = (self.min_size, self.max_size) {
+            if min_size > max_size {
+                return Err(ApiError::InvalidRequest(String::from("min_size must not be greater than max_size")));
+            }
+        }
+
+        if let (Some(uploaded_after), Some(uploaded_before)) = (self.uploaded_after, self.uploaded_before) {
+            if uploaded_after > uploaded_before {
+                return Err(ApiError::InvalidRequest(String::from("uploaded_after must not be later than uploaded_before")));
+            }
+        }
+
+        let filter = FileMetaFilter::new(
+            self.mime_type.clone().filter(|mime_type| !mime_type.is_empty()),
+            self.min_size,
+            self.max_size,
+            self.uploaded_after,
+            self.uploaded_before,
+        );
+
+        Ok(FileMetaListQuery::new(sort_key, order, filter, cursor))
+    }
 }
 
+// NOTE: next_cursor is null on the last page
 #[derive(serde::Serialize)]
 pub struct FileListByCustomerIdV1RespDTO {
     file_meta_list: Vec<FileMetaListItemV1RespDTO>,
+    next_cursor: Option<String>,
 }
 
-impl From<Vec<FileMeta>> for ResponseData<FileListByCustomerIdV1RespDTO> {
-    fn from(data: Vec<FileMeta>) -> ResponseData<FileListByCustomerIdV1RespDTO> {
-        let file_meta_list: Vec<FileMetaListItemV1RespDTO> = data.into_iter()
+impl From<FileMetaPage> for ResponseData<FileListByCustomerIdV1RespDTO> {
+    fn from(data: FileMetaPage) -> ResponseData<FileListByCustomerIdV1RespDTO> {
+        let file_meta_list: Vec<FileMetaListItemV1RespDTO> = data.get_file_meta_list().into_iter()
         .map(|data| {
             data.into()
         })
         .collect();
 
-        let resp_data = Some(FileListByCustomerIdV1RespDTO{file_meta_list: file_meta_list});
+        let next_cursor = data.get_next_cursor().map(|cursor| cursor.encode());
+        let resp_data = Some(FileListByCustomerIdV1RespDTO{file_meta_list, next_cursor});
         ResponseData::new(true, String::new(), resp_data)
     }
 }
 
-#[derive(MultipartForm)]
-pub struct FileUploadV1ReqDTO{
-    #[multipart(limit = "32 MiB")]
-    data: TempFile
+#[derive(serde::Serialize)]
+pub struct TrashListItemV1RespDTO {
+    #[serde(flatten)]
+    file_meta: FileReadByIdV1RespDTO,
+    #[serde(with = "chrono::serde::ts_seconds")]
+    deleted_at: DateTime<Utc>,
+    #[serde(with = "chrono::serde::ts_seconds")]
+    purge_at: DateTime<Utc>,
+}
+
+#[derive(serde::Serialize)]
+pub struct TrashListByCustomerIdV1RespDTO {
+    file_meta_list: Vec<TrashListItemV1RespDTO>,
+}
+
+// NOTE: the retention is needed to tell the client when a file is purged for good
+impl From<(Vec<FileMeta>, Duration)> for ResponseData<TrashListByCustomerIdV1RespDTO> {
+    fn from((data, retention): (Vec<FileMeta>, Duration)) -> ResponseData<TrashListByCustomerIdV1RespDTO> {
+        let file_meta_list: Vec<TrashListItemV1RespDTO> = data.into_iter()
+        .filter_map(|data| {
+            let deleted_at = data.get_deleted_at()?;
+            Some(TrashListItemV1RespDTO {
+                file_meta: data.into(),
+                deleted_at,
+                purge_at: deleted_at + retention,
+            })
+        })
+        .collect();
+
+        let resp_data = Some(TrashListByCustomerIdV1RespDTO{file_meta_list});
+        ResponseData::new(true, String::new(), resp_data)
+    }
+}
+
+// NOTE: the multipart part carrying the file, every other part is skipped
+pub const FILE_UPLOAD_FIELD_NAME: &str = "data";
+
+pub struct FileUploadV1ReqDTO {
+    file_name: String,
+    content_type: Option<String>,
+}
+
+impl From<&Field> for FileUploadV1ReqDTO {
+    fn from(field: &Field) -> FileUploadV1ReqDTO {
+        FileUploadV1ReqDTO {
+            file_name: field.content_disposition().get_filename().unwrap_or_default().to_string(),
+            content_type: field.content_type().map(|mime| mime.essence_str().to_string()),
+        }
+    }
+}
+
+// NOTE: browsers may send a full client side path, only the last component is kept
+pub fn client_file_name(file_name: &str) -> String {
+    file_name
+        .rsplit(['/', '\\'])
+        .next()
+        .unwrap_or_default()
+        .trim()
+        .to_string()
 }
 
 impl FileUploadV1ReqDTO {
-    pub fn get_temp_filename(&self) -> String {
-        let temp_file = &self.data;
-        temp_file.file.path().to_str().unwrap().to_string()
+    pub fn get_file_name(&self) -> String {
+        client_file_name(&self.file_name)
+    }
+
+    pub fn get_content_type(&self) -> Option<String> {
+        self.content_type.clone()
     }
 }
 
@@ -81,20 +220,10 @@ impl From<FileMeta> for ResponseData<FileUploadV1RespDTO> {
     }
 }
 
-impl From<FileError> for ResponseData<FileUploadV1RespDTO> {
-    fn from(error: FileError) -> ResponseData<FileUploadV1RespDTO> {
-        ResponseData::new(false, error.to_string(), None)
-    }
-}
-
-pub fn map_domain_error_to_response<T: serde::Serialize>(err: FileError, resp: ResponseData<T>) -> HttpResponse {
-    match err {
-        FileError::FileNotFound => HttpResponse::NotFound().json(resp),
-        FileError::FileNotBelongToCustomer => HttpResponse::Forbidden().json(resp),
-        FileError::FileSharingLinkExpired => HttpResponse::Forbidden().json(resp),
-        FileError::FileSharingLinkPasswordIncorrect => HttpResponse::Unauthorized().json(resp),
-    }
-
+// NOTE: a folder id left out or set to null moves the file to the root of the customer
+#[derive(serde::Serialize, serde::Deserialize)]
+pub struct FileMoveV1ReqDTO {
+    pub folder_id: Option<Uuid>,
 }
 
 #[derive(serde::Serialize, serde::Deserialize)]
@@ -103,6 +232,8 @@ pub struct FileSharingCreateV1ReqDTO {
     #[serde(with = "chrono::serde::ts_seconds")]
     pub expireat: DateTime<Utc>,
     pub password: Option<String>,
+    // NOTE: left out for a link without download limit, 1 for a one-time link
+    pub max_downloads: Option<i32>,
 }
 
 #[derive(serde::Deserialize, serde::Serialize)]
@@ -111,6 +242,7 @@ pub struct FileSharingCreateV1RespDTO {
     link: String,
     #[serde(with = "chrono::serde::ts_seconds")]
     expireat: DateTime<Utc>,
+    max_downloads: Option<i32>,
 }
 
 impl From<FileSharingMeta> for ResponseData<FileSharingCreateV1RespDTO> {
@@ -119,18 +251,178 @@ impl From<FileSharingMeta> for ResponseData<FileSharingCreateV1RespDTO> {
             id: data.get_id(),
             link: data.get_link(),
             expireat: data.get_expireat(),
+            max_downloads: data.get_max_downloads(),
         });
 
         ResponseData::new(true, String::new(), resp_data)
     }
 }
 
-impl From<FileError> for ResponseData<FileSharingCreateV1RespDTO> {
-    fn from(error: FileError) -> ResponseData<FileSharingCreateV1RespDTO> {
-        ResponseData::new(false, error.to_string(), None)
+#[derive(serde::Serialize, serde::Deserialize)]
+pub struct FileSharingListV1ReqDTO {
+    pub file_id: Option<Uuid>,
+}
+
+// NOTE: the password itself is never handed back, only whether the link has one
+#[derive(serde::Serialize)]
+pub struct FileSharingReadV1RespDTO {
+    id: Uuid,
+    file_id: Uuid,
+    link: String,
+    #[serde(with = "chrono::serde::ts_seconds")]
+    expireat: DateTime<Utc>,
+    has_password: bool,
+    max_downloads: Option<i32>,
+    downloads: i32,
+}
+
+impl From<FileSharingMeta> for FileSharingReadV1RespDTO {
+    fn from(data: FileSharingMeta) -> FileSharingReadV1RespDTO {
+        FileSharingReadV1RespDTO {
+            id: data.get_id(),
+            file_id: data.get_file_id(),
+            link: data.get_link(),
+            expireat: data.get_expireat(),
+            has_password: data.has_password(),
+            max_downloads: data.get_max_downloads(),
+            downloads: data.get_downloads(),
+        }
+    }
+}
+
+impl From<FileSharingMeta> for ResponseData<FileSharingReadV1RespDTO> {
+    fn from(data: FileSharingMeta) -> ResponseData<FileSharingReadV1RespDTO> {
+        ResponseData::new(true, String::new(), Some(data.into()))
+    }
+}
+
+#[derive(serde::Serialize)]
+pub struct FileSharingListV1RespDTO {
+    file_sharing_list: Vec<FileSharingReadV1RespDTO>,
+}
+
+impl From<Vec<FileSharingMeta>> for ResponseData<FileSharingListV1RespDTO> {
+    fn from(data: Vec<FileSharingMeta>) -> ResponseData<FileSharingListV1RespDTO> {
+        let file_sharing_list: Vec<FileSharingReadV1RespDTO> = data.into_iter()
+        .map(|data| data.into())
+        .collect();
+
+        let resp_data = Some(FileSharingListV1RespDTO{file_sharing_list});
+        ResponseData::new(true, String::new(), resp_data)
+    }
+}
+
+// NOTE: a left out field is kept as it is, a password explicitly set to null is removed
+#[derive(serde::Serialize, serde::Deserialize)]
+pub struct FileSharingUpdateV1ReqDTO {
+    #[serde(default, with = "chrono::serde::ts_seconds_option")]
+    pub expireat: Option<DateTime<Utc>>,
+    #[serde(default, deserialize_with = "deserialize_present")]
+    pub password: Option<Option<String>>,
+}
+
+fn deserialize_present<'de, T, D>(deserializer: D) -> Result<Option<T>, D::Error>
+where
+    T: serde::Deserialize<'de>,
+    D: serde::Deserializer<'de>,
+{
+    T::deserialize(deserializer).map(Some)
+}
+
+pub const SHARING_ACCESS_LIST_DEFAULT_LIMIT: u32 = 50;
+pub const SHARING_ACCESS_LIST_MAX_LIMIT: u32 = 200;
+
+#[derive(serde::Deserialize)]
+pub struct FileSharingAccessListV1ReqDTO {
+    cursor: Option<String>,
+    limit: Option<u32>,
+}
+
+impl FileSharingAccessListV1ReqDTO {
+    pub fn get_limit(&self) -> Result<u32, ApiError> {
+        match self.limit {
+            None => Ok(SHARING_ACCESS_LIST_DEFAULT_LIMIT),
+            Some(limit) if (1..=SHARING_ACCESS_LIST_MAX_LIMIT).contains(&limit) => Ok(limit),
+            Some(_) => Err(ApiError::InvalidRequest(format!("limit must be between 1 and {}", SHARING_ACCESS_LIST_MAX_LIMIT))),
+        }
+    }
+
+    pub fn get_cursor(&self) -> Result<Option<SharingAccessCursor>, ApiError> {
+        match &self.cursor {
+            Some(cursor) => SharingAccessCursor::decode(cursor)
+                .map(Some)
+                .ok_or_else(|| ApiError::InvalidRequest(String::from("cursor is invalid"))),
+            None => Ok(None),
+        }
+    }
+}
+
+#[derive(serde::Serialize)]
+pub struct SharingAccessV1RespDTO {
+    id: Uuid,
+    #[serde(with = "chrono::serde::ts_seconds")]
+    accessed_at: DateTime<Utc>,
+    client_addr: String,
+    user_agent: Option<String>,
+    outcome: SharingAccessOutcome,
+    bytes_served: i64,
+}
+
+impl From<SharingAccess> for SharingAccessV1RespDTO {
+    fn from(data: SharingAccess) -> SharingAccessV1RespDTO {
+        SharingAccessV1RespDTO {
+            id: data.get_id(),
+            accessed_at: data.get_accessed_at(),
+            client_addr: data.get_client().get_addr(),
+            user_agent: data.get_client().get_user_agent(),
+            outcome: data.get_outcome(),
+            bytes_served: data.get_bytes_served(),
+        }
+    }
+}
+
+#[derive(serde::Serialize)]
+pub struct SharingAccessStatsV1RespDTO {
+    total_accesses: i64,
+    accesses: BTreeMap<SharingAccessOutcome, i64>,
+    bytes_served: i64,
+    #[serde(with = "chrono::serde::ts_seconds_option")]
+    last_accessed_at: Option<DateTime<Utc>>,
+}
+
+impl From<SharingAccessStats> for SharingAccessStatsV1RespDTO {
+    fn from(data: SharingAccessStats) -> SharingAccessStatsV1RespDTO {
+        SharingAccessStatsV1RespDTO {
+            total_accesses: data.get_total_accesses(),
+            accesses: data.get_accesses(),
+            bytes_served: data.get_bytes_served(),
+            last_accessed_at: data.get_last_accessed_at(),
+        }
     }
 }
 
+#[derive(serde::Serialize)]
+pub struct FileSharingAccessListV1RespDTO {
+    stats: SharingAccessStatsV1RespDTO,
+    access_list: Vec<SharingAccessV1RespDTO>,
+    next_cursor: Option<String>,
+}
+
+impl From<(SharingAccessStats, SharingAccessPage)> for ResponseData<FileSharingAccessListV1RespDTO> {
+    fn from((stats, page): (SharingAccessStats, SharingAccessPage)) -> ResponseData<FileSharingAccessListV1RespDTO> {
+        let access_list: Vec<SharingAccessV1RespDTO> = page.get_sharing_access_list()
+        .into_iter()
+        .map(|data| data.into())
+        .collect();
+
+        let resp_data = Some(FileSharingAccessListV1RespDTO{
+            stats: stats.into(),
+            access_list,
+            next_cursor: page.get_next_cursor().map(|cursor| cursor.encode()),
+        });
+        ResponseData::new(true, String::new(), resp_data)
+    }
+}
 
 #[derive(serde::Serialize, serde::Deserialize)]
 pub struct FileSharingGetByIdV1ReqDTO {
@@ -140,6 +432,57 @@ pub struct FileSharingGetByIdV1ReqDTO {
 #[derive(serde::Deserialize, serde::Serialize)]
 pub struct FileSharingGetByIdV1RespDTO {}
 
+// NOTE: what a landing page shows before the download, the content itself is never part of it
+#[derive(serde::Serialize)]
+pub struct FileSharingReadByIdV1RespDTO {
+    name: String,
+    size: i64,
+    mime_type: String,
+    #[serde(with = "chrono::serde::ts_seconds")]
+    expireat: DateTime<Utc>,
+    has_password: bool,
+    downloads_left: Option<i32>,
+}
+
+impl From<(FileSharingMeta, FileMeta)> for ResponseData<FileSharingReadByIdV1RespDTO> {
+    fn from((file_sharing_meta, file_meta): (FileSharingMeta, FileMeta)) -> ResponseData<FileSharingReadByIdV1RespDTO> {
+        let content = file_meta.get_content();
+        let resp_data = Some(FileSharingReadByIdV1RespDTO{
+            name: file_meta.get_download_name(),
+            size: content.get_size(),
+            mime_type: content.get_mime_type(),
+            expireat: file_sharing_meta.get_expireat(),
+            has_password: file_sharing_meta.has_password(),
+            downloads_left: file_sharing_meta
+                .get_max_downloads()
+                .map(|max_downloads| (max_downloads - file_sharing_meta.get_downloads()).max(0)),
+        });
+
+        ResponseData::new(true, String::new(), resp_data)
+    }
+}
+
+#[derive(serde::Serialize, serde::Deserialize)]
+pub struct FileSharingUnlockV1ReqDTO {
+    pub password: Option<String>,
+}
+
+#[derive(serde::Serialize)]
+pub struct FileSharingUnlockV1RespDTO {
+    token: String,
+    #[serde(with = "chrono::serde::ts_seconds")]
+    expireat: DateTime<Utc>,
+}
+
+impl FileSharingUnlockV1RespDTO {
+    pub fn new(token: &str, expireat: &DateTime<Utc>) -> FileSharingUnlockV1RespDTO {
+        FileSharingUnlockV1RespDTO {
+            token: token.to_string(),
+            expireat: *expireat,
+        }
+    }
+}
+
 impl From<FileSharingMeta> for ResponseData<FileSharingGetByIdV1RespDTO> {
     fn from(data: FileSharingMeta) -> ResponseData<FileSharingGetByIdV1RespDTO> {
         let resp_data = Some(FileSharingGetByIdV1RespDTO{});
@@ -148,8 +491,191 @@ impl From<FileSharingMeta> for ResponseData<FileSharingGetByIdV1RespDTO> {
     }
 }
 
-impl From<FileError> for ResponseData<FileSharingGetByIdV1RespDTO> {
-    fn from(error: FileError) -> ResponseData<FileSharingGetByIdV1RespDTO> {
-        ResponseData::new(false, error.to_string(), None)
+#[derive(serde::Serialize, serde::Deserialize)]
+pub struct FileRequestCreateV1ReqDTO {
+    // NOTE: left out for a request whose files end up at the root
+    pub folder_id: Option<Uuid>,
+    #[serde(with = "chrono::serde::ts_seconds")]
+    pub expireat: DateTime<Utc>,
+    pub password: Option<String>,
+    pub max_files: Option<i32>,
+    // NOTE: left out to take files as large as the customer could upload
+    pub max_size_bytes: Option<i64>,
+}
+
+// NOTE: the password itself is never handed back, only whether the request has one
+#[derive(serde::Serialize)]
+pub struct FileRequestReadV1RespDTO {
+    id: Uuid,
+    folder_id: Option<Uuid>,
+    link: String,
+    #[serde(with = "chrono::serde::ts_seconds")]
+    expireat: DateTime<Utc>,
+    has_password: bool,
+    max_files: Option<i32>,
+    max_size_bytes: i64,
+    uploads: i32,
+}
+
+impl From<FileRequest> for FileRequestReadV1RespDTO {
+    fn from(data: FileRequest) -> FileRequestReadV1RespDTO {
+        FileRequestReadV1RespDTO {
+            id: data.get_id(),
+            folder_id: data.get_folder_id(),
+            link: data.get_link(),
+            expireat: data.get_expireat(),
+            has_password: data.has_password(),
+            max_files: data.get_max_files(),
+            max_size_bytes: data.get_max_size_bytes(),
+            uploads: data.get_uploads(),
+        }
+    }
+}
+
+impl From<FileRequest> for ResponseData<FileRequestReadV1RespDTO> {
+    fn from(data: FileRequest) -> ResponseData<FileRequestReadV1RespDTO> {
+        ResponseData::new(true, String::new(), Some(data.into()))
+    }
+}
+
+#[derive(serde::Serialize)]
+pub struct FileRequestListV1RespDTO {
+    file_request_list: Vec<FileRequestReadV1RespDTO>,
+}
+
+impl From<Vec<FileRequest>> for ResponseData<FileRequestListV1RespDTO> {
+    fn from(data: Vec<FileRequest>) -> ResponseData<FileRequestListV1RespDTO> {
+        let file_request_list: Vec<FileRequestReadV1RespDTO> = data.into_iter()
+        .map(|data| data.into())
+        .collect();
+
+        let resp_data = Some(FileRequestListV1RespDTO{file_request_list});
+        ResponseData::new(true, String::new(), resp_data)
+    }
+}
+
+// NOTE: what an outsider needs to know before uploading, nothing about the account is shown
+#[derive(serde::Serialize)]
+pub struct FileRequestGetByCodeRespDTO {
+    #[serde(with = "chrono::serde::ts_seconds")]
+    expireat: DateTime<Utc>,
+    has_password: bool,
+    max_size_bytes: i64,
+    files_left: Option<i32>,
+}
+
+impl From<FileRequest> for ResponseData<FileRequestGetByCodeRespDTO> {
+    fn from(data: FileRequest) -> ResponseData<FileRequestGetByCodeRespDTO> {
+        let resp_data = Some(FileRequestGetByCodeRespDTO{
+            expireat: data.get_expireat(),
+            has_password: data.has_password(),
+            max_size_bytes: data.get_max_size_bytes(),
+            files_left: data.get_max_files().map(|max_files| (max_files - data.get_uploads()).max(0)),
+        });
+
+        ResponseData::new(true, String::new(), resp_data)
+    }
+}
+
+// NOTE: enough for the outsider to check what arrived, the id of the file is kept from them
+#[derive(serde::Serialize)]
+pub struct FileRequestUploadByCodeRespDTO {
+    name: String,
+    size: i64,
+    mime_type: String,
+    sha256: String,
+}
+
+impl From<FileMeta> for ResponseData<FileRequestUploadByCodeRespDTO> {
+    fn from(data: FileMeta) -> ResponseData<FileRequestUploadByCodeRespDTO> {
+        let content = data.get_content();
+        let resp_data = Some(FileRequestUploadByCodeRespDTO{
+            name: content.get_name(),
+            size: content.get_size(),
+            mime_type: content.get_mime_type(),
+            sha256: content.get_sha256(),
+        });
+
+        ResponseData::new(true, String::new(), resp_data)
+    }
+}
+
+// NOTE: the grantee is named by username, sharing again with the same customer changes the permission
+#[derive(serde::Serialize, serde::Deserialize)]
+pub struct FileGrantV1ReqDTO {
+    pub username: String,
+    pub permission: FilePermission,
+}
+
+#[derive(serde::Serialize)]
+pub struct FileGrantReadV1RespDTO {
+    customer_id: Uuid,
+    username: String,
+    permission: FilePermission,
+    #[serde(with = "chrono::serde::ts_seconds")]
+    granted_at: DateTime<Utc>,
+}
+
+impl From<FileGrant> for FileGrantReadV1RespDTO {
+    fn from(data: FileGrant) -> FileGrantReadV1RespDTO {
+        FileGrantReadV1RespDTO {
+            customer_id: data.get_customer_id(),
+            username: data.get_username(),
+            permission: data.get_permission(),
+            granted_at: data.get_granted_at(),
+        }
+    }
+}
+
+impl From<FileGrant> for ResponseData<FileGrantReadV1RespDTO> {
+    fn from(data: FileGrant) -> ResponseData<FileGrantReadV1RespDTO> {
+        ResponseData::new(true, String::new(), Some(data.into()))
+    }
+}
+
+#[derive(serde::Serialize)]
+pub struct FileGrantListV1RespDTO {
+    file_grant_list: Vec<FileGrantReadV1RespDTO>,
+}
+
+impl From<Vec<FileGrant>> for ResponseData<FileGrantListV1RespDTO> {
+    fn from(data: Vec<FileGrant>) -> ResponseData<FileGrantListV1RespDTO> {
+        let file_grant_list: Vec<FileGrantReadV1RespDTO> = data.into_iter()
+        .map(|data| data.into())
+        .collect();
+
+        let resp_data = Some(FileGrantListV1RespDTO{file_grant_list});
+        ResponseData::new(true, String::new(), resp_data)
+    }
+}
+
+#[derive(serde::Serialize)]
+pub struct SharedFileListItemV1RespDTO {
+    #[serde(flatten)]
+    file_meta: FileReadByIdV1RespDTO,
+    owner_username: String,
+    permission: FilePermission,
+    #[serde(with = "chrono::serde::ts_seconds")]
+    granted_at: DateTime<Utc>,
+}
+
+#[derive(serde::Serialize)]
+pub struct SharedFileListV1RespDTO {
+    file_meta_list: Vec<SharedFileListItemV1RespDTO>,
+}
+
+impl From<Vec<SharedFile>> for ResponseData<SharedFileListV1RespDTO> {
+    fn from(data: Vec<SharedFile>) -> ResponseData<SharedFileListV1RespDTO> {
+        let file_meta_list: Vec<SharedFileListItemV1RespDTO> = data.into_iter()
+        .map(|data| SharedFileListItemV1RespDTO {
+            file_meta: data.get_file_meta().clone().into(),
+            owner_username: data.get_owner_username(),
+            permission: data.get_permission(),
+            granted_at: data.get_granted_at(),
+        })
+        .collect();
+
+        let resp_data = Some(SharedFileListV1RespDTO{file_meta_list});
+        ResponseData::new(true, String::new(), resp_data)
     }
 }
@@ -0,0 +1,78 @@
+use actix_web::web;
+use chrono::{DateTime, TimeZone, Utc};
+use uuid::uuid;
+
+use super::dto::{FileListByCustomerIdV1ReqDTO, FileSharingUpdateV1ReqDTO, FILE_LIST_DEFAULT_LIMIT};
+use crate::domain::entity::{file_list::{FileMetaCursor, FileMetaFilter, FileMetaListQuery, FileMetaSortKey, SortOrder}, file_meta::{FileContent, FileMeta}};
+
+fn parse(query: &str) -> FileListByCustomerIdV1ReqDTO {
+    web::Query::<FileListByCustomerIdV1ReqDTO>::from_query(query).unwrap().into_inner()
+}
+
+#[test]
+fn test_file_list_req_dto_defaults() {
+    let form = parse("");
+    assert_eq!(form.get_limit().unwrap(), FILE_LIST_DEFAULT_LIMIT);
+    assert_eq!(form.get_query().unwrap(), FileMetaListQuery::new(FileMetaSortKey::UploadedAt, SortOrder::Desc, FileMetaFilter::default(), None));
+}
+
+#[test]
+fn test_file_list_req_dto() {
+    let file_meta = FileMeta::new_with_content(&uuid!("67e55044-10b1-426f-9247-bb680e5fe0c8"), &uuid!("8a1f2c7e-4b3d-4e5f-9a6b-7c8d9e0f1a2b"), "", &FileContent::default(), &DateTime::<Utc>::default());
+    let cursor = FileMetaCursor::new(&file_meta, FileMetaSortKey::Size, SortOrder::Asc);
+
+    let form = parse(&format!(
+        "limit=20&sort=size&order=asc&mime_type=image/&min_size=1&max_size=1024&uploaded_after=636422400&uploaded_before=636508800&cursor={}",
+        cursor.encode()
+    ));
+    let filter = FileMetaFilter::new(
+        Some(String::from("image/")),
+        Some(1),
+        Some(1024),
+        Some(Utc.with_ymd_and_hms(1990, 3, 3, 0, 0, 0).unwrap()),
+        Some(Utc.with_ymd_and_hms(1990, 3, 4, 0, 0, 0).unwrap()),
+    );
+
+    assert_eq!(form.get_limit().unwrap(), 20);
+    assert_eq!(form.get_query().unwrap(), FileMetaListQuery::new(FileMetaSortKey::Size, SortOrder::Asc, filter, Some(cursor)));
+}
+
+#[test]
+fn test_file_list_req_dto_invalid() {
+    let file_meta = FileMeta::new_with_content(&uuid!("67e55044-10b1-426f-9247-bb680e5fe0c8"), &uuid!("8a1f2c7e-4b3d-4e5f-9a6b-7c8d9e0f1a2b"), "", &FileContent::default(), &DateTime::<Utc>::default());
+    let name_cursor = FileMetaCursor::new(&file_meta, FileMetaSortKey::Name, SortOrder::Desc).encode();
+
+    for query in ["limit=0", "limit=201"] {
+        let err = parse(query).get_limit().err().unwrap();
+        assert_eq!(err.error_code(), "INVALID_REQUEST");
+    }
+
+    for query in [
+        String::from("cursor=garbage"),
+        format!("sort=size&cursor={}", name_cursor),
+        format!("sort=name&order=asc&cursor={}", name_cursor),
+        String::from("min_size=10&max_size=1"),
+        String::from("uploaded_after=636508800&uploaded_before=636422400"),
+    ] {
+        let err = parse(&query).get_query().err().unwrap();
+        assert_eq!(err.error_code(), "INVALID_REQUEST", "{}", query);
+    }
+
+    assert!(web::Query::<FileListByCustomerIdV1ReqDTO>::from_query("sort=color").is_err());
+}
+
+#[test]
+fn test_file_sharing_update_req_dto() {
+    let test_context = vec![
+        ("{}", None, None),
+        (r#"{"expireat": 636508800}"#, Some(Utc.with_ymd_and_hms(1990, 3, 4, 0, 0, 0).unwrap()), None),
+        (r#"{"password": null}"#, None, Some(None)),
+        (r#"{"password": "secret"}"#, None, Some(Some(String::from("secret")))),
+    ];
+
+    for (body, expireat, password) in test_context {
+        let form: FileSharingUpdateV1ReqDTO = serde_json::from_str(body).unwrap();
+        assert_eq!(form.expireat, expireat);
+        assert_eq!(form.password, password);
+    }
+}
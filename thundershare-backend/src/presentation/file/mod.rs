@@ -1,2 +1,6 @@
 pub mod view;
-pub mod dto;
\ No newline at end of file
+#[cfg(test)]
+pub mod view_test;
+pub mod dto;
+#[cfg(test)]
+pub mod dto_test;
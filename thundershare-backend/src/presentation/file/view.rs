@@ -1,186 +1,740 @@
-use crate::domain::entity::identity::Identity;
+use crate::config::HttpConfig;
+use crate::domain::entity::file_meta::FileMeta;
+use crate::domain::entity::sharing_access::{SharingAccess, SharingClient};
+use crate::domain::entity::sharing_unlock::SharingUnlock;
+use crate::domain::entity::storage_object::{ByteRange, StorageObjectStat};
+use crate::domain::entity::storage_object::StorageStream;
 use crate::domain::error::file::FileError;
+use crate::domain::service::customer::CustomerServiceTrait;
 use crate::domain::service::file::FileServiceTrait;
+use crate::domain::service::folder::FolderServiceTrait;
 use crate::domain::service::ServerService;
+use crate::presentation::auth::AuthenticatedCustomer;
+use crate::presentation::error::ApiError;
 use crate::presentation::ResponseData;
 
-use actix_multipart::form::MultipartForm;
-use actix_web::Responder;
-use actix_web::{web, HttpRequest, HttpResponse};
-use actix_files::NamedFile;
-use tokio::stream;
+use actix_multipart::Multipart;
+use anyhow::anyhow;
+use actix_web::body::{BodySize, BoxBody, MessageBody, SizedStream};
+use actix_web::http::header::{self, Charset, ContentDisposition, ContentType, DispositionParam, DispositionType, ETag, EntityTag, ExtendedValue, IfNoneMatch, IfRange, LastModified};
+use actix_web::http::{Method, StatusCode};
+use actix_web::{web, HttpMessage, HttpRequest, HttpResponse};
+use actix_files::HttpRange;
+use bytes::Bytes;
+use chrono::Duration;
+use futures_util::{Stream, StreamExt};
+use std::net::SocketAddr;
+use std::pin::Pin;
+use std::sync::Arc;
+use std::task::{Context, Poll};
+use std::time::SystemTime;
+use tokio::sync::mpsc::{channel, Sender};
 use uuid::Uuid;
-use log::info;
 
-use super::dto::{map_domain_error_to_response, FileListByCustomerIdV1RespDTO, FileReadByIdV1RespDTO, FileSharingCreateV1ReqDTO, FileSharingCreateV1RespDTO, FileSharingGetByIdV1ReqDTO, FileUploadV1ReqDTO, FileUploadV1RespDTO};
+use super::dto::{FileGrantListV1RespDTO, FileGrantReadV1RespDTO, FileGrantV1ReqDTO, FileListByCustomerIdV1ReqDTO, FileListByCustomerIdV1RespDTO, FileMoveV1ReqDTO, FileReadByIdV1RespDTO, FileRequestCreateV1ReqDTO, FileRequestGetByCodeRespDTO, FileRequestListV1RespDTO, FileRequestReadV1RespDTO, FileRequestUploadByCodeRespDTO, FileSharingAccessListV1ReqDTO, FileSharingAccessListV1RespDTO, FileSharingCreateV1ReqDTO, FileSharingCreateV1RespDTO, FileSharingGetByIdV1ReqDTO, FileSharingListV1ReqDTO, FileSharingListV1RespDTO, FileSharingReadByIdV1RespDTO, FileSharingReadV1RespDTO, FileSharingUnlockV1ReqDTO, FileSharingUnlockV1RespDTO, FileSharingUpdateV1ReqDTO, FileUploadV1ReqDTO, FileUploadV1RespDTO, SharedFileListV1RespDTO, TrashListByCustomerIdV1RespDTO, FILE_UPLOAD_FIELD_NAME};
 
 pub async fn file_read_by_id_v1(
     server_services: web::Data<ServerService>,
-    request: HttpRequest,
+    customer: AuthenticatedCustomer,
     file_id: web::Path<Uuid>,
-) -> impl Responder {
-    // NOTE: authn checking
-    let token = match request.cookie("token") {
-        Some(token) => token,
-        None => {
-            return HttpResponse::Unauthorized().finish();
-        }
-    };
+) -> Result<HttpResponse, ApiError> {
+    let identity = customer.get_identity();
+    let svc = server_services.file_service.clone();
+    let file_meta = svc.file_read_by_id(&file_id, &identity.get_id()).await?;
 
-    // TODO: Add proper authz checking
-    let identity = Identity::from_string(&token.value()).unwrap();
+    let resp: ResponseData<FileReadByIdV1RespDTO> = file_meta.into();
+    Ok(HttpResponse::Ok().json(resp))
 
-    let svc = server_services.file_service.clone();
-    let result = svc.file_read_by_id(&file_id, &identity.get_id()).await;
+}
 
-    match result {
-        Ok(file_meta) => {
-            let resp: ResponseData<FileReadByIdV1RespDTO> = file_meta.into();
-            HttpResponse::Ok().json(resp)
-        },
-        Err(err) => {
-            let domain_err: FileError = err.downcast().unwrap();
-            let resp: ResponseData<FileReadByIdV1RespDTO> = domain_err.clone().into();
+fn content_disposition(file_meta: &FileMeta) -> ContentDisposition {
+    let name = file_meta.get_download_name();
+    let mut parameters = vec![DispositionParam::Filename(name.replace(|c: char| !c.is_ascii() || c.is_ascii_control(), "_"))];
+    if !name.is_ascii() {
+        parameters.push(DispositionParam::FilenameExt(ExtendedValue {
+            charset: Charset::Ext(String::from("UTF-8")),
+            language_tag: None,
+            value: name.into_bytes(),
+        }));
+    }
 
-            map_domain_error_to_response(domain_err, resp)
-        }
+    ContentDisposition {
+        disposition: DispositionType::Attachment,
+        parameters,
     }
+}
 
+// NOTE: the stored digest is the same whichever backend holds the file, the backend's own tag
+//       is only used for files uploaded before digests were recorded.
+fn entity_tag(file_meta: &FileMeta, stat: &StorageObjectStat) -> EntityTag {
+    let sha256 = file_meta.get_content().get_sha256();
+    if sha256.is_empty() {
+        EntityTag::new_strong(stat.get_etag())
+    } else {
+        EntityTag::new_strong(sha256)
+    }
 }
 
-pub async fn file_list_by_customer_id_v1(
-    server_services: web::Data<ServerService>,
-    request: HttpRequest,
-) -> impl Responder {
-    // NOTE: authn checking
-    let token = match request.cookie("token") {
-        Some(token) => token,
-        None => {
-            return HttpResponse::Unauthorized().finish();
-        }
+// NOTE: only the first range of a multi range request is served, the same as NamedFile did.
+//       a range is ignored when If-Range names another version of the file.
+fn requested_range(request: &HttpRequest, etag: &EntityTag, size: u64) -> Result<Option<ByteRange>, ()> {
+    let Some(range) = request.headers().get(header::RANGE).and_then(|value| value.to_str().ok()) else {
+        return Ok(None);
     };
-
-    info!("[DEBUG] {}", &token.value());
-    let identity = Identity::from_string(&token.value()).unwrap();
-    let svc = server_services.file_service.clone();
-    let result = svc
-        .file_list_by_customer_id(&identity.get_id())
-        .await;
-
-    match result {
-        Ok(file_meta_list) => {
-            let resp: ResponseData<FileListByCustomerIdV1RespDTO> = file_meta_list.into();
-            HttpResponse::Ok().json(resp)
-        },
-        Err(err) => {
-            let domain_err: FileError = err.downcast().unwrap();
-            let resp: ResponseData<FileReadByIdV1RespDTO> = domain_err.clone().into();
-            map_domain_error_to_response(domain_err, resp)
+    if size == 0 {
+        return Ok(None);
+    }
+    if let Some(IfRange::EntityTag(if_range)) = request.get_header::<IfRange>() {
+        if !if_range.strong_eq(etag) {
+            return Ok(None);
         }
     }
+
+    match HttpRange::parse(range, size) {
+        Ok(ranges) => Ok(ranges.first().map(|range| ByteRange::new(range.start, range.length))),
+        Err(_) => Err(()),
+    }
 }
 
-pub async fn file_upload_v1(
-    server_services: web::Data<ServerService>,
-    MultipartForm(form): MultipartForm<FileUploadV1ReqDTO>,
-    request: HttpRequest,
-) -> impl Responder {
-    // NOTE: authn checking
-    let token = match request.cookie("token") {
-        Some(token) => token,
-        None => {
-            return HttpResponse::Unauthorized().finish();
+// NOTE: serves a file the caller is already allowed to read with Range, If-None-Match and HEAD
+//       support, the recorded type and name are used and a file is never kept by a shared cache.
+//       a sharing access is counted as a download whenever a body is sent, whatever its range,
+//       so a link can not be read in pieces past its limit. revalidations and HEAD requests are free.
+pub async fn file_response(svc: &dyn FileServiceTrait, file_meta: &FileMeta, sharing_access: Option<&SharingAccess>, request: &HttpRequest) -> Result<HttpResponse, ApiError> {
+    let stat = svc.file_stat_content(file_meta).await?;
+    let etag = entity_tag(file_meta, &stat);
+
+    let mime_type = file_meta
+        .get_content()
+        .get_mime_type()
+        .parse()
+        .unwrap_or(ContentType::octet_stream().0);
+
+    let mut response = HttpResponse::Ok();
+    response
+        .insert_header(ContentType(mime_type))
+        .insert_header(content_disposition(file_meta))
+        .insert_header(ETag(etag.clone()))
+        .insert_header(LastModified(SystemTime::from(stat.get_last_modified()).into()))
+        .insert_header((header::ACCEPT_RANGES, "bytes"))
+        .insert_header((header::CACHE_CONTROL, "private, no-cache"))
+        .insert_header((header::X_CONTENT_TYPE_OPTIONS, "nosniff"));
+
+    if let Some(IfNoneMatch::Any) = request.get_header::<IfNoneMatch>() {
+        return Ok(response.status(StatusCode::NOT_MODIFIED).finish());
+    }
+    if let Some(IfNoneMatch::Items(items)) = request.get_header::<IfNoneMatch>() {
+        if items.iter().any(|item| item.weak_eq(&etag)) {
+            return Ok(response.status(StatusCode::NOT_MODIFIED).finish());
+        }
+    }
+
+    let size = stat.get_size();
+    let range = match requested_range(request, &etag, size) {
+        Ok(range) => range,
+        Err(_) => {
+            return Ok(response
+                .status(StatusCode::RANGE_NOT_SATISFIABLE)
+                .insert_header((header::CONTENT_RANGE, format!("bytes */{}", size)))
+                .finish());
         }
     };
 
-    let identity = match Identity::from_string(&token.value()) {
-        Ok(identity) => identity,
-        Err(_err) => {
-            return HttpResponse::Unauthorized().finish();
+    // NOTE: the body of a HEAD response is never sent, there is no point in opening the file
+    if request.method() == Method::HEAD {
+        let body = futures_util::stream::empty::<Result<Bytes, std::io::Error>>();
+        return Ok(response.body(SizedStream::new(size, body)));
+    }
+
+    if let Some(sharing_access) = sharing_access {
+        svc.file_consume_sharing_download(sharing_access).await?;
+    }
+
+    let storage_object = svc.file_open_content(file_meta, range).await?;
+    let size = storage_object.get_stat().get_size();
+    let length = match storage_object.get_range() {
+        Some(range) => {
+            response
+                .status(StatusCode::PARTIAL_CONTENT)
+                .insert_header((header::CONTENT_RANGE, format!("bytes {}-{}/{}", range.start, range.get_end(), size)));
+            range.length
         }
+        None => size,
     };
 
-    let temp_filename = form.get_temp_filename();
+    Ok(response.body(SizedStream::new(length, storage_object.into_body())))
+}
+
+pub async fn file_download_by_id_v1(
+    server_services: web::Data<ServerService>,
+    customer: AuthenticatedCustomer,
+    request: HttpRequest,
+    file_id: web::Path<Uuid>,
+) -> Result<HttpResponse, ApiError> {
+    let identity = customer.get_identity();
+    let svc = server_services.file_service.clone();
+    let file_meta = svc.file_read_by_id(&file_id, &identity.get_id()).await?;
+
+    file_response(svc.as_ref(), &file_meta, None, &request).await
+}
+
+pub async fn file_delete_by_id_v1(
+    server_services: web::Data<ServerService>,
+    customer: AuthenticatedCustomer,
+    file_id: web::Path<Uuid>,
+) -> Result<HttpResponse, ApiError> {
+    let identity = customer.get_identity();
+    let svc = server_services.file_service.clone();
+    svc.file_delete(&file_id, &identity.get_id()).await?;
+
+    Ok(HttpResponse::NoContent().finish())
+}
+
+pub async fn file_move_by_id_v1(
+    server_services: web::Data<ServerService>,
+    customer: AuthenticatedCustomer,
+    file_id: web::Path<Uuid>,
+    user_data: web::Json<FileMoveV1ReqDTO>,
+) -> Result<HttpResponse, ApiError> {
+    let identity = customer.get_identity();
+    let svc = server_services.folder_service.clone();
+    let file_meta = svc
+        .folder_move_file(&file_id, &identity.get_id(), &user_data.folder_id)
+        .await?;
+
+    let resp: ResponseData<FileReadByIdV1RespDTO> = file_meta.into();
+    Ok(HttpResponse::Ok().json(resp))
+}
+
+pub async fn trash_list_by_customer_id_v1(
+    server_services: web::Data<ServerService>,
+    customer: AuthenticatedCustomer,
+) -> Result<HttpResponse, ApiError> {
+    let identity = customer.get_identity();
+    let svc = server_services.file_service.clone();
+    let file_meta_list = svc
+        .file_list_trash_by_customer_id(&identity.get_id())
+        .await?;
+
+    let retention = Duration::seconds(server_services.config.trash.retention_seconds);
+    let resp: ResponseData<TrashListByCustomerIdV1RespDTO> = (file_meta_list, retention).into();
+    Ok(HttpResponse::Ok().json(resp))
+}
+
+pub async fn trash_restore_by_id_v1(
+    server_services: web::Data<ServerService>,
+    customer: AuthenticatedCustomer,
+    file_id: web::Path<Uuid>,
+) -> Result<HttpResponse, ApiError> {
+    let identity = customer.get_identity();
+    let svc = server_services.file_service.clone();
+    let file_meta = svc.file_restore_by_id(&file_id, &identity.get_id()).await?;
+
+    let resp: ResponseData<FileReadByIdV1RespDTO> = file_meta.into();
+    Ok(HttpResponse::Ok().json(resp))
+}
+
+pub async fn trash_purge_by_id_v1(
+    server_services: web::Data<ServerService>,
+    customer: AuthenticatedCustomer,
+    file_id: web::Path<Uuid>,
+) -> Result<HttpResponse, ApiError> {
+    let identity = customer.get_identity();
+    let svc = server_services.file_service.clone();
+    svc.file_purge_by_id(&file_id, &identity.get_id()).await?;
+
+    Ok(HttpResponse::NoContent().finish())
+}
+
+pub async fn file_list_by_customer_id_v1(
+    server_services: web::Data<ServerService>,
+    customer: AuthenticatedCustomer,
+    user_data: web::Query<FileListByCustomerIdV1ReqDTO>,
+) -> Result<HttpResponse, ApiError> {
+    let query = user_data.get_query()?;
+    let limit = user_data.get_limit()?;
 
+    let identity = customer.get_identity();
     let svc = server_services.file_service.clone();
-    let result = svc
-        .file_upload(&identity.get_id(), &temp_filename)
-        .await;
+    let file_meta_page = svc
+        .file_list_by_customer_id(&identity.get_id(), &query, limit)
+        .await?;
+
+    let resp: ResponseData<FileListByCustomerIdV1RespDTO> = file_meta_page.into();
+    Ok(HttpResponse::Ok().json(resp))
+}
 
-    match result {
-        Ok(file_meta) => {
-            let resp: ResponseData<FileUploadV1RespDTO> = file_meta.into();
-            HttpResponse::Ok().json(resp)
-        },
-        Err(err) => {
-            let domain_err: FileError = err.downcast().unwrap();
-            let resp: ResponseData<FileUploadV1RespDTO> = domain_err.clone().into();
+// NOTE: a multipart field can not leave the worker thread while a storage backend may need to
+//       send its body elsewhere, so the chunks are handed over through a small channel. the
+//       channel also slows the client down to the pace of the backend.
+const UPLOAD_CHANNEL_CAPACITY: usize = 8;
+
+pub fn upload_channel() -> (Sender<std::io::Result<Bytes>>, StorageStream) {
+    let (sender, receiver) = channel(UPLOAD_CHANNEL_CAPACITY);
+    let body = futures_util::stream::unfold(receiver, |mut receiver| async move {
+        receiver.recv().await.map(|chunk| (chunk, receiver))
+    });
+
+    (sender, Box::pin(body))
+}
 
-            map_domain_error_to_response(domain_err, resp)
+// NOTE: stops reading as soon as the backend gives up on the body, e.g. once it is too large
+pub async fn forward_body<S, E>(mut body: S, sender: Sender<std::io::Result<Bytes>>) -> Result<(), E>
+where
+    S: Stream<Item = Result<Bytes, E>> + Unpin,
+    E: std::fmt::Display,
+{
+    while let Some(chunk) = body.next().await {
+        match chunk {
+            Ok(chunk) => {
+                if sender.send(Ok(chunk)).await.is_err() {
+                    return Ok(());
+                }
+            }
+            Err(err) => {
+                let _ = sender.send(Err(std::io::Error::other(err.to_string()))).await;
+                return Err(err);
+            }
         }
     }
+
+    Ok(())
+}
+
+pub async fn file_upload_v1(
+    server_services: web::Data<ServerService>,
+    customer: AuthenticatedCustomer,
+    mut payload: Multipart,
+) -> Result<HttpResponse, ApiError> {
+    let identity = customer.get_identity();
+    let customer = server_services
+        .customer_service
+        .get_customer_by_id(&identity.get_id())
+        .await?;
+
+    while let Some(field) = payload.next().await {
+        let field = field.map_err(|err| ApiError::InvalidRequest(err.to_string()))?;
+        if field.name() != FILE_UPLOAD_FIELD_NAME {
+            continue;
+        }
+
+        let form = FileUploadV1ReqDTO::from(&field);
+        let file_name = form.get_file_name();
+        let (sender, body) = upload_channel();
+        let svc = server_services.file_service.clone();
+        let (forwarded, file_meta) = futures_util::join!(
+            forward_body(field, sender),
+            svc.file_upload(&customer, &file_name, form.get_content_type(), body),
+        );
+        forwarded.map_err(|err| ApiError::InvalidRequest(err.to_string()))?;
+        let file_meta = file_meta?;
+
+        let resp: ResponseData<FileUploadV1RespDTO> = file_meta.into();
+        return Ok(HttpResponse::Ok().json(resp));
+    }
+
+    Err(ApiError::InvalidRequest(format!("multipart field {} is missing", FILE_UPLOAD_FIELD_NAME)))
 }
 
 pub async fn file_sharing_create_v1(
     server_services: web::Data<ServerService>,
-    request: HttpRequest,
+    customer: AuthenticatedCustomer,
     user_data: web::Json<FileSharingCreateV1ReqDTO>,
-) -> impl Responder {
-    // NOTE: authn checking
-    let token = match request.cookie("token") {
-        Some(token) => token,
-        None => {
-            return HttpResponse::Unauthorized().finish();
+) -> Result<HttpResponse, ApiError> {
+    let identity = customer.get_identity();
+    let svc = server_services.file_service.clone();
+    let file_meta = svc
+        .file_create_sharing_link(&user_data.file_id, &identity.get_id(), &user_data.expireat, &user_data.password, &user_data.max_downloads)
+        .await?;
+
+    let resp: ResponseData<FileSharingCreateV1RespDTO> = file_meta.into();
+    Ok(HttpResponse::Ok().json(resp))
+}
+
+pub async fn file_sharing_get_by_id_v1(
+    server_services: web::Data<ServerService>,
+    request: HttpRequest,
+    id: web::Path<Uuid>,
+    user_data: web::Json<FileSharingGetByIdV1ReqDTO>,
+) -> Result<HttpResponse, ApiError> {
+    let svc = server_services.file_service.clone();
+    let (file_meta, sharing_access) = svc
+        .file_get_sharing_link_by_id(&id, user_data.password.clone(), &sharing_client(&request, &server_services.config.http))
+        .await?;
+
+    sharing_file_response(svc, &file_meta, &sharing_access, &request).await
+}
+
+pub async fn file_sharing_read_by_id_v1(
+    server_services: web::Data<ServerService>,
+    id: web::Path<Uuid>,
+) -> Result<HttpResponse, ApiError> {
+    let svc = server_services.file_service.clone();
+    let file_sharing = svc.file_read_sharing_link_by_id(&id).await?;
+
+    let resp: ResponseData<FileSharingReadByIdV1RespDTO> = file_sharing.into();
+    Ok(HttpResponse::Ok().json(resp))
+}
+
+pub async fn file_sharing_unlock_v1(
+    server_services: web::Data<ServerService>,
+    request: HttpRequest,
+    id: web::Path<Uuid>,
+    user_data: web::Json<FileSharingUnlockV1ReqDTO>,
+) -> Result<HttpResponse, ApiError> {
+    let svc = server_services.file_service.clone();
+    let sharing_unlock = svc
+        .file_unlock_sharing_link(&id, user_data.password.clone(), &sharing_client(&request, &server_services.config.http))
+        .await?;
+    let token = sharing_unlock.to_string(&server_services.jwt_key_set)?;
+
+    let resp_data = FileSharingUnlockV1RespDTO::new(&token, &sharing_unlock.get_expireat());
+    let resp = ResponseData::new(true, String::new(), Some(resp_data));
+    Ok(HttpResponse::Ok().json(resp))
+}
+
+pub async fn file_sharing_download_by_id_v1(
+    server_services: web::Data<ServerService>,
+    request: HttpRequest,
+    id: web::Path<Uuid>,
+) -> Result<HttpResponse, ApiError> {
+    let svc = server_services.file_service.clone();
+    let client = sharing_client(&request, &server_services.config.http);
+    let (file_meta, sharing_access) = match sharing_token(&request) {
+        Some(token) => {
+            let sharing_unlock = SharingUnlock::from_string(&token, &server_services.jwt_key_set)
+                .map_err(|_| anyhow!(FileError::FileSharingUnlockTokenInvalid))?;
+            svc.file_get_sharing_link_by_unlock(&id, &sharing_unlock, &client).await?
         }
+        None => svc.file_get_sharing_link_by_id(&id, sharing_password(&request), &client).await?,
     };
 
-    let identity = match Identity::from_string(&token.value()) {
-        Ok(identity) => identity,
-        Err(_err) => {
-            return HttpResponse::Unauthorized().finish();
+    sharing_file_response(svc, &file_meta, &sharing_access, &request).await
+}
+
+pub async fn file_sharing_access_list_v1(
+    server_services: web::Data<ServerService>,
+    customer: AuthenticatedCustomer,
+    id: web::Path<Uuid>,
+    user_data: web::Query<FileSharingAccessListV1ReqDTO>,
+) -> Result<HttpResponse, ApiError> {
+    let cursor = user_data.get_cursor()?;
+    let limit = user_data.get_limit()?;
+
+    let identity = customer.get_identity();
+    let svc = server_services.file_service.clone();
+    let sharing_access = svc
+        .file_list_sharing_access(&id, &identity.get_id(), &cursor, limit)
+        .await?;
+
+    let resp: ResponseData<FileSharingAccessListV1RespDTO> = sharing_access.into();
+    Ok(HttpResponse::Ok().json(resp))
+}
+
+pub async fn file_sharing_list_v1(
+    server_services: web::Data<ServerService>,
+    customer: AuthenticatedCustomer,
+    user_data: web::Query<FileSharingListV1ReqDTO>,
+) -> Result<HttpResponse, ApiError> {
+    let identity = customer.get_identity();
+    let svc = server_services.file_service.clone();
+    let file_sharing_meta_list = svc
+        .file_list_sharing_links(&identity.get_id(), &user_data.file_id)
+        .await?;
+
+    let resp: ResponseData<FileSharingListV1RespDTO> = file_sharing_meta_list.into();
+    Ok(HttpResponse::Ok().json(resp))
+}
+
+pub async fn file_sharing_update_v1(
+    server_services: web::Data<ServerService>,
+    customer: AuthenticatedCustomer,
+    id: web::Path<Uuid>,
+    user_data: web::Json<FileSharingUpdateV1ReqDTO>,
+) -> Result<HttpResponse, ApiError> {
+    let identity = customer.get_identity();
+    let svc = server_services.file_service.clone();
+    let file_sharing_meta = svc
+        .file_update_sharing_link(&id, &identity.get_id(), &user_data.expireat, &user_data.password)
+        .await?;
+
+    let resp: ResponseData<FileSharingReadV1RespDTO> = file_sharing_meta.into();
+    Ok(HttpResponse::Ok().json(resp))
+}
+
+pub async fn file_sharing_revoke_v1(
+    server_services: web::Data<ServerService>,
+    customer: AuthenticatedCustomer,
+    id: web::Path<Uuid>,
+) -> Result<HttpResponse, ApiError> {
+    let identity = customer.get_identity();
+    let svc = server_services.file_service.clone();
+    svc.file_revoke_sharing_link(&id, &identity.get_id()).await?;
+
+    Ok(HttpResponse::NoContent().finish())
+}
+
+// NOTE: the address of the peer rather than a forwarded header, which any client could set to
+//       dodge the throttling of wrong sharing passwords. behind a trusted reverse proxy the peer is
+//       the proxy for every client, the address it forwards is taken instead.
+pub(super) fn sharing_client(request: &HttpRequest, config: &HttpConfig) -> SharingClient {
+    let peer_addr = request.peer_addr().map(|addr| addr.ip());
+    let addr = match peer_addr {
+        Some(peer_addr) if config.is_trusted_proxy(&peer_addr) => {
+            let connection_info = request.connection_info();
+            let realip_remote_addr = connection_info.realip_remote_addr().unwrap_or_default();
+            realip_remote_addr
+                .parse::<SocketAddr>()
+                .map(|addr| addr.ip().to_string())
+                .unwrap_or_else(|_| realip_remote_addr.to_string())
         }
+        Some(peer_addr) => peer_addr.to_string(),
+        None => String::new(),
     };
+    let user_agent = request
+        .headers()
+        .get(header::USER_AGENT)
+        .and_then(|value| value.to_str().ok())
+        .map(|value| value.to_string());
+
+    SharingClient::new(&addr, &user_agent)
+}
 
+// NOTE: the bytes served are counted while the body is sent and recorded once it is dropped, a
+//       client that hangs up half way is only counted for what it got. a failure to record them
+//       never fails the download.
+pub async fn sharing_file_response(svc: Arc<dyn FileServiceTrait>, file_meta: &FileMeta, sharing_access: &SharingAccess, request: &HttpRequest) -> Result<HttpResponse, ApiError> {
+    let response = file_response(svc.as_ref(), file_meta, Some(sharing_access), request).await?;
+    let access_id = sharing_access.get_id();
 
-    let svc = server_services.file_service.clone();
-    let result = svc
-        .file_create_sharing_link(&user_data.file_id, &user_data.expireat, &user_data.password)
-        .await;
+    Ok(response
+        .map_body(|_head, body| SharingBytesBody { body, svc, access_id, bytes_served: 0 })
+        .map_into_boxed_body())
+}
 
-    match result {
-        Ok(file_meta) => {
-            let resp: ResponseData<FileSharingCreateV1RespDTO> = file_meta.into();
-            HttpResponse::Ok().json(resp)
-        },
-        Err(err) => {
-            let domain_err: FileError = err.downcast().unwrap();
-            let resp: ResponseData<FileUploadV1RespDTO> = domain_err.clone().into();
+struct SharingBytesBody {
+    body: BoxBody,
+    svc: Arc<dyn FileServiceTrait>,
+    access_id: Uuid,
+    bytes_served: u64,
+}
+
+impl MessageBody for SharingBytesBody {
+    type Error = Box<dyn std::error::Error>;
+
+    fn size(&self) -> BodySize {
+        self.body.size()
+    }
 
-            map_domain_error_to_response(domain_err, resp)
+    fn poll_next(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Result<Bytes, Self::Error>>> {
+        let poll = Pin::new(&mut self.body).poll_next(cx);
+        if let Poll::Ready(Some(Ok(chunk))) = &poll {
+            self.bytes_served += chunk.len() as u64;
         }
+
+        poll
     }
 }
 
-pub async fn file_sharing_get_by_id_v1(
+impl Drop for SharingBytesBody {
+    fn drop(&mut self) {
+        if self.bytes_served == 0 {
+            return;
+        }
+
+        let svc = self.svc.clone();
+        let access_id = self.access_id;
+        let bytes_served = self.bytes_served as i64;
+        actix_web::rt::spawn(async move {
+            if let Err(err) = svc.file_record_sharing_bytes_served(&access_id, bytes_served).await {
+                log::warn!("failed to record the bytes served by sharing access {}: {}", access_id, err);
+            }
+        });
+    }
+}
+
+// NOTE: a header rather than a query parameter so the password never shows up in access logs
+pub const SHARING_PASSWORD_HEADER_NAME: &str = "X-Sharing-Password";
+
+fn sharing_password(request: &HttpRequest) -> Option<String> {
+    request
+        .headers()
+        .get(SHARING_PASSWORD_HEADER_NAME)
+        .and_then(|value| value.to_str().ok())
+        .map(|value| value.to_string())
+}
+
+// NOTE: the unlock token is kept out of the url for the same reason. it has a header of its own,
+//       Authorization carries the access token of a signed in customer
+pub const SHARING_TOKEN_HEADER_NAME: &str = "X-Sharing-Token";
+
+pub(super) fn sharing_token(request: &HttpRequest) -> Option<String> {
+    request
+        .headers()
+        .get(SHARING_TOKEN_HEADER_NAME)
+        .and_then(|value| value.to_str().ok())
+        .map(|value| value.trim().to_string())
+}
+
+pub async fn file_sharing_get_by_code(
     server_services: web::Data<ServerService>,
     request: HttpRequest,
+    code: web::Path<String>,
+) -> Result<HttpResponse, ApiError> {
+    let password = sharing_password(&request);
+
+    let svc = server_services.file_service.clone();
+    let (file_meta, sharing_access) = svc
+        .file_get_sharing_link_by_code(&code, password, &sharing_client(&request, &server_services.config.http))
+        .await?;
+
+    sharing_file_response(svc, &file_meta, &sharing_access, &request).await
+}
+
+pub async fn file_request_create_v1(
+    server_services: web::Data<ServerService>,
+    customer: AuthenticatedCustomer,
+    user_data: web::Json<FileRequestCreateV1ReqDTO>,
+) -> Result<HttpResponse, ApiError> {
+    let identity = customer.get_identity();
+    let customer = server_services
+        .customer_service
+        .get_customer_by_id(&identity.get_id())
+        .await?;
+
+    let svc = server_services.file_service.clone();
+    let file_request = svc
+        .file_create_request(&customer, &user_data.folder_id, &user_data.expireat, &user_data.password, &user_data.max_files, &user_data.max_size_bytes)
+        .await?;
+
+    let resp: ResponseData<FileRequestReadV1RespDTO> = file_request.into();
+    Ok(HttpResponse::Ok().json(resp))
+}
+
+pub async fn file_request_list_v1(
+    server_services: web::Data<ServerService>,
+    customer: AuthenticatedCustomer,
+) -> Result<HttpResponse, ApiError> {
+    let identity = customer.get_identity();
+    let svc = server_services.file_service.clone();
+    let file_request_list = svc.file_list_requests(&identity.get_id()).await?;
+
+    let resp: ResponseData<FileRequestListV1RespDTO> = file_request_list.into();
+    Ok(HttpResponse::Ok().json(resp))
+}
+
+pub async fn file_request_revoke_v1(
+    server_services: web::Data<ServerService>,
+    customer: AuthenticatedCustomer,
     id: web::Path<Uuid>,
-    user_data: web::Json<FileSharingGetByIdV1ReqDTO>,
-) -> impl Responder {
+) -> Result<HttpResponse, ApiError> {
+    let identity = customer.get_identity();
     let svc = server_services.file_service.clone();
-    let result = svc
-        .file_get_sharing_link_by_id(&id, user_data.password.clone())
-        .await;
+    svc.file_revoke_request(&id, &identity.get_id()).await?;
 
-    match result {
-        Ok(file_stream) => {
-            file_stream.into_response(&request)
-        },
-        Err(err) => {
-            let domain_err: FileError = err.downcast().unwrap();
-            let resp: ResponseData<FileUploadV1RespDTO> = domain_err.clone().into();
+    Ok(HttpResponse::NoContent().finish())
+}
+
+pub async fn file_request_get_by_code(
+    server_services: web::Data<ServerService>,
+    code: web::Path<String>,
+) -> Result<HttpResponse, ApiError> {
+    let svc = server_services.file_service.clone();
+    let file_request = svc.file_read_request_by_code(&code).await?;
+
+    let resp: ResponseData<FileRequestGetByCodeRespDTO> = file_request.into();
+    Ok(HttpResponse::Ok().json(resp))
+}
+
+// NOTE: one file per request, the same multipart field as an upload of the customer
+pub async fn file_request_upload_by_code(
+    server_services: web::Data<ServerService>,
+    request: HttpRequest,
+    code: web::Path<String>,
+    mut payload: Multipart,
+) -> Result<HttpResponse, ApiError> {
+    let password = sharing_password(&request);
+    let client = sharing_client(&request, &server_services.config.http);
 
-            map_domain_error_to_response(domain_err, resp)
+    while let Some(field) = payload.next().await {
+        let field = field.map_err(|err| ApiError::InvalidRequest(err.to_string()))?;
+        if field.name() != FILE_UPLOAD_FIELD_NAME {
+            continue;
         }
+
+        let form = FileUploadV1ReqDTO::from(&field);
+        let file_name = form.get_file_name();
+        let (sender, body) = upload_channel();
+        let svc = server_services.file_service.clone();
+        let (forwarded, file_meta) = futures_util::join!(
+            forward_body(field, sender),
+            svc.file_upload_by_request(&code, password, &client, &file_name, form.get_content_type(), body),
+        );
+        forwarded.map_err(|err| ApiError::InvalidRequest(err.to_string()))?;
+        let file_meta = file_meta?;
+
+        let resp: ResponseData<FileRequestUploadByCodeRespDTO> = file_meta.into();
+        return Ok(HttpResponse::Ok().json(resp));
     }
-}
\ No newline at end of file
+
+    Err(ApiError::InvalidRequest(format!("multipart field {} is missing", FILE_UPLOAD_FIELD_NAME)))
+}
+
+pub async fn file_grant_v1(
+    server_services: web::Data<ServerService>,
+    customer: AuthenticatedCustomer,
+    file_id: web::Path<Uuid>,
+    user_data: web::Json<FileGrantV1ReqDTO>,
+) -> Result<HttpResponse, ApiError> {
+    let identity = customer.get_identity();
+    let grantee = server_services
+        .customer_service
+        .get_customer_by_username(&user_data.username)
+        .await?;
+
+    let svc = server_services.file_service.clone();
+    let file_grant = svc
+        .file_grant(&file_id, &identity.get_id(), &grantee, user_data.permission)
+        .await?;
+
+    let resp: ResponseData<FileGrantReadV1RespDTO> = file_grant.into();
+    Ok(HttpResponse::Ok().json(resp))
+}
+
+pub async fn file_grant_list_v1(
+    server_services: web::Data<ServerService>,
+    customer: AuthenticatedCustomer,
+    file_id: web::Path<Uuid>,
+) -> Result<HttpResponse, ApiError> {
+    let identity = customer.get_identity();
+    let svc = server_services.file_service.clone();
+    let file_grant_list = svc.file_list_grants(&file_id, &identity.get_id()).await?;
+
+    let resp: ResponseData<FileGrantListV1RespDTO> = file_grant_list.into();
+    Ok(HttpResponse::Ok().json(resp))
+}
+
+pub async fn file_grant_revoke_v1(
+    server_services: web::Data<ServerService>,
+    customer: AuthenticatedCustomer,
+    path: web::Path<(Uuid, Uuid)>,
+) -> Result<HttpResponse, ApiError> {
+    let identity = customer.get_identity();
+    let (file_id, grantee_id) = path.into_inner();
+    let svc = server_services.file_service.clone();
+    svc.file_revoke_grant(&file_id, &identity.get_id(), &grantee_id).await?;
+
+    Ok(HttpResponse::NoContent().finish())
+}
+
+pub async fn file_shared_with_me_v1(
+    server_services: web::Data<ServerService>,
+    customer: AuthenticatedCustomer,
+) -> Result<HttpResponse, ApiError> {
+    let identity = customer.get_identity();
+    let svc = server_services.file_service.clone();
+    let shared_file_list = svc.file_list_shared_with(&identity.get_id()).await?;
+
+    let resp: ResponseData<SharedFileListV1RespDTO> = shared_file_list.into();
+    Ok(HttpResponse::Ok().json(resp))
+}
@@ -0,0 +1,298 @@
+use std::sync::Arc;
+
+use actix_web::body::{to_bytes, MessageBody};
+use actix_web::http::{header, Method, StatusCode};
+use actix_web::test::TestRequest;
+use bytes::Bytes;
+use chrono::{TimeZone, Utc};
+use uuid::Uuid;
+
+use super::view::{file_response, sharing_client, sharing_file_response, sharing_token, SHARING_TOKEN_HEADER_NAME};
+use crate::config::HttpConfig;
+use crate::domain::entity::file_meta::{FileContent, FileMeta};
+use crate::domain::entity::sharing_access::{SharingAccess, SharingAccessOutcome, SharingClient};
+use crate::domain::entity::storage_object::{ByteRange, StorageObject, StorageObjectStat};
+use crate::domain::error::file::FileError;
+use crate::domain::service::file::MockFileServiceTrait;
+use crate::presentation::error::ApiError;
+
+const FAKE_CONTENT: &[u8] = b"hello world";
+const FAKE_SHA256: &str = "b94d27b9934d3e08a52e52d7da7dabfac484efe37a5380ee9088f7ace2efcde9";
+
+fn fake_file_meta() -> FileMeta {
+    let content = FileContent::new("hello.txt", 11, "text/plain", FAKE_SHA256);
+    let uploaded_at = Utc.with_ymd_and_hms(1990, 3, 3, 0, 0, 0).unwrap();
+    FileMeta::new_with_content(
+        &Uuid::default(),
+        &Uuid::default(),
+        "stored-key",
+        &content,
+        &uploaded_at,
+    )
+}
+
+fn fake_sharing_access() -> SharingAccess {
+    SharingAccess::new_full(
+        &Uuid::default(),
+        &Some(Uuid::default()),
+        &Utc.with_ymd_and_hms(1990, 3, 3, 0, 0, 0).unwrap(),
+        &SharingClient::new("127.0.0.1", &None),
+        SharingAccessOutcome::Success,
+        0,
+    )
+}
+
+fn fake_file_service(open_times: usize, consume_times: usize) -> MockFileServiceTrait {
+    let mut mock_svc = MockFileServiceTrait::new();
+    let last_modified = Utc.with_ymd_and_hms(1990, 3, 3, 0, 0, 0).unwrap();
+
+    mock_svc
+        .expect_file_stat_content()
+        .times(1)
+        .returning(move |_file_meta| {
+            Ok(StorageObjectStat::new(11, "backend-etag", &last_modified))
+        });
+    mock_svc
+        .expect_file_open_content()
+        .times(open_times)
+        .returning(move |_file_meta, range| {
+            let stat = StorageObjectStat::new(11, "backend-etag", &last_modified);
+            let content = match range {
+                Some(ByteRange { start, length }) => {
+                    &FAKE_CONTENT[start as usize..(start + length) as usize]
+                }
+                None => FAKE_CONTENT,
+            };
+            let body = futures_util::stream::iter(vec![Ok(Bytes::from_static(content))]);
+            Ok(StorageObject::new(stat, range, Box::pin(body)))
+        });
+    mock_svc
+        .expect_file_consume_sharing_download()
+        .times(consume_times)
+        .returning(|_sharing_access| Ok(()));
+
+    mock_svc
+}
+
+#[actix_rt::test]
+async fn test_file_response() {
+    let etag = format!("\"{}\"", FAKE_SHA256);
+    let test_context = vec![
+        (
+            TestRequest::get(),
+            1,
+            1,
+            StatusCode::OK,
+            None,
+            FAKE_CONTENT,
+        ),
+        (
+            TestRequest::get().insert_header((header::RANGE, "bytes=6-")),
+            1,
+            1,
+            StatusCode::PARTIAL_CONTENT,
+            Some("bytes 6-10/11"),
+            &b"world"[..],
+        ),
+        (
+            TestRequest::get().insert_header((header::RANGE, "bytes=0-4")),
+            1,
+            1,
+            StatusCode::PARTIAL_CONTENT,
+            Some("bytes 0-4/11"),
+            &b"hello"[..],
+        ),
+        (
+            TestRequest::get()
+                .insert_header((header::RANGE, "bytes=6-"))
+                .insert_header((header::IF_RANGE, "\"another-version\"")),
+            1,
+            1,
+            StatusCode::OK,
+            None,
+            FAKE_CONTENT,
+        ),
+        (
+            TestRequest::get().insert_header((header::RANGE, "bytes=20-30")),
+            0,
+            0,
+            StatusCode::RANGE_NOT_SATISFIABLE,
+            Some("bytes */11"),
+            &b""[..],
+        ),
+        (
+            TestRequest::get().insert_header((header::IF_NONE_MATCH, etag.clone())),
+            0,
+            0,
+            StatusCode::NOT_MODIFIED,
+            None,
+            &b""[..],
+        ),
+        (
+            TestRequest::default().method(Method::HEAD),
+            0,
+            0,
+            StatusCode::OK,
+            None,
+            &b""[..],
+        ),
+    ];
+
+    // NOTE: every body sent counts as a download of a sharing link, whatever its range
+    for (request, open_times, consume_times, expected_status, expected_content_range, expected_body) in
+        test_context
+    {
+        let request = request.to_http_request();
+        let svc = fake_file_service(open_times, consume_times);
+        let sharing_access = fake_sharing_access();
+
+        let response = file_response(&svc, &fake_file_meta(), Some(&sharing_access), &request)
+            .await
+            .unwrap();
+        assert_eq!(response.status(), expected_status);
+        assert_eq!(
+            response
+                .headers()
+                .get(header::CONTENT_RANGE)
+                .map(|value| value.to_str().unwrap()),
+            expected_content_range
+        );
+        assert_eq!(response.headers().get(header::ETAG).unwrap(), etag.as_str());
+        assert_eq!(
+            response.headers().get(header::CONTENT_TYPE).unwrap(),
+            "text/plain"
+        );
+
+        let body = to_bytes(response.into_body()).await.unwrap();
+        assert_eq!(body, expected_body);
+    }
+}
+
+#[actix_rt::test]
+async fn test_sharing_file_response_records_bytes_served() {
+    // NOTE: request, whether the client reads the body, bytes recorded once the body is dropped
+    let test_context = vec![
+        (TestRequest::get(), true, Some(11)),
+        (TestRequest::get().insert_header((header::RANGE, "bytes=6-")), true, Some(5)),
+        (TestRequest::get(), false, None),
+        (TestRequest::default().method(Method::HEAD), true, None),
+    ];
+
+    for (request, is_read, expected_bytes_served) in test_context {
+        let request = request.to_http_request();
+        let is_head = request.method() == Method::HEAD;
+        let svc = {
+            let mut mock_svc = fake_file_service(usize::from(!is_head), usize::from(!is_head));
+            mock_svc
+                .expect_file_record_sharing_bytes_served()
+                .withf(move |access_id, bytes_served| *access_id == Uuid::default() && Some(*bytes_served) == expected_bytes_served)
+                .times(usize::from(expected_bytes_served.is_some()))
+                .returning(|_access_id, _bytes_served| Ok(()));
+            Arc::new(mock_svc)
+        };
+
+        let response = sharing_file_response(svc.clone(), &fake_file_meta(), &fake_sharing_access(), &request)
+            .await
+            .unwrap();
+        let body = response.into_body();
+        if is_read {
+            to_bytes(body).await.unwrap();
+        } else {
+            assert!(!body.size().is_eof());
+            drop(body);
+        }
+
+        // NOTE: the bytes are recorded by a task spawned when the body goes away
+        actix_rt::task::yield_now().await;
+        assert_eq!(Arc::strong_count(&svc), 1);
+    }
+}
+
+#[test]
+fn test_sharing_token() {
+    // NOTE: X-Sharing-Token header, Authorization header, expected token
+    let test_context = vec![
+        (Some("sharing-token"), None, Some("sharing-token")),
+        (None, Some("Bearer access-token"), None),
+        (Some("sharing-token"), Some("Bearer access-token"), Some("sharing-token")),
+        (None, None, None),
+    ];
+
+    for (sharing_token_header, authorization, expected) in test_context {
+        let mut request = TestRequest::get();
+        if let Some(value) = sharing_token_header {
+            request = request.insert_header((SHARING_TOKEN_HEADER_NAME, value));
+        }
+        if let Some(value) = authorization {
+            request = request.insert_header((header::AUTHORIZATION, value));
+        }
+
+        assert_eq!(sharing_token(&request.to_http_request()), expected.map(String::from));
+    }
+}
+
+#[test]
+fn test_sharing_client() {
+    let config = HttpConfig {
+        trusted_proxies: vec![String::from("10.0.0.1")],
+        ..HttpConfig::default()
+    };
+
+    // NOTE: peer address, X-Forwarded-For header, expected client address
+    let test_context = vec![
+        ("203.0.113.7:4000", None, "203.0.113.7"),
+        ("203.0.113.7:4000", Some("198.51.100.1"), "203.0.113.7"),
+        ("10.0.0.1:4000", Some("198.51.100.1"), "198.51.100.1"),
+        ("10.0.0.1:4000", None, "10.0.0.1"),
+    ];
+
+    for (peer_addr, forwarded_for, expected) in test_context {
+        let mut request = TestRequest::get().peer_addr(peer_addr.parse().unwrap());
+        if let Some(value) = forwarded_for {
+            request = request.insert_header(("X-Forwarded-For", value));
+        }
+
+        let client = sharing_client(&request.to_http_request(), &config);
+        assert_eq!(client.get_addr(), expected);
+    }
+}
+
+#[actix_rt::test]
+async fn test_file_response_limited_sharing_link() {
+    // NOTE: a one-time link, the download left is taken by a request for all but the first byte
+    let mut svc = MockFileServiceTrait::new();
+    let last_modified = Utc.with_ymd_and_hms(1990, 3, 3, 0, 0, 0).unwrap();
+    svc.expect_file_stat_content()
+        .times(2)
+        .returning(move |_file_meta| Ok(StorageObjectStat::new(11, "backend-etag", &last_modified)));
+    svc.expect_file_open_content()
+        .times(1)
+        .returning(move |_file_meta, range| {
+            let stat = StorageObjectStat::new(11, "backend-etag", &last_modified);
+            let body = futures_util::stream::iter(vec![Ok(Bytes::from_static(&FAKE_CONTENT[1..]))]);
+            Ok(StorageObject::new(stat, range, Box::pin(body)))
+        });
+    let mut downloads_left = 1;
+    svc.expect_file_consume_sharing_download()
+        .times(2)
+        .returning(move |_sharing_access| {
+            if downloads_left == 0 {
+                anyhow::bail!(FileError::FileSharingLinkExhausted)
+            }
+            downloads_left -= 1;
+            Ok(())
+        });
+
+    let sharing_access = fake_sharing_access();
+    let request = TestRequest::get()
+        .insert_header((header::RANGE, "bytes=1-"))
+        .to_http_request();
+    let response = file_response(&svc, &fake_file_meta(), Some(&sharing_access), &request)
+        .await
+        .unwrap();
+    assert_eq!(response.status(), StatusCode::PARTIAL_CONTENT);
+
+    let request = TestRequest::get().to_http_request();
+    let result = file_response(&svc, &fake_file_meta(), Some(&sharing_access), &request).await;
+    assert!(matches!(result, Err(ApiError::File(FileError::FileSharingLinkExhausted))));
+}
@@ -0,0 +1,68 @@
+use chrono::{DateTime, Utc};
+use uuid::Uuid;
+
+use crate::{domain::entity::folder::{Folder, FolderContent}, presentation::{file::dto::FileReadByIdV1RespDTO, ResponseData}};
+
+// NOTE: a parent id left out or set to null stands for the root of the customer
+#[derive(serde::Serialize, serde::Deserialize)]
+pub struct FolderCreateV1ReqDTO {
+    pub name: String,
+    pub parent_id: Option<Uuid>,
+}
+
+#[derive(serde::Serialize, serde::Deserialize)]
+pub struct FolderRenameV1ReqDTO {
+    pub name: String,
+}
+
+#[derive(serde::Serialize, serde::Deserialize)]
+pub struct FolderMoveV1ReqDTO {
+    pub parent_id: Option<Uuid>,
+}
+
+#[derive(serde::Serialize)]
+pub struct FolderReadByIdV1RespDTO {
+    id: Uuid,
+    parent_id: Option<Uuid>,
+    name: String,
+    #[serde(with = "chrono::serde::ts_seconds")]
+    created_at: DateTime<Utc>,
+}
+
+impl From<Folder> for FolderReadByIdV1RespDTO {
+    fn from(data: Folder) -> FolderReadByIdV1RespDTO {
+        FolderReadByIdV1RespDTO {
+            id: data.get_id(),
+            parent_id: data.get_parent_id(),
+            name: data.get_name(),
+            created_at: data.get_created_at(),
+        }
+    }
+}
+
+impl From<Folder> for ResponseData<FolderReadByIdV1RespDTO> {
+    fn from(data: Folder) -> ResponseData<FolderReadByIdV1RespDTO> {
+        let resp_data = Some(data.into());
+        ResponseData::new(true, String::new(), resp_data)
+    }
+}
+
+// NOTE: folder is null when the root of the customer is listed
+#[derive(serde::Serialize)]
+pub struct FolderContentV1RespDTO {
+    folder: Option<FolderReadByIdV1RespDTO>,
+    folder_list: Vec<FolderReadByIdV1RespDTO>,
+    file_meta_list: Vec<FileReadByIdV1RespDTO>,
+}
+
+impl From<FolderContent> for ResponseData<FolderContentV1RespDTO> {
+    fn from(data: FolderContent) -> ResponseData<FolderContentV1RespDTO> {
+        let resp_data = Some(FolderContentV1RespDTO {
+            folder: data.get_folder().map(|folder| folder.into()),
+            folder_list: data.get_folder_list().into_iter().map(|folder| folder.into()).collect(),
+            file_meta_list: data.get_file_meta_list().into_iter().map(|file_meta| file_meta.into()).collect(),
+        });
+
+        ResponseData::new(true, String::new(), resp_data)
+    }
+}
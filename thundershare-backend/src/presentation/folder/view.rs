@@ -0,0 +1,96 @@
+use crate::domain::service::folder::FolderServiceTrait;
+use crate::domain::service::ServerService;
+use crate::presentation::auth::AuthenticatedCustomer;
+use crate::presentation::error::ApiError;
+use crate::presentation::ResponseData;
+
+use actix_web::{web, HttpResponse};
+use uuid::Uuid;
+
+use super::dto::{FolderContentV1RespDTO, FolderCreateV1ReqDTO, FolderMoveV1ReqDTO, FolderReadByIdV1RespDTO, FolderRenameV1ReqDTO};
+
+pub async fn folder_create_v1(
+    server_services: web::Data<ServerService>,
+    customer: AuthenticatedCustomer,
+    user_data: web::Json<FolderCreateV1ReqDTO>,
+) -> Result<HttpResponse, ApiError> {
+    let identity = customer.get_identity();
+    let svc = server_services.folder_service.clone();
+    let folder = svc
+        .folder_create(&identity.get_id(), &user_data.parent_id, &user_data.name)
+        .await?;
+
+    let resp: ResponseData<FolderReadByIdV1RespDTO> = folder.into();
+    Ok(HttpResponse::Ok().json(resp))
+}
+
+pub async fn folder_read_root_v1(
+    server_services: web::Data<ServerService>,
+    customer: AuthenticatedCustomer,
+) -> Result<HttpResponse, ApiError> {
+    let identity = customer.get_identity();
+    let svc = server_services.folder_service.clone();
+    let folder_content = svc.folder_read_content(&None, &identity.get_id()).await?;
+
+    let resp: ResponseData<FolderContentV1RespDTO> = folder_content.into();
+    Ok(HttpResponse::Ok().json(resp))
+}
+
+pub async fn folder_read_by_id_v1(
+    server_services: web::Data<ServerService>,
+    customer: AuthenticatedCustomer,
+    folder_id: web::Path<Uuid>,
+) -> Result<HttpResponse, ApiError> {
+    let identity = customer.get_identity();
+    let svc = server_services.folder_service.clone();
+    let folder_content = svc
+        .folder_read_content(&Some(*folder_id), &identity.get_id())
+        .await?;
+
+    let resp: ResponseData<FolderContentV1RespDTO> = folder_content.into();
+    Ok(HttpResponse::Ok().json(resp))
+}
+
+pub async fn folder_rename_v1(
+    server_services: web::Data<ServerService>,
+    customer: AuthenticatedCustomer,
+    folder_id: web::Path<Uuid>,
+    user_data: web::Json<FolderRenameV1ReqDTO>,
+) -> Result<HttpResponse, ApiError> {
+    let identity = customer.get_identity();
+    let svc = server_services.folder_service.clone();
+    let folder = svc
+        .folder_rename(&folder_id, &identity.get_id(), &user_data.name)
+        .await?;
+
+    let resp: ResponseData<FolderReadByIdV1RespDTO> = folder.into();
+    Ok(HttpResponse::Ok().json(resp))
+}
+
+pub async fn folder_move_v1(
+    server_services: web::Data<ServerService>,
+    customer: AuthenticatedCustomer,
+    folder_id: web::Path<Uuid>,
+    user_data: web::Json<FolderMoveV1ReqDTO>,
+) -> Result<HttpResponse, ApiError> {
+    let identity = customer.get_identity();
+    let svc = server_services.folder_service.clone();
+    let folder = svc
+        .folder_move(&folder_id, &identity.get_id(), &user_data.parent_id)
+        .await?;
+
+    let resp: ResponseData<FolderReadByIdV1RespDTO> = folder.into();
+    Ok(HttpResponse::Ok().json(resp))
+}
+
+pub async fn folder_delete_by_id_v1(
+    server_services: web::Data<ServerService>,
+    customer: AuthenticatedCustomer,
+    folder_id: web::Path<Uuid>,
+) -> Result<HttpResponse, ApiError> {
+    let identity = customer.get_identity();
+    let svc = server_services.folder_service.clone();
+    svc.folder_delete(&folder_id, &identity.get_id()).await?;
+
+    Ok(HttpResponse::NoContent().finish())
+}
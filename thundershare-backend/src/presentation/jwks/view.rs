@@ -0,0 +1,17 @@
+use crate::domain::service::ServerService;
+
+use actix_web::http::header::CacheControl;
+use actix_web::http::header::CacheDirective;
+use actix_web::Responder;
+use actix_web::{web, HttpResponse};
+
+pub async fn jwks_get(server_services: web::Data<ServerService>) -> impl Responder {
+    let jwks = server_services.jwt_key_set.jwks();
+
+    HttpResponse::Ok()
+        .insert_header(CacheControl(vec![
+            CacheDirective::Public,
+            CacheDirective::MaxAge(300),
+        ]))
+        .json(jwks)
+}
@@ -1,9 +1,19 @@
+pub mod auth;
+#[cfg(test)]
+pub mod auth_test;
 pub mod customer;
+pub mod error;
+#[cfg(test)]
+pub mod error_test;
 pub mod file;
+pub mod folder;
+pub mod jwks;
+pub mod upload;
 
 #[derive(serde::Serialize)]
 pub struct ResponseData<T: serde::Serialize> {
     success: bool,
+    error_code: String,
     error_msg: String,
     data: std::option::Option<T>,
 }
@@ -12,8 +22,18 @@ impl<T: serde::Serialize> ResponseData<T> {
     pub fn new(success: bool, error_msg: String, data: Option<T>) -> ResponseData<T> {
         ResponseData {
             success,
+            error_code: String::new(),
             error_msg,
             data,
         }
     }
+
+    pub fn new_error(error_code: &str, error_msg: String) -> ResponseData<T> {
+        ResponseData {
+            success: false,
+            error_code: error_code.to_string(),
+            error_msg,
+            data: None,
+        }
+    }
 }
@@ -0,0 +1,161 @@
+use std::collections::HashMap;
+
+use actix_web::http::header::CONTENT_TYPE;
+use actix_web::HttpRequest;
+use base64::engine::general_purpose::STANDARD;
+use base64::Engine;
+
+use crate::domain::entity::upload::{ChecksumAlgorithm, Upload, UploadChecksum};
+use crate::domain::error::upload::UploadError;
+use crate::presentation::error::ApiError;
+use crate::presentation::file::dto::client_file_name;
+
+pub const TUS_VERSION: &str = "1.0.0";
+pub const TUS_EXTENSIONS: &str = "creation,termination,checksum";
+pub const TUS_CHECKSUM_ALGORITHMS: &str = "sha1,sha256";
+pub const TUS_CONTENT_TYPE: &str = "application/offset+octet-stream";
+
+pub const TUS_RESUMABLE_HEADER_NAME: &str = "Tus-Resumable";
+pub const TUS_VERSION_HEADER_NAME: &str = "Tus-Version";
+pub const TUS_EXTENSION_HEADER_NAME: &str = "Tus-Extension";
+pub const TUS_MAX_SIZE_HEADER_NAME: &str = "Tus-Max-Size";
+pub const TUS_CHECKSUM_ALGORITHM_HEADER_NAME: &str = "Tus-Checksum-Algorithm";
+pub const UPLOAD_LENGTH_HEADER_NAME: &str = "Upload-Length";
+pub const UPLOAD_DEFER_LENGTH_HEADER_NAME: &str = "Upload-Defer-Length";
+pub const UPLOAD_OFFSET_HEADER_NAME: &str = "Upload-Offset";
+pub const UPLOAD_METADATA_HEADER_NAME: &str = "Upload-Metadata";
+pub const UPLOAD_CHECKSUM_HEADER_NAME: &str = "Upload-Checksum";
+
+// NOTE: not part of tus, tells the client which file a finished upload became
+pub const UPLOAD_FILE_ID_HEADER_NAME: &str = "X-File-Id";
+
+fn header_value<'a>(request: &'a HttpRequest, name: &str) -> Option<&'a str> {
+    request.headers().get(name).and_then(|value| value.to_str().ok())
+}
+
+fn required_u64_header(request: &HttpRequest, name: &str) -> Result<u64, ApiError> {
+    header_value(request, name)
+        .and_then(|value| value.trim().parse::<u64>().ok())
+        .ok_or_else(|| ApiError::InvalidRequest(format!("{} must be a non-negative integer", name)))
+}
+
+pub fn check_tus_resumable(request: &HttpRequest) -> Result<(), ApiError> {
+    match header_value(request, TUS_RESUMABLE_HEADER_NAME) {
+        Some(TUS_VERSION) => Ok(()),
+        _ => Err(UploadError::UploadVersionUnsupported.into()),
+    }
+}
+
+// NOTE: "filename d29ybGQucG5n,filetype aW1hZ2UvcG5n", a key may also come without a value
+pub fn parse_upload_metadata(value: &str) -> Result<HashMap<String, String>, ApiError> {
+    let mut metadata = HashMap::new();
+    for pair in value.split(',').map(str::trim).filter(|pair| !pair.is_empty()) {
+        let (key, encoded) = pair.split_once(' ').unwrap_or((pair, ""));
+        let decoded = STANDARD
+            .decode(encoded.trim())
+            .ok()
+            .and_then(|decoded| String::from_utf8(decoded).ok())
+            .ok_or_else(|| ApiError::InvalidRequest(format!("{} value of {} is not base64 encoded utf-8", UPLOAD_METADATA_HEADER_NAME, key)))?;
+        metadata.insert(key.to_string(), decoded);
+    }
+
+    Ok(metadata)
+}
+
+pub fn upload_metadata(upload: &Upload) -> String {
+    let mut metadata = vec![format!("filename {}", STANDARD.encode(upload.get_name()))];
+    if let Some(mime_type) = upload.get_mime_type() {
+        metadata.push(format!("filetype {}", STANDARD.encode(mime_type)));
+    }
+
+    metadata.join(",")
+}
+
+pub fn parse_upload_checksum(value: &str) -> Result<UploadChecksum, ApiError> {
+    let (name, encoded) = value
+        .trim()
+        .split_once(' ')
+        .ok_or_else(|| ApiError::InvalidRequest(format!("{} must be an algorithm and a base64 digest", UPLOAD_CHECKSUM_HEADER_NAME)))?;
+    let algorithm = ChecksumAlgorithm::from_name(name).ok_or(UploadError::UploadChecksumAlgorithmUnsupported)?;
+    let digest = STANDARD
+        .decode(encoded.trim())
+        .map_err(|_| ApiError::InvalidRequest(format!("{} digest is not base64 encoded", UPLOAD_CHECKSUM_HEADER_NAME)))?;
+
+    Ok(UploadChecksum::new(algorithm, &digest))
+}
+
+pub struct UploadCreateV1ReqDTO {
+    length: u64,
+    file_name: String,
+    content_type: Option<String>,
+}
+
+impl TryFrom<&HttpRequest> for UploadCreateV1ReqDTO {
+    type Error = ApiError;
+
+    // NOTE: tus-js-client sends filename and filetype, uppy also sends name and type
+    fn try_from(request: &HttpRequest) -> Result<UploadCreateV1ReqDTO, ApiError> {
+        if header_value(request, UPLOAD_LENGTH_HEADER_NAME).is_none() && header_value(request, UPLOAD_DEFER_LENGTH_HEADER_NAME).is_some() {
+            return Err(ApiError::InvalidRequest(format!("{} is not supported", UPLOAD_DEFER_LENGTH_HEADER_NAME)));
+        }
+
+        let length = required_u64_header(request, UPLOAD_LENGTH_HEADER_NAME)?;
+        let metadata = match header_value(request, UPLOAD_METADATA_HEADER_NAME) {
+            Some(value) => parse_upload_metadata(value)?,
+            None => HashMap::new(),
+        };
+
+        Ok(UploadCreateV1ReqDTO {
+            length,
+            file_name: metadata.get("filename").or(metadata.get("name")).cloned().unwrap_or_default(),
+            content_type: metadata.get("filetype").or(metadata.get("type")).cloned(),
+        })
+    }
+}
+
+impl UploadCreateV1ReqDTO {
+    pub fn get_length(&self) -> u64 {
+        self.length
+    }
+
+    pub fn get_file_name(&self) -> String {
+        client_file_name(&self.file_name)
+    }
+
+    pub fn get_content_type(&self) -> Option<String> {
+        self.content_type.clone()
+    }
+}
+
+pub struct UploadAppendV1ReqDTO {
+    offset: u64,
+    checksum: Option<UploadChecksum>,
+}
+
+impl TryFrom<&HttpRequest> for UploadAppendV1ReqDTO {
+    type Error = ApiError;
+
+    fn try_from(request: &HttpRequest) -> Result<UploadAppendV1ReqDTO, ApiError> {
+        if header_value(request, CONTENT_TYPE.as_str()) != Some(TUS_CONTENT_TYPE) {
+            return Err(UploadError::UploadContentTypeInvalid.into());
+        }
+
+        let offset = required_u64_header(request, UPLOAD_OFFSET_HEADER_NAME)?;
+        let checksum = match header_value(request, UPLOAD_CHECKSUM_HEADER_NAME) {
+            Some(value) => Some(parse_upload_checksum(value)?),
+            None => None,
+        };
+
+        Ok(UploadAppendV1ReqDTO { offset, checksum })
+    }
+}
+
+impl UploadAppendV1ReqDTO {
+    pub fn get_offset(&self) -> u64 {
+        self.offset
+    }
+
+    pub fn get_checksum(&self) -> Option<UploadChecksum> {
+        self.checksum.clone()
+    }
+}
@@ -0,0 +1,118 @@
+use actix_web::test::TestRequest;
+use chrono::{TimeZone, Utc};
+use ring::digest::{digest, SHA256};
+use uuid::Uuid;
+
+use super::dto::{check_tus_resumable, parse_upload_checksum, parse_upload_metadata, upload_metadata, UploadAppendV1ReqDTO, UploadCreateV1ReqDTO, TUS_CONTENT_TYPE};
+use crate::domain::entity::upload::{ChecksumAlgorithm, Upload, UploadChecksum};
+
+#[test]
+fn test_parse_upload_metadata() {
+    let metadata = parse_upload_metadata("filename YXZhdGFyLnBuZw==, filetype aW1hZ2UvcG5n,is_confidential").unwrap();
+    assert_eq!(metadata.get("filename").unwrap(), "avatar.png");
+    assert_eq!(metadata.get("filetype").unwrap(), "image/png");
+    assert_eq!(metadata.get("is_confidential").unwrap(), "");
+
+    let err = parse_upload_metadata("filename avatar.png").err().unwrap();
+    assert_eq!(err.error_code(), "INVALID_REQUEST");
+}
+
+#[test]
+fn test_upload_metadata_round_trip() {
+    let created_at = Utc.with_ymd_and_hms(1990, 3, 3, 0, 0, 0).unwrap();
+    let upload = Upload::new_full(&Uuid::default(), &Uuid::default(), "報告.pdf", &Some(String::from("application/pdf")), 11, &created_at);
+
+    let metadata = parse_upload_metadata(&upload_metadata(&upload)).unwrap();
+    assert_eq!(metadata.get("filename").unwrap(), "報告.pdf");
+    assert_eq!(metadata.get("filetype").unwrap(), "application/pdf");
+}
+
+#[test]
+fn test_parse_upload_checksum() {
+    let sha256 = digest(&SHA256, b"hello world");
+    let result = parse_upload_checksum("sha256 uU0nuZNNPgilLlLX2n2r+sSE7+N6U4DukIj3rOLvzek=").unwrap();
+    assert_eq!(result, UploadChecksum::new(ChecksumAlgorithm::Sha256, sha256.as_ref()));
+
+    let test_context = vec![
+        ("md5 XrY7u+Ae7tCTyyK7j1rNww==", "UPLOAD_CHECKSUM_ALGORITHM_UNSUPPORTED"),
+        ("sha256", "INVALID_REQUEST"),
+        ("sha256 not-base64!", "INVALID_REQUEST"),
+    ];
+
+    for (value, expected_code) in test_context {
+        let err = parse_upload_checksum(value).err().unwrap();
+        assert_eq!(err.error_code(), expected_code);
+    }
+}
+
+#[test]
+fn test_check_tus_resumable() {
+    let request = TestRequest::default().insert_header(("Tus-Resumable", "1.0.0")).to_http_request();
+    assert!(check_tus_resumable(&request).is_ok());
+
+    for request in [
+        TestRequest::default().to_http_request(),
+        TestRequest::default().insert_header(("Tus-Resumable", "0.2.2")).to_http_request(),
+    ] {
+        let err = check_tus_resumable(&request).err().unwrap();
+        assert_eq!(err.error_code(), "UPLOAD_VERSION_UNSUPPORTED");
+    }
+}
+
+#[test]
+fn test_upload_create_req_dto() {
+    let request = TestRequest::default()
+        .insert_header(("Upload-Length", "11"))
+        .insert_header(("Upload-Metadata", "name QzpcVXNlcnNcbWlrZVxhdmF0YXIucG5n,type aW1hZ2UvcG5n"))
+        .to_http_request();
+    let form = UploadCreateV1ReqDTO::try_from(&request).unwrap();
+    assert_eq!(form.get_length(), 11);
+    assert_eq!(form.get_file_name(), "avatar.png");
+    assert_eq!(form.get_content_type(), Some(String::from("image/png")));
+
+    let test_context = vec![
+        TestRequest::default().to_http_request(),
+        TestRequest::default().insert_header(("Upload-Length", "-1")).to_http_request(),
+        TestRequest::default().insert_header(("Upload-Defer-Length", "1")).to_http_request(),
+    ];
+
+    for request in test_context {
+        let err = UploadCreateV1ReqDTO::try_from(&request).err().unwrap();
+        assert_eq!(err.error_code(), "INVALID_REQUEST");
+    }
+}
+
+#[test]
+fn test_upload_append_req_dto() {
+    let request = TestRequest::default()
+        .insert_header(("Content-Type", TUS_CONTENT_TYPE))
+        .insert_header(("Upload-Offset", "5"))
+        .to_http_request();
+    let form = UploadAppendV1ReqDTO::try_from(&request).unwrap();
+    assert_eq!(form.get_offset(), 5);
+    assert_eq!(form.get_checksum(), None);
+
+    let test_context = vec![
+        (
+            TestRequest::default().insert_header(("Upload-Offset", "5")).to_http_request(),
+            "UPLOAD_CONTENT_TYPE_INVALID",
+        ),
+        (
+            TestRequest::default().insert_header(("Content-Type", TUS_CONTENT_TYPE)).to_http_request(),
+            "INVALID_REQUEST",
+        ),
+        (
+            TestRequest::default()
+                .insert_header(("Content-Type", TUS_CONTENT_TYPE))
+                .insert_header(("Upload-Offset", "5"))
+                .insert_header(("Upload-Checksum", "crc32 AAAAAA=="))
+                .to_http_request(),
+            "UPLOAD_CHECKSUM_ALGORITHM_UNSUPPORTED",
+        ),
+    ];
+
+    for (request, expected_code) in test_context {
+        let err = UploadAppendV1ReqDTO::try_from(&request).err().unwrap();
+        assert_eq!(err.error_code(), expected_code);
+    }
+}
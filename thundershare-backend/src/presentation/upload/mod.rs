@@ -0,0 +1,4 @@
+pub mod view;
+pub mod dto;
+#[cfg(test)]
+pub mod dto_test;
@@ -0,0 +1,127 @@
+use crate::domain::entity::upload::Upload;
+use crate::domain::service::customer::CustomerServiceTrait;
+use crate::domain::service::upload::UploadServiceTrait;
+use crate::domain::service::ServerService;
+use crate::presentation::auth::AuthenticatedCustomer;
+use crate::presentation::error::ApiError;
+use crate::presentation::file::view::{forward_body, upload_channel};
+
+use actix_web::http::header;
+use actix_web::{web, HttpRequest, HttpResponse, HttpResponseBuilder};
+use uuid::Uuid;
+
+use super::dto::{check_tus_resumable, upload_metadata, UploadAppendV1ReqDTO, UploadCreateV1ReqDTO, TUS_CHECKSUM_ALGORITHMS, TUS_CHECKSUM_ALGORITHM_HEADER_NAME, TUS_EXTENSIONS, TUS_EXTENSION_HEADER_NAME, TUS_MAX_SIZE_HEADER_NAME, UPLOAD_FILE_ID_HEADER_NAME, UPLOAD_LENGTH_HEADER_NAME, UPLOAD_METADATA_HEADER_NAME, UPLOAD_OFFSET_HEADER_NAME};
+
+// NOTE: the progress of an upload is never worth caching, the client always needs the latest offset
+fn upload_response(mut response: HttpResponseBuilder, upload: &Upload) -> HttpResponse {
+    response
+        .insert_header((UPLOAD_OFFSET_HEADER_NAME, upload.get_offset().to_string()))
+        .insert_header((UPLOAD_LENGTH_HEADER_NAME, upload.get_length().to_string()))
+        .insert_header((header::CACHE_CONTROL, "no-store"));
+
+    if let Some(file_id) = upload.get_file_id() {
+        response.insert_header((UPLOAD_FILE_ID_HEADER_NAME, file_id.to_string()));
+    }
+
+    response.finish()
+}
+
+// NOTE: the limit of a customer may differ, the server default is what an anonymous client can learn
+pub async fn upload_options_v1(
+    server_services: web::Data<ServerService>,
+) -> HttpResponse {
+    HttpResponse::NoContent()
+        .insert_header((TUS_EXTENSION_HEADER_NAME, TUS_EXTENSIONS))
+        .insert_header((TUS_MAX_SIZE_HEADER_NAME, server_services.config.upload.max_size_bytes.to_string()))
+        .insert_header((TUS_CHECKSUM_ALGORITHM_HEADER_NAME, TUS_CHECKSUM_ALGORITHMS))
+        .finish()
+}
+
+pub async fn upload_create_v1(
+    server_services: web::Data<ServerService>,
+    customer: AuthenticatedCustomer,
+    request: HttpRequest,
+) -> Result<HttpResponse, ApiError> {
+    check_tus_resumable(&request)?;
+    let form = UploadCreateV1ReqDTO::try_from(&request)?;
+
+    let identity = customer.get_identity();
+    let customer = server_services
+        .customer_service
+        .get_customer_by_id(&identity.get_id())
+        .await?;
+
+    let svc = server_services.upload_service.clone();
+    let upload = svc
+        .upload_create(&customer, &form.get_file_name(), form.get_content_type(), form.get_length())
+        .await?;
+
+    let location = format!(
+        "{}/api/v1/uploads/{}",
+        server_services.config.http.public_base_url.trim_end_matches('/'),
+        upload.get_id()
+    );
+    let mut response = HttpResponse::Created();
+    response.insert_header((header::LOCATION, location));
+    Ok(upload_response(response, &upload))
+}
+
+pub async fn upload_read_by_id_v1(
+    server_services: web::Data<ServerService>,
+    customer: AuthenticatedCustomer,
+    request: HttpRequest,
+    upload_id: web::Path<Uuid>,
+) -> Result<HttpResponse, ApiError> {
+    check_tus_resumable(&request)?;
+
+    let identity = customer.get_identity();
+    let svc = server_services.upload_service.clone();
+    let upload = svc.upload_read_by_id(&upload_id, &identity.get_id()).await?;
+
+    let mut response = HttpResponse::Ok();
+    response.insert_header((UPLOAD_METADATA_HEADER_NAME, upload_metadata(&upload)));
+    Ok(upload_response(response, &upload))
+}
+
+pub async fn upload_append_v1(
+    server_services: web::Data<ServerService>,
+    customer: AuthenticatedCustomer,
+    request: HttpRequest,
+    upload_id: web::Path<Uuid>,
+    payload: web::Payload,
+) -> Result<HttpResponse, ApiError> {
+    check_tus_resumable(&request)?;
+    let form = UploadAppendV1ReqDTO::try_from(&request)?;
+
+    let identity = customer.get_identity();
+    let customer = server_services
+        .customer_service
+        .get_customer_by_id(&identity.get_id())
+        .await?;
+
+    let (sender, body) = upload_channel();
+    let svc = server_services.upload_service.clone();
+    let (forwarded, upload) = futures_util::join!(
+        forward_body(payload, sender),
+        svc.upload_append(&customer, &upload_id, form.get_offset(), form.get_checksum(), body),
+    );
+    forwarded.map_err(|err| ApiError::InvalidRequest(err.to_string()))?;
+    let upload = upload?;
+
+    Ok(upload_response(HttpResponse::NoContent(), &upload))
+}
+
+pub async fn upload_terminate_v1(
+    server_services: web::Data<ServerService>,
+    customer: AuthenticatedCustomer,
+    request: HttpRequest,
+    upload_id: web::Path<Uuid>,
+) -> Result<HttpResponse, ApiError> {
+    check_tus_resumable(&request)?;
+
+    let identity = customer.get_identity();
+    let svc = server_services.upload_service.clone();
+    svc.upload_terminate(&upload_id, &identity.get_id()).await?;
+
+    Ok(HttpResponse::NoContent().finish())
+}
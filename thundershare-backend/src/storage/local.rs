@@ -0,0 +1,180 @@
+use std::io::{ErrorKind, SeekFrom};
+use std::path::PathBuf;
+use std::sync::Arc;
+
+use anyhow::{bail, Result};
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use futures_util::StreamExt;
+use tokio::fs::{create_dir_all, remove_file, rename, File};
+use tokio::io::{AsyncReadExt, AsyncSeekExt, AsyncWriteExt};
+use tokio_util::io::ReaderStream;
+use uuid::Uuid;
+
+use crate::domain::entity::storage_object::{
+    ByteRange, StorageObject, StorageObjectStat, StorageStream,
+};
+use crate::domain::error::file::FileError;
+use crate::domain::repository::storage::StorageBackendTrait;
+
+// NOTE: two levels of two characters keep every directory small, a uuid key
+//       "67e55044-..." is stored at <root>/67/e5/67e55044-...
+const SHARD_LEVELS: usize = 2;
+const SHARD_WIDTH: usize = 2;
+
+pub struct LocalStorageBackend {
+    root: PathBuf,
+}
+
+impl LocalStorageBackend {
+    pub fn new(root: &str) -> Result<Arc<dyn StorageBackendTrait>> {
+        std::fs::create_dir_all(root)?;
+        Ok(Arc::new(LocalStorageBackend {
+            root: PathBuf::from(root),
+        }))
+    }
+
+    // NOTE: keys never contain a path separator, so an object can not escape the root directory
+    fn object_path(&self, key: &str) -> Result<PathBuf> {
+        if key.is_empty()
+            || key.starts_with('.')
+            || !key
+                .chars()
+                .all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_' || c == '.')
+        {
+            bail!("invalid storage key {:?}", key)
+        }
+
+        let mut path = self.root.clone();
+        if key.len() >= SHARD_LEVELS * SHARD_WIDTH {
+            for level in 0..SHARD_LEVELS {
+                path.push(&key[level * SHARD_WIDTH..(level + 1) * SHARD_WIDTH]);
+            }
+        }
+        path.push(key);
+
+        Ok(path)
+    }
+
+    // NOTE: files uploaded before sharding was introduced sit directly in the root directory
+    async fn existing_object_path(&self, key: &str) -> Result<Option<PathBuf>> {
+        let sharded_path = self.object_path(key)?;
+        let legacy_path = self.root.join(key);
+
+        for path in [sharded_path, legacy_path] {
+            match tokio::fs::metadata(&path).await {
+                Ok(metadata) if metadata.is_file() => return Ok(Some(path)),
+                Ok(_) => {}
+                Err(err) if err.kind() == ErrorKind::NotFound => {}
+                Err(err) => return Err(err.into()),
+            }
+        }
+
+        Ok(None)
+    }
+
+    async fn stat_path(path: &PathBuf) -> Result<StorageObjectStat> {
+        let metadata = tokio::fs::metadata(path).await?;
+        let last_modified: DateTime<Utc> = metadata.modified()?.into();
+        let etag = format!("{:x}-{:x}", last_modified.timestamp(), metadata.len());
+
+        Ok(StorageObjectStat::new(
+            metadata.len(),
+            &etag,
+            &last_modified,
+        ))
+    }
+}
+
+#[async_trait]
+impl StorageBackendTrait for LocalStorageBackend {
+    // NOTE: the stream is written next to its final location and renamed once complete, a reader
+    //       never sees a partial object and the rename never crosses a filesystem.
+    async fn put(&self, key: &str, mut body: StorageStream) -> Result<StorageObjectStat> {
+        let path = self.object_path(key)?;
+        if let Some(parent) = path.parent() {
+            create_dir_all(parent).await?;
+        }
+
+        let partial_path = path.with_file_name(format!(".{}.{}.partial", key, Uuid::new_v4()));
+        let written = async {
+            let mut file = File::create(&partial_path).await?;
+            while let Some(chunk) = body.next().await {
+                file.write_all(&chunk?).await?;
+            }
+            file.sync_all().await
+        }
+        .await;
+
+        if let Err(err) = written {
+            let _ = remove_file(&partial_path).await;
+            return Err(err.into());
+        }
+
+        rename(&partial_path, &path).await?;
+        LocalStorageBackend::stat_path(&path).await
+    }
+
+    // NOTE: the parts are opened one after another while the new object is written
+    async fn assemble(&self, key: &str, parts: &[String]) -> Result<StorageObjectStat> {
+        let mut path_list = vec![];
+        for part in parts {
+            let Some(path) = self.existing_object_path(part).await? else {
+                bail!(FileError::FileNotFound)
+            };
+            path_list.push(path);
+        }
+
+        let body = futures_util::stream::iter(path_list)
+            .then(File::open)
+            .map(|file| -> StorageStream {
+                match file {
+                    Ok(file) => Box::pin(ReaderStream::new(file)),
+                    Err(err) => Box::pin(futures_util::stream::once(async move { Err(err) })),
+                }
+            })
+            .flatten();
+
+        self.put(key, Box::pin(body)).await
+    }
+
+    async fn get(&self, key: &str, range: Option<ByteRange>) -> Result<StorageObject> {
+        let Some(path) = self.existing_object_path(key).await? else {
+            bail!(FileError::FileNotFound)
+        };
+
+        let stat = LocalStorageBackend::stat_path(&path).await?;
+        let mut file = File::open(&path).await?;
+        let body: StorageStream = match range {
+            Some(range) => {
+                if range.length == 0 || range.start + range.length > stat.get_size() {
+                    bail!("range {:?} is outside of storage key {}", range, key)
+                }
+                file.seek(SeekFrom::Start(range.start)).await?;
+                Box::pin(ReaderStream::new(file.take(range.length)))
+            }
+            None => Box::pin(ReaderStream::new(file)),
+        };
+
+        Ok(StorageObject::new(stat, range, body))
+    }
+
+    async fn delete(&self, key: &str) -> Result<()> {
+        let Some(path) = self.existing_object_path(key).await? else {
+            return Ok(());
+        };
+
+        match remove_file(&path).await {
+            Ok(_) => Ok(()),
+            Err(err) if err.kind() == ErrorKind::NotFound => Ok(()),
+            Err(err) => Err(err.into()),
+        }
+    }
+
+    async fn stat(&self, key: &str) -> Result<Vec<StorageObjectStat>> {
+        match self.existing_object_path(key).await? {
+            Some(path) => Ok(vec![LocalStorageBackend::stat_path(&path).await?]),
+            None => Ok(vec![]),
+        }
+    }
+}
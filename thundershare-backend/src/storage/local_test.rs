@@ -0,0 +1,148 @@
+use std::path::PathBuf;
+
+use bytes::Bytes;
+use futures_util::StreamExt;
+use uuid::Uuid;
+
+use super::local::LocalStorageBackend;
+use crate::domain::entity::storage_object::{ByteRange, StorageStream};
+use crate::domain::error::file::FileError;
+
+const FAKE_KEY: &str = "67e55044-10b1-426f-9247-bb680e5fe0c8";
+
+fn temp_root() -> PathBuf {
+    std::env::temp_dir().join(format!("thundershare-storage-{}", Uuid::new_v4()))
+}
+
+fn stream_of(content: &'static [u8]) -> StorageStream {
+    let chunks = content
+        .chunks(4)
+        .map(|chunk| Ok(Bytes::from_static(chunk)))
+        .collect::<Vec<std::io::Result<Bytes>>>();
+    Box::pin(futures_util::stream::iter(chunks))
+}
+
+async fn read_all(body: StorageStream) -> Vec<u8> {
+    let chunks: Vec<std::io::Result<Bytes>> = body.collect().await;
+    chunks
+        .into_iter()
+        .flat_map(|chunk| chunk.unwrap().to_vec())
+        .collect()
+}
+
+#[actix_rt::test]
+async fn test_local_storage_put_get_stat_delete() {
+    let root = temp_root();
+    let backend = LocalStorageBackend::new(root.to_str().unwrap()).unwrap();
+
+    let stat = backend
+        .put(FAKE_KEY, stream_of(b"hello world"))
+        .await
+        .unwrap();
+    assert_eq!(stat.get_size(), 11);
+    assert!(root.join("67").join("e5").join(FAKE_KEY).is_file());
+
+    let stat_list = backend.stat(FAKE_KEY).await.unwrap();
+    assert_eq!(stat_list, vec![stat]);
+
+    let storage_object = backend.get(FAKE_KEY, None).await.unwrap();
+    assert_eq!(storage_object.get_range(), None);
+    assert_eq!(read_all(storage_object.into_body()).await, b"hello world");
+
+    let range = Some(ByteRange::new(6, 5));
+    let storage_object = backend.get(FAKE_KEY, range).await.unwrap();
+    assert_eq!(storage_object.get_range(), range);
+    assert_eq!(read_all(storage_object.into_body()).await, b"world");
+
+    backend.delete(FAKE_KEY).await.unwrap();
+    assert_eq!(backend.stat(FAKE_KEY).await.unwrap(), vec![]);
+    let err = backend.get(FAKE_KEY, None).await.err().unwrap();
+    assert_eq!(
+        err.downcast::<FileError>().unwrap(),
+        FileError::FileNotFound
+    );
+
+    // NOTE: deleting twice is not an error
+    backend.delete(FAKE_KEY).await.unwrap();
+
+    std::fs::remove_dir_all(&root).unwrap();
+}
+
+#[actix_rt::test]
+async fn test_local_storage_discards_failed_put() {
+    let root = temp_root();
+    let backend = LocalStorageBackend::new(root.to_str().unwrap()).unwrap();
+
+    let chunks: Vec<std::io::Result<Bytes>> = vec![
+        Ok(Bytes::from_static(b"hello")),
+        Err(std::io::Error::other("connection reset")),
+    ];
+    let body: StorageStream = Box::pin(futures_util::stream::iter(chunks));
+    assert!(backend.put(FAKE_KEY, body).await.is_err());
+    assert_eq!(backend.stat(FAKE_KEY).await.unwrap(), vec![]);
+    assert_eq!(
+        std::fs::read_dir(root.join("67").join("e5"))
+            .unwrap()
+            .count(),
+        0
+    );
+
+    std::fs::remove_dir_all(&root).unwrap();
+}
+
+#[actix_rt::test]
+async fn test_local_storage_assemble() {
+    let root = temp_root();
+    let backend = LocalStorageBackend::new(root.to_str().unwrap()).unwrap();
+    let parts = vec![Uuid::new_v4().to_string(), Uuid::new_v4().to_string()];
+    backend.put(&parts[0], stream_of(b"hello ")).await.unwrap();
+    backend.put(&parts[1], stream_of(b"world")).await.unwrap();
+
+    let stat = backend.assemble(FAKE_KEY, &parts).await.unwrap();
+    assert_eq!(stat.get_size(), 11);
+    let storage_object = backend.get(FAKE_KEY, None).await.unwrap();
+    assert_eq!(read_all(storage_object.into_body()).await, b"hello world");
+
+    // NOTE: the parts are left for the caller to delete
+    assert_eq!(backend.stat(&parts[0]).await.unwrap().len(), 1);
+
+    // NOTE: nothing is stored when a part is missing
+    let missing = vec![parts[0].clone(), Uuid::new_v4().to_string()];
+    let other_key = Uuid::new_v4().to_string();
+    let err = backend.assemble(&other_key, &missing).await.err().unwrap();
+    assert_eq!(
+        err.downcast::<FileError>().unwrap(),
+        FileError::FileNotFound
+    );
+    assert_eq!(backend.stat(&other_key).await.unwrap(), vec![]);
+
+    std::fs::remove_dir_all(&root).unwrap();
+}
+
+#[actix_rt::test]
+async fn test_local_storage_reads_unsharded_files() {
+    let root = temp_root();
+    let backend = LocalStorageBackend::new(root.to_str().unwrap()).unwrap();
+    std::fs::write(root.join(FAKE_KEY), b"legacy").unwrap();
+
+    let storage_object = backend.get(FAKE_KEY, None).await.unwrap();
+    assert_eq!(storage_object.get_stat().get_size(), 6);
+    assert_eq!(read_all(storage_object.into_body()).await, b"legacy");
+
+    backend.delete(FAKE_KEY).await.unwrap();
+    assert!(!root.join(FAKE_KEY).exists());
+
+    std::fs::remove_dir_all(&root).unwrap();
+}
+
+#[actix_rt::test]
+async fn test_local_storage_rejects_invalid_keys() {
+    let root = temp_root();
+    let backend = LocalStorageBackend::new(root.to_str().unwrap()).unwrap();
+
+    for key in ["", "../etc/passwd", "a/b", ".hidden"] {
+        assert!(backend.stat(key).await.is_err(), "{:?} is accepted", key);
+    }
+
+    std::fs::remove_dir_all(&root).unwrap();
+}
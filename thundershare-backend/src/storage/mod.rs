@@ -0,0 +1,22 @@
+pub mod local;
+#[cfg(test)]
+pub mod local_test;
+pub mod s3;
+#[cfg(test)]
+pub mod s3_test;
+
+use std::sync::Arc;
+
+use anyhow::Result;
+
+use crate::config::StorageConfig;
+use crate::domain::repository::storage::StorageBackendTrait;
+
+use self::{local::LocalStorageBackend, s3::S3StorageBackend};
+
+pub fn storage_backend_builder(config: &StorageConfig) -> Result<Arc<dyn StorageBackendTrait>> {
+    match config.backend.as_str() {
+        "s3" => S3StorageBackend::new(&config.s3),
+        _ => LocalStorageBackend::new(&config.local.root),
+    }
+}
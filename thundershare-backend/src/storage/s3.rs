@@ -0,0 +1,586 @@
+use std::collections::BTreeMap;
+use std::sync::Arc;
+
+use anyhow::{anyhow, bail, Result};
+use async_trait::async_trait;
+use bytes::{Bytes, BytesMut};
+use chrono::{DateTime, Utc};
+use futures_util::{StreamExt, TryStreamExt};
+use reqwest::header::{
+    HeaderMap, HeaderName, CONTENT_LENGTH, CONTENT_RANGE, ETAG, LAST_MODIFIED, RANGE,
+};
+use reqwest::{Method, StatusCode, Url};
+use ring::digest::{digest, SHA256};
+use ring::hmac;
+
+use crate::config::S3StorageConfig;
+use crate::domain::entity::storage_object::{
+    ByteRange, StorageObject, StorageObjectStat, StorageStream,
+};
+use crate::domain::error::file::FileError;
+use crate::domain::repository::storage::StorageBackendTrait;
+
+// NOTE: a request needs its length up front, so a body is cut into parts of this size that are
+//       buffered in memory and sent as a multipart upload. S3 allows up to 10000 parts, which puts
+//       the largest object at 156 GiB.
+const MULTIPART_PART_SIZE: usize = 16 * 1024 * 1024;
+
+// NOTE: S3 only copies objects into a multipart upload while every part but the last is within
+//       these limits.
+const MULTIPART_COPY_MIN_PART_SIZE: u64 = 5 * 1024 * 1024;
+const MULTIPART_COPY_MAX_PART_SIZE: u64 = 5 * 1024 * 1024 * 1024;
+
+fn to_hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|byte| format!("{:02x}", byte)).collect()
+}
+
+// NOTE: every byte except the unreserved characters is percent encoded, a path keeps its "/" so
+//       that the key still reads as a path.
+fn uri_encode(value: &str, keep_slash: bool) -> String {
+    value
+        .bytes()
+        .map(|byte| match byte {
+            b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'_' | b'.' | b'~' => {
+                (byte as char).to_string()
+            }
+            b'/' if keep_slash => String::from("/"),
+            _ => format!("%{:02X}", byte),
+        })
+        .collect()
+}
+
+pub fn canonical_query(query: &[(&str, &str)]) -> String {
+    let mut query: Vec<String> = query
+        .iter()
+        .map(|(name, value)| format!("{}={}", uri_encode(name, false), uri_encode(value, false)))
+        .collect();
+    query.sort();
+    query.join("&")
+}
+
+fn xml_value<'a>(xml: &'a str, tag: &str) -> Option<&'a str> {
+    let start = xml.find(&format!("<{}>", tag))? + tag.len() + 2;
+    let end = start + xml[start..].find(&format!("</{}>", tag))?;
+    Some(&xml[start..end])
+}
+
+// NOTE: fills the part up to MULTIPART_PART_SIZE, returns true once the body is exhausted
+async fn read_part(body: &mut StorageStream, part: &mut BytesMut) -> Result<bool> {
+    while part.len() < MULTIPART_PART_SIZE {
+        match body.next().await {
+            Some(chunk) => part.extend_from_slice(&chunk?),
+            None => return Ok(true),
+        }
+    }
+
+    Ok(false)
+}
+
+#[derive(Debug, Clone)]
+pub struct S3Credentials {
+    pub access_key_id: String,
+    pub secret_access_key: String,
+    pub region: String,
+}
+
+impl S3Credentials {
+    // NOTE: AWS signature version 4, the headers must hold host and every x-amz-* header with
+    //       lower case names.
+    pub fn authorization_header(
+        &self,
+        method: &str,
+        canonical_uri: &str,
+        canonical_query: &str,
+        headers: &BTreeMap<String, String>,
+        payload_sha256: &str,
+        now: &DateTime<Utc>,
+    ) -> String {
+        let amz_date = now.format("%Y%m%dT%H%M%SZ").to_string();
+        let date = now.format("%Y%m%d").to_string();
+        let scope = format!("{}/{}/s3/aws4_request", date, self.region);
+
+        let canonical_headers: String = headers
+            .iter()
+            .map(|(name, value)| format!("{}:{}\n", name, value.trim()))
+            .collect();
+        let signed_headers = headers.keys().cloned().collect::<Vec<String>>().join(";");
+        let canonical_request = format!(
+            "{}\n{}\n{}\n{}\n{}\n{}",
+            method,
+            canonical_uri,
+            canonical_query,
+            canonical_headers,
+            signed_headers,
+            payload_sha256
+        );
+
+        let string_to_sign = format!(
+            "AWS4-HMAC-SHA256\n{}\n{}\n{}",
+            amz_date,
+            scope,
+            to_hex(digest(&SHA256, canonical_request.as_bytes()).as_ref())
+        );
+
+        let mut signing_key = format!("AWS4{}", self.secret_access_key).into_bytes();
+        for part in [
+            date.as_str(),
+            self.region.as_str(),
+            "s3",
+            "aws4_request",
+            string_to_sign.as_str(),
+        ] {
+            let key = hmac::Key::new(hmac::HMAC_SHA256, &signing_key);
+            signing_key = hmac::sign(&key, part.as_bytes()).as_ref().to_vec();
+        }
+
+        format!(
+            "AWS4-HMAC-SHA256 Credential={}/{},SignedHeaders={},Signature={}",
+            self.access_key_id,
+            scope,
+            signed_headers,
+            to_hex(&signing_key)
+        )
+    }
+}
+
+fn parse_stat(headers: &HeaderMap, status: StatusCode) -> Result<StorageObjectStat> {
+    let header = |name: HeaderName| headers.get(name).and_then(|value| value.to_str().ok());
+
+    // NOTE: a partial response carries the size of the whole object in "bytes 0-9/1234"
+    let size = if status == StatusCode::PARTIAL_CONTENT {
+        header(CONTENT_RANGE).and_then(|value| value.rsplit('/').next()?.parse().ok())
+    } else {
+        header(CONTENT_LENGTH).and_then(|value| value.parse().ok())
+    };
+    let Some(size) = size else {
+        bail!("s3 response does not tell the object size")
+    };
+
+    let etag = header(ETAG).unwrap_or_default().trim_matches('"');
+    let last_modified = header(LAST_MODIFIED)
+        .and_then(|value| DateTime::parse_from_rfc2822(value).ok())
+        .map(|value| value.with_timezone(&Utc))
+        .unwrap_or_else(Utc::now);
+
+    Ok(StorageObjectStat::new(size, etag, &last_modified))
+}
+
+#[derive(Debug, Clone)]
+pub struct S3Location {
+    endpoint: Url,
+    bucket: String,
+    path_style: bool,
+}
+
+impl S3Location {
+    pub fn new(config: &S3StorageConfig) -> Result<S3Location> {
+        let endpoint = Url::parse(&config.endpoint)
+            .map_err(|err| anyhow!("invalid s3 endpoint {}: {}", config.endpoint, err))?;
+
+        Ok(S3Location {
+            endpoint,
+            bucket: config.bucket.clone(),
+            path_style: config.path_style,
+        })
+    }
+
+    // NOTE: returns the url of the object together with the host and path it is signed with
+    pub fn object_location(&self, key: &str) -> Result<(Url, String, String)> {
+        let Some(host) = self.endpoint.host_str() else {
+            bail!("s3 endpoint {} has no host", self.endpoint)
+        };
+
+        let host = if self.path_style {
+            host.to_string()
+        } else {
+            format!("{}.{}", self.bucket, host)
+        };
+        let host = match self.endpoint.port() {
+            Some(port) => format!("{}:{}", host, port),
+            None => host,
+        };
+
+        let base_path = self.endpoint.path().trim_end_matches('/');
+        let path = if self.path_style {
+            format!("{}/{}/{}", base_path, self.bucket, key)
+        } else {
+            format!("{}/{}", base_path, key)
+        };
+        let path = uri_encode(&path, true);
+
+        let url = Url::parse(&format!("{}://{}{}", self.endpoint.scheme(), host, path))?;
+        Ok((url, host, path))
+    }
+}
+
+#[derive(Clone)]
+pub struct S3StorageBackend {
+    client: reqwest::Client,
+    location: S3Location,
+    credentials: S3Credentials,
+}
+
+impl S3StorageBackend {
+    pub fn new(config: &S3StorageConfig) -> Result<Arc<dyn StorageBackendTrait>> {
+        Ok(Arc::new(S3StorageBackend {
+            client: reqwest::Client::new(),
+            location: S3Location::new(config)?,
+            credentials: S3Credentials {
+                access_key_id: config.access_key_id.clone(),
+                secret_access_key: config.secret_access_key.clone(),
+                region: config.region.clone(),
+            },
+        }))
+    }
+
+    async fn send(
+        &self,
+        method: Method,
+        key: &str,
+        query: &[(&str, &str)],
+        extra_headers: Vec<(String, String)>,
+        body: Bytes,
+    ) -> Result<reqwest::Response> {
+        let (mut url, host, path) = self.location.object_location(key)?;
+        let query = canonical_query(query);
+        if !query.is_empty() {
+            url.set_query(Some(&query));
+        }
+        let payload_sha256 = to_hex(digest(&SHA256, &body).as_ref());
+        let now = Utc::now();
+
+        let mut headers = BTreeMap::new();
+        headers.insert(String::from("host"), host);
+        headers.insert(String::from("x-amz-content-sha256"), payload_sha256.clone());
+        headers.insert(
+            String::from("x-amz-date"),
+            now.format("%Y%m%dT%H%M%SZ").to_string(),
+        );
+        for (name, value) in extra_headers {
+            headers.insert(name.to_lowercase(), value);
+        }
+
+        let authorization = self.credentials.authorization_header(
+            method.as_str(),
+            &path,
+            &query,
+            &headers,
+            &payload_sha256,
+            &now,
+        );
+
+        let mut request = self
+            .client
+            .request(method, url)
+            .header("authorization", authorization);
+        for (name, value) in headers.iter().filter(|(name, _)| name.as_str() != "host") {
+            request = request.header(name.as_str(), value.as_str());
+        }
+        if !body.is_empty() {
+            request = request.body(body);
+        }
+
+        Ok(request.send().await?)
+    }
+
+    async fn put_object(&self, key: &str, body: Bytes) -> Result<StorageObjectStat> {
+        let size = body.len() as u64;
+        let response = self.send(Method::PUT, key, &[], vec![], body).await?;
+        if !response.status().is_success() {
+            return Err(S3StorageBackend::fail("put", key, response).await);
+        }
+
+        let etag = response
+            .headers()
+            .get(ETAG)
+            .and_then(|value| value.to_str().ok())
+            .unwrap_or_default()
+            .trim_matches('"');
+        Ok(StorageObjectStat::new(size, etag, &Utc::now()))
+    }
+
+    async fn create_multipart_upload(&self, key: &str) -> Result<String> {
+        let response = self
+            .send(Method::POST, key, &[("uploads", "")], vec![], Bytes::new())
+            .await?;
+        if !response.status().is_success() {
+            return Err(S3StorageBackend::fail("create multipart upload", key, response).await);
+        }
+
+        let xml = response.text().await?;
+        match xml_value(&xml, "UploadId") {
+            Some(upload_id) => Ok(upload_id.to_string()),
+            None => bail!(
+                "s3 create multipart upload of {} returned no upload id",
+                key
+            ),
+        }
+    }
+
+    async fn upload_parts(
+        &self,
+        key: &str,
+        upload_id: &str,
+        mut body: StorageStream,
+        mut part: BytesMut,
+    ) -> Result<StorageObjectStat> {
+        let mut etag_list = vec![];
+        let mut size: u64 = 0;
+        let mut finished = false;
+
+        while !part.is_empty() {
+            let data = part.split().freeze();
+            size += data.len() as u64;
+
+            let part_number = (etag_list.len() + 1).to_string();
+            let query = [
+                ("partNumber", part_number.as_str()),
+                ("uploadId", upload_id),
+            ];
+            let response = self.send(Method::PUT, key, &query, vec![], data).await?;
+            if !response.status().is_success() {
+                return Err(S3StorageBackend::fail("upload part", key, response).await);
+            }
+            let etag = response
+                .headers()
+                .get(ETAG)
+                .and_then(|value| value.to_str().ok())
+                .unwrap_or_default()
+                .to_string();
+            etag_list.push(etag);
+
+            if !finished {
+                finished = read_part(&mut body, &mut part).await?;
+            }
+        }
+
+        self.complete_multipart_upload(key, upload_id, &etag_list, size)
+            .await
+    }
+
+    // NOTE: the parts are copied by S3 itself, none of their bytes pass through the service
+    async fn copy_parts(
+        &self,
+        key: &str,
+        upload_id: &str,
+        parts: &[String],
+        size: u64,
+    ) -> Result<StorageObjectStat> {
+        let mut etag_list = vec![];
+        for (index, part) in parts.iter().enumerate() {
+            let part_number = (index + 1).to_string();
+            let query = [
+                ("partNumber", part_number.as_str()),
+                ("uploadId", upload_id),
+            ];
+            let copy_source = uri_encode(&format!("/{}/{}", self.location.bucket, part), true);
+            let extra_headers = vec![(String::from("x-amz-copy-source"), copy_source)];
+            let response = self
+                .send(Method::PUT, key, &query, extra_headers, Bytes::new())
+                .await?;
+            if !response.status().is_success() {
+                return Err(S3StorageBackend::fail("upload part copy", key, response).await);
+            }
+
+            let xml = response.text().await?;
+            let Some(etag) = xml_value(&xml, "ETag") else {
+                bail!("s3 upload part copy of {} into {} failed: {}", part, key, xml)
+            };
+            etag_list.push(etag.replace("&quot;", "\""));
+        }
+
+        self.complete_multipart_upload(key, upload_id, &etag_list, size)
+            .await
+    }
+
+    async fn complete_multipart_upload(
+        &self,
+        key: &str,
+        upload_id: &str,
+        etag_list: &[String],
+        size: u64,
+    ) -> Result<StorageObjectStat> {
+        let parts: String = etag_list
+            .iter()
+            .enumerate()
+            .map(|(index, etag)| {
+                format!(
+                    "<Part><PartNumber>{}</PartNumber><ETag>{}</ETag></Part>",
+                    index + 1,
+                    etag
+                )
+            })
+            .collect();
+        let xml = format!(
+            "<CompleteMultipartUpload>{}</CompleteMultipartUpload>",
+            parts
+        );
+
+        let query = [("uploadId", upload_id)];
+        let response = self
+            .send(Method::POST, key, &query, vec![], Bytes::from(xml))
+            .await?;
+        if !response.status().is_success() {
+            return Err(S3StorageBackend::fail("complete multipart upload", key, response).await);
+        }
+
+        // NOTE: a failure to complete may still be reported with status 200
+        let xml = response.text().await?;
+        if xml.contains("<Error>") {
+            bail!("s3 complete multipart upload of {} failed: {}", key, xml)
+        }
+
+        let etag = xml_value(&xml, "ETag")
+            .unwrap_or_default()
+            .replace("&quot;", "")
+            .replace('"', "");
+        Ok(StorageObjectStat::new(size, &etag, &Utc::now()))
+    }
+
+    async fn abort_multipart_upload(&self, key: &str, upload_id: &str) {
+        let query = [("uploadId", upload_id)];
+        match self
+            .send(Method::DELETE, key, &query, vec![], Bytes::new())
+            .await
+        {
+            Ok(response) if response.status().is_success() => {}
+            Ok(response) => log::warn!(
+                "{}",
+                S3StorageBackend::fail("abort multipart upload", key, response).await
+            ),
+            Err(err) => log::warn!("s3 abort multipart upload of {} failed: {}", key, err),
+        }
+    }
+
+    // NOTE: the parts are read back one after another and put as a single body
+    async fn concat_parts(&self, key: &str, parts: &[String]) -> Result<StorageObjectStat> {
+        let backend = Arc::new(self.clone());
+        let body = futures_util::stream::iter(parts.to_vec())
+            .then(move |part| {
+                let backend = backend.clone();
+                async move { backend.get(&part, None).await }
+            })
+            .map(|storage_object| -> StorageStream {
+                match storage_object {
+                    Ok(storage_object) => storage_object.into_body(),
+                    Err(err) => Box::pin(futures_util::stream::once(async move {
+                        Err(std::io::Error::other(err))
+                    })),
+                }
+            })
+            .flatten();
+
+        self.put(key, Box::pin(body)).await
+    }
+
+    async fn fail(method: &str, key: &str, response: reqwest::Response) -> anyhow::Error {
+        let status = response.status();
+        let message = response.text().await.unwrap_or_default();
+        anyhow!(
+            "s3 {} of {} failed with {}: {}",
+            method,
+            key,
+            status,
+            message
+        )
+    }
+}
+
+#[async_trait]
+impl StorageBackendTrait for S3StorageBackend {
+    // NOTE: a body that fits into one part is sent as a single object
+    async fn put(&self, key: &str, mut body: StorageStream) -> Result<StorageObjectStat> {
+        let mut part = BytesMut::new();
+        if read_part(&mut body, &mut part).await? {
+            return self.put_object(key, part.freeze()).await;
+        }
+
+        let upload_id = self.create_multipart_upload(key).await?;
+        let result = self.upload_parts(key, &upload_id, body, part).await;
+        if result.is_err() {
+            self.abort_multipart_upload(key, &upload_id).await;
+        }
+
+        result
+    }
+
+    // NOTE: parts that S3 can not copy, because one before the last is too small or any is too
+    //       large, fall back to reading them back.
+    async fn assemble(&self, key: &str, parts: &[String]) -> Result<StorageObjectStat> {
+        let mut size_list = vec![];
+        for part in parts {
+            let stat_list = self.stat(part).await?;
+            let Some(stat) = stat_list.first() else {
+                bail!(FileError::FileNotFound)
+            };
+            size_list.push(stat.get_size());
+        }
+
+        let copyable = size_list.split_last().is_some_and(|(last, rest)| {
+            *last <= MULTIPART_COPY_MAX_PART_SIZE
+                && rest.iter().all(|size| {
+                    (MULTIPART_COPY_MIN_PART_SIZE..=MULTIPART_COPY_MAX_PART_SIZE).contains(size)
+                })
+        });
+        if !copyable {
+            return self.concat_parts(key, parts).await;
+        }
+
+        let upload_id = self.create_multipart_upload(key).await?;
+        let result = self
+            .copy_parts(key, &upload_id, parts, size_list.iter().sum())
+            .await;
+        if result.is_err() {
+            self.abort_multipart_upload(key, &upload_id).await;
+        }
+
+        result
+    }
+
+    async fn get(&self, key: &str, range: Option<ByteRange>) -> Result<StorageObject> {
+        let extra_headers = match range {
+            Some(range) => vec![(
+                RANGE.to_string(),
+                format!("bytes={}-{}", range.start, range.get_end()),
+            )],
+            None => vec![],
+        };
+
+        let response = self
+            .send(Method::GET, key, &[], extra_headers, Bytes::new())
+            .await?;
+        match response.status() {
+            StatusCode::NOT_FOUND => bail!(FileError::FileNotFound),
+            status if status.is_success() => {
+                let stat = parse_stat(response.headers(), status)?;
+                let range = range.filter(|_| status == StatusCode::PARTIAL_CONTENT);
+                let body: StorageStream =
+                    Box::pin(response.bytes_stream().map_err(std::io::Error::other));
+                Ok(StorageObject::new(stat, range, body))
+            }
+            _ => Err(S3StorageBackend::fail("get", key, response).await),
+        }
+    }
+
+    async fn delete(&self, key: &str) -> Result<()> {
+        let response = self
+            .send(Method::DELETE, key, &[], vec![], Bytes::new())
+            .await?;
+        match response.status() {
+            StatusCode::NOT_FOUND => Ok(()),
+            status if status.is_success() => Ok(()),
+            _ => Err(S3StorageBackend::fail("delete", key, response).await),
+        }
+    }
+
+    async fn stat(&self, key: &str) -> Result<Vec<StorageObjectStat>> {
+        let response = self
+            .send(Method::HEAD, key, &[], vec![], Bytes::new())
+            .await?;
+        match response.status() {
+            StatusCode::NOT_FOUND => Ok(vec![]),
+            status if status.is_success() => Ok(vec![parse_stat(response.headers(), status)?]),
+            status => bail!("s3 stat of {} failed with {}", key, status),
+        }
+    }
+}